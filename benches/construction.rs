@@ -0,0 +1,132 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Time and count the allocations of `MerkleTree::merkle_root` and `MerkleTree::merkle_proof`
+//! over 1M and 10M leaves. Run with `cargo bench --bench construction`, optionally followed by
+//! `--` and the numbers of leaves to build. Every case reports the best time of 3 runs, each
+//! in a process of its own, with the allocations of the call and the peak resident set size
+//! of the process, leaves included. Results are recorded in BENCHMARKS.md.
+use merkle_tree::MerkleTree;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// System allocator that counts allocations and allocated bytes.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Peak resident set size of this process in MiB, read from `/proc/self/status`, or None on
+/// platforms without it.
+fn peak_resident_mib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib >> 10)
+}
+
+/// Build `num_of_leaves` leaves, call `name` on them once, and print the elapsed seconds, the
+/// allocations and allocated bytes of the call, and the peak resident set size in MiB.
+///
+/// # Arguments
+///
+/// * `num_of_leaves` - Number of leaves.
+/// * `name` - `merkle_root` or `merkle_proof`.
+fn run(num_of_leaves: usize, name: &str) {
+    let leaves: Vec<String> = (0..num_of_leaves).map(|i| format!("leaf{}", i)).collect();
+    let (start_allocations, start_bytes) = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    );
+    let start = Instant::now();
+    match name {
+        "merkle_root" => drop(black_box(MerkleTree::merkle_root(&leaves))),
+        "merkle_proof" => drop(black_box(MerkleTree::merkle_proof(
+            &leaves,
+            num_of_leaves / 2,
+        ))),
+        _ => panic!("unknown function {name}"),
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{} {} {} {}",
+        elapsed.as_secs_f64(),
+        ALLOCATIONS.load(Ordering::Relaxed) - start_allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - start_bytes,
+        peak_resident_mib().map_or("-".to_string(), |mib| mib.to_string())
+    );
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, flag, num_of_leaves, name] = &args[..] {
+        if flag == "--run" {
+            return run(num_of_leaves.parse().unwrap(), name);
+        }
+    }
+
+    // `cargo bench` passes `--bench`, which is not a number of leaves.
+    let counts: Vec<usize> = args[1..]
+        .iter()
+        .filter_map(|arg| arg.parse().ok())
+        .collect();
+    let counts = match counts.is_empty() {
+        true => vec![1_000_000, 10_000_000],
+        false => counts,
+    };
+
+    // Trees of `merkle_root` are never freed, so every run gets a process of its own.
+    println!("| leaves | function | best of 3 | allocations | allocated | peak resident |");
+    println!("|---:|---|---:|---:|---:|---:|");
+    for n in counts {
+        for name in ["merkle_root", "merkle_proof"] {
+            let mut best = f64::MAX;
+            let mut row = Vec::new();
+            for _ in 0..3 {
+                let output = Command::new(std::env::current_exe().unwrap())
+                    .args(["--run", &n.to_string(), name])
+                    .output()
+                    .unwrap();
+                assert!(
+                    output.status.success(),
+                    "run of {name} over {n} leaves failed"
+                );
+                row = String::from_utf8(output.stdout)
+                    .unwrap()
+                    .split_whitespace()
+                    .map(str::to_owned)
+                    .collect();
+                best = best.min(row[0].parse().unwrap());
+            }
+            let bytes: u64 = row[2].parse().unwrap();
+            println!(
+                "| {n} | `{name}` | {best:.2} s | {} | {} MiB | {} MiB |",
+                row[1],
+                bytes >> 20,
+                row[3]
+            );
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Utreexo-style dynamic hash accumulator.
+//!
+//! Leaves are appended to a forest of perfect binary trees, one per set bit of the number of
+//! leaves added so far, largest tree first. Leaves keep their position forever; deleting a leaf
+//! empties it, and a node with one empty child takes the value of the other child, so the
+//! accumulator only needs to keep the roots of the forest.
+use crate::Hash;
+
+/// Parent of two possibly empty nodes.
+///
+/// # Arguments
+///
+/// * `left` - Left child node value.
+/// * `right` - Right child node value.
+fn parent(left: Option<&String>, right: Option<&String>) -> Option<String> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(Hash::hash(&format!("{}{}", left, right))),
+        (Some(only), None) | (None, Some(only)) => Some(only.to_owned()),
+        (None, None) => None,
+    }
+}
+
+/// Membership proof for a leaf of an accumulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccumulatorProof {
+    /// Position of the leaf, counting all leaves ever added.
+    pub position: u64,
+
+    /// Sibling node values from the leaf level up to just below the root of its tree.
+    /// Empty siblings are None.
+    pub hashes: Vec<Option<String>>,
+}
+
+impl AccumulatorProof {
+    /// Root value of the tree containing the leaf, computed from `leaf_value` and the audit path.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_value` - Leaf node value, None if the leaf is empty.
+    fn root_from(&self, leaf_value: Option<String>) -> Option<String> {
+        let mut node = leaf_value;
+        for (level, sibling) in self.hashes.iter().enumerate() {
+            node = if (self.position >> level) & 1 == 1 {
+                parent(sibling.as_ref(), node.as_ref())
+            } else {
+                parent(node.as_ref(), sibling.as_ref())
+            };
+        }
+        node
+    }
+
+    /// Update this proof after a leaf is added to `before`, the accumulator state prior to the
+    /// addition.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Accumulator state before the addition.
+    /// * `leaf` - Leaf content that was added.
+    pub fn update_after_add(&mut self, before: &Accumulator, leaf: &str) {
+        let mut merged = false;
+        for (height, left, right) in before.merges(leaf) {
+            if merged {
+                self.hashes.push(left); // Our tree is the right child from now on.
+            } else if before.tree_height(self.position) == Some(height) {
+                self.hashes.push(right);
+                merged = true;
+            }
+        }
+    }
+
+    /// Update this proof after another leaf is deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `deleted` - Proof of the deleted leaf, valid before the deletion.
+    pub fn update_after_delete(&mut self, deleted: &AccumulatorProof) {
+        if deleted.position == self.position {
+            return;
+        }
+        let mut node: Option<String> = None;
+        for level in 0..deleted.hashes.len().min(self.hashes.len()) {
+            if (self.position >> level) ^ 1 == deleted.position >> level {
+                self.hashes[level] = node.to_owned();
+            }
+            let sibling = deleted.hashes[level].as_ref();
+            node = if (deleted.position >> level) & 1 == 1 {
+                parent(sibling, node.as_ref())
+            } else {
+                parent(node.as_ref(), sibling)
+            };
+        }
+    }
+}
+
+/// Compact accumulator state: the roots of the forest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accumulator {
+    /// Number of leaves ever added, including deleted ones.
+    num_of_leaves: u64,
+
+    /// Root values of the trees, largest tree first. Fully deleted trees have an empty root.
+    roots: Vec<Option<String>>,
+}
+
+impl Accumulator {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of leaves ever added, including deleted ones.
+    pub fn num_of_leaves(&self) -> u64 {
+        self.num_of_leaves
+    }
+
+    /// Root values of the trees, largest tree first.
+    pub fn roots(&self) -> &[Option<String>] {
+        &self.roots
+    }
+
+    /// Index into `roots` and height of the tree containing `position`.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Leaf position.
+    fn tree_of(&self, position: u64) -> Option<(usize, usize)> {
+        if position >= self.num_of_leaves {
+            return None;
+        }
+        let mut start = 0;
+        let mut index = 0;
+        for height in (0..64).rev() {
+            if self.num_of_leaves >> height & 1 == 1 {
+                if position < start + (1 << height) {
+                    return Some((index, height));
+                }
+                start += 1 << height;
+                index += 1;
+            }
+        }
+        None
+    }
+
+    /// Height of the tree containing `position`.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Leaf position.
+    fn tree_height(&self, position: u64) -> Option<usize> {
+        self.tree_of(position).map(|(_, height)| height)
+    }
+
+    /// Merges performed when adding `leaf`, as (height, left, right) child node values.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Leaf content to add.
+    fn merges(&self, leaf: &str) -> Vec<(usize, Option<String>, Option<String>)> {
+        let mut merges = Vec::new();
+        let mut node = Some(Hash::hash(leaf));
+        let mut roots = self.roots.iter().rev();
+        let mut height = 0;
+        while self.num_of_leaves >> height & 1 == 1 {
+            let left = roots.next().unwrap().to_owned();
+            let merged = parent(left.as_ref(), node.as_ref());
+            merges.push((height, left, node));
+            node = merged;
+            height += 1;
+        }
+        merges
+    }
+
+    /// Add a leaf. Its position is the number of leaves added before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Leaf content to add.
+    pub fn add(&mut self, leaf: &str) {
+        let merges = self.merges(leaf);
+        let root = match merges.last() {
+            Some((_, left, right)) => parent(left.as_ref(), right.as_ref()),
+            None => Some(Hash::hash(leaf)),
+        };
+        self.roots.truncate(self.roots.len() - merges.len());
+        self.roots.push(root);
+        self.num_of_leaves += 1;
+    }
+
+    /// Verify that `leaf` is present at the position given by `proof`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Leaf content.
+    /// * `proof` - Membership proof.
+    pub fn verify(&self, leaf: &str, proof: &AccumulatorProof) -> bool {
+        match self.tree_of(proof.position) {
+            Some((index, height)) => {
+                proof.hashes.len() == height
+                    && self.roots[index].is_some()
+                    && proof.root_from(Some(Hash::hash(leaf))) == self.roots[index]
+            }
+            None => false,
+        }
+    }
+
+    /// Delete `leaf`. Return false, leaving the accumulator unchanged, if `proof` does not
+    /// verify.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Leaf content.
+    /// * `proof` - Membership proof of the leaf.
+    pub fn delete(&mut self, leaf: &str, proof: &AccumulatorProof) -> bool {
+        if !self.verify(leaf, proof) {
+            return false;
+        }
+        let (index, _) = self.tree_of(proof.position).unwrap();
+        self.roots[index] = proof.root_from(None);
+        true
+    }
+}
+
+/// Accumulator together with all leaves, able to produce membership proofs.
+#[derive(Debug, Clone, Default)]
+pub struct Forest {
+    /// Leaf node values by position, None once deleted.
+    leaves: Vec<Option<String>>,
+
+    /// Accumulator state.
+    accumulator: Accumulator,
+}
+
+impl Forest {
+    /// Create an empty forest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accumulator state.
+    pub fn accumulator(&self) -> &Accumulator {
+        &self.accumulator
+    }
+
+    /// Add a leaf and return its position.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Leaf content to add.
+    pub fn add(&mut self, leaf: &str) -> u64 {
+        self.accumulator.add(leaf);
+        self.leaves.push(Some(Hash::hash(leaf)));
+        self.accumulator.num_of_leaves - 1
+    }
+
+    /// Generate a membership proof for the leaf at `position`. Return None if it is out of
+    /// range or deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Leaf position.
+    pub fn proof(&self, position: u64) -> Option<AccumulatorProof> {
+        let (_, height) = self.accumulator.tree_of(position)?;
+        self.leaves[position as usize].as_ref()?;
+        let start = (position >> height << height) as usize;
+        let mut level: Vec<Option<String>> = self.leaves[start..start + (1 << height)].to_vec();
+        let mut index = (position as usize) - start;
+        let mut hashes = Vec::with_capacity(height);
+        while level.len() > 1 {
+            hashes.push(level[index ^ 1].to_owned());
+            level = level
+                .chunks(2)
+                .map(|pair| parent(pair[0].as_ref(), pair[1].as_ref()))
+                .collect();
+            index /= 2;
+        }
+        Some(AccumulatorProof { position, hashes })
+    }
+
+    /// Delete the leaf at `position`. Return false if it is out of range or already deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Leaf position.
+    pub fn delete(&mut self, position: u64) -> bool {
+        let Some(proof) = self.proof(position) else {
+            return false;
+        };
+        let leaf = self.leaves[position as usize].take().unwrap();
+        let (index, _) = self.accumulator.tree_of(position).unwrap();
+        debug_assert_eq!(proof.root_from(Some(leaf)), self.accumulator.roots[index]);
+        self.accumulator.roots[index] = proof.root_from(None);
+        true
+    }
+}
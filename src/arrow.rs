@@ -0,0 +1,115 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Merkle tree over the rows of an Apache Arrow `RecordBatch`.
+//!
+//! Every row is encoded into a canonical leaf, so a dataset can be committed to with a root
+//! and every row proven with an ordinary `MerkleProof`. Columns are encoded in canonical
+//! order, sorted by field name, so reordering the columns of a schema keeps the root.
+//! Every column contributes its name, its data type and its value, each prefixed by its length:
+//! `{len}:{name}{len}:{type}` followed by `-` for a null or `={len}:{value}`.
+//! Integers are in decimal, floats are the hex of their IEEE 754 bits, booleans are
+//! `true` or `false`, strings are kept and binary values are in hex.
+use crate::{hex, MerkleError, MerkleTree};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, RecordBatch};
+use arrow_schema::DataType;
+
+/// Return the canonical encoding of a value of a column, or None if it is null.
+/// Fail with `MerkleError::UnsupportedColumn` if its data type has no canonical encoding.
+///
+/// # Arguments
+///
+/// * `name` - Field name of the column.
+/// * `column` - Column of a record batch.
+/// * `row` - Index of the row.
+fn value(name: &str, column: &dyn Array, row: usize) -> Result<Option<String>, MerkleError> {
+    if column.is_null(row) {
+        return Ok(None);
+    }
+    Ok(Some(match column.data_type() {
+        DataType::Boolean => column.as_boolean().value(row).to_string(),
+        DataType::Int8 => column.as_primitive::<Int8Type>().value(row).to_string(),
+        DataType::Int16 => column.as_primitive::<Int16Type>().value(row).to_string(),
+        DataType::Int32 => column.as_primitive::<Int32Type>().value(row).to_string(),
+        DataType::Int64 => column.as_primitive::<Int64Type>().value(row).to_string(),
+        DataType::UInt8 => column.as_primitive::<UInt8Type>().value(row).to_string(),
+        DataType::UInt16 => column.as_primitive::<UInt16Type>().value(row).to_string(),
+        DataType::UInt32 => column.as_primitive::<UInt32Type>().value(row).to_string(),
+        DataType::UInt64 => column.as_primitive::<UInt64Type>().value(row).to_string(),
+        DataType::Float32 => {
+            format!(
+                "{:08x}",
+                column.as_primitive::<Float32Type>().value(row).to_bits()
+            )
+        }
+        DataType::Float64 => {
+            format!(
+                "{:016x}",
+                column.as_primitive::<Float64Type>().value(row).to_bits()
+            )
+        }
+        DataType::Utf8 => column.as_string::<i32>().value(row).to_owned(),
+        DataType::LargeUtf8 => column.as_string::<i64>().value(row).to_owned(),
+        DataType::Binary => hex::encode(column.as_binary::<i32>().value(row)),
+        DataType::LargeBinary => hex::encode(column.as_binary::<i64>().value(row)),
+        _ => return Err(MerkleError::UnsupportedColumn(name.to_owned())),
+    }))
+}
+
+/// Return the canonical leaf of a row of a record batch.
+/// Fail with `MerkleError::UnsupportedColumn` if a column has no canonical encoding.
+///
+/// # Arguments
+///
+/// * `batch` - Record batch.
+/// * `row` - Index of the row, less than the number of rows of `batch`.
+pub fn row_leaf(batch: &RecordBatch, row: usize) -> Result<String, MerkleError> {
+    let schema = batch.schema();
+    let mut columns: Vec<usize> = (0..batch.num_columns()).collect();
+    columns.sort_by(|a, b| schema.field(*a).name().cmp(schema.field(*b).name()));
+
+    let mut leaf = String::new();
+    for index in columns {
+        let field = schema.field(index);
+        let data_type = field.data_type().to_string();
+        leaf.push_str(&format!(
+            "{}:{}{}:{}",
+            field.name().len(),
+            field.name(),
+            data_type.len(),
+            data_type
+        ));
+        match value(field.name(), batch.column(index), row)? {
+            None => leaf.push('-'),
+            Some(value) => leaf.push_str(&format!("={}:{}", value.len(), value)),
+        }
+    }
+    Ok(leaf)
+}
+
+/// Return the canonical leaves of every row of a record batch, in row order.
+/// Fail with `MerkleError::UnsupportedColumn` if a column has no canonical encoding.
+///
+/// # Arguments
+///
+/// * `batch` - Record batch.
+pub fn leaves(batch: &RecordBatch) -> Result<Vec<String>, MerkleError> {
+    (0..batch.num_rows())
+        .map(|row| row_leaf(batch, row))
+        .collect()
+}
+
+/// Build a merkle tree whose leaves are the rows of a record batch, in row order, so the
+/// proof of a row is `tree.proof(row)`. To configure the tree, pass `leaves(batch)` to a
+/// `MerkleTreeBuilder`; with `LeafOrdering::SortedByHash` rows no longer keep their index.
+/// Fail with `MerkleError::UnsupportedColumn` if a column has no canonical encoding.
+///
+/// # Arguments
+///
+/// * `batch` - Record batch.
+pub fn tree(batch: &RecordBatch) -> Result<MerkleTree, MerkleError> {
+    Ok(MerkleTree::from_leaves(&leaves(batch)?))
+}
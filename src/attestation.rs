@@ -0,0 +1,79 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! in-toto attestations of file sets, so supply-chain pipelines (SLSA) can emit the root of a
+//! `manifest::Manifest` as evidence alongside the digests of the files it covers.
+//!
+//! The statement follows in-toto Statement v1: every file of the manifest is a subject with
+//! its digest, and the predicate records the root and how the tree was built, so a verifier
+//! can rebuild the tree from the subjects and compare roots.
+use crate::json_tree::quote;
+use crate::manifest::Manifest;
+use crate::{HashAlgorithm, MerkleError};
+
+/// Type of an in-toto Statement v1.
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+
+/// Type of the predicate of statements made by `statement`.
+pub const PREDICATE_TYPE: &str = "https://github.com/elliotwutingfeld/merkle-tree/attestation/v1";
+
+/// Return the name of a hash function in in-toto digest sets, or None if it has none.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+fn digest_name(algorithm: HashAlgorithm) -> Option<&'static str> {
+    match algorithm {
+        HashAlgorithm::Sha256 => Some("sha256"),
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_256 => Some("sha3_256"),
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_512 => Some("sha3_512"),
+        #[cfg(feature = "ripemd")]
+        HashAlgorithm::Ripemd160 => Some("ripemd160"),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Wrap the root of a manifest into an in-toto Statement v1 as JSON, with one subject per
+/// file and a predicate of type `PREDICATE_TYPE`:
+///
+/// ```text
+/// {"root": <hexdigest>, "algorithm": <digest name>, "leafCount": <number of files>,
+///  "leafFormat": "<hexdigest>  <name>", "concatenation": "hex", "padding": "promote"}
+/// ```
+///
+/// Fail with `MerkleError::UnsupportedScheme` if the hash function has no name in in-toto
+/// digest sets (Keccak-256 and Hash160).
+///
+/// # Arguments
+///
+/// * `manifest` - Manifest of the file set.
+pub fn statement(manifest: &Manifest) -> Result<String, MerkleError> {
+    let algorithm = digest_name(manifest.algorithm).ok_or(MerkleError::UnsupportedScheme)?;
+    let subjects: Vec<String> = manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "{{\"name\":{},\"digest\":{{\"{}\":\"{}\"}}}}",
+                quote(&entry.name),
+                algorithm,
+                entry.digest
+            )
+        })
+        .collect();
+    let predicate = format!(
+        "{{\"root\":\"{}\",\"algorithm\":\"{}\",\"leafCount\":{},\
+         \"leafFormat\":\"<hexdigest>  <name>\",\"concatenation\":\"hex\",\"padding\":\"promote\"}}",
+        manifest.root,
+        algorithm,
+        manifest.entries.len()
+    );
+    Ok(format!(
+        "{{\"_type\":\"{}\",\"subject\":[{}],\"predicateType\":\"{}\",\"predicate\":{}}}",
+        STATEMENT_TYPE,
+        subjects.join(","),
+        PREDICATE_TYPE,
+        predicate
+    ))
+}
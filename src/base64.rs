@@ -0,0 +1,96 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+
+/// Standard base64 alphabet.
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// URL and filename safe base64 alphabet.
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode bytes as base64 of `alphabet`, padded with `=` if `padded` is set.
+fn encode_with(bytes: &[u8], alphabet: &[u8; 64], padded: bool) -> String {
+    let mut value = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let mut bits = [0; 4];
+        bits[1..1 + group.len()].copy_from_slice(group);
+        let bits = u32::from_be_bytes(bits);
+        for index in 0..4 {
+            if index <= group.len() {
+                value.push(alphabet[(bits >> (18 - 6 * index) & 63) as usize] as char);
+            } else if padded {
+                value.push('=');
+            }
+        }
+    }
+    value
+}
+
+/// Decode base64 of `alphabet` into bytes, padded with `=` if `padded` is set. Return None if
+/// it is not valid, including when unused bits of the last group are set, so every byte
+/// string has exactly one encoding.
+fn decode_with(value: &str, alphabet: &[u8; 64], padded: bool) -> Option<Vec<u8>> {
+    let mut value = value.as_bytes();
+    if padded {
+        if value.len() % 4 != 0 {
+            return None;
+        }
+        value = value.strip_suffix(b"==").unwrap_or(value);
+        value = value.strip_suffix(b"=").unwrap_or(value);
+    }
+    if value.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(value.len() / 4 * 3 + 2);
+    for group in value.chunks(4) {
+        let mut bits = 0;
+        for byte in group {
+            bits = bits << 6 | alphabet.iter().position(|c| c == byte)? as u32;
+        }
+        let missing = 4 - group.len();
+        bits <<= 6 * missing;
+        if bits & ((1 << (8 * missing)) - 1) != 0 {
+            return None;
+        }
+        bytes.extend_from_slice(&bits.to_be_bytes()[1..4 - missing]);
+    }
+    Some(bytes)
+}
+
+/// Encode bytes as a padded string of the standard base64 alphabet.
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes to encode.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    encode_with(bytes, STANDARD, true)
+}
+
+/// Decode a padded string of the standard base64 alphabet into bytes. Return None if it is
+/// not valid base64, including when unused bits of the last group are set, so every byte
+/// string has exactly one encoding.
+///
+/// # Arguments
+///
+/// * `value` - Base64 string to decode.
+pub(crate) fn decode(value: &str) -> Option<Vec<u8>> {
+    decode_with(value, STANDARD, true)
+}
+
+/// Encode bytes as unpadded base64 of the URL and filename safe alphabet, which needs no
+/// escaping in HTTP headers, URL paths and query parameters.
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes to encode.
+pub(crate) fn encode_url_safe(bytes: &[u8]) -> String {
+    encode_with(bytes, URL_SAFE, false)
+}
+
+/// Decode unpadded base64 of the URL and filename safe alphabet into bytes. Return None if it
+/// is not valid like `decode`.
+///
+/// # Arguments
+///
+/// * `value` - Base64 string to decode.
+pub(crate) fn decode_url_safe(value: &str) -> Option<Vec<u8>> {
+    decode_with(value, URL_SAFE, false)
+}
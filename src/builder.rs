@@ -0,0 +1,599 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::chunked::Chunked;
+use crate::provider::{digest, BulkHasher, CustomDigest};
+use crate::{hex, telemetry, truncate};
+use crate::{
+    Concatenation, HashAlgorithm, LazyTree, LeafEncoding, LeafOrdering, MerkleError, MerkleTree,
+    Padding, StreamingProver,
+};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// Bounds on the leaves accepted at construction, checked leaf by leaf so that leaves
+/// consumed from an iterator are refused at the first violation.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LeafLimits {
+    max_leaves: Option<usize>,
+    max_leaf_size: Option<usize>,
+}
+
+impl LeafLimits {
+    /// Fail with `MerkleError::TooManyLeaves` if the leaf at `leaf_index` is past the
+    /// maximum number of leaves, with the number of leaves seen so far, or with
+    /// `MerkleError::LeafTooLarge` if it is longer than the maximum leaf size.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf.
+    /// * `leaf` - Content of leaf.
+    pub(crate) fn check(&self, leaf_index: usize, leaf: &str) -> Result<(), MerkleError> {
+        if self.max_leaves.is_some_and(|max| leaf_index >= max) {
+            return Err(MerkleError::TooManyLeaves(leaf_index + 1));
+        }
+        if self.max_leaf_size.is_some_and(|max| leaf.len() > max) {
+            return Err(MerkleError::LeafTooLarge(leaf_index));
+        }
+        Ok(())
+    }
+}
+
+/// Configure how a `MerkleTree` is constructed. Created with `MerkleTree::builder`.
+///
+/// ```
+/// use merkle_tree::{Concatenation, MerkleTree};
+///
+/// let leaves: Vec<String> = vec!["abc".to_string(), "bcd".to_string()];
+/// let tree = MerkleTree::builder()
+///     .concatenation(Concatenation::Bytes)
+///     .build(&leaves);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MerkleTreeBuilder {
+    concatenation: Concatenation,
+    padding: Padding,
+    ordering: LeafOrdering,
+    comparator: Option<fn(&str, &str) -> Ordering>,
+    truncation: Option<usize>,
+    algorithm: HashAlgorithm,
+    leaf_encoding: LeafEncoding,
+    reject_empty_leaves: bool,
+    limits: LeafLimits,
+    blinding_key: Option<[u8; 32]>,
+}
+
+impl MerkleTreeBuilder {
+    /// Set how child hashes are concatenated before hashing them into their parent.
+    /// Defaults to `Concatenation::Hex`.
+    ///
+    /// # Arguments
+    ///
+    /// * `concatenation` - Concatenation encoding.
+    pub fn concatenation(mut self, concatenation: Concatenation) -> Self {
+        self.concatenation = concatenation;
+        self
+    }
+
+    /// Set how the last node of a level with an odd number of nodes is combined into the
+    /// upper level. Defaults to `Padding::Promote`.
+    ///
+    /// # Arguments
+    ///
+    /// * `padding` - Padding rule.
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set how leaves are ordered before construction. Defaults to `LeafOrdering::Identity`.
+    /// `LeafOrdering::Custom` keeps leaves in the order given, and only records that the
+    /// caller ordered them; use `custom_ordering` to have them sorted by a comparator.
+    ///
+    /// # Arguments
+    ///
+    /// * `ordering` - Leaf ordering.
+    pub fn ordering(mut self, ordering: LeafOrdering) -> Self {
+        self.ordering = ordering;
+        self.comparator = None;
+        self
+    }
+
+    /// Sort leaves with a custom comparator before construction. The sort is stable.
+    /// The tree records its ordering as `LeafOrdering::Custom`.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - Comparator of leaf contents.
+    pub fn custom_ordering(mut self, comparator: fn(&str, &str) -> Ordering) -> Self {
+        self.ordering = LeafOrdering::Custom;
+        self.comparator = Some(comparator);
+        self
+    }
+
+    /// Set the hash function for leaves and parents. Defaults to `HashAlgorithm::Sha256`.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    pub fn algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Truncate every node value, leaves and parents alike, to its first `num_of_bytes` bytes,
+    /// as protocols using shortened digests require. Defaults to full digests. Truncating to
+    /// the digest size of the hash function or more keeps full digests.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_of_bytes` - Number of bytes to keep, at least 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_of_bytes` is 0.
+    pub fn truncation(mut self, num_of_bytes: usize) -> Self {
+        assert!(num_of_bytes > 0, "truncation must be at least 1 byte");
+        self.truncation = Some(num_of_bytes);
+        self
+    }
+
+    /// Blind every leaf by hashing its content followed by a blinder, the SHA-256 hexdigest of
+    /// `key` followed by the content. Proofs carry the blinder of their own leaf only, so
+    /// publishing a proof does not let third parties brute-force the content of low-entropy
+    /// sibling leaves. Keep `key` secret; equal leaves share a blinder.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Secret blinding key.
+    pub fn blinding(mut self, key: [u8; 32]) -> Self {
+        self.blinding_key = Some(key);
+        self
+    }
+
+    /// Hash with the digest `D`, such as any hash function of the RustCrypto ecosystem,
+    /// instead of a built-in `HashAlgorithm`. Same as
+    /// `algorithm(HashAlgorithm::Custom(CustomDigest::new::<D>()))`.
+    pub fn hasher<D: digest::Digest>(self) -> Self {
+        self.algorithm(HashAlgorithm::Custom(CustomDigest::new::<D>()))
+    }
+
+    /// Set how leaf contents encode the bytes hashed into leaf node values. Defaults to
+    /// `LeafEncoding::Utf8`. Contents not valid in the encoding are refused at construction,
+    /// and appending one panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_encoding` - Leaf encoding.
+    pub fn leaf_encoding(mut self, leaf_encoding: LeafEncoding) -> Self {
+        self.leaf_encoding = leaf_encoding;
+        self
+    }
+
+    /// Refuse empty leaves at construction, for protocols where empty content indicates a bug.
+    /// By default empty leaves are allowed and hashed as the digest of zero bytes.
+    /// Leaves appended to the built tree are not checked.
+    pub fn reject_empty_leaves(mut self) -> Self {
+        self.reject_empty_leaves = true;
+        self
+    }
+
+    /// Refuse more than `num_of_leaves` leaves at construction, so services building trees
+    /// from untrusted batches fail before allocating for them. Unlimited by default.
+    /// Leaves appended to the built tree are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_of_leaves` - Maximum number of leaves.
+    pub fn max_leaves(mut self, num_of_leaves: usize) -> Self {
+        self.limits.max_leaves = Some(num_of_leaves);
+        self
+    }
+
+    /// Refuse leaves longer than `num_of_bytes` bytes at construction. Unlimited by default.
+    /// Leaves appended to the built tree are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_of_bytes` - Maximum length of a leaf in bytes.
+    pub fn max_leaf_size(mut self, num_of_bytes: usize) -> Self {
+        self.limits.max_leaf_size = Some(num_of_bytes);
+        self
+    }
+
+    /// Build a merkle tree from leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a leaf is empty and `reject_empty_leaves` is set, if a leaf is not valid in
+    /// the leaf encoding, if the leaves exceed `max_leaves` or `max_leaf_size`, or if the
+    /// padding constant is not a hexdigest of the node size. Use `try_build` to handle it.
+    pub fn build(&self, leaves: &[String]) -> MerkleTree {
+        match self.try_build(leaves) {
+            Ok(tree) => tree,
+            Err(MerkleError::EmptyLeaf(index)) => panic!("leaf {index} is empty"),
+            Err(MerkleError::InvalidLeafEncoding(index)) => {
+                panic!("leaf {index} is not valid {:?}", self.leaf_encoding)
+            }
+            Err(MerkleError::TooManyLeaves(num_of_leaves)) => {
+                panic!("{num_of_leaves} leaves exceed the maximum number of leaves")
+            }
+            Err(MerkleError::LeafTooLarge(index)) => {
+                panic!("leaf {index} exceeds the maximum leaf size")
+            }
+            Err(MerkleError::InvalidEncoding) => {
+                panic!("padding constant is not a hexdigest of the node size")
+            }
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Build a merkle tree from leaves. Fail with `MerkleError::EmptyLeaf` if a leaf is empty
+    /// and `reject_empty_leaves` is set, with `MerkleError::InvalidLeafEncoding` if a leaf is not
+    /// valid in the leaf encoding, with `MerkleError::TooManyLeaves` if there are more
+    /// leaves than `max_leaves`, with `MerkleError::LeafTooLarge` if a leaf is longer than
+    /// `max_leaf_size`, or with `MerkleError::InvalidEncoding` if the padding constant is not
+    /// a lowercase hexdigest of the node size, the digest size or the truncation if shorter.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    pub fn try_build(&self, leaves: &[String]) -> Result<MerkleTree, MerkleError> {
+        self.check_leaves(leaves)?;
+        let mut tree = self.empty_tree(leaves.len());
+        let leaves = self.hashed_leaves(&tree, leaves);
+
+        let mut level = Chunked::with_capacity(leaves.len());
+        for (hash, leaf) in leaves {
+            tree.leaves.push(leaf.into());
+            level.push(hash);
+        }
+
+        while level.len() > 1 {
+            let mut parents = Chunked::with_capacity(level.len().div_ceil(2));
+            // Iterate through sibling-pairs on the same level.
+            for i in (0..level.len()).step_by(2) {
+                parents.push(tree.parent_value(level.get(i).unwrap(), level.get(i + 1)));
+            }
+            tree.levels.push(level);
+            level = parents;
+        }
+        if level.len() == 1 {
+            tree.levels.push(level);
+        }
+        tree.debug_check_tree();
+        telemetry::leaves_appended(tree.num_of_leaves());
+        Ok(tree)
+    }
+
+    /// Build a merkle tree from leaves consumed in order from an iterator. Fails like
+    /// `try_build`, but `max_leaves` and `max_leaf_size` are checked as each leaf is
+    /// consumed, so an unbounded iterator is refused at the first leaf past a limit rather
+    /// than collected in full. `MerkleError::TooManyLeaves` then holds the number of leaves
+    /// consumed, one more than `max_leaves`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    pub fn try_build_iter<I, S>(&self, leaves: I) -> Result<MerkleTree, MerkleError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut collected = Vec::new();
+        for (leaf_index, leaf) in leaves.into_iter().enumerate() {
+            let leaf = leaf.into();
+            self.limits.check(leaf_index, &leaf)?;
+            collected.push(leaf);
+        }
+        self.try_build(&collected)
+    }
+
+    /// Build a merkle tree from leaves like `try_build`, but hash all leaves, then every level
+    /// of parents, in one batch each with `hasher`. The tree is identical to the one
+    /// `try_build` returns. Fails like `try_build`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    /// * `hasher` - Bulk hasher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hasher` does not implement the hash function, or returns another number of
+    /// digests than messages.
+    pub fn try_build_bulk(
+        &self,
+        leaves: &[String],
+        hasher: &dyn BulkHasher,
+    ) -> Result<MerkleTree, MerkleError> {
+        self.check_leaves(leaves)?;
+        let mut tree = self.empty_tree(leaves.len());
+        let preimages: Vec<Vec<u8>> = leaves.iter().map(|leaf| tree.leaf_preimage(leaf)).collect();
+        let values = self.hash_batch(&tree, hasher, &preimages);
+        let leaves = self.ordered(values.into_iter().zip(leaves.iter().cloned()).collect());
+
+        let mut level = Chunked::with_capacity(leaves.len());
+        for (hash, leaf) in leaves {
+            tree.leaves.push(leaf.into());
+            level.push(hash);
+        }
+
+        while level.len() > 1 {
+            let mut preimages = Vec::with_capacity(level.len().div_ceil(2));
+            let mut promoted = None;
+            for i in (0..level.len()).step_by(2) {
+                let left = level.get(i).unwrap();
+                match level
+                    .get(i + 1)
+                    .cloned()
+                    .or_else(|| tree.padding_sibling(left))
+                {
+                    Some(right) => preimages.push(
+                        tree.concatenation
+                            .preimage(left, &right)
+                            .ok_or(MerkleError::InvalidEncoding)?,
+                    ),
+                    None => promoted = Some(left.to_owned()),
+                }
+            }
+            let mut parents = Chunked::with_capacity(level.len().div_ceil(2));
+            for value in self.hash_batch(&tree, hasher, &preimages) {
+                parents.push(value);
+            }
+            if let Some(value) = promoted {
+                parents.push(value);
+            }
+            tree.levels.push(level);
+            level = parents;
+        }
+        if level.len() == 1 {
+            tree.levels.push(level);
+        }
+        tree.debug_check_tree();
+        telemetry::leaves_appended(tree.num_of_leaves());
+        Ok(tree)
+    }
+
+    /// Hash a batch of messages with a bulk hasher into truncated node values.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - Empty tree with this configuration.
+    /// * `hasher` - Bulk hasher.
+    /// * `messages` - Messages to hash.
+    fn hash_batch(
+        &self,
+        scheme: &MerkleTree,
+        hasher: &dyn BulkHasher,
+        messages: &[Vec<u8>],
+    ) -> Vec<String> {
+        let digests = hasher
+            .hash_batch(scheme.algorithm, messages)
+            .unwrap_or_else(|| panic!("bulk hasher does not implement {:?}", scheme.algorithm));
+        assert_eq!(
+            digests.len(),
+            messages.len(),
+            "bulk hasher returned {} digests for {} messages",
+            digests.len(),
+            messages.len()
+        );
+        digests
+            .iter()
+            .map(|digest| truncate(hex::encode(digest), scheme.truncation))
+            .collect()
+    }
+
+    /// Restore a merkle tree from its leaves and the node values returned by
+    /// `MerkleTree::levels`, without recomputing hashes outside of debug builds. The leaves
+    /// are taken in the order given; the recorded ordering is not reapplied. Empty leaves whose
+    /// node value is the tombstone, as `MerkleTree::levels` returns removed leaves, are
+    /// restored as removed. Fail with `MerkleError::InvalidEncoding` if the levels do not
+    /// have the shape of a tree over the leaves or, in debug builds, if a node does not hold
+    /// the value of its leaf or children, with `MerkleError::EmptyLeaf` if a leaf that is not
+    /// removed is empty and `reject_empty_leaves` is set, or like `try_build` if the leaves
+    /// exceed `max_leaves` or `max_leaf_size` or are not valid in the leaf encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree, in tree order.
+    /// * `levels` - Node values of every level as hexdigests, from the leaf level up.
+    pub fn build_from_levels(
+        &self,
+        leaves: &[String],
+        levels: Vec<Vec<String>>,
+    ) -> Result<MerkleTree, MerkleError> {
+        self.check_contents(leaves)?;
+        let mut expected = leaves.len();
+        for level in &levels {
+            if expected == 0 || level.len() != expected {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            expected = if expected == 1 {
+                0
+            } else {
+                expected.div_ceil(2)
+            };
+        }
+        if expected != 0 {
+            return Err(MerkleError::InvalidEncoding);
+        }
+
+        let mut tree = self.empty_tree(leaves.len());
+        for values in levels {
+            let mut level = Chunked::with_capacity(values.len());
+            for value in values {
+                level.push(value);
+            }
+            tree.levels.push(level);
+        }
+        let tombstone = tree.tombstone();
+        tree.removed = Rc::new(
+            (0..leaves.len())
+                .filter(|index| {
+                    leaves[*index].is_empty() && *tree.levels[0].get(*index).unwrap() == tombstone
+                })
+                .collect(),
+        );
+        if self.reject_empty_leaves {
+            if let Some(index) = (0..leaves.len())
+                .position(|index| leaves[index].is_empty() && !tree.is_removed(index))
+            {
+                return Err(MerkleError::EmptyLeaf(index));
+            }
+        }
+        for leaf in leaves {
+            tree.leaves.push(leaf.to_owned().into());
+        }
+        // The levels come from the caller, so a mismatch is an error rather than a bug.
+        if cfg!(debug_assertions) {
+            tree.check_tree()?;
+        }
+        Ok(tree)
+    }
+
+    /// Return a prover that generates proofs for `targets` during a single pass over the leaves,
+    /// as trees with this configuration would. The leaves are taken in the order pushed; the
+    /// ordering is not applied, and empty leaves are not rejected. `max_leaves` and
+    /// `max_leaf_size` are checked as each leaf is pushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets` - 0-based indices of leaves to prove.
+    pub fn streaming_prover(&self, targets: &[usize]) -> StreamingProver {
+        StreamingProver::with_scheme(self.empty_tree(0), targets).with_limits(self.limits)
+    }
+
+    /// Build a tree that stores only the leaves and their hashes, and computes inner nodes
+    /// when first needed. Fails like `try_build`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    pub fn build_lazy(&self, leaves: &[String]) -> Result<LazyTree, MerkleError> {
+        self.check_leaves(leaves)?;
+        let scheme = self.empty_tree(0);
+        let leaves = self.hashed_leaves(&scheme, leaves);
+        telemetry::leaves_appended(leaves.len());
+        Ok(LazyTree::with_scheme(scheme, leaves))
+    }
+
+    /// Fail if a leaf is empty and `reject_empty_leaves` is set, if a leaf is not valid in the
+    /// leaf encoding, or if the leaves exceed `max_leaves` or `max_leaf_size`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    fn check_leaves(&self, leaves: &[String]) -> Result<(), MerkleError> {
+        self.check_contents(leaves)?;
+        if self.reject_empty_leaves {
+            if let Some(index) = leaves.iter().position(|leaf| leaf.is_empty()) {
+                return Err(MerkleError::EmptyLeaf(index));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the leaf values and contents of leaves, in the configured order.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - Empty tree with this configuration.
+    /// * `leaves` - Leaves of merkle tree.
+    fn hashed_leaves(&self, scheme: &MerkleTree, leaves: &[String]) -> Vec<(String, String)> {
+        self.ordered(
+            leaves
+                .iter()
+                .map(|leaf| (scheme.leaf_value(leaf), leaf.to_owned()))
+                .collect(),
+        )
+    }
+
+    /// Sort leaves in the configured order.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaf values and contents.
+    fn ordered(&self, mut leaves: Vec<(String, String)>) -> Vec<(String, String)> {
+        match (self.ordering, self.comparator) {
+            (LeafOrdering::SortedByHash, _) => leaves.sort(),
+            (LeafOrdering::Custom, Some(comparator)) => {
+                leaves.sort_by(|(_, a), (_, b)| comparator(a, b))
+            }
+            _ => {}
+        }
+        leaves
+    }
+
+    /// Fail if the leaves exceed `max_leaves` or `max_leaf_size`, if a leaf is not valid in
+    /// the leaf encoding, or if the padding is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    fn check_contents(&self, leaves: &[String]) -> Result<(), MerkleError> {
+        self.check_padding()?;
+        if self.limits.max_leaves.is_some_and(|max| leaves.len() > max) {
+            return Err(MerkleError::TooManyLeaves(leaves.len()));
+        }
+        if let Some(max) = self.limits.max_leaf_size {
+            if let Some(index) = leaves.iter().position(|leaf| leaf.len() > max) {
+                return Err(MerkleError::LeafTooLarge(index));
+            }
+        }
+        if let Some(index) = leaves
+            .iter()
+            .position(|leaf| self.leaf_encoding.decode(leaf).is_none())
+        {
+            return Err(MerkleError::InvalidLeafEncoding(index));
+        }
+        Ok(())
+    }
+
+    /// Fail with `MerkleError::InvalidEncoding` if the padding is a constant that is not a
+    /// lowercase hexdigest of the node size. Any other constant gives roots that other
+    /// implementations, which pad with node values, cannot reproduce.
+    fn check_padding(&self) -> Result<(), MerkleError> {
+        let Padding::Constant(value) = &self.padding else {
+            return Ok(());
+        };
+        let digest_size = self.algorithm.digest_size();
+        let node_size = self.truncation.map_or(digest_size, |n| n.min(digest_size));
+        if value.len() != 2 * node_size
+            || !value
+                .bytes()
+                .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+        {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    /// Limits on the leaves accepted at construction.
+    #[cfg(feature = "import")]
+    pub(crate) fn limits(&self) -> LeafLimits {
+        self.limits
+    }
+
+    /// Return a tree with this configuration and no leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_leaves` - Number of leaves to preallocate for.
+    fn empty_tree(&self, expected_leaves: usize) -> MerkleTree {
+        MerkleTree {
+            concatenation: self.concatenation,
+            padding: self.padding.to_owned(),
+            ordering: self.ordering,
+            truncation: self
+                .truncation
+                .filter(|num_of_bytes| *num_of_bytes < self.algorithm.digest_size()),
+            algorithm: self.algorithm,
+            leaf_encoding: self.leaf_encoding,
+            blinding_key: self.blinding_key,
+            ..MerkleTree::with_capacity(expected_leaves)
+        }
+    }
+}
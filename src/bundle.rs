@@ -0,0 +1,102 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::codec::{Artifact, Decoder, Encoder};
+use crate::{CommittedRoot, MerkleError, MerkleProof, MerkleTree};
+
+/// Single artifact holding a published root and proofs of leaves under it, so an offline
+/// auditor can check every proof with one call to `verify_bundle`.
+pub struct ExportBundle {
+    /// Published root, with the hash function, scheme and number of leaves of its tree.
+    pub root: CommittedRoot,
+
+    /// Proofs of leaves under the root.
+    pub proofs: Vec<MerkleProof>,
+
+    /// When the bundle was created, in seconds since the Unix epoch. Supplied by the caller,
+    /// so exporting the same proofs twice at the same time gives identical bytes.
+    pub created_at: u64,
+}
+
+impl ExportBundle {
+    /// Export the current root of a tree and the proofs of some of its leaves. Return None if
+    /// the tree has no leaves, or a leaf is out of range or removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - Merkle tree.
+    /// * `leaf_indices` - 0-based indices of leaves to prove, in bundle order.
+    /// * `built_at` - When the tree was built, in seconds since the Unix epoch.
+    /// * `created_at` - When the bundle is created, in seconds since the Unix epoch.
+    pub fn of(
+        tree: &MerkleTree,
+        leaf_indices: &[usize],
+        built_at: u64,
+        created_at: u64,
+    ) -> Option<Self> {
+        Some(ExportBundle {
+            root: CommittedRoot::of(tree, built_at)?,
+            proofs: leaf_indices
+                .iter()
+                .map(|leaf_index| tree.proof(*leaf_index))
+                .collect::<Option<Vec<MerkleProof>>>()?,
+            created_at,
+        })
+    }
+
+    /// Verify every proof against the root. Fail with the error of the first proof that
+    /// does not verify, as `CommittedRoot::verify_proof` reports it.
+    pub fn verify(&self) -> Result<(), MerkleError> {
+        self.proofs
+            .iter()
+            .try_for_each(|proof| self.root.verify_proof(proof))
+    }
+
+    /// Serialize into canonical bytes, after a versioned header: the serialized root, the
+    /// creation time and the serialized proofs in order. Equal bundles always serialize to
+    /// equal bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::ExportBundle, self.root.algorithm);
+        encoder.put_bytes(&self.root.to_bytes());
+        encoder.put_u64(self.created_at);
+        encoder.put_usize(self.proofs.len());
+        for proof in &self.proofs {
+            encoder.put_bytes(&proof.to_bytes());
+        }
+        encoder.finish()
+    }
+
+    /// Deserialize a bundle serialized by `to_bytes`, without verifying its proofs.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized bundle.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::ExportBundle)?;
+        let root = CommittedRoot::from_bytes(decoder.bytes()?)?;
+        let created_at = decoder.u64()?;
+        let proofs = (0..decoder.usize()?)
+            .map(|_| MerkleProof::from_bytes(decoder.bytes()?))
+            .collect::<Result<Vec<MerkleProof>, MerkleError>>()?;
+        decoder.finish()?;
+
+        if root.algorithm != algorithm {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(ExportBundle {
+            root,
+            proofs,
+            created_at,
+        })
+    }
+}
+
+/// Deserialize a bundle serialized by `ExportBundle::to_bytes` and verify every proof in it
+/// against its root. Return the bundle if they all verify.
+///
+/// # Arguments
+///
+/// * `bytes` - Serialized bundle.
+pub fn verify_bundle(bytes: &[u8]) -> Result<ExportBundle, MerkleError> {
+    let bundle = ExportBundle::from_bytes(bytes)?;
+    bundle.verify()?;
+    Ok(bundle)
+}
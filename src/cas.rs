@@ -0,0 +1,285 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Content-addressable blob store keyed by merkle roots.
+//!
+//! A blob is split into chunks of `chunk_size` bytes, the last one possibly shorter; an empty
+//! blob is a single empty chunk. The root of a merkle tree whose leaves are the hexdigests of
+//! the chunks, in chunk order, addresses the blob. Chunks are stored under their digest, so
+//! chunks shared by blobs are stored once, and the list of chunk digests is stored under the
+//! root. Everything read back from storage is verified against the root, so the storage
+//! itself need not be trusted. A `Download` verifies chunks fetched from untrusted sources
+//! the same way, one chunk at a time.
+use crate::codec::{Decoder, Encoder};
+use crate::{Hash, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+
+/// Key-value storage of a content-addressable store.
+pub trait Storage {
+    /// Return the value stored under `key`, or None if there is none.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to look up.
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+
+    /// Store `value` under `key`, replacing any previous value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to store under.
+    /// * `value` - Value to store.
+    fn put(&mut self, key: String, value: Vec<u8>);
+}
+
+/// Storage kept in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    values: HashMap<String, Vec<u8>>,
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.values.get(key).cloned()
+    }
+
+    fn put(&mut self, key: String, value: Vec<u8>) {
+        self.values.insert(key, value);
+    }
+}
+
+/// Storage key of the list of chunk digests of the blob with root `root`.
+fn blob_key(root: &str) -> String {
+    format!("blob:{}", root)
+}
+
+/// Storage key of the chunk with hexdigest `digest`.
+fn chunk_key(digest: &str) -> String {
+    format!("chunk:{}", digest)
+}
+
+/// Hexdigest of a chunk.
+fn chunk_digest(chunk: &[u8]) -> String {
+    Hash::hash_reader(chunk).unwrap()
+}
+
+/// Content-addressable blob store keyed by merkle roots.
+#[derive(Debug, Clone)]
+pub struct Cas<S: Storage> {
+    storage: S,
+    chunk_size: usize,
+}
+
+impl<S: Storage> Cas<S> {
+    /// Create a store over `storage`.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - Storage of chunks and chunk lists.
+    /// * `chunk_size` - Number of bytes of every chunk but the last.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn new(storage: S, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be at least 1 byte");
+        Cas {
+            storage,
+            chunk_size,
+        }
+    }
+
+    /// Storage of the store.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Store a blob and return its root as hexdigest.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Content of the blob.
+    pub fn put(&mut self, bytes: &[u8]) -> String {
+        let chunks: Vec<&[u8]> = if bytes.is_empty() {
+            vec![bytes]
+        } else {
+            bytes.chunks(self.chunk_size).collect()
+        };
+        let digests: Vec<String> = chunks.iter().map(|chunk| chunk_digest(chunk)).collect();
+        let root = MerkleTree::merkle_root(&digests).borrow().value.to_owned();
+
+        let mut encoder = Encoder::new();
+        encoder.put_usize(digests.len());
+        for (chunk, digest) in chunks.into_iter().zip(&digests) {
+            encoder.put_str(digest);
+            self.storage.put(chunk_key(digest), chunk.to_vec());
+        }
+        self.storage.put(blob_key(&root), encoder.finish());
+        root
+    }
+
+    /// Return the verified chunk digests of the blob with root `root`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root of the blob, as hexdigest.
+    fn digests(&self, root: &str) -> Option<Vec<String>> {
+        let bytes = self.storage.get(&blob_key(root))?;
+        let mut decoder = Decoder::new(&bytes);
+        let digests = (0..decoder.usize().ok()?)
+            .map(|_| decoder.string().ok())
+            .collect::<Option<Vec<String>>>()?;
+        decoder.finish().ok()?;
+        (!digests.is_empty() && MerkleTree::merkle_root(&digests).borrow().value == root)
+            .then_some(digests)
+    }
+
+    /// Return the verified chunk with hexdigest `digest`.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest` - Hexdigest of the chunk.
+    fn chunk(&self, digest: &str) -> Option<Vec<u8>> {
+        let chunk = self.storage.get(&chunk_key(digest))?;
+        (chunk_digest(&chunk) == digest).then_some(chunk)
+    }
+
+    /// Return the content of the blob with root `root`, or None if it is not stored or the
+    /// stored data does not match the root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root of the blob, as hexdigest.
+    pub fn get(&self, root: &str) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        for digest in self.digests(root)? {
+            bytes.extend(self.chunk(&digest)?);
+        }
+        Some(bytes)
+    }
+
+    /// Return a chunk of the blob with root `root` and the proof of its digest, or None if
+    /// there is no such chunk or the stored data does not match the root.
+    /// Verify them with `verify_chunk`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root of the blob, as hexdigest.
+    /// * `index` - Index of the chunk.
+    pub fn prove_chunk(&self, root: &str, index: usize) -> Option<(Vec<u8>, MerkleProof)> {
+        let digests = self.digests(root)?;
+        let chunk = self.chunk(digests.get(index)?)?;
+        Some((chunk, MerkleTree::merkle_proof(&digests, index)))
+    }
+
+    /// Return the proofs of every chunk digest of the blob with root `root`, in chunk order,
+    /// or None if it is not stored or the stored data does not match the root. A client
+    /// downloading the blob from elsewhere checks chunks against them with a `Download`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root of the blob, as hexdigest.
+    pub fn chunk_proofs(&self, root: &str) -> Option<Vec<MerkleProof>> {
+        Some(MerkleTree::all_proofs(&self.digests(root)?))
+    }
+}
+
+/// Resumable download of a blob whose chunks may arrive in any order, from any source.
+///
+/// Every chunk is checked against the proof of its digest as it arrives, so only verified
+/// chunks are kept, and the indices of the chunks that are still needed are known exactly.
+pub struct Download {
+    proofs: Vec<MerkleProof>,
+    chunks: Vec<Option<Vec<u8>>>,
+    corrupted: BTreeSet<usize>,
+}
+
+impl Download {
+    /// Start a download of the blob with root `root`. Return None unless `proofs` holds, in
+    /// chunk order, one proof per chunk that verifies against `root`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root of the blob, as hexdigest.
+    /// * `proofs` - Proofs of the chunk digests, as returned by `Cas::chunk_proofs`.
+    pub fn new(root: &str, proofs: Vec<MerkleProof>) -> Option<Self> {
+        let root = Rc::new(RefCell::new(Hash::new(root.to_owned())));
+        let num_of_chunks = proofs.len();
+        let valid = num_of_chunks > 0
+            && proofs.iter().enumerate().all(|(index, proof)| {
+                proof.leaf_index == index
+                    && proof.num_of_leaves == num_of_chunks
+                    && MerkleTree::verify_proof(root.to_owned(), proof)
+            });
+        valid.then(|| Download {
+            proofs,
+            chunks: vec![None; num_of_chunks],
+            corrupted: BTreeSet::new(),
+        })
+    }
+
+    /// Number of chunks of the blob.
+    pub fn num_of_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Check a chunk that arrived and keep it if it is the chunk at `index`. Return whether it
+    /// was kept. A chunk that does not match is recorded as corrupted until a matching one
+    /// arrives; once a chunk is kept, later chunks for the same index are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the chunk.
+    /// * `chunk` - Content of the chunk.
+    pub fn receive(&mut self, index: usize, chunk: Vec<u8>) -> bool {
+        match self.chunks.get(index) {
+            Some(None) => {}
+            _ => return false,
+        }
+        if chunk_digest(&chunk) != self.proofs[index].leaf_content {
+            self.corrupted.insert(index);
+            return false;
+        }
+        self.corrupted.remove(&index);
+        self.chunks[index] = Some(chunk);
+        true
+    }
+
+    /// Indices of the chunks not kept yet, corrupted ones included, in ascending order.
+    pub fn missing(&self) -> Vec<usize> {
+        (0..self.chunks.len())
+            .filter(|index| self.chunks[*index].is_none())
+            .collect()
+    }
+
+    /// Indices of the chunks for which only chunks that do not match arrived, in ascending
+    /// order.
+    pub fn corrupted(&self) -> Vec<usize> {
+        self.corrupted.iter().copied().collect()
+    }
+
+    /// Whether every chunk was kept.
+    pub fn is_complete(&self) -> bool {
+        self.chunks.iter().all(Option::is_some)
+    }
+
+    /// Return the content of the blob, or None if chunks are still missing.
+    pub fn into_bytes(self) -> Option<Vec<u8>> {
+        self.chunks
+            .into_iter()
+            .collect::<Option<Vec<Vec<u8>>>>()
+            .map(|chunks| chunks.concat())
+    }
+}
+
+/// Verify that a chunk belongs to the blob with root `root` at the index of `proof`.
+///
+/// # Arguments
+///
+/// * `root` - Root of the blob, as hexdigest.
+/// * `chunk` - Content of the chunk.
+/// * `proof` - Proof of the chunk, as returned by `prove_chunk`.
+pub fn verify_chunk(root: &str, chunk: &[u8], proof: &MerkleProof) -> bool {
+    proof.leaf_content == chunk_digest(chunk)
+        && MerkleTree::verify_proof(Rc::new(RefCell::new(Hash::new(root.to_owned()))), proof)
+}
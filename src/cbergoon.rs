@@ -0,0 +1,118 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Construction mode reproducing the roots and merkle paths of Go's
+//! `github.com/cbergoon/merkletree` with its default sha256 hash strategy.
+//!
+//! Unlike this crate, the last node of an odd level is duplicated and paired with itself
+//! instead of being promoted, a single leaf is also paired with itself, and parents are the
+//! hash of the concatenated raw 32-byte digests rather than of the concatenated hexdigests.
+use crate::{provider, Hash};
+
+/// Size of a sha256 digest in bytes.
+pub const HASH_SIZE: usize = 32;
+
+/// Hash leaf content the way a typical `Content.CalculateHash` implementation does,
+/// i.e. sha256 over the raw bytes.
+///
+/// # Arguments
+///
+/// * `leaf` - Leaf content.
+pub fn leaf_hash(leaf: &[u8]) -> [u8; HASH_SIZE] {
+    provider::sha256(leaf)
+}
+
+/// Build every level below the root, each padded to an even length by duplicating its last node,
+/// arranged from the bottom level (leaves) upwards. Return the levels and the root.
+fn levels(leaf_hashes: &[[u8; HASH_SIZE]]) -> (Vec<Vec<[u8; HASH_SIZE]>>, [u8; HASH_SIZE]) {
+    let mut levels = Vec::new();
+    let mut nodes = leaf_hashes.to_vec();
+    loop {
+        if nodes.len() % 2 != 0 {
+            nodes.push(nodes[nodes.len() - 1]); // Last node is paired with itself.
+        }
+        let parents: Vec<[u8; HASH_SIZE]> = nodes
+            .chunks_exact(2)
+            .map(|pair| Hash::hash_pair_bytes(&pair[0], &pair[1]))
+            .collect();
+        levels.push(nodes);
+        if parents.len() == 1 {
+            return (levels, parents[0]);
+        }
+        nodes = parents;
+    }
+}
+
+/// Compute the root like `MerkleTree.MerkleRoot()`. Return None if there are no leaves.
+///
+/// # Arguments
+///
+/// * `leaf_hashes` - Hashed leaves of merkle tree.
+pub fn root(leaf_hashes: &[[u8; HASH_SIZE]]) -> Option<[u8; HASH_SIZE]> {
+    if leaf_hashes.is_empty() {
+        return None;
+    }
+    Some(levels(leaf_hashes).1)
+}
+
+/// Generate a merkle path like `MerkleTree.GetMerklePath(content)`, arranged from the
+/// bottom-most hash up to the top-most hash (closest to root node). Each index is 1 if the
+/// sibling is a right child, otherwise 0. Return None if `leaf_index` is out of range.
+///
+/// # Arguments
+///
+/// * `leaf_hashes` - Hashed leaves of merkle tree.
+/// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+pub fn merkle_path(
+    leaf_hashes: &[[u8; HASH_SIZE]],
+    mut leaf_index: usize,
+) -> Option<(Vec<[u8; HASH_SIZE]>, Vec<i64>)> {
+    if leaf_index >= leaf_hashes.len() {
+        return None;
+    }
+    let mut path = Vec::new();
+    let mut indexes = Vec::new();
+    for nodes in levels(leaf_hashes).0 {
+        let current = nodes[leaf_index];
+        let left = nodes[leaf_index & !1];
+        // Like the Go implementation, compare by hash: a node whose hash equals its parent's
+        // left child is treated as the left child, even if it is a duplicate.
+        if left == current {
+            path.push(nodes[leaf_index | 1]);
+            indexes.push(1);
+        } else {
+            path.push(left);
+            indexes.push(0);
+        }
+        leaf_index /= 2;
+    }
+    Some((path, indexes))
+}
+
+/// Verify a merkle path produced by `merkle_path` or by `GetMerklePath`.
+///
+/// # Arguments
+///
+/// * `root` - Root of the merkle tree.
+/// * `leaf_hash` - Hashed leaf to be verified.
+/// * `path` - Audit hashes, arranged from the bottom-most hash up to the top-most hash.
+/// * `indexes` - 1 if the corresponding audit hash is a right child, otherwise 0.
+pub fn verify_path(
+    root: &[u8; HASH_SIZE],
+    leaf_hash: &[u8; HASH_SIZE],
+    path: &[[u8; HASH_SIZE]],
+    indexes: &[i64],
+) -> bool {
+    if path.is_empty() || path.len() != indexes.len() {
+        return false;
+    }
+    let result = path
+        .iter()
+        .zip(indexes)
+        .fold(*leaf_hash, |result, (sibling, index)| {
+            if *index == 1 {
+                Hash::hash_pair_bytes(&result, sibling)
+            } else {
+                Hash::hash_pair_bytes(sibling, &result)
+            }
+        });
+    result == *root
+}
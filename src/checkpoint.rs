@@ -0,0 +1,236 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Checkpoints in the signed note format of the Go transparency ecosystem (sumdb, witnesses
+//! and tiled logs), so roots of this crate can be gossiped and cosigned with its tooling.
+//!
+//! A checkpoint is the origin line naming the log, the decimal tree size and the base64 root
+//! hash, each ending with a newline, followed by optional extension lines. A signed note
+//! appends a blank line and one line `— <name> <base64(key hash ‖ signature)>` per signature,
+//! where signatures cover the checkpoint text.
+use crate::cosign::{quorum, Cosignature, WitnessKey};
+use crate::{base64, hex, CommittedRoot, MerkleError};
+
+/// Prefix of every signature line of a signed note: an em dash followed by a space.
+const SIGNATURE_PREFIX: &str = "\u{2014} ";
+
+/// Tree head of a log, as published in the checkpoint format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Unique identifier of the log, conventionally a schema-less URL.
+    pub origin: String,
+
+    /// Number of leaves.
+    pub size: u64,
+
+    /// Root value as hexdigest.
+    pub root: String,
+
+    /// Extension lines after the root hash, without their trailing newlines.
+    pub extensions: Vec<String>,
+}
+
+impl Checkpoint {
+    /// Checkpoint of a published root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Published root.
+    /// * `origin` - Unique identifier of the log.
+    pub fn of(root: &CommittedRoot, origin: &str) -> Self {
+        Checkpoint {
+            origin: origin.to_owned(),
+            size: root.leaf_count as u64,
+            root: root.root.to_owned(),
+            extensions: vec![],
+        }
+    }
+
+    /// Serialize into checkpoint text, which witnesses sign.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the origin or an extension line is empty or has a newline, or if the root is
+    /// not a hexdigest.
+    pub fn to_text(&self) -> String {
+        let root = hex::decode(&self.root).expect("root is not a hexdigest");
+        let mut text = String::new();
+        for line in [&self.origin, &self.size.to_string(), &base64::encode(&root)]
+            .into_iter()
+            .chain(&self.extensions)
+        {
+            assert!(
+                !line.is_empty() && !line.contains('\n'),
+                "checkpoint line is empty or has a newline"
+            );
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Deserialize checkpoint text. Fail with `MerkleError::InvalidEncoding` if it is
+    /// malformed, or if the size is not in canonical decimal form.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Checkpoint text.
+    pub fn from_text(text: &str) -> Result<Self, MerkleError> {
+        let mut lines = text
+            .strip_suffix('\n')
+            .ok_or(MerkleError::InvalidEncoding)?
+            .split('\n');
+        let mut line = || match lines.next() {
+            Some(line) if !line.is_empty() => Ok(line.to_owned()),
+            _ => Err(MerkleError::InvalidEncoding),
+        };
+        let origin = line()?;
+        let size = line()?;
+        if size.starts_with(['+', '-']) || (size.starts_with('0') && size != "0") {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let size = size.parse().map_err(|_| MerkleError::InvalidEncoding)?;
+        let root = base64::decode(&line()?)
+            .filter(|root| !root.is_empty())
+            .ok_or(MerkleError::InvalidEncoding)?;
+        let extensions = lines
+            .map(|extension| match extension {
+                "" => Err(MerkleError::InvalidEncoding),
+                _ => Ok(extension.to_owned()),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Checkpoint {
+            origin,
+            size,
+            root: hex::encode(&root),
+            extensions,
+        })
+    }
+}
+
+/// Signature line of a signed note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteSignature {
+    /// Name of the signer, without whitespace or `+`.
+    pub name: String,
+
+    /// First 4 bytes of the hash identifying the key of the signer.
+    pub key_hash: [u8; 4],
+
+    /// Signature over the note text.
+    pub signature: Vec<u8>,
+}
+
+/// Checkpoint together with the signatures of the log and its witnesses, in the signed note
+/// format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCheckpoint {
+    /// Signed checkpoint.
+    pub checkpoint: Checkpoint,
+
+    /// Signatures over the checkpoint text.
+    pub signatures: Vec<NoteSignature>,
+}
+
+impl SignedCheckpoint {
+    /// Serialize into a signed note.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint cannot be serialized, or if a signer name is empty or has
+    /// whitespace or `+`.
+    pub fn to_note(&self) -> String {
+        let mut note = self.checkpoint.to_text();
+        note.push('\n');
+        for signature in &self.signatures {
+            assert!(
+                is_valid_name(&signature.name),
+                "note signer name is empty or has whitespace or '+'"
+            );
+            let bytes = [&signature.key_hash[..], &signature.signature].concat();
+            note.push_str(SIGNATURE_PREFIX);
+            note.push_str(&signature.name);
+            note.push(' ');
+            note.push_str(&base64::encode(&bytes));
+            note.push('\n');
+        }
+        note
+    }
+
+    /// Deserialize a signed note carrying a checkpoint. Fail with
+    /// `MerkleError::InvalidEncoding` if it is malformed. Signatures are not verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `note` - Signed note.
+    pub fn from_note(note: &str) -> Result<Self, MerkleError> {
+        let split = note.rfind("\n\n").ok_or(MerkleError::InvalidEncoding)?;
+        let checkpoint = Checkpoint::from_text(&note[..split + 1])?;
+        let lines = note[split + 2..]
+            .strip_suffix('\n')
+            .ok_or(MerkleError::InvalidEncoding)?;
+        let signatures = lines
+            .split('\n')
+            .map(|line| {
+                let (name, bytes) = line
+                    .strip_prefix(SIGNATURE_PREFIX)
+                    .and_then(|line| line.split_once(' '))
+                    .filter(|(name, _)| is_valid_name(name))
+                    .ok_or(MerkleError::InvalidEncoding)?;
+                let bytes = base64::decode(bytes)
+                    .filter(|bytes| bytes.len() > 4)
+                    .ok_or(MerkleError::InvalidEncoding)?;
+                Ok(NoteSignature {
+                    name: name.to_owned(),
+                    key_hash: bytes[..4].try_into().unwrap(),
+                    signature: bytes[4..].to_vec(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(SignedCheckpoint {
+            checkpoint,
+            signatures,
+        })
+    }
+
+    /// Check that at least `threshold` of the trusted witnesses signed the checkpoint, and
+    /// return the number that did, counting like `cosign::verify_cosigned_root`. Signature
+    /// lines whose key hash differs from that of the trusted key of the same name are ignored
+    /// without checking the signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `witnesses` - Keys of the trusted witnesses, which may include the log itself.
+    /// * `threshold` - Number of trusted witnesses that must have signed.
+    pub fn verify(
+        &self,
+        witnesses: &[&dyn WitnessKey],
+        threshold: usize,
+    ) -> Result<usize, MerkleError> {
+        let signatures: Vec<Cosignature> = self
+            .signatures
+            .iter()
+            .filter(|signature| {
+                witnesses.iter().any(|witness| {
+                    witness.name() == signature.name
+                        && witness
+                            .key_hash()
+                            .map_or(true, |key_hash| key_hash == signature.key_hash)
+                })
+            })
+            .map(|signature| Cosignature {
+                witness: signature.name.to_owned(),
+                signature: signature.signature.to_owned(),
+            })
+            .collect();
+        let text = self.checkpoint.to_text();
+        quorum(text.as_bytes(), &signatures, witnesses, threshold)
+    }
+}
+
+/// Whether `name` may name the signer of a note: non-empty, without whitespace or `+`.
+///
+/// # Arguments
+///
+/// * `name` - Signer name.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '+')
+}
@@ -0,0 +1,106 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use std::rc::Rc;
+
+/// Number of elements per chunk.
+const CHUNK_SIZE: usize = 64;
+
+/// Append-friendly vector split into reference-counted chunks.
+///
+/// Cloning only copies the chunk pointers, and writing afterwards only copies the
+/// chunk being written to, so clones share all untouched chunks.
+pub(crate) struct Chunked<T> {
+    chunks: Vec<Rc<Vec<T>>>,
+    len: usize,
+}
+
+impl<T> Clone for Chunked<T> {
+    fn clone(&self) -> Self {
+        Chunked {
+            chunks: self.chunks.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for Chunked<T> {
+    fn default() -> Self {
+        Chunked {
+            chunks: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T: Clone> Chunked<T> {
+    /// Create an empty vector with room for the chunk pointers of `capacity` elements.
+    /// Chunks themselves are allocated at their full size when first written to.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - Expected number of elements.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Chunked {
+            chunks: Vec::with_capacity(capacity.div_ceil(CHUNK_SIZE)),
+            len: 0,
+        }
+    }
+
+    /// Number of elements.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return the element at `index`, or None if it is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - 0-based index of element.
+    pub(crate) fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.chunks[index / CHUNK_SIZE][index % CHUNK_SIZE])
+    }
+
+    /// Append an element.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Element to append.
+    pub(crate) fn push(&mut self, value: T) {
+        if self.len % CHUNK_SIZE == 0 {
+            self.chunks.push(Rc::new(Vec::with_capacity(CHUNK_SIZE)));
+        }
+        let last = self.chunks.len() - 1;
+        Rc::make_mut(&mut self.chunks[last]).push(value);
+        self.len += 1;
+    }
+
+    /// Replace the element at `index`. `index` must be in range.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - 0-based index of element.
+    /// * `value` - New element.
+    pub(crate) fn set(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "index out of range");
+        Rc::make_mut(&mut self.chunks[index / CHUNK_SIZE])[index % CHUNK_SIZE] = value;
+    }
+
+    /// Shorten to `len` elements. Has no effect if `len` is not less than the current length.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - New length.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.chunks.truncate(len.div_ceil(CHUNK_SIZE));
+        if len % CHUNK_SIZE != 0 {
+            let last = self.chunks.len() - 1;
+            Rc::make_mut(&mut self.chunks[last]).truncate(len % CHUNK_SIZE);
+        }
+        self.len = len;
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{HashAlgorithm, MerkleError};
+
+/// Magic bytes at the start of every serialized artifact.
+const MAGIC: &[u8; 4] = b"MRKL";
+
+/// Version of the serialized format, so data of another version is rejected instead of
+/// silently misverified. The format has not been released, so its layout is still settling
+/// at version 1; data written before the first release may not deserialize.
+pub(crate) const FORMAT_VERSION: u64 = 1;
+
+/// Tag of a hash algorithm recorded in the header.
+fn algorithm_tag(algorithm: HashAlgorithm) -> u64 {
+    match algorithm {
+        HashAlgorithm::Sha256 => 0,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_256 => 1,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_512 => 2,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Keccak256 => 3,
+        #[cfg(feature = "ripemd")]
+        HashAlgorithm::Ripemd160 => 4,
+        #[cfg(feature = "ripemd")]
+        HashAlgorithm::Hash160 => 5,
+        // Not recorded, so deserialization rejects it as an unsupported scheme.
+        HashAlgorithm::Custom(_) => u64::MAX,
+    }
+}
+
+/// Hash algorithm of a tag recorded in the header, or None if it is unknown or its
+/// feature is not enabled.
+fn algorithm_from_tag(tag: u64) -> Option<HashAlgorithm> {
+    match tag {
+        0 => Some(HashAlgorithm::Sha256),
+        #[cfg(feature = "sha3")]
+        1 => Some(HashAlgorithm::Sha3_256),
+        #[cfg(feature = "sha3")]
+        2 => Some(HashAlgorithm::Sha3_512),
+        #[cfg(feature = "sha3")]
+        3 => Some(HashAlgorithm::Keccak256),
+        #[cfg(feature = "ripemd")]
+        4 => Some(HashAlgorithm::Ripemd160),
+        #[cfg(feature = "ripemd")]
+        5 => Some(HashAlgorithm::Hash160),
+        _ => None,
+    }
+}
+
+/// Number of children per inner node recorded in the header. Only binary trees are supported.
+const ARITY: u64 = 2;
+
+/// Kind of serialized artifact, recorded in the header.
+#[derive(Clone, Copy)]
+pub(crate) enum Artifact {
+    Tree = 0,
+    Proof = 1,
+    Delta = 2,
+    CommittedRoot = 3,
+    HashChain = 4,
+    ChainReceipt = 5,
+    #[cfg(feature = "mmap")]
+    MappedTree = 6,
+    ExportBundle = 7,
+}
+
+/// Builder for the compact binary encoding shared by serializable types.
+/// Integers are encoded as little-endian u64, strings and byte strings are length-prefixed.
+#[derive(Default)]
+pub(crate) struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    /// Initialize an empty encoder.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Initialize an encoder that starts with the versioned header for `artifact`:
+    /// magic bytes, format version, artifact kind, hash algorithm and arity.
+    pub(crate) fn with_header(artifact: Artifact, algorithm: HashAlgorithm) -> Self {
+        let mut encoder = Self::new();
+        encoder.bytes.extend_from_slice(MAGIC);
+        encoder.put_u64(FORMAT_VERSION);
+        encoder.put_u64(artifact as u64);
+        encoder.put_u64(algorithm_tag(algorithm));
+        encoder.put_u64(ARITY);
+        encoder
+    }
+
+    /// Append an integer.
+    pub(crate) fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Append an integer.
+    pub(crate) fn put_usize(&mut self, value: usize) {
+        self.put_u64(value as u64);
+    }
+
+    /// Append a length-prefixed byte string.
+    pub(crate) fn put_bytes(&mut self, value: &[u8]) {
+        self.put_usize(value.len());
+        self.bytes.extend_from_slice(value);
+    }
+
+    /// Append a length-prefixed string.
+    pub(crate) fn put_str(&mut self, value: &str) {
+        self.put_bytes(value.as_bytes());
+    }
+
+    /// Return the encoded bytes.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reader for bytes produced by `Encoder`. Every read fails with
+/// `MerkleError::InvalidEncoding` if the input is truncated or malformed.
+pub(crate) struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Initialize a decoder over encoded bytes.
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Decoder { bytes }
+    }
+
+    /// Initialize a decoder over bytes produced by `Encoder::with_header`, checking the header,
+    /// and return it with the recorded hash algorithm. Fail with
+    /// `MerkleError::UnsupportedVersion` for another format version, and with
+    /// `MerkleError::UnsupportedScheme` for an unknown hash algorithm or another arity.
+    pub(crate) fn with_header(
+        bytes: &'a [u8],
+        artifact: Artifact,
+    ) -> Result<(Self, HashAlgorithm), MerkleError> {
+        let mut decoder = Decoder::new(bytes);
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let version = decoder.u64()?;
+        if version != FORMAT_VERSION {
+            return Err(MerkleError::UnsupportedVersion(version));
+        }
+        if decoder.u64()? != artifact as u64 {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let algorithm = algorithm_from_tag(decoder.u64()?);
+        match (algorithm, decoder.u64()?) {
+            (Some(algorithm), ARITY) => Ok((decoder, algorithm)),
+            _ => Err(MerkleError::UnsupportedScheme),
+        }
+    }
+
+    /// Read exactly `len` raw bytes.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MerkleError> {
+        if len > self.bytes.len() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Read an integer.
+    pub(crate) fn u64(&mut self) -> Result<u64, MerkleError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Read an integer that must fit in `usize`.
+    pub(crate) fn usize(&mut self) -> Result<usize, MerkleError> {
+        usize::try_from(self.u64()?).map_err(|_| MerkleError::InvalidEncoding)
+    }
+
+    /// Read a length-prefixed byte string.
+    pub(crate) fn bytes(&mut self) -> Result<&'a [u8], MerkleError> {
+        let len = self.usize()?;
+        self.take(len)
+    }
+
+    /// Read a length-prefixed UTF-8 string.
+    pub(crate) fn string(&mut self) -> Result<String, MerkleError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| MerkleError::InvalidEncoding)
+    }
+
+    /// Return the unread bytes.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn rest(self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Fail if there are unread bytes left.
+    pub(crate) fn finish(self) -> Result<(), MerkleError> {
+        if !self.bytes.is_empty() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::codec::{Artifact, Decoder, Encoder};
+use crate::serialize::{put_scheme, scheme};
+use crate::{
+    Concatenation, HashAlgorithm, LeafEncoding, LeafOrdering, MerkleError, MerkleProof, MerkleTree,
+    Padding,
+};
+
+/// How a merkle tree is constructed, apart from its hash function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeScheme {
+    /// How child hashes are concatenated.
+    pub concatenation: Concatenation,
+
+    /// How the last node of an odd level is combined into the upper level.
+    pub padding: Padding,
+
+    /// How leaves were ordered before construction.
+    pub ordering: LeafOrdering,
+
+    /// Number of bytes node values are truncated to, or None for full digests.
+    pub truncation: Option<usize>,
+
+    /// How leaf contents encode the bytes hashed into leaf node values.
+    pub leaf_encoding: LeafEncoding,
+}
+
+/// Self-describing published root: the root value together with every parameter needed to
+/// reproduce it, so a proof built with the wrong hash function or scheme is reported as such
+/// instead of as an unexplained root mismatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedRoot {
+    /// Root value as hexdigest.
+    pub root: String,
+
+    /// Hash function of the tree.
+    pub algorithm: HashAlgorithm,
+
+    /// How the tree is constructed.
+    pub scheme: TreeScheme,
+
+    /// Number of leaves.
+    pub leaf_count: usize,
+
+    /// When the tree was built, in seconds since the Unix epoch. Supplied by the caller,
+    /// so committing the same tree twice at the same time gives identical bytes.
+    pub built_at: u64,
+}
+
+impl CommittedRoot {
+    /// Commit to the current root of a tree. Return None if it has no leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - Merkle tree.
+    /// * `built_at` - When the tree was built, in seconds since the Unix epoch.
+    pub fn of(tree: &MerkleTree, built_at: u64) -> Option<Self> {
+        Some(CommittedRoot {
+            root: tree.root()?.borrow().value.to_owned(),
+            algorithm: tree.algorithm(),
+            scheme: tree.scheme(),
+            leaf_count: tree.num_of_leaves(),
+            built_at,
+        })
+    }
+
+    /// Serialize into canonical bytes, after a versioned header. Equal commitments always
+    /// serialize to equal bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::CommittedRoot, self.algorithm);
+        put_scheme(&mut encoder, &self.scheme);
+        encoder.put_str(&self.root);
+        encoder.put_usize(self.leaf_count);
+        encoder.put_u64(self.built_at);
+        encoder.finish()
+    }
+
+    /// Deserialize a commitment serialized by `to_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized commitment.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::CommittedRoot)?;
+        let scheme = scheme(&mut decoder, algorithm)?;
+        let root = decoder.string()?;
+        let leaf_count = decoder.usize()?;
+        let built_at = decoder.u64()?;
+        decoder.finish()?;
+        Ok(CommittedRoot {
+            root,
+            algorithm,
+            scheme,
+            leaf_count,
+            built_at,
+        })
+    }
+
+    /// Digest of the canonical bytes as hexdigest, with the committed hash function.
+    pub fn hash(&self) -> String {
+        self.algorithm.hash(&self.to_bytes())
+    }
+
+    /// Verify a merkle proof against the committed root. Fail with
+    /// `MerkleError::SchemeMismatch` if the proof was built with another hash function,
+    /// concatenation, truncation, leaf encoding or number of leaves, and with
+    /// `MerkleError::ProofHashMismatch` if it does not reproduce the root.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - Proof to be verified.
+    pub fn verify_proof(&self, proof: &MerkleProof) -> Result<(), MerkleError> {
+        if proof.algorithm != self.algorithm
+            || proof.concatenation != self.scheme.concatenation
+            || proof.truncation != self.scheme.truncation
+            || proof.leaf_encoding != self.scheme.leaf_encoding
+            || proof.num_of_leaves != self.leaf_count
+        {
+            return Err(MerkleError::SchemeMismatch);
+        }
+        if proof.root_value() != self.root {
+            return Err(MerkleError::ProofHashMismatch);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{Hash, MerkleError, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Proof of a leaf across nested trees, where the root of every inner tree is a leaf of the
+/// next outer tree, as when the roots of shards or of per-block trees are collected into a
+/// global tree. The trees may differ in hash function and scheme.
+pub struct ComposedProof {
+    /// Proofs from the innermost tree, holding the proven leaf, to the outermost tree.
+    proofs: Vec<MerkleProof>,
+}
+
+impl MerkleProof {
+    /// Splice a proof of a leaf in an inner tree with a proof of the root of that tree in an
+    /// outer tree. Fail with `MerkleError::ProofHashMismatch` if the leaf content of `outer`
+    /// is not the root `inner` reconstructs.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Proof of a leaf in the inner tree.
+    /// * `outer` - Proof of the root of the inner tree in the outer tree.
+    pub fn compose(inner: MerkleProof, outer: MerkleProof) -> Result<ComposedProof, MerkleError> {
+        ComposedProof {
+            proofs: vec![inner],
+        }
+        .then(outer)
+    }
+}
+
+impl ComposedProof {
+    /// Extend the proof with a proof of the current outermost root in a further outer tree.
+    /// Fail with `MerkleError::ProofHashMismatch` if the leaf content of `outer` is not that
+    /// root.
+    ///
+    /// # Arguments
+    ///
+    /// * `outer` - Proof of the current outermost root in the further outer tree.
+    pub fn then(mut self, outer: MerkleProof) -> Result<Self, MerkleError> {
+        if outer.leaf_content != self.root_value() {
+            return Err(MerkleError::ProofHashMismatch);
+        }
+        self.proofs.push(outer);
+        Ok(self)
+    }
+
+    /// Proofs from the innermost tree to the outermost tree.
+    pub fn proofs(&self) -> &[MerkleProof] {
+        &self.proofs
+    }
+
+    /// Content of the proven leaf of the innermost tree.
+    pub fn leaf_content(&self) -> &str {
+        &self.proofs[0].leaf_content
+    }
+
+    /// Reconstruct the root of the outermost tree implied by this proof, as hexdigest.
+    pub fn root_value(&self) -> String {
+        self.proofs.last().unwrap().root_value()
+    }
+
+    /// Given the root node of the outermost tree, verify that every inner root is the leaf of
+    /// the next proof and that the outermost proof reconstructs the root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the outermost tree.
+    pub fn verify(&self, root: Rc<RefCell<Hash>>) -> bool {
+        self.proofs
+            .windows(2)
+            .all(|pair| pair[1].leaf_content == pair[0].root_value())
+            && MerkleTree::verify_proof(root, self.proofs.last().unwrap())
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Verification of proofs in const contexts, with a const SHA-256 implementation. Only `core`
+//! is used, nothing is allocated and nothing panics, so the module also suits firmware
+//! bootloaders that check proofs against roots known at build time.
+
+/// SHA-256 round constants.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 initial hash value.
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Return the byte at `position` of the padded SHA-256 message made of `first` followed by
+/// `second`.
+///
+/// # Arguments
+///
+/// * `first` - First part of the message.
+/// * `second` - Second part of the message.
+/// * `padded_len` - Length of the padded message, a multiple of 64.
+/// * `position` - Position of the byte in the padded message.
+const fn padded_byte(first: &[u8], second: &[u8], padded_len: usize, position: usize) -> u8 {
+    let len = first.len() + second.len();
+    if position < first.len() {
+        first[position]
+    } else if position < len {
+        second[position - first.len()]
+    } else if position == len {
+        0x80
+    } else if position >= padded_len - 8 {
+        ((len as u64 * 8) >> (8 * (padded_len - 1 - position))) as u8
+    } else {
+        0
+    }
+}
+
+/// Hash the concatenation of two byte strings to a raw SHA-256 digest.
+///
+/// # Arguments
+///
+/// * `first` - First part of the message.
+/// * `second` - Second part of the message.
+const fn sha256(first: &[u8], second: &[u8]) -> [u8; 32] {
+    let padded_len = (first.len() + second.len() + 9).div_ceil(64) * 64;
+    let mut state = H0;
+    let mut offset = 0;
+    while offset < padded_len {
+        let mut w = [0u32; 64];
+        let mut t = 0;
+        while t < 16 {
+            let mut b = 0;
+            while b < 4 {
+                let byte = padded_byte(first, second, padded_len, offset + 4 * t + b);
+                w[t] = w[t] << 8 | byte as u32;
+                b += 1;
+            }
+            t += 1;
+        }
+        while t < 64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1);
+            t += 1;
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        t = 0;
+        while t < 64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[t])
+                .wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+            t += 1;
+        }
+        let words = [a, b, c, d, e, f, g, h];
+        t = 0;
+        while t < 8 {
+            state[t] = state[t].wrapping_add(words[t]);
+            t += 1;
+        }
+        offset += 64;
+    }
+
+    let mut digest = [0u8; 32];
+    let mut t = 0;
+    while t < 32 {
+        digest[t] = (state[t / 4] >> (24 - 8 * (t % 4))) as u8;
+        t += 1;
+    }
+    digest
+}
+
+/// Same as `verify_proof_in_place`, but usable in const contexts, so a proof against a root
+/// known at build time can be checked at compile time:
+///
+/// ```
+/// use merkle_tree::verify_proof_const;
+///
+/// // Root of the tree with the single leaf "abc".
+/// const ROOT: [u8; 32] = [
+///     0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22,
+///     0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00,
+///     0x15, 0xad,
+/// ];
+/// const _: () = assert!(verify_proof_const(&ROOT, b"abc", &[], 0));
+/// ```
+///
+/// Slower than `verify_proof_in_place` at run time, as the built-in SHA-256 implementation
+/// uses hardware acceleration where available.
+///
+/// # Arguments
+///
+/// * `root` - Root node value.
+/// * `leaf` - Bytes of the leaf content, hashed into the leaf node value.
+/// * `siblings` - Audit hashes, arranged from the bottom-most hash up to the top-most hash
+///   (closest to root node).
+/// * `directions` - Bit `i` is set if `siblings[i]` is a left child.
+pub const fn verify_proof_const(
+    root: &[u8; 32],
+    leaf: &[u8],
+    siblings: &[[u8; 32]],
+    directions: u64,
+) -> bool {
+    if siblings.len() > 64 {
+        return false;
+    }
+    if let Some(unused) = directions.checked_shr(siblings.len() as u32) {
+        if unused != 0 {
+            return false;
+        }
+    }
+    let mut node = sha256(leaf, &[]);
+    let mut level = 0;
+    while level < siblings.len() {
+        node = match directions >> level & 1 {
+            1 => sha256(&siblings[level], &node),
+            _ => sha256(&node, &siblings[level]),
+        };
+        level += 1;
+    }
+    let mut index = 0;
+    while index < 32 {
+        if node[index] != root[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
@@ -0,0 +1,268 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Witness cosigning of published roots, as transparency logs use to guard against split views.
+//!
+//! Each witness checks a `CommittedRoot`, for example that it extends the last root it saw,
+//! and signs its canonical bytes. Clients accept the root only once `verify_cosigned_root`
+//! finds valid signatures from a quorum of the witnesses they trust. Signature schemes plug in
+//! through `WitnessKey`; the `ring` feature adds Ed25519.
+#[cfg(feature = "ring")]
+use crate::checkpoint::{is_valid_name, Checkpoint, NoteSignature};
+#[cfg(feature = "ring")]
+use crate::{base64, hex, HashAlgorithm};
+use crate::{CommittedRoot, MerkleError};
+use std::collections::BTreeSet;
+
+/// Prefix of every cosigned message, so cosignatures cannot be replayed as signatures over
+/// other data.
+const COSIGNATURE_CONTEXT: &[u8] = b"merkle-tree cosignature v1\n";
+
+/// Public key of a witness, verifying its cosignatures.
+pub trait WitnessKey {
+    /// Name of the witness, unique among the witnesses trusted together.
+    fn name(&self) -> &str;
+
+    /// Whether `signature` is a valid signature of `message` by this witness.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Signed message.
+    /// * `signature` - Signature to check.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+
+    /// Key hash identifying the key in signature lines of signed notes, or None if the key
+    /// has none, in which case signature lines are matched by name alone.
+    fn key_hash(&self) -> Option<[u8; 4]> {
+        None
+    }
+}
+
+/// Signature of a witness over a published root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cosignature {
+    /// Name of the witness that signed.
+    pub witness: String,
+
+    /// Signature over `cosigned_message` of the root.
+    pub signature: Vec<u8>,
+}
+
+/// Return the message witnesses sign for a root: a fixed context string followed by the
+/// canonical bytes of the root.
+///
+/// # Arguments
+///
+/// * `root` - Published root.
+pub fn cosigned_message(root: &CommittedRoot) -> Vec<u8> {
+    [COSIGNATURE_CONTEXT, &root.to_bytes()].concat()
+}
+
+/// Check that at least `threshold` of the trusted witnesses cosigned a root, and return the
+/// number that did. Cosignatures by untrusted witnesses or with invalid signatures are
+/// ignored, and every witness counts once however many cosignatures name it or however many
+/// times its key is listed. Fail with `MerkleError::InvalidThreshold` if `threshold` is 0, and
+/// with `MerkleError::QuorumNotReached` holding that number if it is below `threshold`.
+///
+/// # Arguments
+///
+/// * `root` - Published root.
+/// * `signatures` - Cosignatures of the root.
+/// * `witnesses` - Keys of the trusted witnesses.
+/// * `threshold` - Number of trusted witnesses that must have cosigned.
+pub fn verify_cosigned_root(
+    root: &CommittedRoot,
+    signatures: &[Cosignature],
+    witnesses: &[&dyn WitnessKey],
+    threshold: usize,
+) -> Result<usize, MerkleError> {
+    quorum(&cosigned_message(root), signatures, witnesses, threshold)
+}
+
+/// Check that at least `threshold` of the trusted witnesses signed a message, like
+/// `verify_cosigned_root`.
+///
+/// # Arguments
+///
+/// * `message` - Signed message.
+/// * `signatures` - Signatures of the message.
+/// * `witnesses` - Keys of the trusted witnesses.
+/// * `threshold` - Number of trusted witnesses that must have signed.
+pub(crate) fn quorum(
+    message: &[u8],
+    signatures: &[Cosignature],
+    witnesses: &[&dyn WitnessKey],
+    threshold: usize,
+) -> Result<usize, MerkleError> {
+    if threshold == 0 {
+        return Err(MerkleError::InvalidThreshold);
+    }
+    let mut cosigned = BTreeSet::new();
+    for signature in signatures {
+        if cosigned.contains(signature.witness.as_str()) {
+            continue;
+        }
+        if witnesses.iter().any(|witness| {
+            witness.name() == signature.witness && witness.verify(message, &signature.signature)
+        }) {
+            cosigned.insert(signature.witness.as_str());
+        }
+    }
+    if cosigned.len() < threshold {
+        return Err(MerkleError::QuorumNotReached(cosigned.len()));
+    }
+    Ok(cosigned.len())
+}
+
+/// Ed25519 public key of a witness.
+#[cfg(feature = "ring")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ed25519WitnessKey {
+    name: String,
+    public_key: [u8; 32],
+}
+
+#[cfg(feature = "ring")]
+impl Ed25519WitnessKey {
+    /// Initialize the key of a witness.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the witness.
+    /// * `public_key` - Ed25519 public key of the witness.
+    pub fn new(name: &str, public_key: [u8; 32]) -> Self {
+        Ed25519WitnessKey {
+            name: name.to_owned(),
+            public_key,
+        }
+    }
+
+    /// Parse a verifier key in the signed note format, `<name>+<key hash>+<base64 key>`, where
+    /// the key is the Ed25519 algorithm byte 1 followed by the public key. Fail with
+    /// `MerkleError::InvalidEncoding` if it is malformed or its key hash does not match.
+    ///
+    /// # Arguments
+    ///
+    /// * `verifier_key` - Verifier key.
+    pub fn from_verifier_key(verifier_key: &str) -> Result<Self, MerkleError> {
+        let parts: Vec<&str> = verifier_key.split('+').collect();
+        let [name, key_hash, key] = parts[..] else {
+            return Err(MerkleError::InvalidEncoding);
+        };
+        let key = base64::decode(key).ok_or(MerkleError::InvalidEncoding)?;
+        let public_key = match key.split_first() {
+            Some((1, public_key)) if is_valid_name(name) => public_key.try_into(),
+            _ => return Err(MerkleError::InvalidEncoding),
+        };
+        let key =
+            Ed25519WitnessKey::new(name, public_key.map_err(|_| MerkleError::InvalidEncoding)?);
+        if key_hash.len() != 8 || hex::decode(key_hash) != Some(key.key_hash().to_vec()) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(key)
+    }
+
+    /// Serialize into a verifier key in the signed note format, like `from_verifier_key`.
+    pub fn verifier_key(&self) -> String {
+        let key = [&[1][..], &self.public_key].concat();
+        format!(
+            "{}+{}+{}",
+            self.name,
+            hex::encode(&self.key_hash()),
+            base64::encode(&key)
+        )
+    }
+
+    /// First 4 bytes of the SHA-256 digest of the name, a newline and the key with its
+    /// algorithm byte, which identify the key in signature lines of signed notes.
+    pub fn key_hash(&self) -> [u8; 4] {
+        let message = [self.name.as_bytes(), b"\n\x01", &self.public_key].concat();
+        HashAlgorithm::Sha256.digest(&message)[..4]
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(feature = "ring")]
+impl WitnessKey for Ed25519WitnessKey {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn key_hash(&self) -> Option<[u8; 4]> {
+        Some(Ed25519WitnessKey::key_hash(self))
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, &self.public_key)
+            .verify(message, signature)
+            .is_ok()
+    }
+}
+
+/// Ed25519 signing key of a witness.
+#[cfg(feature = "ring")]
+pub struct Ed25519Witness {
+    name: String,
+    key_pair: ring::signature::Ed25519KeyPair,
+}
+
+#[cfg(feature = "ring")]
+impl Ed25519Witness {
+    /// Initialize a witness from the 32-byte seed of its Ed25519 private key. Keep the seed
+    /// secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the witness.
+    /// * `seed` - Seed of the private key.
+    pub fn from_seed(name: &str, seed: &[u8; 32]) -> Self {
+        Ed25519Witness {
+            name: name.to_owned(),
+            key_pair: ring::signature::Ed25519KeyPair::from_seed_unchecked(seed).unwrap(),
+        }
+    }
+
+    /// Public key verifying the cosignatures of this witness.
+    pub fn key(&self) -> Ed25519WitnessKey {
+        use ring::signature::KeyPair;
+
+        let public_key = self.key_pair.public_key().as_ref().try_into().unwrap();
+        Ed25519WitnessKey::new(&self.name, public_key)
+    }
+
+    /// Cosign a root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Published root, checked by the caller.
+    pub fn cosign(&self, root: &CommittedRoot) -> Cosignature {
+        Cosignature {
+            witness: self.name.to_owned(),
+            signature: self
+                .key_pair
+                .sign(&cosigned_message(root))
+                .as_ref()
+                .to_vec(),
+        }
+    }
+
+    /// Sign a checkpoint as a signed note signature line.
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint` - Checkpoint, checked by the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint cannot be serialized.
+    pub fn sign_checkpoint(&self, checkpoint: &Checkpoint) -> NoteSignature {
+        NoteSignature {
+            name: self.name.to_owned(),
+            key_hash: self.key().key_hash(),
+            signature: self
+                .key_pair
+                .sign(checkpoint.to_text().as_bytes())
+                .as_ref()
+                .to_vec(),
+        }
+    }
+}
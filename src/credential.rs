@@ -0,0 +1,222 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Selectively disclosable credentials in the style of SD-JWT, built on the field leaves of
+//! `json_tree`.
+//!
+//! The issuer flattens the claims of a JSON document into one leaf per field, each salted so
+//! that undisclosed claims cannot be guessed from the proofs of disclosed ones, and signs the
+//! `CommittedRoot` of the tree like a witness cosigns a root. The holder later presents a
+//! `DisclosureBundle` with the signed root and the proofs of only the claims it chooses to
+//! reveal. A leaf is the JSON array `[salt,pointer,value]`, like an SD-JWT disclosure.
+use crate::cosign::{verify_cosigned_root, Cosignature, WitnessKey};
+use crate::json_tree::{flatten, quote, unquote};
+use crate::{base64, CommittedRoot, HashAlgorithm, MerkleError, MerkleProof, MerkleTree};
+
+/// Number of bytes of a salt, as SD-JWT recommends.
+const SALT_SIZE: usize = 16;
+
+/// Claim of a credential together with the salt hiding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Claim {
+    /// Salt as unpadded URL-safe base64.
+    pub salt: String,
+
+    /// JSON Pointer of the claim in the credential document.
+    pub pointer: String,
+
+    /// Value of the claim in canonical form, as `json_tree::flatten` returns it.
+    pub value: String,
+}
+
+impl Claim {
+    /// Leaf content of the claim, the JSON array `[salt,pointer,value]`.
+    pub fn leaf(&self) -> String {
+        format!(
+            "[{},{},{}]",
+            quote(&self.salt),
+            quote(&self.pointer),
+            self.value
+        )
+    }
+
+    /// Parse the leaf content of a claim. Fail with `MerkleError::InvalidEncoding` if it is
+    /// not a leaf written by `leaf`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Leaf content.
+    pub fn from_leaf(leaf: &str) -> Result<Self, MerkleError> {
+        let fields = flatten(leaf)?;
+        let [(first, salt), (second, pointer), (third, value)] = &fields[..] else {
+            return Err(MerkleError::InvalidEncoding);
+        };
+        if (first.as_str(), second.as_str(), third.as_str()) != ("/0", "/1", "/2") {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let claim = Claim {
+            salt: unquote(salt).ok_or(MerkleError::InvalidEncoding)?,
+            pointer: unquote(pointer).ok_or(MerkleError::InvalidEncoding)?,
+            value: value.to_owned(),
+        };
+        // Only one encoding of a claim verifies, so claims compare equal by their leaves.
+        if claim.leaf() != leaf {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(claim)
+    }
+}
+
+/// Credential held by its holder: the salted claims and the tree over them.
+#[derive(Clone)]
+pub struct Credential {
+    claims: Vec<Claim>,
+    tree: MerkleTree,
+}
+
+impl Credential {
+    /// Issue a credential over the fields of a JSON document, failing like
+    /// `json_tree::flatten`. The salt of each claim is derived from `salt_key` and its
+    /// pointer, so the key must be random, secret and used for a single credential.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - JSON document of the claims.
+    /// * `salt_key` - Secret key salts are derived from.
+    pub fn issue(document: &str, salt_key: &[u8; 32]) -> Result<Self, MerkleError> {
+        let claims: Vec<Claim> = flatten(document)?
+            .into_iter()
+            .map(|(pointer, value)| {
+                let digest = HashAlgorithm::Sha256.digest(&[salt_key, pointer.as_bytes()].concat());
+                Claim {
+                    salt: base64::encode_url_safe(&digest[..SALT_SIZE]),
+                    pointer,
+                    value,
+                }
+            })
+            .collect();
+        let leaves: Vec<String> = claims.iter().map(Claim::leaf).collect();
+        Ok(Credential {
+            tree: MerkleTree::from_leaves(&leaves),
+            claims,
+        })
+    }
+
+    /// Tree over the salted claims, whose root the issuer signs.
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Salted claims sorted by pointer.
+    pub fn claims(&self) -> &[Claim] {
+        &self.claims
+    }
+
+    /// Present the claims at `pointers`, and only those, with the signed root of the
+    /// credential. Return None if the credential has no claim at one of the pointers.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointers` - JSON Pointers of the claims to disclose.
+    /// * `root` - Root of the credential, committed to by the issuer.
+    /// * `signature` - Signature of the issuer over `root`.
+    pub fn disclose(
+        &self,
+        pointers: &[&str],
+        root: &CommittedRoot,
+        signature: &Cosignature,
+    ) -> Option<DisclosureBundle> {
+        let proofs = pointers
+            .iter()
+            .map(|pointer| {
+                let index = self
+                    .claims
+                    .binary_search_by(|claim| claim.pointer.as_str().cmp(pointer))
+                    .ok()?;
+                self.tree.proof(index)
+            })
+            .collect::<Option<Vec<MerkleProof>>>()?;
+        Some(DisclosureBundle {
+            root: root.to_owned(),
+            signature: signature.to_owned(),
+            proofs,
+        })
+    }
+}
+
+/// Presentation of some claims of a credential, with the proof of each against the root
+/// signed by the issuer.
+pub struct DisclosureBundle {
+    /// Root of the credential.
+    pub root: CommittedRoot,
+
+    /// Signature of the issuer over `root`.
+    pub signature: Cosignature,
+
+    /// Proofs of the disclosed claims, whose leaf contents are the claims.
+    pub proofs: Vec<MerkleProof>,
+}
+
+impl DisclosureBundle {
+    /// Verify the signature of the issuer and every proof, and return the disclosed claims.
+    /// Fail with `MerkleError::QuorumNotReached` if the issuer did not sign the root, with the
+    /// errors of `CommittedRoot::verify_proof` if a proof does not verify, and with
+    /// `MerkleError::InvalidEncoding` if a leaf is not a claim or a claim is disclosed twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuer` - Key of the trusted issuer.
+    pub fn verify(&self, issuer: &dyn WitnessKey) -> Result<Vec<Claim>, MerkleError> {
+        verify_cosigned_root(
+            &self.root,
+            std::slice::from_ref(&self.signature),
+            &[issuer],
+            1,
+        )?;
+        let mut claims: Vec<Claim> = Vec::with_capacity(self.proofs.len());
+        for proof in &self.proofs {
+            self.root.verify_proof(proof)?;
+            let claim = Claim::from_leaf(&proof.leaf_content)?;
+            if claims.iter().any(|other| other.pointer == claim.pointer) {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            claims.push(claim);
+        }
+        Ok(claims)
+    }
+
+    /// Serialize into text in the style of an SD-JWT presentation: the canonical bytes of the
+    /// root, the name of the issuer, its signature and every proof, each as unpadded URL-safe
+    /// base64, separated by `~`.
+    pub fn to_text(&self) -> String {
+        let mut parts = vec![
+            base64::encode_url_safe(&self.root.to_bytes()),
+            base64::encode_url_safe(self.signature.witness.as_bytes()),
+            base64::encode_url_safe(&self.signature.signature),
+        ];
+        parts.extend(self.proofs.iter().map(MerkleProof::to_base64));
+        parts.join("~")
+    }
+
+    /// Deserialize text written by `to_text`. Fail with `MerkleError::InvalidEncoding` if it
+    /// is malformed. Nothing is verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Serialized bundle.
+    pub fn from_text(text: &str) -> Result<Self, MerkleError> {
+        let decode = |part: &str| base64::decode_url_safe(part).ok_or(MerkleError::InvalidEncoding);
+        let mut parts = text.split('~');
+        let mut part = || parts.next().ok_or(MerkleError::InvalidEncoding);
+        let root = CommittedRoot::from_bytes(&decode(part()?)?)?;
+        let witness =
+            String::from_utf8(decode(part()?)?).map_err(|_| MerkleError::InvalidEncoding)?;
+        let signature = decode(part()?)?;
+        let proofs = parts
+            .map(MerkleProof::from_base64)
+            .collect::<Result<_, _>>()?;
+        Ok(DisclosureBundle {
+            root,
+            signature: Cosignature { witness, signature },
+            proofs,
+        })
+    }
+}
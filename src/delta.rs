@@ -0,0 +1,329 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::chunked::Chunked;
+use crate::codec::{Artifact, Decoder, Encoder};
+use crate::serialize::{put_scheme, scheme};
+use crate::verify::audit_path_positions;
+use crate::{Hash, HashAlgorithm, MerkleError, MerkleProof, MerkleTree, Padding, TreeScheme};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Exactly the leaves and nodes that differ between two versions of a merkle tree.
+///
+/// Replicas holding the old version can apply a delta to obtain the new version
+/// instead of re-downloading every level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeDelta {
+    /// Root value of the old version as hexdigest, or None if it has no leaves.
+    pub base_root: Option<String>,
+
+    /// Number of nodes on every level of the new version, from the bottom level up to the root.
+    pub level_lengths: Vec<usize>,
+
+    /// Leaves of the new version that are new or changed, as (leaf index, content).
+    pub leaves: Vec<(usize, String)>,
+
+    /// Nodes of the new version that are new or changed, as (level, index, hexdigest),
+    /// ordered by level then index.
+    pub nodes: Vec<(usize, usize, String)>,
+
+    /// Indices of every removed leaf of the new version, in ascending order.
+    pub removed: Vec<usize>,
+
+    /// Hash function of the tree.
+    pub algorithm: HashAlgorithm,
+
+    /// How the tree is constructed, apart from its hash function.
+    pub scheme: TreeScheme,
+}
+
+impl TreeDelta {
+    /// Compute the delta that turns `old` into `new`.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - Old version of the tree.
+    /// * `new` - New version of the tree.
+    pub fn between(old: &MerkleTree, new: &MerkleTree) -> Self {
+        let leaves = (0..new.leaves.len())
+            .filter_map(|index| {
+                let leaf = new.leaves.get(index).unwrap();
+                (old.leaves.get(index) != Some(leaf)).then(|| (index, leaf.to_string()))
+            })
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (level, new_nodes) in new.levels.iter().enumerate() {
+            let old_nodes = old.levels.get(level);
+            for index in 0..new_nodes.len() {
+                let value = new_nodes.get(index).unwrap();
+                if old_nodes.and_then(|old_nodes| old_nodes.get(index)) != Some(value) {
+                    nodes.push((level, index, value.to_owned()));
+                }
+            }
+        }
+
+        TreeDelta {
+            base_root: old.root().map(|root| root.borrow().value.to_owned()),
+            level_lengths: new.levels.iter().map(Chunked::len).collect(),
+            leaves,
+            nodes,
+            removed: new.removed.iter().copied().collect(),
+            algorithm: new.algorithm,
+            scheme: new.scheme(),
+        }
+    }
+
+    /// Apply the delta to a tree holding the old version, turning it into the new version.
+    /// The new version is assembled apart from the tree, and every leaf and node the delta
+    /// changes is checked against its leaf or children up to the root before the tree is
+    /// replaced by it. Fail without modifying the tree with `MerkleError::DeltaBaseMismatch`
+    /// if its root differs from the old version's root, or if it uses another hash function
+    /// or scheme, and with `MerkleError::InvalidEncoding` if the delta leaves gaps in a level
+    /// or does not produce a valid tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - Tree holding the old version.
+    pub fn apply(&self, tree: &mut MerkleTree) -> Result<(), MerkleError> {
+        if tree.root().map(|root| root.borrow().value.to_owned()) != self.base_root
+            || tree.algorithm != self.algorithm
+            || tree.scheme() != self.scheme
+        {
+            return Err(MerkleError::DeltaBaseMismatch);
+        }
+
+        let mut new = tree.clone();
+        let num_of_leaves = self.level_lengths.first().copied().unwrap_or(0);
+        new.leaves.truncate(num_of_leaves);
+        for (index, leaf) in &self.leaves {
+            if *index < new.leaves.len() {
+                new.leaves.set(*index, leaf.to_owned().into());
+            } else if *index == new.leaves.len() {
+                new.leaves.push(leaf.to_owned().into());
+            } else {
+                return Err(MerkleError::InvalidEncoding);
+            }
+        }
+
+        new.levels.truncate(self.level_lengths.len());
+        new.levels
+            .resize_with(self.level_lengths.len(), Chunked::default);
+        for (nodes, len) in new.levels.iter_mut().zip(&self.level_lengths) {
+            nodes.truncate(*len);
+        }
+        for (level, index, value) in &self.nodes {
+            let nodes = new
+                .levels
+                .get_mut(*level)
+                .ok_or(MerkleError::InvalidEncoding)?;
+            if *index < nodes.len() {
+                nodes.set(*index, value.to_owned());
+            } else if *index == nodes.len() {
+                nodes.push(value.to_owned());
+            } else {
+                return Err(MerkleError::InvalidEncoding);
+            }
+        }
+        new.removed = Rc::new(self.removed.iter().copied().collect());
+        new.digest_index.take();
+
+        // Only leaves and nodes the delta changes can differ from the valid old version.
+        let changed_removals = tree
+            .removed
+            .symmetric_difference(&new.removed)
+            .copied()
+            .filter(|index| *index < num_of_leaves)
+            .collect::<Vec<usize>>();
+        new.check_changed(
+            self.leaves
+                .iter()
+                .map(|(index, _)| *index)
+                .chain(changed_removals),
+            self.nodes.iter().map(|(level, index, _)| (*level, *index)),
+        )?;
+        new.debug_check_tree();
+        *tree = new;
+        Ok(())
+    }
+
+    /// Upgrade a proof against the old version into a proof of the same leaf against the new
+    /// version, without the new tree, for trees with `Padding::Promote` that were only
+    /// appended to. Every audit hash of the new proof is either unchanged, and so in the old
+    /// proof, or new, and so in the delta. Fail with `MerkleError::SchemeMismatch` if the
+    /// proof or delta uses another hash function or scheme, or padding other than
+    /// `Padding::Promote`, with
+    /// `MerkleError::DeltaBaseMismatch` if it does not verify against the old root, with
+    /// `MerkleError::NotAppendOnly` if the delta changes leaves the old version already had,
+    /// and with `MerkleError::ProofHashMismatch` if the upgraded proof does not verify against
+    /// the new root.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - Proof against the old version.
+    pub fn upgrade_proof(&self, proof: &MerkleProof) -> Result<MerkleProof, MerkleError> {
+        let old_len = proof.num_of_leaves;
+        let old_positions = audit_path_positions(proof.leaf_index, old_len);
+        if proof.algorithm != self.algorithm
+            || proof.concatenation != self.scheme.concatenation
+            || proof.truncation != self.scheme.truncation
+            || proof.leaf_encoding != self.scheme.leaf_encoding
+            || self.scheme.padding != Padding::Promote
+            || old_positions.len() != proof.hashes.len()
+        {
+            return Err(MerkleError::SchemeMismatch);
+        }
+        if self.base_root.as_deref() != Some(&proof.root_value()) {
+            return Err(MerkleError::DeltaBaseMismatch);
+        }
+        let new_len = self.level_lengths.first().copied().unwrap_or(0);
+        if new_len < old_len
+            || self.leaves.iter().any(|(index, _)| *index < old_len)
+            || self
+                .nodes
+                .iter()
+                .any(|(level, index, _)| *level == 0 && *index < old_len)
+        {
+            return Err(MerkleError::NotAppendOnly);
+        }
+
+        let changed = |level: usize, index: usize| {
+            self.nodes
+                .binary_search_by(|(l, i, _)| (*l, *i).cmp(&(level, index)))
+                .ok()
+                .map(|found| self.nodes[found].2.to_owned())
+        };
+        let unchanged = |level: usize, index: usize| {
+            old_positions
+                .iter()
+                .position(|(l, i, _)| (*l, *i) == (level, index))
+                .map(|found| proof.hashes[found].borrow().value.to_owned())
+        };
+        let hashes = audit_path_positions(proof.leaf_index, new_len)
+            .into_iter()
+            .map(|(level, index, is_left)| {
+                let value = changed(level, index)
+                    .or_else(|| unchanged(level, index))
+                    .ok_or(MerkleError::IncompleteProof)?;
+                let mut hash = Hash::new(value);
+                hash.is_left = is_left;
+                Ok(Rc::new(RefCell::new(hash)))
+            })
+            .collect::<Result<Vec<_>, MerkleError>>()?;
+
+        let upgraded = MerkleProof {
+            hashes,
+            num_of_leaves: new_len,
+            leaf_index: proof.leaf_index,
+            leaf_content: proof.leaf_content.to_owned(),
+            concatenation: proof.concatenation,
+            truncation: proof.truncation,
+            algorithm: proof.algorithm,
+            leaf_encoding: proof.leaf_encoding,
+            blinder: proof.blinder.to_owned(),
+        };
+        let new_root = changed(self.level_lengths.len().saturating_sub(1), 0)
+            .or_else(|| self.base_root.to_owned());
+        if new_root != Some(upgraded.root_value()) {
+            return Err(MerkleError::ProofHashMismatch);
+        }
+        Ok(upgraded)
+    }
+
+    /// Serialize the delta into bytes, after a versioned header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::Delta, self.algorithm);
+        put_scheme(&mut encoder, &self.scheme);
+        match &self.base_root {
+            Some(root) => encoder.put_str(root),
+            None => encoder.put_str(""),
+        }
+        encoder.put_usize(self.level_lengths.len());
+        for len in &self.level_lengths {
+            encoder.put_usize(*len);
+        }
+        encoder.put_usize(self.leaves.len());
+        for (index, leaf) in &self.leaves {
+            encoder.put_usize(*index);
+            encoder.put_str(leaf);
+        }
+        encoder.put_usize(self.nodes.len());
+        for (level, index, value) in &self.nodes {
+            encoder.put_usize(*level);
+            encoder.put_usize(*index);
+            encoder.put_str(value);
+        }
+        encoder.put_usize(self.removed.len());
+        for index in &self.removed {
+            encoder.put_usize(*index);
+        }
+        encoder.finish()
+    }
+
+    /// Deserialize a delta serialized by `to_bytes`. Fail with `MerkleError::InvalidEncoding`
+    /// if the level lengths are not those of a tree, or if leaves, nodes or removed leaves are
+    /// out of range or not in strictly ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized delta.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::Delta)?;
+        let scheme = scheme(&mut decoder, algorithm)?;
+        let base_root = Some(decoder.string()?).filter(|root| !root.is_empty());
+
+        let level_lengths = (0..decoder.usize()?)
+            .map(|_| decoder.usize())
+            .collect::<Result<Vec<usize>, MerkleError>>()?;
+        let leaves = (0..decoder.usize()?)
+            .map(|_| Ok((decoder.usize()?, decoder.string()?)))
+            .collect::<Result<Vec<(usize, String)>, MerkleError>>()?;
+        let nodes = (0..decoder.usize()?)
+            .map(|_| Ok((decoder.usize()?, decoder.usize()?, decoder.string()?)))
+            .collect::<Result<Vec<(usize, usize, String)>, MerkleError>>()?;
+        let removed = (0..decoder.usize()?)
+            .map(|_| decoder.usize())
+            .collect::<Result<Vec<usize>, MerkleError>>()?;
+        decoder.finish()?;
+
+        // Reject deltas that would not apply in order: levels must have the shape of a tree,
+        // and entries must be in range and strictly ascending, so none is given twice.
+        let num_of_leaves = level_lengths.first().copied().unwrap_or(0);
+        let mut expected = num_of_leaves;
+        for len in &level_lengths {
+            if expected == 0 || *len != expected {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            expected = if expected == 1 {
+                0
+            } else {
+                expected.div_ceil(2)
+            };
+        }
+        if expected != 0
+            || leaves.windows(2).any(|pair| pair[0].0 >= pair[1].0)
+            || leaves
+                .last()
+                .is_some_and(|(index, _)| *index >= num_of_leaves)
+            || removed.windows(2).any(|pair| pair[0] >= pair[1])
+            || removed.last().is_some_and(|index| *index >= num_of_leaves)
+            || nodes
+                .windows(2)
+                .any(|pair| (pair[0].0, pair[0].1) >= (pair[1].0, pair[1].1))
+            || nodes
+                .iter()
+                .any(|(level, index, _)| level_lengths.get(*level).map_or(true, |len| index >= len))
+        {
+            return Err(MerkleError::InvalidEncoding);
+        }
+
+        Ok(TreeDelta {
+            base_root,
+            level_lengths,
+            leaves,
+            nodes,
+            removed,
+            algorithm,
+            scheme,
+        })
+    }
+}
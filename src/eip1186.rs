@@ -0,0 +1,177 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Export of Merkle Patricia Trie proofs in the JSON shape of `eth_getProof` (EIP-1186).
+//!
+//! Like Ethereum's secure tries, the state trie stores the RLP-encoded account under the
+//! keccak256 hash of its address, and a storage trie stores the RLP-encoded value of a slot
+//! under the keccak256 hash of its key. Byte strings are written as 0x-prefixed hex (DATA),
+//! and numbers as 0x-prefixed hex without leading zeros (QUANTITY).
+use crate::hex;
+use crate::mpt::{keccak256, rlp_bytes, rlp_decode, rlp_list, PatriciaTrie, Rlp};
+
+/// Strip leading zero bytes, as RLP and QUANTITY encode integers.
+fn trim_leading_zeros(value: &[u8]) -> &[u8] {
+    let start = value
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(value.len());
+    &value[start..]
+}
+
+/// Encode bytes as DATA.
+fn data(value: &[u8]) -> String {
+    format!("\"0x{}\"", hex::encode(value))
+}
+
+/// Encode a big-endian integer as QUANTITY.
+fn quantity(value: &[u8]) -> String {
+    let digits = hex::encode(value);
+    let digits = digits.trim_start_matches('0');
+    format!("\"0x{}\"", if digits.is_empty() { "0" } else { digits })
+}
+
+/// Encode a list of byte strings as an array of DATA.
+fn data_array(values: &[Vec<u8>]) -> String {
+    let items: Vec<String> = values.iter().map(|value| data(value)).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Account fields stored in the state trie, apart from its storage root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    /// Number of transactions sent.
+    pub nonce: u64,
+
+    /// Balance in wei.
+    pub balance: u128,
+
+    /// keccak256 hash of the account's code.
+    pub code_hash: [u8; 32],
+}
+
+impl Account {
+    /// RLP-encode the account as stored in the state trie.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_hash` - Root hash of the account's storage trie.
+    pub fn rlp(&self, storage_hash: &[u8; 32]) -> Vec<u8> {
+        rlp_list(&[
+            rlp_bytes(trim_leading_zeros(&self.nonce.to_be_bytes())),
+            rlp_bytes(trim_leading_zeros(&self.balance.to_be_bytes())),
+            rlp_bytes(storage_hash),
+            rlp_bytes(&self.code_hash),
+        ])
+    }
+}
+
+/// Proof of one storage slot, an entry of `storageProof`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    /// Storage key.
+    pub key: Vec<u8>,
+
+    /// Big-endian value of the slot, empty if it is absent.
+    pub value: Vec<u8>,
+
+    /// RLP-encoded nodes on the path from the storage root.
+    pub proof: Vec<Vec<u8>>,
+}
+
+impl StorageProof {
+    /// Generate the proof of `key` from a storage trie.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - Storage trie.
+    /// * `key` - Storage key.
+    pub fn from_trie(storage: &PatriciaTrie, key: &[u8]) -> Self {
+        let path = keccak256(key);
+        let value = match storage.get(&path).map(rlp_decode) {
+            Some(Ok(Rlp::Bytes(value))) => value,
+            _ => Vec::new(),
+        };
+        StorageProof {
+            key: key.to_vec(),
+            value,
+            proof: storage.proof(&path),
+        }
+    }
+
+    /// Encode the proof as a JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"key\":{},\"value\":{},\"proof\":{}}}",
+            data(&self.key),
+            quantity(&self.value),
+            data_array(&self.proof)
+        )
+    }
+}
+
+/// Proof of an account and some of its storage slots, the result of `eth_getProof`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountProof {
+    /// Address of the account.
+    pub address: Vec<u8>,
+
+    /// Account fields.
+    pub account: Account,
+
+    /// Root hash of the account's storage trie.
+    pub storage_hash: [u8; 32],
+
+    /// RLP-encoded nodes on the path from the state root.
+    pub account_proof: Vec<Vec<u8>>,
+
+    /// Proofs of the requested storage slots.
+    pub storage_proof: Vec<StorageProof>,
+}
+
+impl AccountProof {
+    /// Generate the proof of an account and some of its storage slots.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - State trie.
+    /// * `address` - Address of the account.
+    /// * `account` - Account fields.
+    /// * `storage` - Storage trie of the account.
+    /// * `keys` - Storage keys to prove.
+    pub fn from_tries(
+        state: &PatriciaTrie,
+        address: &[u8],
+        account: Account,
+        storage: &PatriciaTrie,
+        keys: &[&[u8]],
+    ) -> Self {
+        AccountProof {
+            address: address.to_vec(),
+            account,
+            storage_hash: storage.root_hash(),
+            account_proof: state.proof(&keccak256(address)),
+            storage_proof: keys
+                .iter()
+                .map(|key| StorageProof::from_trie(storage, key))
+                .collect(),
+        }
+    }
+
+    /// Encode the proof as a JSON object with the fields of an `eth_getProof` result.
+    pub fn to_json(&self) -> String {
+        let storage_proof: Vec<String> = self
+            .storage_proof
+            .iter()
+            .map(StorageProof::to_json)
+            .collect();
+        format!(
+            "{{\"address\":{},\"accountProof\":{},\"balance\":{},\"codeHash\":{},\"nonce\":{},\"storageHash\":{},\"storageProof\":[{}]}}",
+            data(&self.address),
+            data_array(&self.account_proof),
+            quantity(&self.account.balance.to_be_bytes()),
+            data(&self.account.code_hash),
+            quantity(&self.account.nonce.to_be_bytes()),
+            data(&self.storage_hash),
+            storage_proof.join(",")
+        )
+    }
+}
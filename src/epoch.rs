@@ -0,0 +1,144 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{MerkleProof, MerkleTree};
+
+/// Two-layer merkle tree for batching roots into epochs, e.g. per-minute roots into hourly
+/// epoch roots, and hourly epoch roots into a daily super-root.
+///
+/// Roots are pushed into the current epoch until it is rolled over. Only rolled over
+/// (sealed) epochs are committed to by the super-root.
+#[derive(Default)]
+pub struct EpochTree {
+    /// Roots of every sealed epoch, as hexdigests.
+    epochs: Vec<Vec<String>>,
+
+    /// Roots pushed into the current, not yet sealed, epoch.
+    current: Vec<String>,
+}
+
+/// Hold information needed to verify that a root belongs to an epoch,
+/// and that the epoch belongs to a super-root.
+pub struct EpochProof {
+    /// 0-based index of the epoch.
+    pub epoch: usize,
+
+    /// Proof that the root is a leaf of the epoch tree.
+    pub epoch_proof: MerkleProof,
+
+    /// Proof that the epoch root is a leaf of the super-root tree.
+    pub super_proof: MerkleProof,
+}
+
+impl EpochTree {
+    /// Initialize an empty epoch tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a root to the current epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root value as hexdigest.
+    pub fn push(&mut self, root: String) {
+        self.current.push(root);
+    }
+
+    /// Seal the current epoch and start a new one. Return the root of the sealed epoch,
+    /// or None if no roots were pushed into the current epoch.
+    ///
+    /// Proofs issued before rolling over are anchored to the previous super-root;
+    /// use `reanchor` to update them.
+    pub fn roll_over(&mut self) -> Option<String> {
+        if self.current.is_empty() {
+            return None;
+        }
+        let epoch = std::mem::take(&mut self.current);
+        let epoch_root = MerkleTree::merkle_root(&epoch).borrow().value.to_owned();
+        self.epochs.push(epoch);
+        Some(epoch_root)
+    }
+
+    /// Number of sealed epochs.
+    pub fn num_of_epochs(&self) -> usize {
+        self.epochs.len()
+    }
+
+    /// Return the root of a sealed epoch as hexdigest, or None if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - 0-based index of the epoch.
+    pub fn epoch_root(&self, epoch: usize) -> Option<String> {
+        self.epochs
+            .get(epoch)
+            .map(|roots| MerkleTree::merkle_root(roots).borrow().value.to_owned())
+    }
+
+    /// Return the super-root over all sealed epochs as hexdigest, or None if no epoch is sealed.
+    pub fn super_root(&self) -> Option<String> {
+        if self.epochs.is_empty() {
+            return None;
+        }
+        Some(
+            MerkleTree::merkle_root(&self.epoch_roots())
+                .borrow()
+                .value
+                .to_owned(),
+        )
+    }
+
+    /// Generate a two-layer proof for a root in a sealed epoch, anchored to the current
+    /// super-root. Return None if the epoch or root does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - 0-based index of the epoch.
+    /// * `index` - 0-based index of the root within the epoch.
+    pub fn prove(&self, epoch: usize, index: usize) -> Option<EpochProof> {
+        let roots = self.epochs.get(epoch)?;
+        if index >= roots.len() {
+            return None;
+        }
+        Some(EpochProof {
+            epoch,
+            epoch_proof: MerkleTree::merkle_proof(roots, index),
+            super_proof: MerkleTree::merkle_proof(&self.epoch_roots(), epoch),
+        })
+    }
+
+    /// Re-anchor a proof issued against an earlier super-root to the current super-root.
+    /// The epoch layer of a proof never changes once its epoch is sealed, so only the
+    /// super-root layer is regenerated. Return None if the epoch does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - Proof to re-anchor.
+    pub fn reanchor(&self, proof: EpochProof) -> Option<EpochProof> {
+        if proof.epoch >= self.epochs.len() {
+            return None;
+        }
+        Some(EpochProof {
+            super_proof: MerkleTree::merkle_proof(&self.epoch_roots(), proof.epoch),
+            ..proof
+        })
+    }
+
+    /// Verify a two-layer proof against a super-root.
+    ///
+    /// # Arguments
+    ///
+    /// * `super_root` - Super-root value as hexdigest.
+    /// * `proof` - Proof to be verified.
+    pub fn verify(super_root: &str, proof: &EpochProof) -> bool {
+        proof.super_proof.leaf_index == proof.epoch
+            && proof.epoch_proof.root_value() == proof.super_proof.leaf_content
+            && proof.super_proof.root_value() == super_root
+    }
+
+    /// Roots of every sealed epoch, as hexdigests.
+    fn epoch_roots(&self) -> Vec<String> {
+        (0..self.epochs.len())
+            .filter_map(|epoch| self.epoch_root(epoch))
+            .collect()
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use std::fmt;
+
+/// Errors returned by fallible merkle tree operations. New variants may be added in minor
+/// releases, so matches need a wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MerkleError {
+    /// Serialized proof length is not a multiple of the digest size.
+    InvalidProofLength(usize),
+
+    /// Serialized data is truncated or malformed.
+    InvalidEncoding,
+
+    /// Serialized data was written with an unsupported format version.
+    UnsupportedVersion(u64),
+
+    /// Serialized data uses a hash algorithm or tree arity that is not supported.
+    UnsupportedScheme,
+
+    /// Proof or tree was built with another hash function or scheme than a committed root records.
+    SchemeMismatch,
+
+    /// Delta was computed against a tree whose root differs from the tree it is applied to.
+    DeltaBaseMismatch,
+
+    /// Delta changes leaves that the version it was computed against already had, so it is
+    /// not append-only.
+    NotAppendOnly,
+
+    /// Leaf content does not hash to the expected leaf digest. Holds the computed digest.
+    LeafDigestMismatch(String),
+
+    /// Proof ends before reaching the node it needs to prove.
+    IncompleteProof,
+
+    /// Proof node does not hash to the value its parent (or the root) commits to.
+    ProofHashMismatch,
+
+    /// Leaf is empty but the builder was configured to reject empty leaves. Holds its index.
+    EmptyLeaf(usize),
+
+    /// More leaves were given than the builder was configured to accept. Holds their number.
+    TooManyLeaves(usize),
+
+    /// Leaf is larger than the builder was configured to accept. Holds its index.
+    LeafTooLarge(usize),
+
+    /// Leaf content is not valid in the leaf encoding of the builder. Holds its index.
+    InvalidLeafEncoding(usize),
+
+    /// Fewer trusted witnesses cosigned a root than required. Holds the number that did.
+    QuorumNotReached(usize),
+
+    /// Quorum threshold is 0, which any set of signatures would trivially satisfy.
+    InvalidThreshold,
+
+    /// Tile needed to assemble a proof could not be fetched. Holds its path.
+    MissingTile(String),
+
+    /// Tree differs from a golden snapshot. Holds the 1-based number of the first line that
+    /// differs.
+    GoldenMismatch(usize),
+
+    /// Column has a data type without a canonical leaf encoding. Holds its field name.
+    UnsupportedColumn(String),
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerkleError::InvalidProofLength(len) => write!(
+                f,
+                "serialized proof of {len} bytes is not a whole number of digests; \
+                 check that it was not truncated and uses the expected hash function"
+            ),
+            MerkleError::InvalidEncoding => write!(
+                f,
+                "serialized data is truncated or malformed; check that it was written by \
+                 this crate and transferred intact"
+            ),
+            MerkleError::UnsupportedVersion(version) => write!(
+                f,
+                "serialized data has format version {version}, which this release cannot \
+                 read; upgrade the crate"
+            ),
+            MerkleError::UnsupportedScheme => write!(
+                f,
+                "serialized data uses a hash function or tree arity that is not supported; \
+                 enable the cargo feature of its hash function"
+            ),
+            MerkleError::SchemeMismatch => write!(
+                f,
+                "hash function or tree scheme differs from the committed root; build the \
+                 tree or proof with the configuration the root records"
+            ),
+            MerkleError::DeltaBaseMismatch => write!(
+                f,
+                "delta was computed against another tree; apply it to the tree it was \
+                 computed from"
+            ),
+            MerkleError::NotAppendOnly => write!(
+                f,
+                "delta changes existing leaves, not only appends; rebuild proofs from the new \
+                 tree instead"
+            ),
+            MerkleError::LeafDigestMismatch(digest) => write!(
+                f,
+                "leaf content hashes to {digest}, not the expected leaf digest"
+            ),
+            MerkleError::IncompleteProof => write!(
+                f,
+                "proof ends before reaching the node it proves; it may be truncated"
+            ),
+            MerkleError::ProofHashMismatch => write!(
+                f,
+                "proof does not reproduce the root; the leaf, proof or root is wrong"
+            ),
+            MerkleError::EmptyLeaf(index) => write!(
+                f,
+                "leaf {index} is empty, which the builder was configured to reject"
+            ),
+            MerkleError::TooManyLeaves(count) => write!(
+                f,
+                "{count} leaves exceed the maximum the builder was configured to accept"
+            ),
+            MerkleError::LeafTooLarge(index) => write!(
+                f,
+                "leaf {index} exceeds the maximum size the builder was configured to accept"
+            ),
+            MerkleError::InvalidLeafEncoding(index) => write!(
+                f,
+                "leaf {index} is not valid in the leaf encoding of the builder"
+            ),
+            MerkleError::QuorumNotReached(count) => write!(
+                f,
+                "only {count} trusted witnesses cosigned the root, fewer than required"
+            ),
+            MerkleError::InvalidThreshold => write!(
+                f,
+                "quorum threshold is 0; require at least one trusted witness"
+            ),
+            MerkleError::MissingTile(path) => write!(
+                f,
+                "tile {path} could not be fetched; check that the tile server is reachable \
+                 and holds the tree"
+            ),
+            MerkleError::GoldenMismatch(line) => write!(
+                f,
+                "tree differs from the golden snapshot at line {line}; regenerate the \
+                 snapshot if the change is intended"
+            ),
+            MerkleError::UnsupportedColumn(field) => write!(
+                f,
+                "column {field} has a data type without a canonical leaf encoding; cast it \
+                 to a supported type first"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MerkleError {}
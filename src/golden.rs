@@ -0,0 +1,55 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{MerkleError, MerkleTree};
+
+/// First line of every golden snapshot, naming the version of the snapshot format.
+const GOLDEN_HEADER: &str = "merkle-tree golden v1";
+
+impl MerkleTree {
+    /// Dump the configuration and every node of the tree as canonical text, for golden-file
+    /// regression tests across refactors of the construction code. After the header and one
+    /// line per configuration parameter, every level from the leaf level up is a line
+    /// `level <index> <number of nodes>` followed by one line per node value as hexdigest.
+    /// The blinding key is not written, so snapshots of blinded trees are not secret. Equal
+    /// trees always give equal text.
+    pub fn to_golden(&self) -> String {
+        let mut lines = vec![
+            GOLDEN_HEADER.to_owned(),
+            format!("algorithm {:?}", self.algorithm),
+            format!("concatenation {:?}", self.concatenation),
+            format!("padding {:?}", self.padding),
+            format!("ordering {:?}", self.ordering),
+            format!("truncation {:?}", self.truncation),
+            format!("leaf_encoding {:?}", self.leaf_encoding),
+            format!("blinded {}", self.blinding_key.is_some()),
+            format!("leaves {}", self.num_of_leaves()),
+        ];
+        for (level, nodes) in self.levels.iter().enumerate() {
+            lines.push(format!("level {} {}", level, nodes.len()));
+            lines.extend((0..nodes.len()).map(|index| nodes.get(index).unwrap().to_owned()));
+        }
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    /// Check the tree against a snapshot written by `to_golden`. Line endings are ignored, so
+    /// snapshots checked out with CRLF line endings still match. Fail with
+    /// `MerkleError::GoldenMismatch` holding the 1-based number of the first line that
+    /// differs, or of the first missing or extra line.
+    ///
+    /// # Arguments
+    ///
+    /// * `golden` - Snapshot text.
+    pub fn verify_golden(&self, golden: &str) -> Result<(), MerkleError> {
+        let actual = self.to_golden();
+        let mut expected = golden.lines();
+        let mut actual = actual.lines();
+        for line in 1.. {
+            match (expected.next(), actual.next()) {
+                (None, None) => return Ok(()),
+                (Some(expected), Some(actual)) if expected == actual => {}
+                _ => return Err(MerkleError::GoldenMismatch(line)),
+            }
+        }
+        unreachable!()
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::codec::{Artifact, Decoder, Encoder};
+use crate::{Concatenation, HashAlgorithm, MerkleError};
+
+/// Append-only log in which every entry commits to all entries before it.
+///
+/// The link of the first entry is the hash of the entry; the link of every later entry is the
+/// hexdigest concatenation of the previous link and the hash of the entry, hashed as parents
+/// of a `Concatenation::Hex` merkle tree are. The last link, the head, commits to the whole log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashChain {
+    entries: Vec<String>,
+    links: Vec<String>,
+    algorithm: HashAlgorithm,
+}
+
+/// Return the link of an entry, as hexdigest.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+/// * `previous` - Link of the previous entry, or None for the first entry.
+/// * `entry_hash` - Hash of the entry as hexdigest.
+fn link(algorithm: HashAlgorithm, previous: Option<&str>, entry_hash: &str) -> String {
+    match previous {
+        Some(previous) => Concatenation::Hex.hash_with(algorithm, previous, entry_hash),
+        None => entry_hash.to_owned(),
+    }
+}
+
+impl HashChain {
+    /// Initialize an empty chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function for entries and links.
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        HashChain {
+            entries: Vec::new(),
+            links: Vec::new(),
+            algorithm,
+        }
+    }
+
+    /// Hash function for entries and links.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return the head as hexdigest, or None if there are no entries.
+    pub fn head(&self) -> Option<&str> {
+        self.links.last().map(String::as_str)
+    }
+
+    /// Append an entry and return the new head.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Content of entry.
+    pub fn append(&mut self, entry: String) -> &str {
+        let entry_hash = self.algorithm.hash(entry.as_bytes());
+        let value = link(self.algorithm, self.head(), &entry_hash);
+        self.entries.push(entry);
+        self.links.push(value);
+        self.head().unwrap()
+    }
+
+    /// Generate a receipt that the entry at `index` is in the chain with the current head.
+    /// Return None if `index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - 0-based index of entry.
+    pub fn receipt(&self, index: usize) -> Option<ChainReceipt> {
+        let entry = self.entries.get(index)?.to_owned();
+        Some(ChainReceipt {
+            index,
+            entry,
+            previous: index
+                .checked_sub(1)
+                .map(|previous| self.links[previous].to_owned()),
+            later: self.entries[index + 1..]
+                .iter()
+                .map(|entry| self.algorithm.hash(entry.as_bytes()))
+                .collect(),
+            algorithm: self.algorithm,
+        })
+    }
+
+    /// Serialize the chain into bytes: the entries in order, after a versioned header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::HashChain, self.algorithm);
+        encoder.put_usize(self.entries.len());
+        for entry in &self.entries {
+            encoder.put_str(entry);
+        }
+        encoder.finish()
+    }
+
+    /// Deserialize a chain serialized by `to_bytes`, recomputing its links.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized chain.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::HashChain)?;
+        let entries = (0..decoder.usize()?)
+            .map(|_| decoder.string())
+            .collect::<Result<Vec<String>, MerkleError>>()?;
+        decoder.finish()?;
+
+        let mut chain = HashChain::new(algorithm);
+        for entry in entries {
+            chain.append(entry);
+        }
+        Ok(chain)
+    }
+}
+
+/// Receipt that an entry is in a hash chain with a given head.
+///
+/// The receipt holds the link before the entry and the hashes of every later entry, so its
+/// size grows with the number of entries appended after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReceipt {
+    /// 0-based index of entry.
+    pub index: usize,
+
+    /// Content of entry.
+    pub entry: String,
+
+    /// Link of the previous entry as hexdigest, or None for the first entry.
+    pub previous: Option<String>,
+
+    /// Hashes of the entries after this one as hexdigests, in order.
+    pub later: Vec<String>,
+
+    /// Hash function of the chain.
+    pub algorithm: HashAlgorithm,
+}
+
+impl ChainReceipt {
+    /// Reconstruct the head implied by this receipt, as hexdigest.
+    pub fn head(&self) -> String {
+        let entry_hash = self.algorithm.hash(self.entry.as_bytes());
+        let mut value = link(self.algorithm, self.previous.as_deref(), &entry_hash);
+        for entry_hash in &self.later {
+            value = link(self.algorithm, Some(&value), entry_hash);
+        }
+        value
+    }
+
+    /// Verify that the entry is in the chain with head `head`. Heads are compared
+    /// case-insensitively.
+    ///
+    /// # Arguments
+    ///
+    /// * `head` - Head of the chain as hexdigest.
+    pub fn verify(&self, head: &str) -> bool {
+        (self.index == 0) == self.previous.is_none() && self.head().eq_ignore_ascii_case(head)
+    }
+
+    /// Serialize the receipt into bytes, after a versioned header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::ChainReceipt, self.algorithm);
+        encoder.put_usize(self.index);
+        encoder.put_str(&self.entry);
+        match &self.previous {
+            None => encoder.put_u64(0),
+            Some(previous) => {
+                encoder.put_u64(1);
+                encoder.put_str(previous);
+            }
+        }
+        encoder.put_usize(self.later.len());
+        for entry_hash in &self.later {
+            encoder.put_str(entry_hash);
+        }
+        encoder.finish()
+    }
+
+    /// Deserialize a receipt serialized by `to_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized receipt.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::ChainReceipt)?;
+        let index = decoder.usize()?;
+        let entry = decoder.string()?;
+        let previous = match decoder.u64()? {
+            0 => None,
+            1 => Some(decoder.string()?),
+            _ => return Err(MerkleError::InvalidEncoding),
+        };
+        let later = (0..decoder.usize()?)
+            .map(|_| decoder.string())
+            .collect::<Result<Vec<String>, MerkleError>>()?;
+        decoder.finish()?;
+
+        if (index == 0) != previous.is_none() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(ChainReceipt {
+            index,
+            entry,
+            previous,
+            later,
+            algorithm,
+        })
+    }
+}
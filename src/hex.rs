@@ -0,0 +1,31 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+
+/// Encode bytes as a lowercase hex string.
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes to encode.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut value = String::with_capacity(2 * bytes.len());
+    for byte in bytes {
+        value.push(DIGITS[(byte >> 4) as usize] as char);
+        value.push(DIGITS[(byte & 15) as usize] as char);
+    }
+    value
+}
+
+/// Decode a hex string (either case) into bytes. Return None if it is not valid hex.
+///
+/// # Arguments
+///
+/// * `value` - Hex string to decode.
+pub(crate) fn decode(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,500 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Versioned AVL+ merkle tree in the style of IAVL.
+//!
+//! Keys are kept in order, values are stored only at the leaves, and every inner node is keyed
+//! by the smallest key of its right subtree. Updates copy the path from the changed leaf to the
+//! root, so every saved version shares all unchanged nodes and stays queryable.
+use crate::{provider, HashAlgorithm, MerkleError};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Key-value pair.
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+/// Node of an AVL+ tree.
+enum Node {
+    Leaf {
+        key: Vec<u8>,
+        value: Vec<u8>,
+        hash: [u8; 32],
+    },
+    Inner {
+        key: Vec<u8>,
+        height: u8,
+        size: usize,
+        left: Rc<Node>,
+        right: Rc<Node>,
+        hash: [u8; 32],
+    },
+}
+
+/// Hash of a leaf, domain separated from inner nodes.
+fn leaf_hash(key: &[u8], value: &[u8]) -> [u8; 32] {
+    let mut hasher = provider::hasher(HashAlgorithm::Sha256);
+    hasher.update(&[0]);
+    hasher.update(&(key.len() as u64).to_le_bytes());
+    hasher.update(key);
+    hasher.update(&(value.len() as u64).to_le_bytes());
+    hasher.update(value);
+    hasher.finalize().try_into().unwrap()
+}
+
+/// Hash of an inner node, domain separated from leaves.
+fn inner_hash(key: &[u8], left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = provider::hasher(HashAlgorithm::Sha256);
+    hasher.update(&[1]);
+    hasher.update(&(key.len() as u64).to_le_bytes());
+    hasher.update(key);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().try_into().unwrap()
+}
+
+/// Root hash of a tree without any keys, the sha256 digest of empty input.
+pub fn empty_root_hash() -> [u8; 32] {
+    provider::sha256(&[])
+}
+
+impl Node {
+    /// Create a leaf node.
+    fn leaf(key: Vec<u8>, value: Vec<u8>) -> Rc<Node> {
+        let hash = leaf_hash(&key, &value);
+        Rc::new(Node::Leaf { key, value, hash })
+    }
+
+    /// Create an inner node keyed by the smallest key of `right`.
+    fn inner(key: Vec<u8>, left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+        Rc::new(Node::Inner {
+            hash: inner_hash(&key, left.hash(), right.hash()),
+            height: 1 + left.height().max(right.height()),
+            size: left.size() + right.size(),
+            key,
+            left,
+            right,
+        })
+    }
+
+    /// Hash of the subtree rooted at this node.
+    fn hash(&self) -> &[u8; 32] {
+        match self {
+            Node::Leaf { hash, .. } | Node::Inner { hash, .. } => hash,
+        }
+    }
+
+    /// Height of the subtree rooted at this node. Leaves have height 0.
+    fn height(&self) -> u8 {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Inner { height, .. } => *height,
+        }
+    }
+
+    /// Number of leaves in the subtree rooted at this node.
+    fn size(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 1,
+            Node::Inner { size, .. } => *size,
+        }
+    }
+
+    /// Height of left subtree minus height of right subtree.
+    fn balance(&self) -> i16 {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Inner { left, right, .. } => left.height() as i16 - right.height() as i16,
+        }
+    }
+
+    /// Rotate an inner node whose left child is an inner node to the right.
+    fn rotate_right(node: &Rc<Node>) -> Rc<Node> {
+        let Node::Inner {
+            key, left, right, ..
+        } = node.as_ref()
+        else {
+            unreachable!()
+        };
+        let Node::Inner {
+            key: left_key,
+            left: left_left,
+            right: left_right,
+            ..
+        } = left.as_ref()
+        else {
+            unreachable!()
+        };
+        Node::inner(
+            left_key.to_owned(),
+            left_left.to_owned(),
+            Node::inner(key.to_owned(), left_right.to_owned(), right.to_owned()),
+        )
+    }
+
+    /// Rotate an inner node whose right child is an inner node to the left.
+    fn rotate_left(node: &Rc<Node>) -> Rc<Node> {
+        let Node::Inner {
+            key, left, right, ..
+        } = node.as_ref()
+        else {
+            unreachable!()
+        };
+        let Node::Inner {
+            key: right_key,
+            left: right_left,
+            right: right_right,
+            ..
+        } = right.as_ref()
+        else {
+            unreachable!()
+        };
+        Node::inner(
+            right_key.to_owned(),
+            Node::inner(key.to_owned(), left.to_owned(), right_left.to_owned()),
+            right_right.to_owned(),
+        )
+    }
+
+    /// Restore the AVL invariant of an inner node whose subtrees are balanced.
+    fn rebalance(node: Rc<Node>) -> Rc<Node> {
+        let Node::Inner {
+            key, left, right, ..
+        } = node.as_ref()
+        else {
+            return node;
+        };
+        let balance = node.balance();
+        if balance > 1 {
+            let left = if left.balance() < 0 {
+                Node::rotate_left(left)
+            } else {
+                left.to_owned()
+            };
+            Node::rotate_right(&Node::inner(key.to_owned(), left, right.to_owned()))
+        } else if balance < -1 {
+            let right = if right.balance() > 0 {
+                Node::rotate_right(right)
+            } else {
+                right.to_owned()
+            };
+            Node::rotate_left(&Node::inner(key.to_owned(), left.to_owned(), right))
+        } else {
+            node
+        }
+    }
+
+    /// Return a copy of the subtree with `value` stored under `key`.
+    fn insert(node: &Rc<Node>, key: Vec<u8>, value: Vec<u8>) -> Rc<Node> {
+        match node.as_ref() {
+            Node::Leaf { key: leaf_key, .. } => match key.as_slice().cmp(leaf_key) {
+                std::cmp::Ordering::Equal => Node::leaf(key, value),
+                std::cmp::Ordering::Less => {
+                    Node::inner(leaf_key.to_owned(), Node::leaf(key, value), node.to_owned())
+                }
+                std::cmp::Ordering::Greater => {
+                    Node::inner(key.to_owned(), node.to_owned(), Node::leaf(key, value))
+                }
+            },
+            Node::Inner {
+                key: node_key,
+                left,
+                right,
+                ..
+            } => {
+                let node = if key < *node_key {
+                    Node::inner(
+                        node_key.to_owned(),
+                        Node::insert(left, key, value),
+                        right.to_owned(),
+                    )
+                } else {
+                    Node::inner(
+                        node_key.to_owned(),
+                        left.to_owned(),
+                        Node::insert(right, key, value),
+                    )
+                };
+                Node::rebalance(node)
+            }
+        }
+    }
+
+    /// Return the value stored under `key` in the subtree.
+    fn get<'a>(mut node: &'a Rc<Node>, key: &[u8]) -> Option<&'a [u8]> {
+        loop {
+            match node.as_ref() {
+                Node::Leaf {
+                    key: leaf_key,
+                    value,
+                    ..
+                } => return (leaf_key == key).then_some(value.as_slice()),
+                Node::Inner {
+                    key: node_key,
+                    left,
+                    right,
+                    ..
+                } => {
+                    node = if key < node_key.as_slice() {
+                        left
+                    } else {
+                        right
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether the keys `lower..upper` of a subtree (None meaning unbounded)
+/// may intersect the queried range `start..end`.
+fn intersects(
+    lower: Option<&[u8]>,
+    upper: Option<&[u8]>,
+    start: &[u8],
+    end: Option<&[u8]>,
+) -> bool {
+    upper.map_or(true, |upper| upper > start)
+        && match (lower, end) {
+            (Some(lower), Some(end)) => lower < end,
+            _ => true,
+        }
+        && end.map_or(true, |end| start < end)
+}
+
+/// Proof of every key-value pair in a range, as the tree with all subtrees
+/// lying entirely outside the range replaced by their hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeProof {
+    /// Tree has no keys.
+    Empty,
+
+    /// Hash of a subtree lying entirely outside the range.
+    Pruned([u8; 32]),
+
+    /// Leaf that may lie inside the range.
+    Leaf { key: Vec<u8>, value: Vec<u8> },
+
+    /// Inner node whose subtree may intersect the range.
+    Inner {
+        key: Vec<u8>,
+        left: Box<RangeProof>,
+        right: Box<RangeProof>,
+    },
+}
+
+impl RangeProof {
+    /// Generate a (sub)proof for the subtree rooted at `node`, whose keys lie in `lower..upper`.
+    fn generate(
+        node: &Node,
+        lower: Option<&[u8]>,
+        upper: Option<&[u8]>,
+        start: &[u8],
+        end: Option<&[u8]>,
+    ) -> Self {
+        if !intersects(lower, upper, start, end) {
+            return RangeProof::Pruned(*node.hash());
+        }
+        match node {
+            Node::Leaf { key, value, .. } => RangeProof::Leaf {
+                key: key.to_owned(),
+                value: value.to_owned(),
+            },
+            Node::Inner {
+                key, left, right, ..
+            } => RangeProof::Inner {
+                key: key.to_owned(),
+                left: Box::new(Self::generate(left, lower, Some(key), start, end)),
+                right: Box::new(Self::generate(right, Some(key), upper, start, end)),
+            },
+        }
+    }
+
+    /// Recompute the hash of this (sub)proof, checking that every key lies where the tree order
+    /// requires and that no pruned subtree could hide a key in the range. Key-value pairs
+    /// inside the range are appended to `entries` in order.
+    fn verify(
+        &self,
+        lower: Option<&[u8]>,
+        upper: Option<&[u8]>,
+        start: &[u8],
+        end: Option<&[u8]>,
+        entries: &mut Vec<Entry>,
+    ) -> Result<[u8; 32], MerkleError> {
+        let within_bounds = |key: &[u8]| {
+            lower.map_or(true, |lower| key >= lower) && upper.map_or(true, |upper| key < upper)
+        };
+        match self {
+            RangeProof::Empty => Err(MerkleError::InvalidEncoding),
+            RangeProof::Pruned(hash) => {
+                if intersects(lower, upper, start, end) {
+                    return Err(MerkleError::IncompleteProof);
+                }
+                Ok(*hash)
+            }
+            RangeProof::Leaf { key, value } => {
+                if !within_bounds(key) {
+                    return Err(MerkleError::InvalidEncoding);
+                }
+                if key.as_slice() >= start && end.map_or(true, |end| key.as_slice() < end) {
+                    entries.push((key.to_owned(), value.to_owned()));
+                }
+                Ok(leaf_hash(key, value))
+            }
+            RangeProof::Inner { key, left, right } => {
+                if !within_bounds(key) {
+                    return Err(MerkleError::InvalidEncoding);
+                }
+                let left = left.verify(lower, Some(key), start, end, entries)?;
+                let right = right.verify(Some(key), upper, start, end, entries)?;
+                Ok(inner_hash(key, &left, &right))
+            }
+        }
+    }
+}
+
+/// Verify a range proof against a root hash. Return every key-value pair with a key in
+/// `start..end` (unbounded above if `end` is None), in key order.
+///
+/// # Arguments
+///
+/// * `root_hash` - Root hash of the tree version.
+/// * `start` - Inclusive lower bound of keys.
+/// * `end` - Exclusive upper bound of keys, or None for no upper bound.
+/// * `proof` - Proof to be verified.
+pub fn verify_range_proof(
+    root_hash: &[u8; 32],
+    start: &[u8],
+    end: Option<&[u8]>,
+    proof: &RangeProof,
+) -> Result<Vec<Entry>, MerkleError> {
+    let mut entries = Vec::new();
+    let hash = match proof {
+        RangeProof::Empty => empty_root_hash(),
+        _ => proof.verify(None, None, start, end, &mut entries)?,
+    };
+    if hash != *root_hash {
+        return Err(MerkleError::ProofHashMismatch);
+    }
+    Ok(entries)
+}
+
+/// Versioned AVL+ merkle tree mapping ordered byte string keys to byte string values.
+///
+/// Changes are made to a working tree, and `save_version` commits the working tree as a new
+/// immutable version that can still be queried and proven after later changes.
+#[derive(Default)]
+pub struct VersionedTree {
+    working: Option<Rc<Node>>,
+    versions: BTreeMap<u64, Option<Rc<Node>>>,
+}
+
+impl VersionedTree {
+    /// Initialize an empty tree without any saved version.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `value` under `key` in the working tree, replacing any previous value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to store under.
+    /// * `value` - Value to store.
+    pub fn set(&mut self, key: &[u8], value: Vec<u8>) {
+        self.working = Some(match &self.working {
+            None => Node::leaf(key.to_vec(), value),
+            Some(root) => Node::insert(root, key.to_vec(), value),
+        });
+    }
+
+    /// Return the value stored under `key` in the working tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to look up.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        Node::get(self.working.as_ref()?, key)
+    }
+
+    /// Number of keys in the working tree.
+    pub fn len(&self) -> usize {
+        self.working.as_ref().map_or(0, |root| root.size())
+    }
+
+    /// Whether the working tree has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.working.is_none()
+    }
+
+    /// Root hash of the working tree.
+    pub fn working_hash(&self) -> [u8; 32] {
+        self.working
+            .as_ref()
+            .map_or_else(empty_root_hash, |root| *root.hash())
+    }
+
+    /// Commit the working tree as a new version, numbered one after the latest version
+    /// (starting from 1). Return the version number and its root hash.
+    pub fn save_version(&mut self) -> (u64, [u8; 32]) {
+        let version = self.latest_version().unwrap_or(0) + 1;
+        self.versions.insert(version, self.working.to_owned());
+        (version, self.working_hash())
+    }
+
+    /// Latest saved version number, or None if no version is saved.
+    pub fn latest_version(&self) -> Option<u64> {
+        self.versions.keys().next_back().copied()
+    }
+
+    /// Root hash of a saved version, or None if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - Version number.
+    pub fn root_hash(&self, version: u64) -> Option<[u8; 32]> {
+        let root = self.versions.get(&version)?;
+        Some(
+            root.as_ref()
+                .map_or_else(empty_root_hash, |root| *root.hash()),
+        )
+    }
+
+    /// Return the value stored under `key` in a saved version.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to look up.
+    /// * `version` - Version number.
+    pub fn get_versioned(&self, key: &[u8], version: u64) -> Option<&[u8]> {
+        Node::get(self.versions.get(&version)?.as_ref()?, key)
+    }
+
+    /// Generate a proof of every key-value pair with a key in `start..end` of a saved version.
+    /// Return None if the version does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Inclusive lower bound of keys.
+    /// * `end` - Exclusive upper bound of keys, or None for no upper bound.
+    /// * `version` - Version number.
+    pub fn range_proof(
+        &self,
+        start: &[u8],
+        end: Option<&[u8]>,
+        version: u64,
+    ) -> Option<RangeProof> {
+        Some(match self.versions.get(&version)? {
+            None => RangeProof::Empty,
+            Some(root) => RangeProof::generate(root, None, None, start, end),
+        })
+    }
+
+    /// Generate a proof of the value stored under `key` in a saved version, or of its absence.
+    /// Verify it with `verify_range_proof(root_hash, key, Some(&[key, &[0]].concat()), proof)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to prove.
+    /// * `version` - Version number.
+    pub fn key_proof(&self, key: &[u8], version: u64) -> Option<RangeProof> {
+        self.range_proof(key, Some(&[key, &[0]].concat()), version)
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Import of tabular exports, CSV (RFC 4180) and NDJSON, into merkle trees, so a root can be
+//! computed from raw data in one call.
+//!
+//! Every row becomes the leaf `[v1,v2,...]`, the JSON array of its selected fields in
+//! selector order. CSV fields are JSON strings, and NDJSON fields are their canonical values
+//! as `json_tree::flatten` returns them, so the leaves do not depend on quoting or
+//! whitespace.
+use crate::json_tree::{flatten, quote};
+use crate::{MerkleError, MerkleTree, MerkleTreeBuilder};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Return an `io::ErrorKind::InvalidData` error for a malformed row.
+///
+/// # Arguments
+///
+/// * `line` - 1-based line number of the row.
+/// * `reason` - What is wrong with the row.
+fn invalid_row(line: usize, reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, reason),
+    )
+}
+
+/// Return an `io::ErrorKind::InvalidData` error for leaves the builder refuses.
+///
+/// # Arguments
+///
+/// * `error` - Reason the leaves are refused.
+fn refused(error: MerkleError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Split CSV text into records of fields, with the 1-based line number each record starts
+/// on. Fields may be quoted, with `""` for a quote, and quoted fields may span lines. Lines
+/// end with LF or CRLF, and a final line ending is optional.
+///
+/// # Arguments
+///
+/// * `text` - CSV text.
+fn csv_records(text: &str) -> io::Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut record = Vec::new();
+        loop {
+            let mut field = String::new();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            field.push(c);
+                        }
+                        None => return Err(invalid_row(start, "unterminated quoted field")),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !matches!(c, ',' | '\r' | '\n')) {
+                    if c == '"' {
+                        return Err(invalid_row(start, "quote in unquoted field"));
+                    }
+                    field.push(c);
+                }
+            }
+            record.push(field);
+            match chars.next() {
+                Some(',') => continue,
+                Some('\r') if chars.next() == Some('\n') => {}
+                Some('\n') | None => {}
+                _ => return Err(invalid_row(start, "unexpected character after field")),
+            }
+            line += 1;
+            break;
+        }
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+impl MerkleTree {
+    /// Build a tree with the default configuration from a CSV file with a header row, with
+    /// one leaf per data row holding the fields of `columns`. Fail with
+    /// `io::ErrorKind::InvalidInput` if a column is not in the header, and with
+    /// `io::ErrorKind::InvalidData` if the file is not UTF-8, has no header, or has a
+    /// malformed row or one with another number of fields than the header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the CSV file.
+    /// * `columns` - Header names of the fields of each leaf, in leaf order.
+    pub fn from_csv<P: AsRef<Path>>(path: P, columns: &[&str]) -> io::Result<Self> {
+        Self::builder().build_from_csv(path, columns)
+    }
+
+    /// Build a tree with the default configuration from an NDJSON file, with one leaf per
+    /// non-blank line holding the fields at `pointers`. Only scalars, empty objects and empty
+    /// arrays are fields. Fail with `io::ErrorKind::InvalidData` if the file is not UTF-8, a
+    /// line is not valid JSON, or a line has no field at one of the pointers.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the NDJSON file.
+    /// * `pointers` - JSON Pointers of the fields of each leaf, in leaf order.
+    pub fn from_ndjson<P: AsRef<Path>>(path: P, pointers: &[&str]) -> io::Result<Self> {
+        Self::builder().build_from_ndjson(path, pointers)
+    }
+}
+
+impl MerkleTreeBuilder {
+    /// Build a tree with this configuration from a CSV file like `MerkleTree::from_csv`.
+    /// Also fail with `io::ErrorKind::InvalidData`, wrapping the `MerkleError`, if the
+    /// leaves are refused like `try_build` refuses them. Limits are checked row by row, so
+    /// rows past the first violation are not turned into leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the CSV file.
+    /// * `columns` - Header names of the fields of each leaf, in leaf order.
+    pub fn build_from_csv<P: AsRef<Path>>(
+        &self,
+        path: P,
+        columns: &[&str],
+    ) -> io::Result<MerkleTree> {
+        let text = fs::read_to_string(path)?;
+        let mut records = csv_records(&text)?.into_iter();
+        let (_, header) = records
+            .next()
+            .ok_or_else(|| invalid_row(1, "missing header"))?;
+        let selected = columns
+            .iter()
+            .map(|column| {
+                header
+                    .iter()
+                    .position(|name| name == column)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("column {} is not in the header", column),
+                        )
+                    })
+            })
+            .collect::<io::Result<Vec<usize>>>()?;
+        let mut leaves = Vec::new();
+        for (line, record) in records {
+            if record.len() != header.len() {
+                return Err(invalid_row(line, "field count differs from the header"));
+            }
+            let fields: Vec<String> = selected.iter().map(|i| quote(&record[*i])).collect();
+            let leaf = format!("[{}]", fields.join(","));
+            self.limits().check(leaves.len(), &leaf).map_err(refused)?;
+            leaves.push(leaf);
+        }
+        self.try_build(&leaves).map_err(refused)
+    }
+
+    /// Build a tree with this configuration from an NDJSON file like
+    /// `MerkleTree::from_ndjson`. Also fail with `io::ErrorKind::InvalidData`, wrapping the
+    /// `MerkleError`, if the leaves are refused like `try_build` refuses them. Limits are
+    /// checked line by line, so lines past the first violation are not parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the NDJSON file.
+    /// * `pointers` - JSON Pointers of the fields of each leaf, in leaf order.
+    pub fn build_from_ndjson<P: AsRef<Path>>(
+        &self,
+        path: P,
+        pointers: &[&str],
+    ) -> io::Result<MerkleTree> {
+        let text = fs::read_to_string(path)?;
+        let mut leaves = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = flatten(line).map_err(|_| invalid_row(index + 1, "invalid JSON"))?;
+            let values = pointers
+                .iter()
+                .map(|pointer| {
+                    fields
+                        .binary_search_by(|(field, _)| field.as_str().cmp(pointer))
+                        .map(|found| fields[found].1.as_str())
+                        .map_err(|_| invalid_row(index + 1, &format!("no field at {}", pointer)))
+                })
+                .collect::<io::Result<Vec<&str>>>()?;
+            let leaf = format!("[{}]", values.join(","));
+            self.limits().check(leaves.len(), &leaf).map_err(refused)?;
+            leaves.push(leaf);
+        }
+        self.try_build(&leaves).map_err(refused)
+    }
+}
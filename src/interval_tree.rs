@@ -0,0 +1,235 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Merkle tree over sorted keys whose nodes are annotated with the interval of keys they cover.
+//!
+//! Every node commits to the smallest and largest key below it. Between the largest key of a
+//! left child and the smallest key of its right sibling there is no key, so the path to that
+//! pair of children proves that a key range falling in the gap is absent, much like DNSSEC's
+//! authenticated denial of existence.
+use crate::Hash;
+
+/// Node of a merkle interval tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalNode {
+    /// Hash value as hexdigest, committing to `min` and `max`.
+    pub value: String,
+
+    /// Smallest key below this node.
+    pub min: String,
+
+    /// Largest key below this node.
+    pub max: String,
+}
+
+impl IntervalNode {
+    /// Leaf node for a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key.
+    fn leaf(key: &str) -> Self {
+        IntervalNode {
+            value: Hash::hash(&format!("leaf:{}", key)),
+            min: key.to_owned(),
+            max: key.to_owned(),
+        }
+    }
+
+    /// Parent node of two children. Return None if the children are not in key order.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child node.
+    /// * `right` - Right child node.
+    fn parent(left: &IntervalNode, right: &IntervalNode) -> Option<Self> {
+        if left.max >= right.min {
+            return None;
+        }
+        Some(IntervalNode {
+            value: Hash::hash(&format!(
+                "node:{}{}{}:{}{}:{}",
+                left.value,
+                right.value,
+                left.min.len(),
+                left.min,
+                right.max.len(),
+                right.max
+            )),
+            min: left.min.to_owned(),
+            max: right.max.to_owned(),
+        })
+    }
+}
+
+/// Proof that a key is present, or that a key range is absent, in a merkle interval tree.
+#[derive(Debug, Clone)]
+pub struct IntervalProof {
+    /// For absence proofs, the pair of sibling nodes whose gap contains the range.
+    /// None for membership proofs, and for ranges outside the interval of the root node.
+    pub gap: Option<(IntervalNode, IntervalNode)>,
+
+    /// Audit nodes with whether each is a left child, arranged from the bottom-most node
+    /// up to the top-most node (closest to root node).
+    pub path: Vec<(IntervalNode, bool)>,
+}
+
+impl IntervalProof {
+    /// Recompute the root node from `node` and the audit path, checking key order at each level.
+    fn root_from(&self, mut node: IntervalNode) -> Option<IntervalNode> {
+        for (sibling, is_left) in &self.path {
+            node = if *is_left {
+                IntervalNode::parent(sibling, &node)?
+            } else {
+                IntervalNode::parent(&node, sibling)?
+            };
+        }
+        Some(node)
+    }
+}
+
+/// Merkle interval tree over a set of keys.
+pub struct IntervalTree {
+    /// Nodes from the bottom level (leaves, in key order) up to the top level (root node).
+    levels: Vec<Vec<IntervalNode>>,
+}
+
+impl IntervalTree {
+    /// Build a merkle interval tree. Keys are sorted and deduplicated first.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - Keys to include.
+    pub fn new(keys: &[String]) -> Self {
+        let mut keys = keys.to_vec();
+        keys.sort();
+        keys.dedup();
+
+        let mut level: Vec<IntervalNode> = keys.iter().map(|key| IntervalNode::leaf(key)).collect();
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => IntervalNode::parent(left, right).unwrap(),
+                    [last] => last.to_owned(), // Last node has no sibling.
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+            level = parents;
+        }
+        levels.push(level);
+        IntervalTree { levels }
+    }
+
+    /// Root node, or None if there are no keys.
+    pub fn root(&self) -> Option<&IntervalNode> {
+        self.levels.last()?.first()
+    }
+
+    /// Audit path from the node at `index` of `level` up to the root node.
+    fn path(&self, mut level: usize, mut index: usize) -> Vec<(IntervalNode, bool)> {
+        let mut path = Vec::new();
+        while level + 1 < self.levels.len() {
+            let sibling_index = index ^ 1;
+            if let Some(sibling) = self.levels[level].get(sibling_index) {
+                path.push((sibling.to_owned(), sibling_index < index));
+            } // Handle edge case for siblingless rightmost node on the level.
+            index /= 2;
+            level += 1;
+        }
+        path
+    }
+
+    /// Generate a proof that `key` is present. Return None if it is absent.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to prove.
+    pub fn prove_membership(&self, key: &str) -> Option<IntervalProof> {
+        let index = self.levels[0]
+            .binary_search_by(|leaf| leaf.min.as_str().cmp(key))
+            .ok()?;
+        Some(IntervalProof {
+            gap: None,
+            path: self.path(0, index),
+        })
+    }
+
+    /// Generate a proof that no key lies in `start..=end`. Return None if some key does,
+    /// or if there are no keys (any range is trivially absent).
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Inclusive lower bound of range.
+    /// * `end` - Inclusive upper bound of range.
+    pub fn prove_absence(&self, start: &str, end: &str) -> Option<IntervalProof> {
+        let root = self.root()?;
+        if end < root.min.as_str() || start > root.max.as_str() {
+            return Some(IntervalProof {
+                gap: None,
+                path: Vec::new(),
+            });
+        }
+
+        // The gap between two consecutive keys lies between the children of
+        // their lowest common ancestor.
+        let leaves = &self.levels[0];
+        let successor = leaves.partition_point(|leaf| leaf.min.as_str() < start);
+        if successor == 0 || successor == leaves.len() || leaves[successor].min.as_str() <= end {
+            return None;
+        }
+        let (mut left, mut right) = (successor - 1, successor);
+        let mut level = 0;
+        while left / 2 != right / 2 {
+            left /= 2;
+            right /= 2;
+            level += 1;
+        }
+        let nodes = &self.levels[level];
+        Some(IntervalProof {
+            gap: Some((nodes[left].to_owned(), nodes[right].to_owned())),
+            path: self.path(level + 1, left / 2),
+        })
+    }
+
+    /// Verify a proof that `key` is present.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle interval tree.
+    /// * `key` - Key to verify.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_membership(root: &IntervalNode, key: &str, proof: &IntervalProof) -> bool {
+        proof.gap.is_none() && proof.root_from(IntervalNode::leaf(key)).as_ref() == Some(root)
+    }
+
+    /// Verify a proof that no key lies in `start..=end`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle interval tree.
+    /// * `start` - Inclusive lower bound of range.
+    /// * `end` - Inclusive upper bound of range.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_absence(
+        root: &IntervalNode,
+        start: &str,
+        end: &str,
+        proof: &IntervalProof,
+    ) -> bool {
+        if start > end {
+            return false;
+        }
+        match &proof.gap {
+            None => proof.path.is_empty() && (end < root.min.as_str() || start > root.max.as_str()),
+            Some((left, right)) => {
+                left.max.as_str() < start
+                    && end < right.min.as_str()
+                    && IntervalNode::parent(left, right)
+                        .and_then(|node| proof.root_from(node))
+                        .as_ref()
+                        == Some(root)
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Invariant checks run after every operation in builds with debug assertions enabled. They
+//! panic naming the level and index of the first node that breaks an invariant, so bugs in
+//! integrations surface where they happen rather than as a root mismatch later. In release
+//! builds every check compiles to nothing.
+//!
+//! Trees assembled from node values given by callers are not internally produced state, so
+//! they are checked with `check_tree`, which fails instead of panicking.
+use crate::{truncate, Hash, MerkleError, MerkleTree, Padding};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+impl MerkleTree {
+    /// Check the shape of every level and the value of every node of a tree assembled from
+    /// node values given by a caller. Fail with `MerkleError::InvalidEncoding` if a leaf is
+    /// not valid in the leaf encoding, or a node does not hold the value of its leaf or
+    /// children.
+    pub(crate) fn check_tree(&self) -> Result<(), MerkleError> {
+        self.check_nodes().map_err(|_| MerkleError::InvalidEncoding)
+    }
+
+    /// Check the shape of every level, the given leaves and nodes, their ancestors and the
+    /// last node of every level, of a tree in which only those changed or were truncated
+    /// since it last held every invariant. Fail like `check_tree`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_indices` - 0-based indices of changed leaves.
+    /// * `nodes` - Changed nodes, as (level, index).
+    pub(crate) fn check_changed(
+        &self,
+        leaf_indices: impl IntoIterator<Item = usize>,
+        nodes: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Result<(), MerkleError> {
+        let mut dirty: Vec<BTreeSet<usize>> = self
+            .levels
+            .iter()
+            .map(|level| BTreeSet::from_iter(level.len().checked_sub(1)))
+            .collect();
+        if let Some(leaves) = dirty.first_mut() {
+            leaves.extend(leaf_indices);
+        }
+        for (level, index) in nodes {
+            dirty
+                .get_mut(level)
+                .ok_or(MerkleError::InvalidEncoding)?
+                .insert(index);
+        }
+
+        let checked = self.check_shape().and_then(|_| {
+            for level in 0..dirty.len() {
+                if level > 0 {
+                    let parents: Vec<usize> =
+                        dirty[level - 1].iter().map(|index| index / 2).collect();
+                    dirty[level].extend(parents);
+                }
+                for &index in &dirty[level] {
+                    if index >= self.levels[level].len() {
+                        return Err(format!("node ({level}, {index}) is out of range"));
+                    }
+                    match level {
+                        0 => self.check_leaf(index)?,
+                        _ => self.check_node(level, index)?,
+                    }
+                }
+            }
+            Ok(())
+        });
+        checked.map_err(|_| MerkleError::InvalidEncoding)
+    }
+
+    /// Check the shape of every level and the value of every node.
+    pub(crate) fn debug_check_tree(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        if let Err(message) = self.check_nodes() {
+            panic!("{message}");
+        }
+    }
+
+    /// Check the shape of every level and the value of every node, describing the first
+    /// broken invariant on failure.
+    fn check_nodes(&self) -> Result<(), String> {
+        self.check_shape()?;
+        for leaf_index in 0..self.num_of_leaves() {
+            self.check_leaf(leaf_index)?;
+        }
+        for level in 1..self.levels.len() {
+            for index in 0..self.levels[level].len() {
+                self.check_node(level, index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the shape of every level, and the leaf and its ancestors.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf.
+    pub(crate) fn debug_check_path(&self, leaf_index: usize) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let checked = self.check_shape().and_then(|_| {
+            self.check_leaf(leaf_index)?;
+            (1..self.levels.len()).try_for_each(|level| self.check_node(level, leaf_index >> level))
+        });
+        if let Err(message) = checked {
+            panic!("{message}");
+        }
+    }
+
+    /// Check that audit hashes of the node at `index` of level `from` have one hash per level
+    /// up to level `to` with a sibling or padding, on the side of that sibling, and that they
+    /// reconstruct the ancestor at level `to` from `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - Audit hashes, from the bottom-most hash up to the top-most hash.
+    /// * `from` - Level of the node, 0 for leaves.
+    /// * `to` - Level of the ancestor.
+    /// * `index` - Index of the node within its level.
+    /// * `value` - Value of the node as hexdigest.
+    pub(crate) fn debug_check_audit_path(
+        &self,
+        hashes: &[Rc<RefCell<Hash>>],
+        from: usize,
+        to: usize,
+        index: usize,
+        value: &str,
+    ) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let mut audit_hashes = hashes.iter();
+        let mut result = value.to_owned();
+        let mut node_index = index;
+        for level in from..to {
+            let sibling_index = node_index ^ 1;
+            let expected_is_left = if sibling_index < self.levels[level].len() {
+                Some(sibling_index < node_index)
+            } else if self.padding != Padding::Promote {
+                Some(false)
+            } else {
+                None
+            };
+            if let Some(expected_is_left) = expected_is_left {
+                let audit_hash = match audit_hashes.next() {
+                    Some(audit_hash) => audit_hash.borrow(),
+                    None => panic!(
+                        "audit path of node ({from}, {index}) ends at level {level}, below level {to}"
+                    ),
+                };
+                assert_eq!(
+                    audit_hash.is_left, expected_is_left,
+                    "audit hash of node ({from}, {index}) at level {level} is on the wrong side"
+                );
+                let (left, right) = if audit_hash.is_left {
+                    (&audit_hash.value, &result)
+                } else {
+                    (&result, &audit_hash.value)
+                };
+                result = truncate(
+                    self.concatenation.hash_with(self.algorithm, left, right),
+                    self.truncation,
+                );
+            }
+            node_index /= 2;
+        }
+        assert!(
+            audit_hashes.next().is_none(),
+            "audit path of node ({from}, {index}) has more than {} hashes",
+            hashes.len() - 1
+        );
+        assert_eq!(
+            Some(&result),
+            self.levels[to].get(node_index),
+            "audit path of node ({from}, {index}) does not reconstruct node ({to}, {node_index})"
+        );
+    }
+
+    /// Check that the leaf level has one node per leaf, every level above has one node per
+    /// pair of nodes below, and the top level is a single root.
+    fn check_shape(&self) -> Result<(), String> {
+        let mut expected = self.num_of_leaves();
+        for (level, nodes) in self.levels.iter().enumerate() {
+            if nodes.len() != expected {
+                return Err(format!(
+                    "level {level} has {} nodes, expected {expected}",
+                    nodes.len()
+                ));
+            }
+            expected = if expected == 1 {
+                0
+            } else {
+                expected.div_ceil(2)
+            };
+        }
+        if expected != 0 {
+            return Err(format!(
+                "tree of {} leaves ends at level {} below its root",
+                self.num_of_leaves(),
+                self.levels.len()
+            ));
+        }
+        match self.removed.range(self.num_of_leaves()..).next() {
+            Some(index) => Err(format!("removed leaf {index} is out of range")),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that a leaf node holds the value of its content, or its tombstone if it was
+    /// removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf.
+    fn check_leaf(&self, leaf_index: usize) -> Result<(), String> {
+        let leaf = self.leaves.get(leaf_index).unwrap();
+        let value = self.levels[0].get(leaf_index).unwrap();
+        if self.is_removed(leaf_index) {
+            if !leaf.is_empty() || *value != self.tombstone() {
+                return Err(format!(
+                    "removed leaf {leaf_index} does not hold the tombstone"
+                ));
+            }
+            return Ok(());
+        }
+        if self.leaf_encoding.decode(leaf).is_none() {
+            return Err(format!(
+                "leaf {leaf_index} is not valid {:?}",
+                self.leaf_encoding
+            ));
+        }
+        if *value != self.leaf_value(leaf) {
+            return Err(format!(
+                "node (0, {leaf_index}) does not hold the value of its leaf"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that an interior node holds the value of its children.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, at least 1.
+    /// * `index` - Index of the node within its level.
+    fn check_node(&self, level: usize, index: usize) -> Result<(), String> {
+        let children = &self.levels[level - 1];
+        let value = self.parent_value(
+            children.get(2 * index).unwrap(),
+            children.get(2 * index + 1),
+        );
+        if *self.levels[level].get(index).unwrap() != value {
+            return Err(format!(
+                "node ({level}, {index}) does not hold the value of its children"
+            ));
+        }
+        Ok(())
+    }
+}
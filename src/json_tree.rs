@@ -0,0 +1,436 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Merkle trees over the fields of a JSON document, so a holder of the document can disclose
+//! single fields with proofs against one root, without revealing the other fields.
+//!
+//! A document is flattened into one leaf per scalar, empty object and empty array, identified
+//! by its JSON Pointer (RFC 6901), `""` for a document that is itself a scalar. A leaf is the
+//! JSON array `[pointer,value]`, with the value in canonical form: strings escaped minimally,
+//! and numbers, booleans and null as written. Leaves are sorted by pointer, so documents that
+//! differ only in whitespace or key order have the same root.
+use crate::{Hash, MerkleError, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Deepest nesting of arrays and objects accepted, so parsing cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Parsed JSON value.
+enum Value {
+    Scalar(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+/// Recursive descent parser of a JSON document (RFC 8259).
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    /// Next byte, without consuming it.
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    /// Skip insignificant whitespace.
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    /// Consume `expected`, which must come next.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - Expected text.
+    fn expect(&mut self, expected: &str) -> Result<(), MerkleError> {
+        if !self.text[self.position..].starts_with(expected) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        self.position += expected.len();
+        Ok(())
+    }
+
+    /// Consume the run of ASCII digits that comes next, failing if it is empty.
+    fn digits(&mut self) -> Result<(), MerkleError> {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.position += 1;
+        }
+        match self.position > start {
+            true => Ok(()),
+            false => Err(MerkleError::InvalidEncoding),
+        }
+    }
+
+    /// Parse a value and the whitespace around it.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - Number of arrays and objects the value is nested in.
+    fn value(&mut self, depth: usize) -> Result<Value, MerkleError> {
+        if depth > MAX_DEPTH {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        self.skip_whitespace();
+        let value = match self.peek().ok_or(MerkleError::InvalidEncoding)? {
+            b'{' => self.object(depth)?,
+            b'[' => self.array(depth)?,
+            b'"' => Value::Scalar(quote(&self.string()?)),
+            b't' => self.literal("true")?,
+            b'f' => self.literal("false")?,
+            b'n' => self.literal("null")?,
+            _ => self.number()?,
+        };
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    /// Parse `true`, `false` or `null`.
+    ///
+    /// # Arguments
+    ///
+    /// * `literal` - Expected literal.
+    fn literal(&mut self, literal: &str) -> Result<Value, MerkleError> {
+        self.expect(literal)?;
+        Ok(Value::Scalar(literal.to_owned()))
+    }
+
+    /// Parse a number, keeping it as written.
+    fn number(&mut self) -> Result<Value, MerkleError> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.position += 1,
+            _ => self.digits()?,
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            self.digits()?;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            self.digits()?;
+        }
+        Ok(Value::Scalar(self.text[start..self.position].to_owned()))
+    }
+
+    /// Parse 4 hex digits of a `\u` escape.
+    fn code_unit(&mut self) -> Result<u32, MerkleError> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .ok_or(MerkleError::InvalidEncoding)?;
+        self.position += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+
+    /// Parse a string, resolving its escapes.
+    fn string(&mut self) -> Result<String, MerkleError> {
+        self.expect("\"")?;
+        let mut string = String::new();
+        loop {
+            let c = self.text[self.position..]
+                .chars()
+                .next()
+                .ok_or(MerkleError::InvalidEncoding)?;
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Ok(string),
+                '\\' => {}
+                '\u{0}'..='\u{1f}' => return Err(MerkleError::InvalidEncoding),
+                _ => {
+                    string.push(c);
+                    continue;
+                }
+            }
+            let escape = self.peek().ok_or(MerkleError::InvalidEncoding)?;
+            self.position += 1;
+            string.push(match escape {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => {
+                    let mut code_point = self.code_unit()?;
+                    if (0xd800..0xdc00).contains(&code_point) {
+                        self.expect("\\u")?;
+                        let low = self.code_unit()?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return Err(MerkleError::InvalidEncoding);
+                        }
+                        code_point = 0x10000 + ((code_point - 0xd800) << 10) + (low - 0xdc00);
+                    }
+                    char::from_u32(code_point).ok_or(MerkleError::InvalidEncoding)?
+                }
+                _ => return Err(MerkleError::InvalidEncoding),
+            });
+        }
+    }
+
+    /// Parse an array.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - Number of arrays and objects the array is nested in.
+    fn array(&mut self, depth: usize) -> Result<Value, MerkleError> {
+        self.expect("[")?;
+        self.skip_whitespace();
+        let mut items = Vec::new();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(MerkleError::InvalidEncoding),
+            }
+        }
+    }
+
+    /// Parse an object. Fail if a key repeats, as its field would be ambiguous.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - Number of arrays and objects the object is nested in.
+    fn object(&mut self, depth: usize) -> Result<Value, MerkleError> {
+        self.expect("{")?;
+        self.skip_whitespace();
+        let mut members: Vec<(String, Value)> = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if members.iter().any(|(existing, _)| *existing == key) {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            self.skip_whitespace();
+            self.expect(":")?;
+            members.push((key, self.value(depth + 1)?));
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(MerkleError::InvalidEncoding),
+            }
+        }
+    }
+}
+
+/// Encode a string as a JSON string, escaping only quotes, backslashes and control
+/// characters.
+///
+/// # Arguments
+///
+/// * `value` - String to encode.
+pub(crate) fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{0}'..='\u{1f}' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Decode a JSON string, or return None if `value` is anything else.
+///
+/// # Arguments
+///
+/// * `value` - JSON string.
+pub(crate) fn unquote(value: &str) -> Option<String> {
+    let mut parser = Parser {
+        text: value,
+        position: 0,
+    };
+    let string = parser.string().ok()?;
+    (parser.position == value.len()).then_some(string)
+}
+
+/// Add the fields of `value` at `pointer` to `fields`.
+///
+/// # Arguments
+///
+/// * `value` - Parsed value.
+/// * `pointer` - JSON Pointer of the value.
+/// * `fields` - Fields collected so far.
+fn collect(value: Value, pointer: String, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Scalar(scalar) => fields.push((pointer, scalar)),
+        Value::Array(items) if items.is_empty() => fields.push((pointer, "[]".to_owned())),
+        Value::Object(members) if members.is_empty() => fields.push((pointer, "{}".to_owned())),
+        Value::Array(items) => {
+            for (index, item) in items.into_iter().enumerate() {
+                collect(item, format!("{}/{}", pointer, index), fields);
+            }
+        }
+        Value::Object(members) => {
+            for (key, member) in members {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                collect(member, format!("{}/{}", pointer, key), fields);
+            }
+        }
+    }
+}
+
+/// Flatten a JSON document into its fields as `(pointer, value)` pairs sorted by pointer,
+/// with values in canonical form. Fail with `MerkleError::InvalidEncoding` if the document is
+/// not valid JSON, has an object with a repeated key, or nests deeper than 128 levels.
+///
+/// # Arguments
+///
+/// * `document` - JSON document.
+pub fn flatten(document: &str) -> Result<Vec<(String, String)>, MerkleError> {
+    let mut parser = Parser {
+        text: document,
+        position: 0,
+    };
+    let value = parser.value(0)?;
+    if parser.position != document.len() {
+        return Err(MerkleError::InvalidEncoding);
+    }
+    let mut fields = Vec::new();
+    collect(value, String::new(), &mut fields);
+    fields.sort_unstable();
+    Ok(fields)
+}
+
+/// Return the leaf content of a field.
+///
+/// # Arguments
+///
+/// * `pointer` - JSON Pointer of the field.
+/// * `value` - Value of the field in canonical form.
+pub fn field_leaf(pointer: &str, value: &str) -> String {
+    format!("[{},{}]", quote(pointer), value)
+}
+
+/// Merkle tree over the fields of a JSON document.
+#[derive(Clone)]
+pub struct JsonTree {
+    tree: MerkleTree,
+    fields: Vec<(String, String)>,
+}
+
+impl JsonTree {
+    /// Flatten a JSON document and build a tree over its fields, failing like `flatten`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - JSON document.
+    pub fn new(document: &str) -> Result<Self, MerkleError> {
+        let fields = flatten(document)?;
+        let leaves: Vec<String> = fields
+            .iter()
+            .map(|(pointer, value)| field_leaf(pointer, value))
+            .collect();
+        Ok(JsonTree {
+            tree: MerkleTree::from_leaves(&leaves),
+            fields,
+        })
+    }
+
+    /// Tree over the fields, whose root commits to the document.
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Fields of the document as `(pointer, value)` pairs sorted by pointer.
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// Return the value of a field in canonical form, or None if the document has no such
+    /// field.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - JSON Pointer of the field.
+    pub fn value(&self, pointer: &str) -> Option<&str> {
+        let index = self.index_of(pointer)?;
+        Some(&self.fields[index].1)
+    }
+
+    /// Prove the value of a field, or return None if the document has no such field. Only
+    /// scalars, empty objects and empty arrays are fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - JSON Pointer of the field.
+    pub fn prove(&self, pointer: &str) -> Option<FieldProof> {
+        let index = self.index_of(pointer)?;
+        Some(FieldProof {
+            pointer: pointer.to_owned(),
+            value: self.fields[index].1.to_owned(),
+            proof: self.tree.proof(index)?,
+        })
+    }
+
+    /// Index of the leaf of a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - JSON Pointer of the field.
+    fn index_of(&self, pointer: &str) -> Option<usize> {
+        self.fields
+            .binary_search_by(|(field, _)| field.as_str().cmp(pointer))
+            .ok()
+    }
+}
+
+/// Disclosed field of a JSON document with the proof of its leaf.
+pub struct FieldProof {
+    /// JSON Pointer of the field.
+    pub pointer: String,
+
+    /// Value of the field in canonical form.
+    pub value: String,
+
+    /// Proof of the leaf of the field.
+    pub proof: MerkleProof,
+}
+
+impl FieldProof {
+    /// Verify that the document committed to by `root` has this field with this value.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the tree over the fields of the document.
+    pub fn verify(&self, root: Rc<RefCell<Hash>>) -> bool {
+        self.proof.leaf_content == field_leaf(&self.pointer, &self.value)
+            && MerkleTree::verify_proof(root, &self.proof)
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::chunked::Chunked;
+use crate::leaf_content::LeafContent;
+use crate::{telemetry, Hash, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Merkle tree that stores only its leaves and their hashes, and computes inner nodes when a
+/// root or proof first needs them. Nodes returned by `node` or used as audit hashes are kept
+/// for later calls; nodes below them are recomputed. This halves the memory of very wide
+/// trees from which few proofs are requested, at the cost of hashing a subtree of up to
+/// `1 << level` leaves the first time a node at `level` is needed. Roots and proofs are
+/// identical to those of a `MerkleTree` built with the same configuration.
+///
+/// ```
+/// use merkle_tree::{LazyTree, MerkleTree};
+///
+/// let leaves: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+/// let tree = LazyTree::from_leaves(&leaves);
+/// let proof = tree.proof(42).unwrap();
+/// assert!(MerkleTree::verify_proof(MerkleTree::from_leaves(&leaves).root().unwrap(), &proof));
+/// ```
+pub struct LazyTree {
+    /// Empty tree with the configuration of this tree.
+    scheme: MerkleTree,
+    leaves: Chunked<LeafContent>,
+    leaf_values: Chunked<String>,
+
+    /// Number of nodes of every level, from the leaf level up.
+    level_lengths: Vec<usize>,
+
+    /// Inner nodes computed so far, by level and index.
+    nodes: RefCell<HashMap<(usize, usize), String>>,
+}
+
+impl LazyTree {
+    /// Build a lazy tree from leaves, with the default configuration. Use
+    /// `MerkleTreeBuilder::build_lazy` for other configurations.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    pub fn from_leaves(leaves: &[String]) -> Self {
+        match MerkleTree::builder().build_lazy(leaves) {
+            Ok(tree) => tree,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Initialize a lazy tree over hashed leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - Empty tree whose configuration is used.
+    /// * `leaves` - Leaf values and contents, in tree order.
+    pub(crate) fn with_scheme(scheme: MerkleTree, leaves: Vec<(String, String)>) -> Self {
+        let mut level_lengths = Vec::new();
+        let mut len = leaves.len();
+        while len > 0 {
+            level_lengths.push(len);
+            len = if len == 1 { 0 } else { len.div_ceil(2) };
+        }
+
+        let mut tree = LazyTree {
+            scheme,
+            leaves: Chunked::with_capacity(leaves.len()),
+            leaf_values: Chunked::with_capacity(leaves.len()),
+            level_lengths,
+            nodes: RefCell::new(HashMap::new()),
+        };
+        for (value, leaf) in leaves {
+            tree.leaf_values.push(value);
+            tree.leaves.push(leaf.into());
+        }
+        tree
+    }
+
+    /// Number of leaves.
+    pub fn num_of_leaves(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Number of inner nodes computed and kept so far.
+    pub fn num_of_cached_nodes(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    /// Return the content of a leaf, or None if `leaf_index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf.
+    pub fn leaf(&self, leaf_index: usize) -> Option<&str> {
+        self.leaves.get(leaf_index).map(|leaf| leaf.as_str())
+    }
+
+    /// Return the root as hexdigest, computing it if needed, or None if the tree has no leaves.
+    pub fn root(&self) -> Option<String> {
+        self.node(self.level_lengths.len().checked_sub(1)?, 0)
+    }
+
+    /// Return the value of a node as hexdigest, computing and keeping it if needed, or None if
+    /// the tree has no such node.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, 0 for leaves.
+    /// * `index` - Index of the node within its level.
+    pub fn node(&self, level: usize, index: usize) -> Option<String> {
+        if index >= *self.level_lengths.get(level)? {
+            return None;
+        }
+        let value = self.value(level, index);
+        if level > 0 {
+            self.nodes
+                .borrow_mut()
+                .entry((level, index))
+                .or_insert_with(|| value.clone());
+        }
+        Some(value)
+    }
+
+    /// Return the value of an existing node, computing it from the leaves below it unless it
+    /// is a leaf or was kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, 0 for leaves.
+    /// * `index` - Index of the node within its level.
+    fn value(&self, level: usize, index: usize) -> String {
+        if level == 0 {
+            return self.leaf_values.get(index).unwrap().to_owned();
+        }
+        if let Some(value) = self.nodes.borrow().get(&(level, index)) {
+            return value.to_owned();
+        }
+        let left = self.value(level - 1, 2 * index);
+        let right = (2 * index + 1 < self.level_lengths[level - 1])
+            .then(|| self.value(level - 1, 2 * index + 1));
+        self.scheme.parent_value(&left, right.as_ref())
+    }
+
+    /// Generate a merkle proof of a leaf, computing and keeping its audit hashes if needed.
+    /// Return None if `leaf_index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&self, leaf_index: usize) -> Option<MerkleProof> {
+        let leaf_content = self.leaf(leaf_index)?.to_owned();
+        let mut hashes = Vec::new();
+        let mut index = leaf_index;
+        for level in 0..self.level_lengths.len() - 1 {
+            let sibling_index = index ^ 1;
+            let sibling = match self.node(level, sibling_index) {
+                Some(sibling) => Some(sibling),
+                None => self.scheme.padding_sibling(&self.value(level, index)),
+            };
+            if let Some(sibling) = sibling {
+                let mut hash = Hash::new(sibling);
+                hash.is_left = sibling_index < index;
+                hashes.push(Rc::new(RefCell::new(hash)));
+            } // Handle edge case for promoted siblingless rightmost node on the level.
+            index /= 2;
+        }
+        telemetry::proof_served(hashes.len());
+
+        Some(MerkleProof {
+            hashes,
+            num_of_leaves: self.num_of_leaves(),
+            leaf_index,
+            blinder: self.scheme.blinder(&leaf_content),
+            leaf_content,
+            concatenation: self.scheme.concatenation,
+            truncation: self.scheme.truncation,
+            algorithm: self.scheme.algorithm,
+            leaf_encoding: self.scheme.leaf_encoding,
+        })
+    }
+
+    /// Compute every node and return the equivalent `MerkleTree`, which stores all of them.
+    pub fn to_tree(&self) -> MerkleTree {
+        let mut tree = self.scheme.clone();
+        tree.leaves = self.leaves.clone();
+        if self.num_of_leaves() > 0 {
+            tree.levels.push(self.leaf_values.clone());
+        }
+        for len in self.level_lengths.iter().skip(1) {
+            let below = tree.levels.last().unwrap();
+            let mut level = Chunked::with_capacity(*len);
+            for index in 0..*len {
+                level.push(
+                    self.scheme
+                        .parent_value(below.get(2 * index).unwrap(), below.get(2 * index + 1)),
+                );
+            }
+            tree.levels.push(level);
+        }
+        tree.debug_check_tree();
+        tree
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use std::ops::Deref;
+
+/// Content of a leaf owned by a tree.
+///
+/// With the `zeroize` feature, the buffer is wiped when dropped, so trees built over secrets
+/// do not leave them behind in freed memory. Trees and their snapshots share leaf contents,
+/// so a leaf is wiped once neither holds it any more. Copies handed out, such as
+/// `MerkleProof::leaf_content`, are owned by the caller.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct LeafContent(String);
+
+impl Deref for LeafContent {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl From<String> for LeafContent {
+    fn from(value: String) -> Self {
+        LeafContent(value)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for LeafContent {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
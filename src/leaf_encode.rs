@@ -0,0 +1,173 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::hex;
+
+/// Order of the bytes of an encoded integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// Most significant byte first, as in network byte order.
+    Big,
+
+    /// Least significant byte first.
+    Little,
+}
+
+/// Encoders of numbers and length-prefixed byte strings into leaf contents with an explicit
+/// width and byte order, so systems that hash the same values agree on the bytes instead of
+/// each formatting them with `to_string`. Contents are lowercase hex strings, so trees must
+/// be built with `LeafEncoding::Hex` for the leaf node values to hash the encoded bytes.
+/// Fields of a record can be joined by concatenating their contents.
+///
+/// ```
+/// use merkle_tree::{Endianness, LeafEncode, LeafEncoding, MerkleTree};
+///
+/// assert_eq!(LeafEncode::u32_be(258), "00000102");
+/// let name = LeafEncode::length_prefixed(b"ab", 1, Endianness::Big).unwrap();
+/// let leaf = LeafEncode::u64_le(1) + &name;
+/// assert_eq!(leaf, "0100000000000000026162");
+/// let tree = MerkleTree::builder()
+///     .leaf_encoding(LeafEncoding::Hex)
+///     .build(&[leaf]);
+/// assert_eq!(tree.num_of_leaves(), 1);
+/// ```
+pub struct LeafEncode;
+
+impl LeafEncode {
+    /// Encode an unsigned integer into `width` bytes. Return None if `width` is not in
+    /// `1..=16` or `value` does not fit in `width` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    /// * `width` - Number of bytes of the encoding.
+    /// * `endianness` - Order of the bytes.
+    pub fn unsigned(value: u128, width: usize, endianness: Endianness) -> Option<String> {
+        if !(1..=16).contains(&width) || (width < 16 && value >> (8 * width) != 0) {
+            return None;
+        }
+        Some(Self::ordered(
+            &value.to_be_bytes()[16 - width..],
+            endianness,
+        ))
+    }
+
+    /// Encode a signed integer in two's complement into `width` bytes. Return None if
+    /// `width` is not in `1..=16` or `value` does not fit in `width` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    /// * `width` - Number of bytes of the encoding.
+    /// * `endianness` - Order of the bytes.
+    pub fn signed(value: i128, width: usize, endianness: Endianness) -> Option<String> {
+        if !(1..=16).contains(&width)
+            || (width < 16 && value >> (8 * width - 1) != 0 && value >> (8 * width - 1) != -1)
+        {
+            return None;
+        }
+        Some(Self::ordered(
+            &value.to_be_bytes()[16 - width..],
+            endianness,
+        ))
+    }
+
+    /// Encode bytes preceded by their length as an unsigned integer of `width` bytes. Return
+    /// None if `width` is not in `1..=8` or the length does not fit in `width` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Bytes to encode.
+    /// * `width` - Number of bytes of the length prefix.
+    /// * `endianness` - Order of the bytes of the length prefix.
+    pub fn length_prefixed(bytes: &[u8], width: usize, endianness: Endianness) -> Option<String> {
+        if width > 8 {
+            return None;
+        }
+        let prefix = Self::unsigned(bytes.len() as u128, width, endianness)?;
+        Some(prefix + &hex::encode(bytes))
+    }
+
+    /// Encode a `u32` into 4 bytes, most significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn u32_be(value: u32) -> String {
+        hex::encode(&value.to_be_bytes())
+    }
+
+    /// Encode a `u32` into 4 bytes, least significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn u32_le(value: u32) -> String {
+        hex::encode(&value.to_le_bytes())
+    }
+
+    /// Encode a `u64` into 8 bytes, most significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn u64_be(value: u64) -> String {
+        hex::encode(&value.to_be_bytes())
+    }
+
+    /// Encode a `u64` into 8 bytes, least significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn u64_le(value: u64) -> String {
+        hex::encode(&value.to_le_bytes())
+    }
+
+    /// Encode a `u128` into 16 bytes, most significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn u128_be(value: u128) -> String {
+        hex::encode(&value.to_be_bytes())
+    }
+
+    /// Encode a `u128` into 16 bytes, least significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn u128_le(value: u128) -> String {
+        hex::encode(&value.to_le_bytes())
+    }
+
+    /// Encode an `i64` in two's complement into 8 bytes, most significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn i64_be(value: i64) -> String {
+        hex::encode(&value.to_be_bytes())
+    }
+
+    /// Encode an `i64` in two's complement into 8 bytes, least significant first.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Integer to encode.
+    pub fn i64_le(value: i64) -> String {
+        hex::encode(&value.to_le_bytes())
+    }
+
+    /// Encode bytes given most significant first in `endianness`.
+    ///
+    /// # Arguments
+    ///
+    /// * `be_bytes` - Bytes, most significant first.
+    /// * `endianness` - Order of the bytes of the encoding.
+    fn ordered(be_bytes: &[u8], endianness: Endianness) -> String {
+        match endianness {
+            Endianness::Big => hex::encode(be_bytes),
+            Endianness::Little => hex::encode(&be_bytes.iter().rev().copied().collect::<Vec<u8>>()),
+        }
+    }
+}
@@ -1,8 +1,184 @@
 // Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
-use sha2::{Digest, Sha256};
-use std::cell::RefCell;
+#[cfg(feature = "tree")]
+use std::cell::{OnceCell, RefCell};
+#[cfg(feature = "tree")]
+use std::collections::BTreeSet;
+#[cfg(feature = "tree")]
+use std::io::{self, Read};
+#[cfg(feature = "tree")]
 use std::rc::Rc;
 
+#[cfg(feature = "tree")]
+pub mod accumulator;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "tree")]
+pub mod attestation;
+#[cfg(feature = "tree")]
+mod base64;
+#[cfg(feature = "tree")]
+mod builder;
+#[cfg(feature = "tree")]
+mod bundle;
+#[cfg(feature = "tree")]
+pub mod cas;
+#[cfg(feature = "tree")]
+pub mod cbergoon;
+#[cfg(feature = "tree")]
+pub mod checkpoint;
+#[cfg(feature = "tree")]
+mod chunked;
+#[cfg(feature = "tree")]
+mod codec;
+#[cfg(feature = "tree")]
+mod committed;
+#[cfg(feature = "tree")]
+mod compose;
+mod const_verify;
+#[cfg(feature = "tree")]
+pub mod cosign;
+#[cfg(feature = "tree")]
+pub mod credential;
+#[cfg(feature = "tree")]
+mod delta;
+#[cfg(feature = "mpt")]
+pub mod eip1186;
+#[cfg(feature = "tree")]
+mod epoch;
+#[cfg(feature = "tree")]
+mod error;
+#[cfg(feature = "tree")]
+mod golden;
+#[cfg(feature = "tree")]
+mod hash_chain;
+#[cfg(feature = "tree")]
+mod hex;
+#[cfg(feature = "tree")]
+pub mod iavl;
+#[cfg(feature = "import")]
+mod import;
+#[cfg(feature = "tree")]
+pub mod interval_tree;
+#[cfg(feature = "tree")]
+mod invariants;
+#[cfg(feature = "tree")]
+pub mod json_tree;
+#[cfg(feature = "tree")]
+mod lazy;
+#[cfg(feature = "tree")]
+mod leaf_content;
+#[cfg(feature = "tree")]
+mod leaf_encode;
+#[cfg(feature = "tree")]
+pub mod manifest;
+#[cfg(feature = "mmap")]
+pub mod mapped;
+#[cfg(feature = "mpt")]
+pub mod mpt;
+#[cfg(feature = "tree")]
+pub mod namespaced_tree;
+#[cfg(feature = "tree")]
+mod node_id;
+#[cfg(feature = "object_store")]
+pub mod object_store;
+#[cfg(feature = "tree")]
+pub mod opentimestamps;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "tree")]
+mod permutation;
+#[cfg(feature = "tree")]
+mod poh;
+#[cfg(feature = "tree")]
+mod profile;
+#[cfg(feature = "tree")]
+mod proof_cache;
+#[cfg(feature = "tree")]
+pub mod provider;
+#[cfg(feature = "tree")]
+mod report;
+#[cfg(feature = "tree")]
+mod rng;
+#[cfg(feature = "tree")]
+pub mod rs_merkle;
+#[cfg(feature = "tree")]
+mod self_test;
+#[cfg(feature = "tree")]
+mod serialize;
+#[cfg(feature = "tree")]
+pub mod solidity;
+#[cfg(feature = "tree")]
+pub mod static_site;
+#[cfg(feature = "tree")]
+mod streaming;
+#[cfg(feature = "tree")]
+mod subtree;
+#[cfg(feature = "tree")]
+pub mod sum_tree;
+#[cfg(feature = "tree")]
+mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "tree")]
+pub mod tiles;
+#[cfg(feature = "tree")]
+mod verified_vec;
+mod verify;
+#[cfg(feature = "tree")]
+mod writer;
+
+#[cfg(feature = "tree")]
+pub use builder::MerkleTreeBuilder;
+#[cfg(feature = "tree")]
+pub use bundle::{verify_bundle, ExportBundle};
+#[cfg(feature = "tree")]
+use chunked::Chunked;
+#[cfg(feature = "tree")]
+pub use committed::{CommittedRoot, TreeScheme};
+#[cfg(feature = "tree")]
+pub use compose::ComposedProof;
+pub use const_verify::verify_proof_const;
+#[cfg(feature = "tree")]
+pub use delta::TreeDelta;
+#[cfg(feature = "tree")]
+pub use epoch::{EpochProof, EpochTree};
+#[cfg(feature = "tree")]
+pub use error::MerkleError;
+#[cfg(feature = "tree")]
+pub use hash_chain::{ChainReceipt, HashChain};
+#[cfg(feature = "tree")]
+pub use lazy::LazyTree;
+#[cfg(feature = "tree")]
+use leaf_content::LeafContent;
+#[cfg(feature = "tree")]
+pub use leaf_encode::{Endianness, LeafEncode};
+#[cfg(feature = "tree")]
+pub use node_id::NodeId;
+#[cfg(feature = "tree")]
+pub use permutation::Permutation;
+#[cfg(feature = "tree")]
+pub use poh::{HistoryEntry, HistoryReceipt, ProofOfHistory};
+#[cfg(feature = "tree")]
+pub use profile::Profile;
+#[cfg(feature = "tree")]
+pub use proof_cache::ProofCache;
+#[cfg(feature = "tree")]
+pub use report::{ConcatenationOrder, VerificationFailure, VerificationReport, VerificationStep};
+#[cfg(feature = "tree")]
+pub use self_test::self_test;
+#[cfg(feature = "tree")]
+pub use streaming::StreamingProver;
+#[cfg(feature = "tree")]
+pub use subtree::SubtreeProof;
+#[cfg(feature = "tree")]
+pub use verified_vec::VerifiedVec;
+#[cfg(feature = "tree")]
+pub use verify::verify;
+pub use verify::{audit_path_positions, verify_proof_in_place};
+#[cfg(feature = "tree")]
+pub use writer::MerkleWriter;
+
+#[cfg(feature = "tree")]
 pub struct Hash {
     pub parent: Option<Rc<RefCell<Hash>>>,
     pub left: Option<Rc<RefCell<Hash>>>,
@@ -11,6 +187,7 @@ pub struct Hash {
     pub is_left: bool, // Needed for proof verification.
 }
 
+#[cfg(feature = "tree")]
 impl Hash {
     /// Initialize node of a merkle tree.
     ///
@@ -37,11 +214,303 @@ impl Hash {
     ///
     /// * `value` - String to hash.
     pub fn hash(value: &str) -> String {
-        format!("{:x}", Sha256::digest(value.as_bytes()))
+        HashAlgorithm::Sha256.hash(value.as_bytes())
+    }
+
+    /// Hash the concatenation of two raw byte strings to its sha256 digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left bytes.
+    /// * `right` - Right bytes.
+    pub(crate) fn hash_pair_bytes(left: &[u8], right: &[u8]) -> [u8; 32] {
+        let mut hasher = provider::hasher(HashAlgorithm::Sha256);
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().try_into().unwrap()
+    }
+
+    /// Hash everything read from a reader to its sha256 hexdigest, without buffering
+    /// the entire content in memory. The result is identical to `Hash::hash` for the same bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader to hash until EOF.
+    pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<String> {
+        let mut hasher = provider::hasher(HashAlgorithm::Sha256);
+        let mut buf = [0; 8192];
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(hex::encode(&hasher.finalize()))
+    }
+
+    /// Hash everything read from an async reader to its sha256 hexdigest, without buffering
+    /// the entire content in memory. The result is identical to `Hash::hash` for the same bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Async reader to hash until EOF.
+    #[cfg(feature = "tokio")]
+    pub async fn hash_async_reader<R: tokio::io::AsyncRead + Unpin>(
+        mut reader: R,
+    ) -> io::Result<String> {
+        use tokio::io::AsyncReadExt;
+
+        let mut hasher = provider::hasher(HashAlgorithm::Sha256);
+        let mut buf = [0; 8192];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(hex::encode(&hasher.finalize()))
+    }
+}
+
+/// Hash function used for leaves and parents of a `MerkleTree`.
+///
+/// For the leaf `"abc"`, the hexdigest starts with `ba7816bf` with `Sha256`, `3a985da7` with
+/// `Sha3_256`, `b751850b` with `Sha3_512`, `4e03657a` with `Keccak256`, `8eb208f7` with
+/// `Ripemd160` and `bb1be98c` with `Hash160`.
+#[cfg(feature = "tree")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// SHA-256.
+    #[default]
+    Sha256,
+
+    /// SHA3-256, as standardized in FIPS 202.
+    #[cfg(feature = "sha3")]
+    Sha3_256,
+
+    /// SHA3-512, as standardized in FIPS 202.
+    #[cfg(feature = "sha3")]
+    Sha3_512,
+
+    /// Keccak-256 with the original padding, as used by Ethereum.
+    #[cfg(feature = "sha3")]
+    Keccak256,
+
+    /// RIPEMD-160.
+    #[cfg(feature = "ripemd")]
+    Ripemd160,
+
+    /// RIPEMD-160 of the SHA-256 digest, as Bitcoin hashes public keys and scripts into
+    /// addresses.
+    #[cfg(feature = "ripemd")]
+    Hash160,
+
+    /// Hash function of a `digest::Digest` that is not built in, set with
+    /// `MerkleTreeBuilder::hasher`. Trees and proofs using it cannot be deserialized, as the
+    /// digest is not recorded.
+    Custom(provider::CustomDigest),
+}
+
+#[cfg(feature = "tree")]
+impl HashAlgorithm {
+    /// Hash bytes to a raw digest with the installed `provider::CryptoProvider`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Bytes to hash.
+    pub fn digest(&self, value: &[u8]) -> Vec<u8> {
+        provider::digest(*self, value)
+    }
+
+    /// Hash bytes to a hexdigest.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Bytes to hash.
+    pub fn hash(&self, value: &[u8]) -> String {
+        hex::encode(&self.digest(value))
+    }
+
+    /// Size of a digest in bytes.
+    pub fn digest_size(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 32,
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 | HashAlgorithm::Keccak256 => 32,
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_512 => 64,
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Ripemd160 | HashAlgorithm::Hash160 => 20,
+            HashAlgorithm::Custom(custom) => custom.digest_size(),
+        }
+    }
+
+    /// Node value that replaces the hash of a removed leaf in trees with this hash function,
+    /// as many zero bytes as a digest, as hexdigest. `TOMBSTONE` is the value for 32-byte
+    /// digests.
+    pub fn tombstone(&self) -> String {
+        "0".repeat(2 * self.digest_size())
     }
 }
 
+/// How two child hashes are concatenated before hashing them into their parent.
+///
+/// For the leaves `["abc", "bcd", "cde", "def", "efg"]`, the root is
+/// `b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073` with `Hex`, and
+/// `5ef8b5df330a94e9af18e6d9e638629c0592b135abd02323a894a3adcf27b65a` with `Bytes`.
+#[cfg(feature = "tree")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Concatenation {
+    /// Hash the concatenated hexdigests, e.g. `sha256("3e23...bbc4")`.
+    #[default]
+    Hex,
+
+    /// Hash the concatenated raw digest bytes, as most other merkle tree implementations do.
+    Bytes,
+}
+
+#[cfg(feature = "tree")]
+impl Concatenation {
+    /// Hash a left child hash and a right child hash into their parent hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child hash as hexdigest.
+    /// * `right` - Right child hash as hexdigest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a child hash is not a hexdigest and `self` is `Concatenation::Bytes`.
+    pub fn hash(&self, left: &str, right: &str) -> String {
+        self.hash_with(HashAlgorithm::Sha256, left, right)
+    }
+
+    /// Hash a left child hash and a right child hash into their parent hash with `algorithm`.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    /// * `left` - Left child hash as hexdigest.
+    /// * `right` - Right child hash as hexdigest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a child hash is not a hexdigest and `self` is `Concatenation::Bytes`.
+    pub fn hash_with(&self, algorithm: HashAlgorithm, left: &str, right: &str) -> String {
+        let preimage = self
+            .preimage(left, right)
+            .expect("child hash is not a hexdigest");
+        algorithm.hash(&preimage)
+    }
+
+    /// Return the bytes hashed into the parent of a left child hash and a right child hash.
+    /// Return None if a child hash is not a hexdigest and `self` is `Concatenation::Bytes`,
+    /// as it has no raw digest bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child hash as hexdigest.
+    /// * `right` - Right child hash as hexdigest.
+    pub(crate) fn preimage(&self, left: &str, right: &str) -> Option<Vec<u8>> {
+        match self {
+            Concatenation::Hex => Some(format!("{}{}", left, right).into_bytes()),
+            Concatenation::Bytes => Some([hex::decode(left)?, hex::decode(right)?].concat()),
+        }
+    }
+}
+
+/// How the last node of a level with an odd number of nodes is combined into the upper level.
+#[cfg(feature = "tree")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Padding {
+    /// Promote the last node to the upper level unchanged.
+    #[default]
+    Promote,
+
+    /// Pair the last node with a duplicate of itself, as Bitcoin does.
+    DuplicateLast,
+
+    /// Pair the last node with a constant hexdigest, e.g. an all-zero hash.
+    Constant(String),
+}
+
+/// How leaves are ordered before a merkle tree is constructed from them.
+#[cfg(feature = "tree")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeafOrdering {
+    /// Keep leaves in the order given.
+    #[default]
+    Identity,
+
+    /// Sort leaves by their hash, so the root does not depend on the order leaves are given in.
+    SortedByHash,
+
+    /// Leaves were ordered by a custom comparator, see `MerkleTreeBuilder::custom_ordering`.
+    Custom,
+}
+
+/// How the content of a leaf encodes the bytes hashed into its node value. Recorded in trees
+/// and proofs, so services in languages whose strings are not UTF-8 agree on the bytes.
+#[cfg(feature = "tree")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeafEncoding {
+    /// Hash the UTF-8 encoding of the content.
+    #[default]
+    Utf8,
+
+    /// Hash one byte per character, so characters must be in `U+0000..=U+00FF`, as
+    /// ISO-8859-1 maps arbitrary bytes to text.
+    RawBytes,
+
+    /// Hash the bytes of a hex string of either case.
+    Hex,
+
+    /// Hash the bytes of a padded string of the standard base64 alphabet.
+    Base64,
+}
+
+#[cfg(feature = "tree")]
+impl LeafEncoding {
+    /// Return the bytes encoded by the content of a leaf, or None if it is not valid in this
+    /// encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    pub fn decode(&self, leaf: &str) -> Option<Vec<u8>> {
+        match self {
+            LeafEncoding::Utf8 => Some(leaf.as_bytes().to_vec()),
+            LeafEncoding::RawBytes => leaf.chars().map(|c| u8::try_from(c).ok()).collect(),
+            LeafEncoding::Hex => leaf
+                .bytes()
+                .all(|byte| byte.is_ascii_hexdigit())
+                .then(|| hex::decode(leaf))
+                .flatten(),
+            LeafEncoding::Base64 => base64::decode(leaf),
+        }
+    }
+}
+
+/// Order in which `MerkleTree::visit` walks the nodes of a tree.
+#[cfg(feature = "tree")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Traversal {
+    /// Visit a node before its children, starting from the root.
+    #[default]
+    PreOrder,
+
+    /// Visit a node after its children, ending with the root.
+    PostOrder,
+}
+
 /// Hold information needed to verify whether a particular leaf node belongs to a merkle tree.
+#[cfg(feature = "tree")]
 pub struct MerkleProof {
     /// List of audit hashes needed to verify that a leaf node belongs to a merkle tree,
     /// arranged from the bottom-most hash up to the top-most hash (closest to root node).
@@ -55,11 +524,748 @@ pub struct MerkleProof {
 
     /// Content of leaf node to be verified.
     pub leaf_content: String,
+
+    /// How child hashes are concatenated in the merkle tree.
+    pub concatenation: Concatenation,
+
+    /// Number of bytes node values are truncated to in the merkle tree, or None for full digests.
+    pub truncation: Option<usize>,
+
+    /// Hash function of the merkle tree.
+    pub algorithm: HashAlgorithm,
+
+    /// How the leaf content encodes the bytes hashed into the leaf node value.
+    pub leaf_encoding: LeafEncoding,
+
+    /// Blinder appended to the leaf content before hashing it, as hexdigest, or None if the
+    /// merkle tree does not blind its leaves.
+    pub blinder: Option<String>,
+}
+
+/// Return the bytes hashed into the node value of a leaf: the bytes its content encodes,
+/// followed by its blinder, if any. Return None if the content is not valid in `encoding`.
+///
+/// # Arguments
+///
+/// * `encoding` - Encoding of leaf content.
+/// * `leaf` - Content of leaf.
+/// * `blinder` - Blinder of leaf as hexdigest.
+#[cfg(feature = "tree")]
+fn leaf_preimage(encoding: LeafEncoding, leaf: &str, blinder: Option<&str>) -> Option<Vec<u8>> {
+    let mut preimage = encoding.decode(leaf)?;
+    if let Some(blinder) = blinder {
+        preimage.extend_from_slice(blinder.as_bytes());
+    }
+    Some(preimage)
+}
+
+/// Return the node value of a leaf, hashing the bytes its content encodes followed by its
+/// blinder, if any. Return None if the content is not valid in `encoding`.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+/// * `truncation` - Number of bytes to keep, or None to keep all.
+/// * `encoding` - Encoding of leaf content.
+/// * `leaf` - Content of leaf.
+/// * `blinder` - Blinder of leaf as hexdigest.
+#[cfg(feature = "tree")]
+fn blinded_leaf_value(
+    algorithm: HashAlgorithm,
+    truncation: Option<usize>,
+    encoding: LeafEncoding,
+    leaf: &str,
+    blinder: Option<&str>,
+) -> Option<String> {
+    let preimage = leaf_preimage(encoding, leaf, blinder)?;
+    Some(truncate(algorithm.hash(&preimage), truncation))
+}
+
+/// Return the node value of the parent of a left child and a right child of a proof, as
+/// hexdigest. A child that is not a hexdigest under `Concatenation::Bytes` gives a parent of
+/// `INVALID_LEAF`, so the proof verifies against no tree.
+///
+/// # Arguments
+///
+/// * `concatenation` - How child hashes are concatenated.
+/// * `algorithm` - Hash function.
+/// * `truncation` - Number of bytes to keep, or None to keep all.
+/// * `left` - Left child value as hexdigest.
+/// * `right` - Right child value as hexdigest.
+#[cfg(feature = "tree")]
+pub(crate) fn proof_parent_value(
+    concatenation: Concatenation,
+    algorithm: HashAlgorithm,
+    truncation: Option<usize>,
+    left: &str,
+    right: &str,
+) -> String {
+    let value = match concatenation.preimage(left, right) {
+        Some(preimage) => algorithm.hash(&preimage),
+        None => INVALID_LEAF.to_owned(),
+    };
+    truncate(value, truncation)
+}
+
+/// Truncate a hexdigest to its first `truncation` bytes, if set.
+///
+/// # Arguments
+///
+/// * `value` - Hexdigest.
+/// * `truncation` - Number of bytes to keep, or None to keep all.
+#[cfg(feature = "tree")]
+pub(crate) fn truncate(mut value: String, truncation: Option<usize>) -> String {
+    if let Some(num_of_bytes) = truncation {
+        value.truncate(2 * num_of_bytes);
+    }
+    value
+}
+
+#[cfg(feature = "tree")]
+impl MerkleProof {
+    /// Reconstruct the root node value implied by this proof, as hexdigest.
+    pub fn root_value(&self) -> String {
+        self.root_value_counted(&mut 0)
+    }
+
+    /// Leaf node value of the leaf content and its blinder, as hexdigest. A leaf content that
+    /// is not valid in the leaf encoding gets `INVALID_LEAF`, so the proof verifies against no
+    /// tree.
+    pub fn leaf_value(&self) -> String {
+        blinded_leaf_value(
+            self.algorithm,
+            self.truncation,
+            self.leaf_encoding,
+            &self.leaf_content,
+            self.blinder.as_deref(),
+        )
+        .unwrap_or_else(|| truncate(INVALID_LEAF.to_owned(), self.truncation))
+    }
+
+    /// Same as `root_value`, but add the number of hash invocations performed to `steps`.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - Counter of hash invocations.
+    fn root_value_counted(&self, steps: &mut u64) -> String {
+        let mut result = self.leaf_value();
+        *steps += 1;
+
+        for audit_hash in &self.hashes {
+            let audit_value = &audit_hash.borrow().value;
+            result = if audit_hash.borrow().is_left {
+                self.parent_value(audit_value, &result)
+            } else {
+                self.parent_value(&result, audit_value)
+            };
+            *steps += 1;
+        }
+
+        result
+    }
+
+    /// Node value of the parent of a left child and a right child in this proof, as hexdigest.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child value as hexdigest.
+    /// * `right` - Right child value as hexdigest.
+    pub(crate) fn parent_value(&self, left: &str, right: &str) -> String {
+        proof_parent_value(
+            self.concatenation,
+            self.algorithm,
+            self.truncation,
+            left,
+            right,
+        )
+    }
 }
 
-pub struct MerkleTree;
+/// Node value that replaces the hash of a removed leaf in trees with 32-byte digests, such as
+/// SHA-256. It is not a digest of any known input, so it cannot be confused with the hash of a
+/// real leaf. Trees with other digest sizes use `HashAlgorithm::tombstone`.
+#[cfg(feature = "tree")]
+pub const TOMBSTONE: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Leaf node value of a proof whose leaf content is not valid in its leaf encoding, and parent
+/// node value of an audit hash that is not a hexdigest under `Concatenation::Bytes`. Like
+/// `TOMBSTONE`, it is not a digest of any known input, and trees never hold it.
+#[cfg(feature = "tree")]
+pub const INVALID_LEAF: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+/// Merkle tree that can be appended to after construction.
+///
+/// Node values of every level are kept, so the root and proofs are available without
+/// rebuilding the tree. Leaves can also be used through the associated functions such as
+/// `MerkleTree::merkle_root` without constructing a tree.
+///
+/// Empty leaves are ordinary leaves: an empty string is hashed like any other content, so its
+/// leaf value is the digest of zero bytes (`e3b0c442...` for SHA-256). Use
+/// `MerkleTreeBuilder::reject_empty_leaves` to refuse them at construction.
+#[cfg(feature = "tree")]
+#[derive(Clone, Default)]
+pub struct MerkleTree {
+    /// Content of every leaf.
+    leaves: Chunked<LeafContent>,
+
+    /// Indices of removed leaves. Clones share the set until either removes a leaf.
+    removed: Rc<BTreeSet<usize>>,
+
+    /// Node values as hexdigests, from the bottom level (leaf hashes) up to the top level (root node).
+    levels: Vec<Chunked<String>>,
+
+    /// How child hashes are concatenated.
+    concatenation: Concatenation,
+
+    /// How the last node of an odd level is combined into the upper level.
+    padding: Padding,
+
+    /// How leaves were ordered before construction.
+    ordering: LeafOrdering,
+
+    /// Number of bytes node values are truncated to, or None for full digests.
+    truncation: Option<usize>,
+
+    /// Hash function for leaves and parents.
+    algorithm: HashAlgorithm,
+
+    /// How leaf contents encode the bytes hashed into leaf node values.
+    leaf_encoding: LeafEncoding,
+
+    /// Number of leaves storage was preallocated for.
+    capacity: usize,
+
+    /// Secret key blinders of leaves are derived from, or None if leaves are not blinded.
+    blinding_key: Option<[u8; 32]>,
+
+    /// Indices of leaves sorted by leaf node value, built by the first
+    /// `find_by_digest_prefix` and discarded when leaves change.
+    digest_index: OnceCell<Rc<Vec<usize>>>,
+}
+
+/// Immutable view of a `MerkleTree` at the moment `MerkleTree::snapshot` was called.
+///
+/// Creating a snapshot is cheap as it shares all unchanged nodes with the tree,
+/// so proofs can be served for an older root while the tree keeps growing.
+#[cfg(feature = "tree")]
+#[derive(Clone)]
+pub struct MerkleSnapshot(MerkleTree);
+
+#[cfg(feature = "tree")]
+impl MerkleSnapshot {
+    /// Number of leaves.
+    pub fn num_of_leaves(&self) -> usize {
+        self.0.num_of_leaves()
+    }
+
+    /// Root node of the snapshot, or None if it has no leaves.
+    pub fn root(&self) -> Option<Rc<RefCell<Hash>>> {
+        self.0.root()
+    }
+
+    /// Generate a merkle proof against the root of the snapshot.
+    /// Return None if `leaf_index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&self, leaf_index: usize) -> Option<MerkleProof> {
+        self.0.proof(leaf_index)
+    }
+}
 
+#[cfg(feature = "tree")]
 impl MerkleTree {
+    /// Initialize an empty merkle tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Initialize an empty merkle tree with storage preallocated for `expected_leaves` leaves,
+    /// so appending up to that many leaves does not reallocate the storage of any level.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_leaves` - Expected number of leaves.
+    pub fn with_capacity(expected_leaves: usize) -> Self {
+        let num_of_levels = match expected_leaves {
+            0 => 0,
+            _ => expected_leaves.next_power_of_two().trailing_zeros() as usize + 1,
+        };
+        MerkleTree {
+            leaves: Chunked::with_capacity(expected_leaves),
+            levels: Vec::with_capacity(num_of_levels),
+            capacity: expected_leaves,
+            ..MerkleTree::default()
+        }
+    }
+
+    /// Storage for a new level, preallocated for the level's share of `capacity`.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Index of the level, 0 for leaf hashes.
+    fn new_level(&self, level: usize) -> Chunked<String> {
+        Chunked::with_capacity(
+            self.capacity
+                .checked_shr(level as u32)
+                .map_or(0, |nodes| nodes + 1),
+        )
+    }
+
+    /// Build a merkle tree from leaves, with the default configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    pub fn from_leaves(leaves: &[String]) -> Self {
+        Self::builder().build(leaves)
+    }
+
+    /// Restore a merkle tree with the default scheme from its leaves and the node values
+    /// returned by `levels`, without recomputing hashes outside of debug builds. Only the shape
+    /// of the levels is checked in release builds; use `MerkleTreeBuilder::build_from_levels` for other schemes.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree, in tree order.
+    /// * `levels` - Node values of every level as hexdigests, from the leaf level up.
+    pub fn from_levels(leaves: &[String], levels: Vec<Vec<String>>) -> Result<Self, MerkleError> {
+        Self::builder().build_from_levels(leaves, levels)
+    }
+
+    /// Configure how a merkle tree is constructed.
+    pub fn builder() -> MerkleTreeBuilder {
+        MerkleTreeBuilder::default()
+    }
+
+    /// Number of leaves.
+    pub fn num_of_leaves(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// How leaves were ordered before construction.
+    pub fn ordering(&self) -> LeafOrdering {
+        self.ordering
+    }
+
+    /// Append a leaf. Only the nodes on the path from the new leaf to the root are updated.
+    /// The leaf is always appended after every existing leaf, regardless of `ordering`.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the leaf is not valid in the leaf encoding of the tree.
+    pub fn append(&mut self, leaf: String) {
+        if self.levels.is_empty() {
+            self.levels.push(self.new_level(0));
+        }
+        let value = self.leaf_value(&leaf);
+        self.levels[0].push(value);
+        self.leaves.push(leaf.into());
+        self.digest_index.take();
+
+        let mut index = self.leaves.len() - 1;
+        let mut level = 0;
+        while self.levels[level].len() > 1 {
+            let nodes = &self.levels[level];
+            let value = if index % 2 != 0 {
+                self.parent_value(nodes.get(index - 1).unwrap(), nodes.get(index))
+            } else {
+                self.parent_value(nodes.get(index).unwrap(), None) // Last node has no sibling.
+            };
+
+            index /= 2;
+            level += 1;
+            if level == self.levels.len() {
+                self.levels.push(self.new_level(level));
+            }
+            if index == self.levels[level].len() {
+                self.levels[level].push(value);
+            } else {
+                self.levels[level].set(index, value);
+            }
+        }
+        self.debug_check_path(self.leaves.len() - 1);
+        telemetry::leaves_appended(1);
+    }
+
+    /// Remove a leaf. Return false if `leaf_index` is out of range or the leaf was already removed.
+    /// See `remove_batch` for the compaction rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf to remove.
+    pub fn remove(&mut self, leaf_index: usize) -> bool {
+        self.remove_batch(&[leaf_index]) == 1
+    }
+
+    /// Remove several leaves at once and return how many were removed. Indices that are out of
+    /// range or already removed are ignored.
+    ///
+    /// Removed leaves are not shifted out. Their content is dropped and their node value is
+    /// replaced by the tombstone of the hash function (truncated like any other node value), so every other leaf
+    /// keeps its index and proofs generated for untouched leaves afterwards verify against
+    /// the new root as usual. Nodes shared by the paths of several removed leaves are only
+    /// recomputed once.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_indices` - 0-based indices of leaves to remove.
+    pub fn remove_batch(&mut self, leaf_indices: &[usize]) -> usize {
+        let tombstone = self.tombstone();
+        let mut dirty = BTreeSet::new();
+        for &index in leaf_indices {
+            if index < self.num_of_leaves() && !self.is_removed(index) {
+                self.leaves.set(index, String::new().into());
+                self.levels[0].set(index, tombstone.to_owned());
+                Rc::make_mut(&mut self.removed).insert(index);
+                dirty.insert(index);
+            }
+        }
+        let removed = dirty.len();
+        if removed > 0 {
+            self.digest_index.take();
+        }
+
+        for level in 0..self.levels.len().saturating_sub(1) {
+            dirty = dirty.into_iter().map(|index| index / 2).collect();
+            for &index in &dirty {
+                let nodes = &self.levels[level];
+                let value =
+                    self.parent_value(nodes.get(2 * index).unwrap(), nodes.get(2 * index + 1));
+                self.levels[level + 1].set(index, value);
+            }
+        }
+        for &index in leaf_indices {
+            if index < self.num_of_leaves() {
+                self.debug_check_path(index);
+            }
+        }
+        removed
+    }
+
+    /// Whether the leaf at `leaf_index` was removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf.
+    pub fn is_removed(&self, leaf_index: usize) -> bool {
+        self.removed.contains(&leaf_index)
+    }
+
+    /// Return the indices of leaves whose leaf node value starts with a hexdigest prefix, in
+    /// ascending order, such as truncated hashes that appear in logs. The prefix is matched
+    /// case-insensitively against the value stored for the leaf, which is blinded if the tree
+    /// blinds leaves. Removed leaves are never returned. A sorted index of leaf node values is
+    /// built by the first call, and rebuilt after leaves change.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Prefix of the leaf node value as hexdigest.
+    pub fn find_by_digest_prefix(&self, prefix: &str) -> Vec<usize> {
+        let Some(values) = self.levels.first() else {
+            return Vec::new();
+        };
+        let index = self.digest_index.get_or_init(|| {
+            let mut index: Vec<usize> = (0..values.len()).collect();
+            index.sort_by(|a, b| values.get(*a).unwrap().cmp(values.get(*b).unwrap()));
+            Rc::new(index)
+        });
+        let prefix = prefix.to_ascii_lowercase();
+        let start = index.partition_point(|leaf| *values.get(*leaf).unwrap() < prefix);
+        let mut found: Vec<usize> = index[start..]
+            .iter()
+            .copied()
+            .take_while(|leaf| values.get(*leaf).unwrap().starts_with(&prefix))
+            .filter(|leaf| !self.is_removed(*leaf))
+            .collect();
+        found.sort_unstable();
+        found
+    }
+
+    /// Root node of the tree, or None if it has no leaves.
+    pub fn root(&self) -> Option<Rc<RefCell<Hash>>> {
+        let value = self.levels.last()?.get(0)?;
+        Some(Rc::new(RefCell::new(Hash::new(value.to_owned()))))
+    }
+
+    /// Generate a merkle proof from the nodes of this tree, without rebuilding it.
+    /// Return None if `leaf_index` is out of range or the leaf was removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&self, leaf_index: usize) -> Option<MerkleProof> {
+        self.proof_to_ancestor(leaf_index, self.levels.len().checked_sub(1)?)
+    }
+
+    /// Generate a merkle proof of a leaf up to its ancestor at `level` instead of the root,
+    /// for consumers that trust that subtree root from elsewhere. The proof reconstructs the
+    /// node at index `leaf_index >> level` of `level`, and verifies against it like any proof
+    /// against a root. Return None if `leaf_index` is out of range, the leaf was removed, or
+    /// the tree has no such level.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    /// * `level` - Level of the ancestor, 0 for the leaf itself.
+    pub fn proof_to_ancestor(&self, leaf_index: usize, level: usize) -> Option<MerkleProof> {
+        if self.is_removed(leaf_index) || level >= self.levels.len() {
+            return None;
+        }
+        let leaf_content = self.leaves.get(leaf_index)?.to_string();
+        let blinder = self.blinder(&leaf_content);
+
+        let hashes = self.audit_hashes(0, level, leaf_index);
+        self.debug_check_audit_path(
+            &hashes,
+            0,
+            level,
+            leaf_index,
+            self.levels[0].get(leaf_index).unwrap(),
+        );
+        telemetry::proof_served(hashes.len());
+        Some(MerkleProof {
+            hashes,
+            num_of_leaves: self.num_of_leaves(),
+            leaf_index,
+            leaf_content,
+            concatenation: self.concatenation,
+            truncation: self.truncation,
+            algorithm: self.algorithm,
+            leaf_encoding: self.leaf_encoding,
+            blinder,
+        })
+    }
+
+    /// Return the audit hashes of a node from its level up to an ancestor level, arranged from
+    /// the bottom-most hash up to the top-most hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Level of the node, 0 for leaves.
+    /// * `to` - Level of the ancestor.
+    /// * `index` - Index of the node within its level.
+    fn audit_hashes(&self, from: usize, to: usize, mut index: usize) -> Vec<Rc<RefCell<Hash>>> {
+        let mut hashes = Vec::new();
+        for nodes in &self.levels[from..to] {
+            let sibling_index = index ^ 1;
+            let sibling = match nodes.get(sibling_index) {
+                Some(sibling) => Some(sibling.to_owned()),
+                None => self.padding_sibling(nodes.get(index).unwrap()),
+            };
+            if let Some(sibling) = sibling {
+                let mut hash = Hash::new(sibling);
+                hash.is_left = sibling_index < index;
+                hashes.push(Rc::new(RefCell::new(hash)));
+            } // Handle edge case for promoted siblingless rightmost node on the level.
+            index /= 2;
+        }
+        hashes
+    }
+
+    /// Deterministically sample `k` distinct leaves for a spot-check audit and return their
+    /// proofs, in sampling order. The sample is derived from `seed` and the root with
+    /// `MerkleTree::sample_indices`, so it cannot be predicted before the tree is committed to,
+    /// yet an auditor can reproduce it. Removed leaves that are sampled have no proof and are
+    /// skipped. Return no proofs for an empty tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed agreed with the auditor, for example a challenge or a block hash.
+    /// * `k` - Number of leaves to sample, at most the number of leaves.
+    pub fn sample_proofs(&self, seed: &[u8], k: usize) -> Vec<MerkleProof> {
+        let root = match self.root() {
+            Some(root) => root.borrow().value.to_owned(),
+            None => return Vec::new(),
+        };
+        Self::sample_indices(&root, self.num_of_leaves(), seed, k)
+            .into_iter()
+            .filter_map(|leaf_index| self.proof(leaf_index))
+            .collect()
+    }
+
+    /// Hash function for leaves and parents.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// How the tree is constructed, apart from its hash function.
+    pub fn scheme(&self) -> TreeScheme {
+        TreeScheme {
+            concatenation: self.concatenation,
+            padding: self.padding.to_owned(),
+            ordering: self.ordering,
+            truncation: self.truncation,
+            leaf_encoding: self.leaf_encoding,
+        }
+    }
+
+    /// How leaf contents encode the bytes hashed into leaf node values.
+    pub fn leaf_encoding(&self) -> LeafEncoding {
+        self.leaf_encoding
+    }
+
+    /// Number of bytes node values are truncated to, or None for full digests.
+    pub fn truncation(&self) -> Option<usize> {
+        self.truncation
+    }
+
+    /// Return the node value of a leaf.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the content is not valid in the leaf encoding of the tree.
+    fn leaf_value(&self, leaf: &str) -> String {
+        truncate(
+            self.algorithm.hash(&self.leaf_preimage(leaf)),
+            self.truncation,
+        )
+    }
+
+    /// Return the node value of a removed leaf, the tombstone of the hash function truncated
+    /// like any other node value.
+    pub(crate) fn tombstone(&self) -> String {
+        truncate(self.algorithm.tombstone(), self.truncation)
+    }
+
+    /// Return the bytes hashed into the node value of a leaf: the bytes its content encodes
+    /// followed by its blinder, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the content is not valid in the leaf encoding of the tree.
+    fn leaf_preimage(&self, leaf: &str) -> Vec<u8> {
+        leaf_preimage(self.leaf_encoding, leaf, self.blinder(leaf).as_deref())
+            .unwrap_or_else(|| panic!("leaf is not valid {:?}", self.leaf_encoding))
+    }
+
+    /// Return the blinder of a leaf as hexdigest, or None if leaves are not blinded.
+    /// The blinder is the SHA-256 digest of the blinding key followed by the leaf content.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    fn blinder(&self, leaf: &str) -> Option<String> {
+        self.blinding_key
+            .map(|key| hex::encode(&provider::sha256(&[&key[..], leaf.as_bytes()].concat())))
+    }
+
+    /// Secret key blinders of leaves are derived from, or None if leaves are not blinded.
+    pub fn blinding_key(&self) -> Option<[u8; 32]> {
+        self.blinding_key
+    }
+
+    /// Return the node that the siblingless rightmost node of a level is paired with,
+    /// or None if it is promoted.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Siblingless rightmost node value as hexdigest.
+    fn padding_sibling(&self, node: &str) -> Option<String> {
+        match &self.padding {
+            Padding::Promote => None,
+            Padding::DuplicateLast => Some(node.to_owned()),
+            Padding::Constant(value) => Some(value.to_owned()),
+        }
+    }
+
+    /// Return the value of the parent of a left child and an optional right child,
+    /// applying the padding rule if there is no right child.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child value as hexdigest.
+    /// * `right` - Right child value as hexdigest, if any.
+    fn parent_value(&self, left: &str, right: Option<&String>) -> String {
+        match right.cloned().or_else(|| self.padding_sibling(left)) {
+            Some(right) => truncate(
+                self.concatenation.hash_with(self.algorithm, left, &right),
+                self.truncation,
+            ),
+            None => left.to_owned(),
+        }
+    }
+
+    /// Walk every stored node of the tree, depth-first and left to right, calling `visitor`
+    /// with the level of the node (0 for leaves), its index within the level and its value as
+    /// hexdigest. The children of the node at index `i` are the nodes at indices `2i` and
+    /// `2i + 1` of the level below, if any. A promoted node is visited once per level it is
+    /// stored at, and removed leaves are visited as their tombstone.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - Whether nodes are visited before or after their children.
+    /// * `visitor` - Function called with the level, index and value of every node.
+    pub fn visit<F: FnMut(usize, usize, &str)>(&self, order: Traversal, mut visitor: F) {
+        if let Some(top) = self.levels.len().checked_sub(1) {
+            self.visit_node(top, 0, order, &mut visitor);
+        }
+    }
+
+    /// Walk the subtree of a node for `visit`.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, 0 for leaves.
+    /// * `index` - Index of the node within its level.
+    /// * `order` - Whether nodes are visited before or after their children.
+    /// * `visitor` - Function called with the level, index and value of every node.
+    fn visit_node<F: FnMut(usize, usize, &str)>(
+        &self,
+        level: usize,
+        index: usize,
+        order: Traversal,
+        visitor: &mut F,
+    ) {
+        let value = match self.levels[level].get(index) {
+            Some(value) => value,
+            None => return,
+        };
+        if order == Traversal::PreOrder {
+            visitor(level, index, value);
+        }
+        if level > 0 {
+            self.visit_node(level - 1, 2 * index, order, visitor);
+            self.visit_node(level - 1, 2 * index + 1, order, visitor);
+        }
+        if order == Traversal::PostOrder {
+            visitor(level, index, value);
+        }
+    }
+
+    /// Return the node values of every level as hexdigests, from the leaf level up to the root
+    /// level, so the complete tree can be stored in columnar form and restored with
+    /// `from_levels` without recomputing hashes. Removed leaves are returned as their tombstone.
+    pub fn levels(&self) -> Vec<Vec<String>> {
+        self.levels
+            .iter()
+            .map(|level| {
+                (0..level.len())
+                    .map(|i| level.get(i).unwrap().to_owned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Take a cheap immutable snapshot of the tree. Subsequent appends to the tree
+    /// do not affect the snapshot.
+    pub fn snapshot(&self) -> MerkleSnapshot {
+        MerkleSnapshot(self.clone())
+    }
+
     /// Given a left child node and a right child node, return a parent node whose value
     /// is the hash of the left child's hash concatenated with the right child's hash.
     /// Links between the parent and children are added accordingly.
@@ -87,118 +1293,221 @@ impl MerkleTree {
         parent
     }
 
-    /// Recursively build a merkle tree from the bottom level (leaves) up to the top level (root node).
+    /// Build a merkle tree level by level from the bottom level (leaves) up to the top level
+    /// (root node). Nodes are moved into their parents rather than cloned, and the buffers of
+    /// two levels are reused for every level.
     ///
     /// # Arguments
     ///
-    /// * `nodes` - Nodes of current level.
-    fn merkle_root_aux(nodes: Vec<Rc<RefCell<Hash>>>) -> Rc<RefCell<Hash>> {
-        if nodes.len() == 1 {
-            return nodes[0].to_owned();
-        }
-
-        let mut parents = Vec::new();
-        let is_odd = nodes.len() % 2 != 0;
-
-        // Iterate through sibling-pairs on the same level.
-        for i in (0..(nodes.len() - if is_odd { 1 } else { 0 })).step_by(2) {
-            parents.push(Self::make_parent(
-                nodes[i].to_owned(),
-                nodes[i + 1].to_owned(),
-            ));
-        }
-
-        if is_odd {
-            parents.push(nodes[nodes.len() - 1].to_owned()); // Last node has no sibling.
+    /// * `nodes` - Nodes of the bottom level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` is empty.
+    fn merkle_root_aux(mut nodes: Vec<Rc<RefCell<Hash>>>) -> Rc<RefCell<Hash>> {
+        let mut parents = Vec::with_capacity(nodes.len().div_ceil(2));
+        while nodes.len() > 1 {
+            let mut level = nodes.drain(..);
+            // Iterate through sibling-pairs on the same level.
+            while let Some(left) = level.next() {
+                parents.push(match level.next() {
+                    Some(right) => Self::make_parent(left, right),
+                    None => left, // Last node has no sibling.
+                });
+            }
+            drop(level);
+            std::mem::swap(&mut nodes, &mut parents);
         }
-
-        Self::merkle_root_aux(parents)
+        nodes.pop().expect("merkle tree needs at least 1 leaf")
     }
 
     /// Generate a merkle tree and return the root node.
     ///
+    /// Every node holds its children and its parent, so the nodes form reference cycles and
+    /// are not freed when the root is dropped. Use `MerkleTree::from_leaves` to build trees
+    /// repeatedly in a long-running process.
+    ///
     /// # Arguments
     ///
     /// * `leaves` - Leaves of merkle tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaves` is empty.
+    #[allow(clippy::ptr_arg)]
     pub fn merkle_root(leaves: &Vec<String>) -> Rc<RefCell<Hash>> {
         let nodes: Vec<Rc<RefCell<Hash>>> = leaves
-            .into_iter()
-            .map(|leaf| Rc::new(RefCell::new(Hash::new(Hash::hash(&leaf)))))
+            .iter()
+            .map(|leaf| Rc::new(RefCell::new(Hash::new(Hash::hash(leaf)))))
             .collect();
         Self::merkle_root_aux(nodes)
     }
 
-    /// Recursively build a merkle tree from the bottom level (leaves) up to the top level (root node).
-    /// This is similar to `__merkle_root_aux` except that an accumulating of `audit_nodes` is maintained along with
-    /// a `target_index`. At each recursive call, the sibling of the node at `target_index` is added to `audit_nodes`,
-    /// then `target_index` is updated to the 0-based index of its parent at the immediate upper level. `audit_nodes`
-    /// is returned when the root node level is reached.
+    /// Generate a merkle tree whose leaves are streamed from readers and return the root node.
+    /// Each leaf is hashed incrementally, so arbitrarily large leaves never need to be fully buffered.
     ///
     /// # Arguments
     ///
-    /// * `nodes` - Nodes of current level.
-    /// * `audit_nodes` - Accumulating list of nodes that are needed for the merkle proof.
-    /// * `target_index` - 0-based index of target node of the current level. The target node's sibling is
-    /// the audit node for the current level.
-    fn merkle_proof_aux(
-        nodes: Vec<Rc<RefCell<Hash>>>,
-        mut audit_nodes: Vec<Rc<RefCell<Hash>>>,
-        target_index: usize,
-    ) -> Vec<Rc<RefCell<Hash>>> {
-        if nodes.len() == 1 {
-            return audit_nodes;
-        }
-
-        let mut parents = Vec::new();
-        let sibling_index = if target_index % 2 == 0 {
-            target_index + 1
-        } else {
-            target_index - 1
-        };
-
-        if sibling_index < nodes.len() {
-            audit_nodes.push(nodes[sibling_index].to_owned());
-        } // Handle edge case for siblingless rightmost node on the level.
+    /// * `readers` - Readers for the leaves of merkle tree, in leaf order.
+    pub fn merkle_root_from_readers<I, R>(readers: I) -> io::Result<Rc<RefCell<Hash>>>
+    where
+        I: IntoIterator<Item = R>,
+        R: Read,
+    {
+        let nodes = readers
+            .into_iter()
+            .map(|reader| Ok(Rc::new(RefCell::new(Hash::new(Hash::hash_reader(reader)?)))))
+            .collect::<io::Result<Vec<Rc<RefCell<Hash>>>>>()?;
+        Ok(Self::merkle_root_aux(nodes))
+    }
 
-        let new_target_index = target_index / 2;
+    /// Generate a merkle tree whose leaves are streamed from async readers and return the
+    /// root node. This is the async counterpart of `merkle_root_from_readers`, so IO-bound
+    /// leaf ingestion does not block the async runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `readers` - Async readers for the leaves of merkle tree, in leaf order.
+    #[cfg(feature = "tokio")]
+    pub async fn merkle_root_from_async_readers<I, R>(readers: I) -> io::Result<Rc<RefCell<Hash>>>
+    where
+        I: IntoIterator<Item = R>,
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let mut values = Vec::new();
+        for reader in readers {
+            values.push(Hash::hash_async_reader(reader).await?);
+        }
+        let nodes = values
+            .into_iter()
+            .map(|value| Rc::new(RefCell::new(Hash::new(value))))
+            .collect();
+        Ok(Self::merkle_root_aux(nodes))
+    }
 
-        let is_odd = nodes.len() % 2 != 0;
+    /// Hash a merkle tree level by level from the bottom level (leaf hashes) up to the top
+    /// level (root node) while collecting the audit nodes of the node at `target_index`. At
+    /// each level, the sibling of the target node is added to the audit nodes, then
+    /// `target_index` is updated to the 0-based index of its parent at the immediate upper
+    /// level. The audit nodes are returned when the root node level is reached. Every level
+    /// overwrites the values of the level below in place, and no linked nodes are made, so
+    /// nothing outlives the call but the audit nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Values of the bottom level as hexdigests.
+    /// * `target_index` - 0-based index of target node of the bottom level. The target node's
+    ///   sibling is the audit node for the level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    fn merkle_proof_aux(
+        mut values: Vec<String>,
+        mut target_index: usize,
+    ) -> Vec<Rc<RefCell<Hash>>> {
+        assert!(!values.is_empty(), "merkle tree needs at least 1 leaf");
+        let mut audit_nodes = Vec::new();
+        while values.len() > 1 {
+            let sibling_index = target_index ^ 1;
+            if let Some(sibling) = values.get(sibling_index) {
+                let mut hash = Hash::new(sibling.to_owned());
+                hash.is_left = sibling_index < target_index;
+                audit_nodes.push(Rc::new(RefCell::new(hash)));
+            } // Handle edge case for siblingless rightmost node on the level.
 
-        // Iterate through sibling-pairs on the same level.
-        for i in (0..(nodes.len() - if is_odd { 1 } else { 0 })).step_by(2) {
-            parents.push(Self::make_parent(
-                nodes[i].to_owned(),
-                nodes[i + 1].to_owned(),
-            ));
+            // Parents only read nodes at or after their own index, so they can replace them.
+            let len = values.len();
+            for index in 0..len / 2 {
+                values[index] =
+                    Hash::hash(&format!("{}{}", values[2 * index], values[2 * index + 1]));
+            }
+            values.swap(len / 2, len - 1); // Last node has no sibling if the level is odd.
+            values.truncate(len.div_ceil(2));
+            target_index /= 2;
         }
+        audit_nodes
+    }
 
-        if is_odd {
-            parents.push(nodes[nodes.len() - 1].to_owned()); // Last node has no sibling.
+    /// Return the indices of `k` distinct leaves sampled from `seed` and a root, as
+    /// `sample_proofs` selects them. The i-th candidate is the SHA-256 digest of the length of
+    /// `seed` (as 8 little-endian bytes), `seed`, the root hexdigest and i (as 8 little-endian
+    /// bytes), whose first 8 bytes as a little-endian integer are reduced modulo
+    /// `num_of_leaves`; candidates already sampled are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root hash as hexdigest.
+    /// * `num_of_leaves` - Number of leaves of the tree.
+    /// * `seed` - Seed agreed with the auditor.
+    /// * `k` - Number of leaves to sample, at most `num_of_leaves`.
+    pub fn sample_indices(root: &str, num_of_leaves: usize, seed: &[u8], k: usize) -> Vec<usize> {
+        let k = k.min(num_of_leaves);
+        let mut sampled = BTreeSet::new();
+        let mut indices = Vec::with_capacity(k);
+        let mut counter: u64 = 0;
+        while indices.len() < k {
+            let digest = provider::sha256(
+                &[
+                    &(seed.len() as u64).to_le_bytes()[..],
+                    seed,
+                    root.as_bytes(),
+                    &counter.to_le_bytes(),
+                ]
+                .concat(),
+            );
+            let candidate = u64::from_le_bytes(digest[..8].try_into().unwrap());
+            let leaf_index = (candidate % num_of_leaves as u64) as usize;
+            if sampled.insert(leaf_index) {
+                indices.push(leaf_index);
+            }
+            counter += 1;
         }
-
-        Self::merkle_proof_aux(parents, audit_nodes, new_target_index)
+        indices
     }
 
-    /// Generate a merkle proof.
+    /// Generate a merkle proof. Every call hashes the whole tree, which takes O(n) work; to
+    /// serve many proofs, build the tree once with `MerkleTree::from_leaves` and call `proof`,
+    /// which reads the stored audit nodes in O(log n) without hashing.
     ///
     /// # Arguments
     ///
     /// * `leaves` - Leaves of merkle tree.
     /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    #[allow(clippy::ptr_arg)]
     pub fn merkle_proof(leaves: &Vec<String>, leaf_index: usize) -> MerkleProof {
-        let nodes: Vec<Rc<RefCell<Hash>>> = leaves
-            .iter()
-            .map(|leaf| Rc::new(RefCell::new(Hash::new(Hash::hash(leaf)))))
-            .collect();
-
-        let audit_nodes = Self::merkle_proof_aux(nodes.to_owned(), Vec::new(), leaf_index);
+        let values = leaves.iter().map(|leaf| Hash::hash(leaf)).collect();
+        let audit_nodes = Self::merkle_proof_aux(values, leaf_index);
 
         MerkleProof {
             hashes: audit_nodes,
-            num_of_leaves: nodes.len(),
+            num_of_leaves: leaves.len(),
             leaf_index,
             leaf_content: leaves[leaf_index].to_owned(),
+            concatenation: Concatenation::Hex,
+            truncation: None,
+            algorithm: HashAlgorithm::Sha256,
+            leaf_encoding: LeafEncoding::Utf8,
+            blinder: None,
+        }
+    }
+
+    /// Generate merkle proofs for every leaf from a single tree construction.
+    /// Upper nodes are shared between proofs, so this is much faster than calling
+    /// `merkle_proof` once per leaf, which rebuilds the whole tree each time.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Leaves of merkle tree.
+    pub fn all_proofs(leaves: &[String]) -> Vec<MerkleProof> {
+        if leaves.is_empty() {
+            return Vec::new();
         }
+
+        let tree = Self::from_leaves(leaves);
+        (0..leaves.len())
+            .filter_map(|leaf_index| tree.proof(leaf_index))
+            .collect()
     }
 
     /// Given a merkle root node, verify a proof by checking whether it is able
@@ -209,17 +1518,113 @@ impl MerkleTree {
     /// * `root` - Root node of the merkle tree.
     /// * `proof` - Proof to be verified.
     pub fn verify_proof(root: Rc<RefCell<Hash>>, proof: &MerkleProof) -> bool {
-        let mut result = Hash::hash(&proof.leaf_content);
+        let verified = proof.root_value() == root.borrow().value;
+        telemetry::verification(verified);
+        verified
+    }
 
-        for audit_hash in &proof.hashes {
-            let audit_value = &audit_hash.borrow().value;
-            result = if audit_hash.borrow().is_left {
-                Hash::hash(&format!("{}{}", audit_value, result))
-            } else {
-                Hash::hash(&format!("{}{}", result, audit_value))
-            };
+    /// Verify a proof against several candidate roots, such as a window of recently published
+    /// roots during root rotation, and return the index of the first root it reconstructs, or
+    /// None if it reconstructs none. The root implied by the proof is computed once.
+    ///
+    /// # Arguments
+    ///
+    /// * `roots` - Candidate root nodes.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_any(roots: &[Rc<RefCell<Hash>>], proof: &MerkleProof) -> Option<usize> {
+        let root_value = proof.root_value();
+        let index = roots
+            .iter()
+            .position(|root| root.borrow().value == root_value);
+        telemetry::verification(index.is_some());
+        index
+    }
+
+    /// Same as `verify_proof`, but add the number of hash invocations performed to `steps`,
+    /// so that metering systems can charge for verification accurately.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle tree.
+    /// * `proof` - Proof to be verified.
+    /// * `steps` - Counter of hash invocations.
+    pub fn verify_proof_counted(
+        root: Rc<RefCell<Hash>>,
+        proof: &MerkleProof,
+        steps: &mut u64,
+    ) -> bool {
+        let verified = proof.root_value_counted(steps) == root.borrow().value;
+        telemetry::verification(verified);
+        verified
+    }
+
+    /// Same as `verify_proof`, but also check the leaf content against the leaf digest the
+    /// caller expects, so that content encoded differently from what was hashed into the tree
+    /// is reported at the leaf instead of as a root mismatch. Fail with
+    /// `MerkleError::LeafDigestMismatch` if the digests disagree, and with
+    /// `MerkleError::ProofHashMismatch` if the proof does not reproduce the root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle tree.
+    /// * `proof` - Proof to be verified.
+    /// * `expected_leaf_digest` - Expected leaf node value as hexdigest, in either case.
+    pub fn verify_proof_pinned(
+        root: Rc<RefCell<Hash>>,
+        proof: &MerkleProof,
+        expected_leaf_digest: &str,
+    ) -> Result<(), MerkleError> {
+        let leaf_value = proof.leaf_value();
+        if !leaf_value.eq_ignore_ascii_case(expected_leaf_digest) {
+            return Err(MerkleError::LeafDigestMismatch(leaf_value));
+        }
+        if !Self::verify_proof(root, proof) {
+            return Err(MerkleError::ProofHashMismatch);
         }
+        Ok(())
+    }
+}
+
+/// Build a merkle tree from leaves yielded in order as bytes, such as strings, byte slices or
+/// byte vectors. A leaf may hold any bytes, so the tree uses `LeafEncoding::Hex` and stores
+/// every leaf as the lowercase hex string of its bytes; otherwise the configuration is the
+/// default. Leaf values are digests of the raw bytes, so the root is the root of a default
+/// tree over the same leaves as UTF-8 text, as `TryFrom<&[B]>` builds when every leaf is
+/// valid UTF-8.
+///
+/// ```
+/// use merkle_tree::{LeafEncoding, MerkleTree};
+///
+/// let tree: MerkleTree = ["a", "b", "c"].iter().collect();
+/// assert_eq!(tree.num_of_leaves(), 3);
+/// let bytes: MerkleTree = [vec![0xff], vec![0x00]].iter().collect();
+/// assert_eq!(bytes.scheme().leaf_encoding, LeafEncoding::Hex);
+/// assert_eq!(bytes.proof(0).unwrap().leaf_content, "ff");
+/// ```
+#[cfg(feature = "tree")]
+impl<B: AsRef<[u8]>> FromIterator<B> for MerkleTree {
+    fn from_iter<I: IntoIterator<Item = B>>(leaves: I) -> Self {
+        Self::builder()
+            .leaf_encoding(LeafEncoding::Hex)
+            .try_build_iter(leaves.into_iter().map(|leaf| hex::encode(leaf.as_ref())))
+            .expect("hex strings are valid hex leaves")
+    }
+}
+
+/// Build a merkle tree with the default configuration from leaves given as bytes. Fail with
+/// `MerkleError::InvalidEncoding` if a leaf is not valid UTF-8, as leaf contents are stored
+/// as text under `LeafEncoding::Utf8`. Collect the leaves with `FromIterator` instead to
+/// accept any bytes; both give the same root for valid UTF-8 leaves.
+#[cfg(feature = "tree")]
+impl<B: AsRef<[u8]>> TryFrom<&[B]> for MerkleTree {
+    type Error = MerkleError;
 
-        result == root.borrow().value
+    fn try_from(leaves: &[B]) -> Result<Self, MerkleError> {
+        let leaves = leaves
+            .iter()
+            .map(|leaf| String::from_utf8(leaf.as_ref().to_vec()))
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|_| MerkleError::InvalidEncoding)?;
+        Ok(Self::from_leaves(&leaves))
     }
 }
@@ -1,8 +1,19 @@
 // Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
-use merkle_tree::MerkleTree;
+use merkle_tree::{Hash, MerkleTree, TreeDelta};
 use std::borrow::BorrowMut;
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+use std::{env, fs, thread};
 
-pub fn main() {
+const USAGE: &str = "usage: merkle-tree [diff <a.leaves> <b.leaves> | watch <dir> [<interval-ms>]]";
+
+/// Milliseconds between two scans of a watched directory, unless given.
+const WATCH_INTERVAL_MS: u64 = 1000;
+
+fn demo() {
     let data: Vec<String> = vec![
         "abc".to_string(),
         "bcd".to_string(),
@@ -16,7 +27,183 @@ pub fn main() {
         "b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073"
     );
     let mut proof = MerkleTree::merkle_proof(&data, 1);
-    assert_eq!(MerkleTree::verify_proof(root.to_owned(), &proof), true);
+    assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
     proof.borrow_mut().leaf_content += "tainted";
-    assert_eq!(MerkleTree::verify_proof(root.to_owned(), &proof), false);
+    assert!(!MerkleTree::verify_proof(root.to_owned(), &proof));
+}
+
+/// Build a tree with the default configuration and one leaf per line of a file.
+///
+/// # Arguments
+///
+/// * `path` - Path of the file.
+fn read_leaves(path: &str) -> io::Result<MerkleTree> {
+    let leaves: Vec<String> = fs::read_to_string(path)?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    Ok(MerkleTree::from_leaves(&leaves))
+}
+
+/// Print every leaf that differs between two files of one leaf per line, as its 0-based index,
+/// its digest in `a` and its digest in `b`, with `-` for a leaf missing from a file. Digests
+/// are colored when printing to a terminal, unless `NO_COLOR` is set. Return whether any leaf
+/// differs.
+///
+/// # Arguments
+///
+/// * `a` - Path of the old leaves.
+/// * `b` - Path of the new leaves.
+fn diff(a: &str, b: &str) -> io::Result<bool> {
+    let (old, new) = (read_leaves(a)?, read_leaves(b)?);
+    let delta = TreeDelta::between(&old, &new);
+    let (old_levels, new_levels) = (old.levels(), new.levels());
+    let old_digests = old_levels.first().map_or(&[][..], Vec::as_slice);
+    let new_digests = new_levels.first().map_or(&[][..], Vec::as_slice);
+
+    // Leaves past the end of `b` are not in the delta, which only holds leaves of `b`.
+    let indices = delta
+        .leaves
+        .iter()
+        .map(|(index, _)| *index)
+        .chain(new_digests.len()..old_digests.len());
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let paint = |code: &str, digest: Option<&String>| match digest {
+        Some(digest) if color => format!("\x1b[{code}m{digest}\x1b[0m"),
+        Some(digest) => digest.to_owned(),
+        None => "-".to_string(),
+    };
+    let mut differs = false;
+    for index in indices {
+        differs = true;
+        println!(
+            "{index} {} {}",
+            paint("31", old_digests.get(index)),
+            paint("32", new_digests.get(index))
+        );
+    }
+    Ok(differs)
+}
+
+/// Return the modification time and size of every file under a directory, by path relative
+/// to it. Symbolic links are not followed.
+///
+/// # Arguments
+///
+/// * `dir` - Directory to scan.
+/// * `prefix` - Path of `dir` relative to the watched directory.
+/// * `files` - Map the files are added to.
+fn scan(
+    dir: &Path,
+    prefix: &Path,
+    files: &mut BTreeMap<PathBuf, (SystemTime, u64)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = prefix.join(entry.file_name());
+        if metadata.is_dir() {
+            scan(&entry.path(), &path, files)?;
+        } else if metadata.is_file() {
+            files.insert(path, (metadata.modified()?, metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Print the root of a directory, then poll it every `interval` and print the changed paths
+/// and the new root whenever a file is added, removed, or changes modification time or size.
+/// Every file is a leaf `<hexdigest>  <path>`, as sha256sum prints it, in path order, and only
+/// changed files are hashed again. Paths are printed as `A`, `M` or `D` and the path, and
+/// roots as `root` and the hexdigest, or `-` for a directory without files. Only returns on
+/// error.
+///
+/// Polling is used as the standard library has no file system notifications.
+///
+/// # Arguments
+///
+/// * `dir` - Directory to watch.
+/// * `interval` - Time between two scans.
+fn watch(dir: &Path, interval: Duration) -> io::Result<()> {
+    let mut leaves: BTreeMap<PathBuf, ((SystemTime, u64), String)> = BTreeMap::new();
+    let mut first = true;
+    loop {
+        let mut files = BTreeMap::new();
+        scan(dir, Path::new(""), &mut files)?;
+
+        let mut changed = false;
+        leaves.retain(|path, _| {
+            let kept = files.contains_key(path);
+            if !kept {
+                println!("D {}", path.display());
+                changed = true;
+            }
+            kept
+        });
+        for (path, stamp) in files {
+            let status = match leaves.get(&path) {
+                Some((old, _)) if *old == stamp => continue,
+                Some(_) => "M",
+                None => "A",
+            };
+            let digest = match fs::File::open(dir.join(&path)) {
+                Ok(file) => Hash::hash_reader(file)?,
+                // Removed since the scan, so it is reported by the next one.
+                Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+            if !first {
+                println!("{status} {}", path.display());
+            }
+            let leaf = format!("{digest}  {}", path.display());
+            leaves.insert(path, (stamp, leaf));
+            changed = true;
+        }
+
+        if changed || first {
+            let leaves: Vec<String> = leaves.values().map(|(_, leaf)| leaf.to_owned()).collect();
+            match MerkleTree::from_leaves(&leaves).root() {
+                Some(root) => println!("root {}", root.borrow().value),
+                None => println!("root -"),
+            }
+        }
+        first = false;
+        thread::sleep(interval);
+    }
+}
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        [] => {
+            demo();
+            ExitCode::SUCCESS
+        }
+        // Exit like diff(1): 0 if the leaves are the same, 1 if they differ, 2 on error.
+        ["diff", a, b] => match diff(a, b) {
+            Ok(false) => ExitCode::SUCCESS,
+            Ok(true) => ExitCode::from(1),
+            Err(error) => {
+                eprintln!("merkle-tree: {error}");
+                ExitCode::from(2)
+            }
+        },
+        ["watch", dir] | ["watch", dir, _] => {
+            let interval = match args.get(2).map(|ms| ms.parse()) {
+                None => WATCH_INTERVAL_MS,
+                Some(Ok(ms)) => ms,
+                Some(Err(_)) => {
+                    eprintln!("{USAGE}");
+                    return ExitCode::from(2);
+                }
+            };
+            let error = watch(Path::new(dir), Duration::from_millis(interval)).unwrap_err();
+            eprintln!("merkle-tree: {error}");
+            ExitCode::from(2)
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
 }
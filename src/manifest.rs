@@ -0,0 +1,354 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Detached integrity manifests of file sets, in DER for embedding in CMS (PKCS #7)
+//! `SignedData`, so code-signing pipelines sign one root instead of every file.
+//!
+//! The leaves of the tree of a manifest are the lines `<hexdigest>  <name>` of its files, in
+//! the format of `sha256sum`, sorted by name. The manifest is the DER encoding of
+//!
+//! ```text
+//! IntegrityManifest ::= SEQUENCE {
+//!     version        INTEGER (1),
+//!     hashAlgorithm  AlgorithmIdentifier,
+//!     root           OCTET STRING,
+//!     entries        SEQUENCE OF SEQUENCE {
+//!         name    UTF8String,
+//!         digest  OCTET STRING } }
+//! ```
+//!
+//! where the algorithm identifier has absent parameters, as RFC 5754 specifies for SHA-2. The
+//! encapsulated content of a `SignedData` carries it with content type `id-data`.
+use crate::{hex, HashAlgorithm, MerkleError, MerkleTree};
+
+/// Version of the manifest structure.
+const VERSION: u8 = 1;
+
+/// DER tag of INTEGER.
+const INTEGER: u8 = 0x02;
+
+/// DER tag of OCTET STRING.
+const OCTET_STRING: u8 = 0x04;
+
+/// DER tag of OBJECT IDENTIFIER.
+const OBJECT_IDENTIFIER: u8 = 0x06;
+
+/// DER tag of UTF8String.
+const UTF8_STRING: u8 = 0x0c;
+
+/// DER tag of SEQUENCE and SEQUENCE OF.
+const SEQUENCE: u8 = 0x30;
+
+/// DER tag of the explicit `[0]` of `eContent`.
+const CONTEXT_0: u8 = 0xa0;
+
+/// Content body of the DER encoding of `id-data` (1.2.840.113549.1.7.1).
+const ID_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+
+/// Return the content body of the DER encoding of the object identifier of a hash function,
+/// or None if it has none.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+fn algorithm_oid(algorithm: HashAlgorithm) -> Option<&'static [u8]> {
+    match algorithm {
+        // 2.16.840.1.101.3.4.2.1
+        HashAlgorithm::Sha256 => Some(&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01]),
+        // 2.16.840.1.101.3.4.2.8
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_256 => Some(&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x08]),
+        // 2.16.840.1.101.3.4.2.10
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_512 => Some(&[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x0a]),
+        // 1.3.36.3.2.1
+        #[cfg(feature = "ripemd")]
+        HashAlgorithm::Ripemd160 => Some(&[0x2b, 0x24, 0x03, 0x02, 0x01]),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Return the hash function of an object identifier, or None if it is not one.
+///
+/// # Arguments
+///
+/// * `oid` - Content body of the DER encoding of the object identifier.
+fn oid_algorithm(oid: &[u8]) -> Option<HashAlgorithm> {
+    let algorithms = [
+        HashAlgorithm::Sha256,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_256,
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Sha3_512,
+        #[cfg(feature = "ripemd")]
+        HashAlgorithm::Ripemd160,
+    ];
+    algorithms
+        .into_iter()
+        .find(|algorithm| algorithm_oid(*algorithm) == Some(oid))
+}
+
+/// Encode a DER element.
+///
+/// # Arguments
+///
+/// * `tag` - Identifier octet.
+/// * `contents` - Content octets.
+fn tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        element.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let skip = len_bytes.iter().take_while(|byte| **byte == 0).count();
+        element.push(0x80 | (len_bytes.len() - skip) as u8);
+        element.extend_from_slice(&len_bytes[skip..]);
+    }
+    element.extend_from_slice(contents);
+    element
+}
+
+/// Split the DER element with identifier octet `tag` off the front of `bytes`, returning its
+/// content octets and the bytes after it. Fail with `MerkleError::InvalidEncoding` if the
+/// tag differs, or the length is not in its minimal form or runs past the end.
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes starting with the element.
+/// * `tag` - Expected identifier octet.
+fn read_tlv(bytes: &[u8], tag: u8) -> Result<(&[u8], &[u8]), MerkleError> {
+    let [actual, first, rest @ ..] = bytes else {
+        return Err(MerkleError::InvalidEncoding);
+    };
+    if *actual != tag {
+        return Err(MerkleError::InvalidEncoding);
+    }
+    let (len, rest) = match *first {
+        len @ 0..=0x7f => (len as usize, rest),
+        0x81..=0x88 => {
+            let num_of_bytes = (*first & 0x7f) as usize;
+            if rest.len() < num_of_bytes || rest[0] == 0 {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            let len = rest[..num_of_bytes]
+                .iter()
+                .fold(0u64, |len, byte| len << 8 | *byte as u64);
+            if len < 0x80 {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            let len = usize::try_from(len).map_err(|_| MerkleError::InvalidEncoding)?;
+            (len, &rest[num_of_bytes..])
+        }
+        _ => return Err(MerkleError::InvalidEncoding),
+    };
+    if rest.len() < len {
+        return Err(MerkleError::InvalidEncoding);
+    }
+    Ok(rest.split_at(len))
+}
+
+/// File of a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Name of the file, typically its path relative to the root of the file set.
+    pub name: String,
+
+    /// Digest of the contents of the file as hexdigest.
+    pub digest: String,
+}
+
+impl ManifestEntry {
+    /// Leaf content of the entry, `<hexdigest>  <name>` as `sha256sum` prints it.
+    pub fn leaf(&self) -> String {
+        format!("{}  {}", self.digest, self.name)
+    }
+}
+
+/// Integrity manifest of a file set: the root of the tree over its files and the listing of
+/// those files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// Hash function of the file digests and the tree.
+    pub algorithm: HashAlgorithm,
+
+    /// Root value as hexdigest.
+    pub root: String,
+
+    /// Files sorted by name.
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Hash a file set into a manifest. Return None if there are no files, or two files have
+    /// the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function of the file digests and the tree.
+    /// * `files` - Names and contents of the files, in any order.
+    pub fn of_files<N: AsRef<str>, B: AsRef<[u8]>>(
+        algorithm: HashAlgorithm,
+        files: &[(N, B)],
+    ) -> Option<Self> {
+        let mut entries: Vec<ManifestEntry> = files
+            .iter()
+            .map(|(name, bytes)| ManifestEntry {
+                name: name.as_ref().to_owned(),
+                digest: algorithm.hash(bytes.as_ref()),
+            })
+            .collect();
+        entries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        if entries.windows(2).any(|pair| pair[0].name == pair[1].name) {
+            return None;
+        }
+        Some(Manifest {
+            algorithm,
+            root: manifest_tree(algorithm, &entries)
+                .root()?
+                .borrow()
+                .value
+                .to_owned(),
+            entries,
+        })
+    }
+
+    /// Tree over the entries, whose proofs show that a single file is listed.
+    pub fn tree(&self) -> MerkleTree {
+        manifest_tree(self.algorithm, &self.entries)
+    }
+
+    /// Check that the root is the root of the tree over the entries, and that they are sorted
+    /// by name without repeats. Fail with `MerkleError::InvalidEncoding` if they are not, and
+    /// with `MerkleError::ProofHashMismatch` if the root differs.
+    pub fn verify(&self) -> Result<(), MerkleError> {
+        if self.entries.is_empty()
+            || self
+                .entries
+                .windows(2)
+                .any(|pair| pair[0].name >= pair[1].name)
+        {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let tree = self.tree();
+        if tree.root().unwrap().borrow().value != self.root {
+            return Err(MerkleError::ProofHashMismatch);
+        }
+        Ok(())
+    }
+
+    /// Whether the manifest lists a file with this name and contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the file.
+    /// * `bytes` - Contents of the file.
+    pub fn contains_file(&self, name: &str, bytes: &[u8]) -> bool {
+        self.entries
+            .binary_search_by(|entry| entry.name.as_str().cmp(name))
+            .is_ok_and(|index| self.entries[index].digest == self.algorithm.hash(bytes))
+    }
+
+    /// Serialize into the DER encoding of `IntegrityManifest`. Fail with
+    /// `MerkleError::UnsupportedScheme` if the hash function has no object identifier
+    /// (Keccak-256 and Hash160), and with `MerkleError::InvalidEncoding` if the root or a
+    /// digest is not a hexdigest.
+    pub fn to_der(&self) -> Result<Vec<u8>, MerkleError> {
+        let oid = algorithm_oid(self.algorithm).ok_or(MerkleError::UnsupportedScheme)?;
+        let digest = |value: &str| hex::decode(value).ok_or(MerkleError::InvalidEncoding);
+        let mut entries = Vec::new();
+        for entry in &self.entries {
+            let name = tlv(UTF8_STRING, entry.name.as_bytes());
+            let digest = tlv(OCTET_STRING, &digest(&entry.digest)?);
+            entries.extend(tlv(SEQUENCE, &[name, digest].concat()));
+        }
+        let contents = [
+            tlv(INTEGER, &[VERSION]),
+            tlv(SEQUENCE, &tlv(OBJECT_IDENTIFIER, oid)),
+            tlv(OCTET_STRING, &digest(&self.root)?),
+            tlv(SEQUENCE, &entries),
+        ]
+        .concat();
+        Ok(tlv(SEQUENCE, &contents))
+    }
+
+    /// Deserialize the DER encoding of `IntegrityManifest`. Fail with
+    /// `MerkleError::UnsupportedVersion` if the version is not 1, with
+    /// `MerkleError::UnsupportedScheme` if the hash function is not supported, and with
+    /// `MerkleError::InvalidEncoding` if it is otherwise malformed, or a digest does not have
+    /// the size of the hash function. The root is not verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - DER encoding.
+    pub fn from_der(der: &[u8]) -> Result<Self, MerkleError> {
+        let (manifest, rest) = read_tlv(der, SEQUENCE)?;
+        if !rest.is_empty() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let (version, manifest) = read_tlv(manifest, INTEGER)?;
+        match version {
+            [VERSION] => {}
+            [version] if *version < 0x80 => {
+                return Err(MerkleError::UnsupportedVersion(*version as u64))
+            }
+            _ => return Err(MerkleError::InvalidEncoding),
+        }
+        let (algorithm_identifier, manifest) = read_tlv(manifest, SEQUENCE)?;
+        let (oid, parameters) = read_tlv(algorithm_identifier, OBJECT_IDENTIFIER)?;
+        if !parameters.is_empty() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let algorithm = oid_algorithm(oid).ok_or(MerkleError::UnsupportedScheme)?;
+        let digest = |bytes: &[u8]| match bytes.len() == algorithm.digest_size() {
+            true => Ok(hex::encode(bytes)),
+            false => Err(MerkleError::InvalidEncoding),
+        };
+
+        let (root, manifest) = read_tlv(manifest, OCTET_STRING)?;
+        let (mut list, rest) = read_tlv(manifest, SEQUENCE)?;
+        if !rest.is_empty() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let mut entries = Vec::new();
+        while !list.is_empty() {
+            let (entry, rest) = read_tlv(list, SEQUENCE)?;
+            let (name, entry) = read_tlv(entry, UTF8_STRING)?;
+            let (file_digest, entry) = read_tlv(entry, OCTET_STRING)?;
+            if !entry.is_empty() {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            entries.push(ManifestEntry {
+                name: String::from_utf8(name.to_vec()).map_err(|_| MerkleError::InvalidEncoding)?,
+                digest: digest(file_digest)?,
+            });
+            list = rest;
+        }
+        Ok(Manifest {
+            algorithm,
+            root: digest(root)?,
+            entries,
+        })
+    }
+
+    /// Serialize into the DER encoding of a CMS `EncapsulatedContentInfo` (RFC 5652) with
+    /// content type `id-data` and the manifest as content, ready to be the
+    /// `encapContentInfo` of a `SignedData`. Fail like `to_der`.
+    pub fn to_encapsulated_content_info(&self) -> Result<Vec<u8>, MerkleError> {
+        let content = tlv(CONTEXT_0, &tlv(OCTET_STRING, &self.to_der()?));
+        Ok(tlv(
+            SEQUENCE,
+            &[tlv(OBJECT_IDENTIFIER, ID_DATA), content].concat(),
+        ))
+    }
+}
+
+/// Build the tree over the entries of a manifest.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function of the tree.
+/// * `entries` - Files sorted by name.
+fn manifest_tree(algorithm: HashAlgorithm, entries: &[ManifestEntry]) -> MerkleTree {
+    let leaves: Vec<String> = entries.iter().map(ManifestEntry::leaf).collect();
+    MerkleTree::builder().algorithm(algorithm).build(&leaves)
+}
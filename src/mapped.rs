@@ -0,0 +1,349 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Merkle trees stored in files with a fixed layout and served through a read-only memory map.
+//!
+//! After the versioned header and the scheme of the tree, the file holds the number of leaves,
+//! the number of levels, the number of bytes of a node and the indices of removed leaves, then
+//! the raw node values of every level from the leaves up, the offsets of the leaf contents and
+//! the leaf contents. Every node sits at an offset computed from its level and index, so
+//! processes that map the same file share its pages and serve proofs without each loading the
+//! tree into memory.
+use crate::codec::{Artifact, Decoder, Encoder};
+use crate::serialize::{put_scheme, scheme};
+use crate::{hex, Hash, MerkleError, MerkleProof, MerkleTree, TreeScheme};
+use memmap2::Mmap;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Merkle tree served from a memory-mapped file written by `MappedTree::create`.
+pub struct MappedTree {
+    map: Mmap,
+
+    /// Empty tree with the configuration of the stored tree.
+    scheme: MerkleTree,
+    num_of_leaves: usize,
+    node_size: usize,
+
+    /// Indices of removed leaves.
+    removed: BTreeSet<usize>,
+
+    /// Offset of the first node of every level, from the leaf level up.
+    level_offsets: Vec<usize>,
+
+    /// Offset of the table of leaf content offsets.
+    leaf_table_offset: usize,
+
+    /// Offset of the first leaf content.
+    leaf_contents_offset: usize,
+}
+
+/// Return the number of nodes of every level of a tree, from the leaf level up.
+///
+/// # Arguments
+///
+/// * `num_of_leaves` - Number of leaves in the merkle tree.
+fn level_lengths(num_of_leaves: usize) -> Vec<usize> {
+    let mut lengths = Vec::new();
+    let mut len = num_of_leaves;
+    while len > 0 {
+        lengths.push(len);
+        len = if len == 1 { 0 } else { len.div_ceil(2) };
+    }
+    lengths
+}
+
+/// Error for a file that is not a valid mapped tree.
+///
+/// # Arguments
+///
+/// * `error` - Reason the file is invalid.
+fn invalid_data(error: MerkleError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl MappedTree {
+    /// Write `tree` to a file at `path` in the mapped layout and map it. The blinding key
+    /// of a tree with blinded leaves is stored with it, so the file is then secret.
+    /// Fail with `io::ErrorKind::InvalidData` if a node is not a digest of the node size.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to create, replaced if it exists.
+    /// * `tree` - Tree to store.
+    pub fn create<P: AsRef<Path>>(path: P, tree: &MerkleTree) -> io::Result<Self> {
+        let node_size = tree.truncation().unwrap_or(tree.algorithm().digest_size());
+        let mut encoder = Encoder::with_header(Artifact::MappedTree, tree.algorithm());
+        put_scheme(&mut encoder, &tree.scheme());
+        match tree.blinding_key() {
+            None => encoder.put_u64(0),
+            Some(key) => {
+                encoder.put_u64(1);
+                encoder.put_bytes(&key);
+            }
+        }
+        encoder.put_usize(tree.num_of_leaves());
+        encoder.put_usize(tree.levels.len());
+        encoder.put_usize(node_size);
+        encoder.put_usize(tree.removed.len());
+        for index in tree.removed.iter() {
+            encoder.put_usize(*index);
+        }
+
+        let mut file = io::BufWriter::new(File::create(&path)?);
+        file.write_all(&encoder.finish())?;
+        for (level, nodes) in tree.levels.iter().enumerate() {
+            for index in 0..nodes.len() {
+                match hex::decode(nodes.get(index).unwrap()) {
+                    Some(node) if node.len() == node_size => file.write_all(&node)?,
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("node ({level}, {index}) is not a digest of {node_size} bytes"),
+                        ))
+                    }
+                }
+            }
+        }
+        let mut offset = 0u64;
+        file.write_all(&offset.to_le_bytes())?;
+        for index in 0..tree.num_of_leaves() {
+            offset += tree.leaves.get(index).unwrap().len() as u64;
+            file.write_all(&offset.to_le_bytes())?;
+        }
+        for index in 0..tree.num_of_leaves() {
+            file.write_all(tree.leaves.get(index).unwrap().as_bytes())?;
+        }
+        file.into_inner()?.sync_all()?;
+        Self::open(path)
+    }
+
+    /// Map a file written by `create`. Fail with `io::ErrorKind::InvalidData` if it is not a
+    /// valid mapped tree.
+    ///
+    /// The file must not be modified while it is mapped; every process serving it should
+    /// only read it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only, and the file is not modified while it is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+
+        let (mut decoder, algorithm) =
+            Decoder::with_header(&map, Artifact::MappedTree).map_err(invalid_data)?;
+        let TreeScheme {
+            concatenation,
+            padding,
+            ordering,
+            truncation,
+            leaf_encoding,
+        } = scheme(&mut decoder, algorithm).map_err(invalid_data)?;
+        let blinding_key = match decoder.u64().map_err(invalid_data)? {
+            0 => None,
+            1 => Some(
+                <[u8; 32]>::try_from(decoder.bytes().map_err(invalid_data)?)
+                    .map_err(|_| invalid_data(MerkleError::InvalidEncoding))?,
+            ),
+            _ => return Err(invalid_data(MerkleError::InvalidEncoding)),
+        };
+        let num_of_leaves = decoder.usize().map_err(invalid_data)?;
+        let num_of_levels = decoder.usize().map_err(invalid_data)?;
+        let node_size = decoder.usize().map_err(invalid_data)?;
+        let removed = (0..decoder.usize().map_err(invalid_data)?)
+            .map(|_| decoder.usize())
+            .collect::<Result<BTreeSet<usize>, MerkleError>>()
+            .map_err(invalid_data)?;
+        if removed.range(num_of_leaves..).next().is_some() {
+            return Err(invalid_data(MerkleError::InvalidEncoding));
+        }
+
+        let mut builder = MerkleTree::builder()
+            .algorithm(algorithm)
+            .concatenation(concatenation)
+            .padding(padding)
+            .leaf_encoding(leaf_encoding);
+        if let Some(num_of_bytes) = truncation {
+            builder = builder.truncation(num_of_bytes);
+        }
+        if let Some(key) = blinding_key {
+            builder = builder.blinding(key);
+        }
+        let mut tree = builder.build(&[]);
+        tree.ordering = ordering;
+        if node_size != truncation.unwrap_or(algorithm.digest_size()) {
+            return Err(invalid_data(MerkleError::InvalidEncoding));
+        }
+
+        let lengths = level_lengths(num_of_leaves);
+        if lengths.len() != num_of_levels {
+            return Err(invalid_data(MerkleError::InvalidEncoding));
+        }
+        let mut offset = map.len() - decoder.rest().len();
+        let mut level_offsets = Vec::with_capacity(num_of_levels);
+        for len in lengths {
+            level_offsets.push(offset);
+            offset = len
+                .checked_mul(node_size)
+                .and_then(|size| size.checked_add(offset))
+                .ok_or_else(|| invalid_data(MerkleError::InvalidEncoding))?;
+        }
+        let leaf_table_offset = offset;
+        let leaf_contents_offset = num_of_leaves
+            .checked_add(1)
+            .and_then(|len| len.checked_mul(8))
+            .and_then(|size| size.checked_add(offset))
+            .filter(|offset| *offset <= map.len())
+            .ok_or_else(|| invalid_data(MerkleError::InvalidEncoding))?;
+
+        let mapped = MappedTree {
+            map,
+            scheme: tree,
+            num_of_leaves,
+            node_size,
+            removed,
+            level_offsets,
+            leaf_table_offset,
+            leaf_contents_offset,
+        };
+        if mapped.leaf_offset(num_of_leaves) != Some(mapped.map.len() - leaf_contents_offset) {
+            return Err(invalid_data(MerkleError::InvalidEncoding));
+        }
+        Ok(mapped)
+    }
+
+    /// Write the tree without its removed leaves to a file at `path` in the mapped layout and
+    /// map it, returning it with the new index of every leaf, or None for a removed leaf.
+    /// Remaining leaves keep their order, so the compacted tree has a different root and
+    /// proofs of this tree do not verify against it.
+    ///
+    /// `path` must not be the path of this tree, as its file must not be modified while it is
+    /// mapped. Fail with `io::ErrorKind::InvalidData` if a leaf content is not valid UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to create, replaced if it exists.
+    pub fn compact<P: AsRef<Path>>(&self, path: P) -> io::Result<(Self, Vec<Option<usize>>)> {
+        let mut tree = self.scheme.clone();
+        let mut remap = Vec::with_capacity(self.num_of_leaves);
+        for leaf_index in 0..self.num_of_leaves {
+            if self.removed.contains(&leaf_index) {
+                remap.push(None);
+                continue;
+            }
+            let leaf = self
+                .leaf(leaf_index)
+                .ok_or_else(|| invalid_data(MerkleError::InvalidEncoding))?;
+            remap.push(Some(tree.num_of_leaves()));
+            tree.append(leaf.to_owned());
+        }
+        Ok((Self::create(path, &tree)?, remap))
+    }
+
+    /// Number of leaves.
+    pub fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+
+    /// Configuration of the stored tree.
+    pub fn scheme(&self) -> TreeScheme {
+        self.scheme.scheme()
+    }
+
+    /// Return the root as hexdigest, or None if the tree has no leaves.
+    pub fn root(&self) -> Option<String> {
+        self.node(self.level_offsets.len().checked_sub(1)?, 0)
+    }
+
+    /// Return the value of a node as hexdigest, or None if the tree has no such node.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, 0 for leaves.
+    /// * `index` - Index of the node within its level.
+    pub fn node(&self, level: usize, index: usize) -> Option<String> {
+        let next = self
+            .level_offsets
+            .get(level + 1)
+            .copied()
+            .unwrap_or(self.leaf_table_offset);
+        let start = index
+            .checked_mul(self.node_size)?
+            .checked_add(*self.level_offsets.get(level)?)?;
+        (start < next).then(|| hex::encode(&self.map[start..start + self.node_size]))
+    }
+
+    /// Return the content of a leaf, or None if `leaf_index` is out of range or the content
+    /// is not valid UTF-8. Removed leaves have empty content.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf.
+    pub fn leaf(&self, leaf_index: usize) -> Option<&str> {
+        if leaf_index >= self.num_of_leaves {
+            return None;
+        }
+        let start = self.leaf_offset(leaf_index)?;
+        let end = self.leaf_offset(leaf_index + 1)?;
+        let contents = &self.map[self.leaf_contents_offset..];
+        std::str::from_utf8(contents.get(start..end)?).ok()
+    }
+
+    /// Return the offset of a leaf content from the first leaf content.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position in the table of leaf content offsets.
+    fn leaf_offset(&self, position: usize) -> Option<usize> {
+        let start = self.leaf_table_offset + 8 * position;
+        let offset = u64::from_le_bytes(self.map[start..start + 8].try_into().unwrap());
+        usize::try_from(offset).ok()
+    }
+
+    /// Generate a merkle proof from the mapped nodes, identical to the proof the stored tree
+    /// generates. Return None if `leaf_index` is out of range or the leaf was removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&self, leaf_index: usize) -> Option<MerkleProof> {
+        if self.removed.contains(&leaf_index) {
+            return None;
+        }
+        let leaf_content = self.leaf(leaf_index)?.to_owned();
+
+        let mut hashes = Vec::new();
+        let mut index = leaf_index;
+        for level in 0..self.level_offsets.len() - 1 {
+            let sibling_index = index ^ 1;
+            let sibling = match self.node(level, sibling_index) {
+                Some(sibling) => Some(sibling),
+                None => self
+                    .scheme
+                    .padding_sibling(&self.node(level, index).unwrap()),
+            };
+            if let Some(sibling) = sibling {
+                let mut hash = Hash::new(sibling);
+                hash.is_left = sibling_index < index;
+                hashes.push(Rc::new(RefCell::new(hash)));
+            } // Handle edge case for promoted siblingless rightmost node on the level.
+            index /= 2;
+        }
+
+        Some(MerkleProof {
+            hashes,
+            num_of_leaves: self.num_of_leaves,
+            leaf_index,
+            blinder: self.scheme.blinder(&leaf_content),
+            leaf_content,
+            concatenation: self.scheme.concatenation,
+            truncation: self.scheme.truncation,
+            algorithm: self.scheme.algorithm,
+            leaf_encoding: self.scheme.leaf_encoding,
+        })
+    }
+}
@@ -0,0 +1,465 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Hex-nibble Merkle Patricia Trie with Ethereum-style node encoding.
+//!
+//! Nodes are RLP-encoded and hashed with keccak256. A node whose encoding is shorter than
+//! 32 bytes is embedded in its parent instead of being referenced by hash, so roots are
+//! identical to those of Ethereum's state and storage tries for the same keys and values.
+use crate::MerkleError;
+use sha3::{Digest, Keccak256};
+
+/// Root hash of a trie without any keys, keccak256 of the RLP encoding of an empty string.
+pub const EMPTY_ROOT: [u8; 32] = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+pub(crate) fn keccak256(value: &[u8]) -> [u8; 32] {
+    Keccak256::digest(value).into()
+}
+
+/// Decoded RLP item.
+pub(crate) enum Rlp {
+    Bytes(Vec<u8>),
+    List(Vec<Rlp>),
+}
+
+/// RLP-encode a length header for a payload of `len` bytes.
+///
+/// # Arguments
+///
+/// * `len` - Length of payload.
+/// * `offset` - 0x80 for strings, 0xc0 for lists.
+fn rlp_header(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes: Vec<u8> = len
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    let mut header = vec![offset + 55 + len_bytes.len() as u8];
+    header.extend(len_bytes);
+    header
+}
+
+/// RLP-encode a byte string.
+pub(crate) fn rlp_bytes(value: &[u8]) -> Vec<u8> {
+    if value.len() == 1 && value[0] < 0x80 {
+        return value.to_vec();
+    }
+    let mut encoded = rlp_header(value.len(), 0x80);
+    encoded.extend_from_slice(value);
+    encoded
+}
+
+/// RLP-encode a list of already encoded items.
+pub(crate) fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut encoded = rlp_header(payload.len(), 0xc0);
+    encoded.extend(payload);
+    encoded
+}
+
+/// Decode a single RLP item from the front of `value`, returning it with the remaining bytes.
+fn rlp_decode_item(value: &[u8]) -> Result<(Rlp, &[u8]), MerkleError> {
+    let (&prefix, rest) = value.split_first().ok_or(MerkleError::InvalidEncoding)?;
+    let (is_list, len, rest) = match prefix {
+        0x00..=0x7f => return Ok((Rlp::Bytes(vec![prefix]), rest)),
+        0x80..=0xb7 => (false, (prefix - 0x80) as usize, rest),
+        0xc0..=0xf7 => (true, (prefix - 0xc0) as usize, rest),
+        _ => {
+            let (is_list, len_of_len) = if prefix < 0xc0 {
+                (false, (prefix - 0xb7) as usize)
+            } else {
+                (true, (prefix - 0xf7) as usize)
+            };
+            if len_of_len > std::mem::size_of::<usize>() || rest.len() < len_of_len {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            let len = rest[..len_of_len]
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+            (is_list, len, &rest[len_of_len..])
+        }
+    };
+    if rest.len() < len {
+        return Err(MerkleError::InvalidEncoding);
+    }
+    let (payload, rest) = rest.split_at(len);
+    if !is_list {
+        return Ok((Rlp::Bytes(payload.to_vec()), rest));
+    }
+    let mut items = Vec::new();
+    let mut payload = payload;
+    while !payload.is_empty() {
+        let (item, remaining) = rlp_decode_item(payload)?;
+        items.push(item);
+        payload = remaining;
+    }
+    Ok((Rlp::List(items), rest))
+}
+
+/// Decode exactly one RLP item.
+pub(crate) fn rlp_decode(value: &[u8]) -> Result<Rlp, MerkleError> {
+    match rlp_decode_item(value)? {
+        (item, []) => Ok(item),
+        _ => Err(MerkleError::InvalidEncoding),
+    }
+}
+
+/// Split bytes into nibbles, most significant nibble first.
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Hex-prefix encode a nibble path, flagging whether it terminates at a leaf.
+fn hex_prefix_encode(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 != 0 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+/// Decode a hex-prefix encoded path into its nibbles and whether it terminates at a leaf.
+fn hex_prefix_decode(encoded: &[u8]) -> Result<(Vec<u8>, bool), MerkleError> {
+    let first = *encoded.first().ok_or(MerkleError::InvalidEncoding)?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(MerkleError::InvalidEncoding);
+    }
+    let mut nibbles = Vec::new();
+    if flag % 2 != 0 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(&encoded[1..]));
+    Ok((nibbles, flag >= 2))
+}
+
+/// Length of the common prefix of two nibble paths.
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Node of a Merkle Patricia Trie.
+#[derive(Clone, Default)]
+enum Node {
+    #[default]
+    Empty,
+    Leaf {
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        path: Vec<u8>,
+        child: Box<Node>,
+    },
+    Branch {
+        children: Box<[Node; 16]>,
+        value: Option<Vec<u8>>,
+    },
+}
+
+impl Node {
+    /// RLP encoding of this node.
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            Node::Empty => rlp_bytes(&[]),
+            Node::Leaf { path, value } => {
+                rlp_list(&[rlp_bytes(&hex_prefix_encode(path, true)), rlp_bytes(value)])
+            }
+            Node::Extension { path, child } => rlp_list(&[
+                rlp_bytes(&hex_prefix_encode(path, false)),
+                child.reference(),
+            ]),
+            Node::Branch { children, value } => {
+                let mut items: Vec<Vec<u8>> = children.iter().map(Node::reference).collect();
+                items.push(rlp_bytes(value.as_deref().unwrap_or_default()));
+                rlp_list(&items)
+            }
+        }
+    }
+
+    /// How a parent refers to this node: embedded if its encoding is shorter than 32 bytes,
+    /// otherwise by the keccak256 hash of its encoding.
+    fn reference(&self) -> Vec<u8> {
+        let encoded = self.encode();
+        if encoded.len() < 32 {
+            encoded
+        } else {
+            rlp_bytes(&keccak256(&encoded))
+        }
+    }
+
+    /// Return a copy of this node with `value` stored under the nibble path `path`.
+    fn insert(self, path: &[u8], value: Vec<u8>) -> Node {
+        match self {
+            Node::Empty => Node::Leaf {
+                path: path.to_vec(),
+                value,
+            },
+            Node::Leaf {
+                path: leaf_path,
+                value: leaf_value,
+            } => {
+                if leaf_path == path {
+                    return Node::Leaf {
+                        path: leaf_path,
+                        value,
+                    };
+                }
+                let prefix_len = common_prefix_len(&leaf_path, path);
+                let branch = Node::Branch {
+                    children: Default::default(),
+                    value: None,
+                }
+                .insert(&leaf_path[prefix_len..], leaf_value)
+                .insert(&path[prefix_len..], value);
+                Self::with_prefix(&path[..prefix_len], branch)
+            }
+            Node::Extension {
+                path: extension_path,
+                child,
+            } => {
+                let prefix_len = common_prefix_len(&extension_path, path);
+                if prefix_len == extension_path.len() {
+                    return Node::Extension {
+                        child: Box::new(child.insert(&path[prefix_len..], value)),
+                        path: extension_path,
+                    };
+                }
+                let mut children: Box<[Node; 16]> = Default::default();
+                children[extension_path[prefix_len] as usize] =
+                    Self::with_prefix(&extension_path[prefix_len + 1..], *child);
+                let branch = Node::Branch {
+                    children,
+                    value: None,
+                }
+                .insert(&path[prefix_len..], value);
+                Self::with_prefix(&path[..prefix_len], branch)
+            }
+            Node::Branch {
+                mut children,
+                value: branch_value,
+            } => match path.split_first() {
+                None => Node::Branch {
+                    children,
+                    value: Some(value),
+                },
+                Some((nibble, rest)) => {
+                    let child = std::mem::take(&mut children[*nibble as usize]);
+                    children[*nibble as usize] = child.insert(rest, value);
+                    Node::Branch {
+                        children,
+                        value: branch_value,
+                    }
+                }
+            },
+        }
+    }
+
+    /// Wrap `node` in an extension node for `prefix`, unless `prefix` is empty.
+    fn with_prefix(prefix: &[u8], node: Node) -> Node {
+        if prefix.is_empty() {
+            node
+        } else {
+            Node::Extension {
+                path: prefix.to_vec(),
+                child: Box::new(node),
+            }
+        }
+    }
+}
+
+/// Merkle Patricia Trie mapping byte string keys to byte string values.
+#[derive(Clone, Default)]
+pub struct PatriciaTrie {
+    root: Node,
+}
+
+impl PatriciaTrie {
+    /// Initialize an empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Root hash of the trie.
+    pub fn root_hash(&self) -> [u8; 32] {
+        keccak256(&self.root.encode())
+    }
+
+    /// Return the value stored under `key`, or None if it is absent.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to look up.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        let nibbles = to_nibbles(key);
+        let mut path = nibbles.as_slice();
+        let mut node = &self.root;
+        loop {
+            match node {
+                Node::Empty => return None,
+                Node::Leaf {
+                    path: leaf_path,
+                    value,
+                } => return (leaf_path == path).then_some(value.as_slice()),
+                Node::Extension {
+                    path: extension_path,
+                    child,
+                } => {
+                    path = path.strip_prefix(extension_path.as_slice())?;
+                    node = child;
+                }
+                Node::Branch { children, value } => match path.split_first() {
+                    None => return value.as_deref(),
+                    Some((nibble, rest)) => {
+                        path = rest;
+                        node = &children[*nibble as usize];
+                    }
+                },
+            }
+        }
+    }
+
+    /// Store `value` under `key`, replacing any previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is empty, since an empty value denotes an absent key in Ethereum tries.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to store under.
+    /// * `value` - Value to store.
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        assert!(!value.is_empty(), "value must not be empty");
+        let root = std::mem::take(&mut self.root);
+        self.root = root.insert(&to_nibbles(key), value);
+    }
+
+    /// Generate a proof for `key`, as the RLP-encoded nodes on the path from the root node,
+    /// like the `accountProof`/`storageProof` arrays of `eth_getProof`. Nodes embedded in
+    /// their parent are not listed separately. The proof shows either the value stored under
+    /// `key` or that `key` is absent.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to prove.
+    pub fn proof(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        let nibbles = to_nibbles(key);
+        let mut path = nibbles.as_slice();
+        let mut node = &self.root;
+        let mut proof = vec![node.encode()];
+        loop {
+            let next = match node {
+                Node::Empty | Node::Leaf { .. } => None,
+                Node::Extension {
+                    path: extension_path,
+                    child,
+                } => path
+                    .strip_prefix(extension_path.as_slice())
+                    .map(|rest| (rest, child.as_ref())),
+                Node::Branch { children, .. } => path
+                    .split_first()
+                    .map(|(nibble, rest)| (rest, &children[*nibble as usize])),
+            };
+            let Some((rest, child)) = next else {
+                return proof;
+            };
+            let encoded = child.encode();
+            if encoded.len() >= 32 {
+                proof.push(encoded);
+            }
+            path = rest;
+            node = child;
+        }
+    }
+}
+
+/// Verify a proof generated by `PatriciaTrie::proof` against a root hash. Return the value
+/// stored under `key`, or None if the proof shows that `key` is absent.
+///
+/// # Arguments
+///
+/// * `root_hash` - Root hash of the trie.
+/// * `key` - Key to verify.
+/// * `proof` - RLP-encoded nodes on the path from the root node.
+pub fn verify_proof(
+    root_hash: &[u8; 32],
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, MerkleError> {
+    let nibbles = to_nibbles(key);
+    let mut path = nibbles.as_slice();
+    let mut expected_hash = *root_hash;
+    let mut proof = proof.iter();
+
+    let mut node = {
+        let encoded = proof.next().ok_or(MerkleError::IncompleteProof)?;
+        if keccak256(encoded) != expected_hash {
+            return Err(MerkleError::ProofHashMismatch);
+        }
+        rlp_decode(encoded)?
+    };
+    loop {
+        // Resolve the reference to the next node on the path, if any.
+        let reference = match node {
+            Rlp::Bytes(empty) if empty.is_empty() => return Ok(None),
+            Rlp::Bytes(_) => return Err(MerkleError::InvalidEncoding),
+            Rlp::List(mut items) if items.len() == 2 => {
+                let (Rlp::Bytes(encoded_path), second) = (items.remove(0), items.remove(0)) else {
+                    return Err(MerkleError::InvalidEncoding);
+                };
+                let (node_path, is_leaf) = hex_prefix_decode(&encoded_path)?;
+                if is_leaf {
+                    let Rlp::Bytes(value) = second else {
+                        return Err(MerkleError::InvalidEncoding);
+                    };
+                    return Ok((node_path == path).then_some(value));
+                }
+                match path.strip_prefix(node_path.as_slice()) {
+                    Some(rest) => path = rest,
+                    None => return Ok(None),
+                }
+                second
+            }
+            Rlp::List(mut items) if items.len() == 17 => match path.split_first() {
+                None => {
+                    let Some(Rlp::Bytes(value)) = items.pop() else {
+                        return Err(MerkleError::InvalidEncoding);
+                    };
+                    return Ok((!value.is_empty()).then_some(value));
+                }
+                Some((nibble, rest)) => {
+                    path = rest;
+                    items.swap_remove(*nibble as usize)
+                }
+            },
+            Rlp::List(_) => return Err(MerkleError::InvalidEncoding),
+        };
+
+        node = match reference {
+            Rlp::Bytes(hash) if hash.len() == 32 => {
+                expected_hash = hash.try_into().unwrap();
+                let encoded = proof.next().ok_or(MerkleError::IncompleteProof)?;
+                if keccak256(encoded) != expected_hash {
+                    return Err(MerkleError::ProofHashMismatch);
+                }
+                rlp_decode(encoded)?
+            }
+            Rlp::Bytes(empty) if empty.is_empty() => return Ok(None),
+            Rlp::Bytes(_) => return Err(MerkleError::InvalidEncoding),
+            embedded => embedded,
+        };
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Namespaced merkle tree, as used by Celestia for data availability.
+//!
+//! Leaves are sorted by namespace, and every node commits to the smallest and largest
+//! namespace below it. The nodes on either side of the leaves of a namespace then show that
+//! no other leaf has it, so a proof returns all data of a namespace, or shows that there is
+//! none, and a light node can fetch only its own namespace from a block.
+use crate::Hash;
+
+/// Node of a namespaced merkle tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceNode {
+    /// Hash value as hexdigest, committing to `min` and `max`.
+    pub value: String,
+
+    /// Smallest namespace below this node.
+    pub min: u64,
+
+    /// Largest namespace below this node.
+    pub max: u64,
+}
+
+impl NamespaceNode {
+    /// Leaf node for data of a namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the data.
+    /// * `data` - Data.
+    fn leaf(namespace: u64, data: &str) -> Self {
+        NamespaceNode {
+            value: Hash::hash(&format!("leaf:{:016x}:{}", namespace, data)),
+            min: namespace,
+            max: namespace,
+        }
+    }
+
+    /// Parent node of two children. Return None if the children are not in namespace order.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child node.
+    /// * `right` - Right child node.
+    fn parent(left: &NamespaceNode, right: &NamespaceNode) -> Option<Self> {
+        if left.max > right.min {
+            return None;
+        }
+        Some(NamespaceNode {
+            value: Hash::hash(&format!(
+                "node:{}{}{:016x}{:016x}",
+                left.value, right.value, left.min, right.max
+            )),
+            min: left.min,
+            max: right.max,
+        })
+    }
+}
+
+/// Proof of all data of a namespace, or of its absence, in a namespaced merkle tree.
+#[derive(Debug, Clone)]
+pub struct NamespaceProof {
+    /// Number of leaves in the namespaced merkle tree.
+    pub num_of_leaves: usize,
+
+    /// Index of the first leaf of the namespace, or of the first leaf of a larger namespace
+    /// for absence proofs.
+    pub start: usize,
+
+    /// Index one past the last leaf covered by the proof. Equal to `start` for absence proofs
+    /// of namespaces outside the interval of the root node.
+    pub end: usize,
+
+    /// For absence proofs within the interval of the root node, the leaf node at `start`.
+    pub absence_leaf: Option<NamespaceNode>,
+
+    /// Audit nodes, arranged from the bottom-most level up to the top-most level (closest to
+    /// root node), with the node left of the covered leaves before the node right of them.
+    pub nodes: Vec<NamespaceNode>,
+}
+
+/// Namespaced merkle tree over data sorted by namespace.
+pub struct NamespacedTree {
+    /// Namespace and data of every leaf, in leaf order.
+    leaves: Vec<(u64, String)>,
+
+    /// Nodes from the bottom level (leaves) up to the top level (root node).
+    levels: Vec<Vec<NamespaceNode>>,
+}
+
+impl NamespacedTree {
+    /// Build a namespaced merkle tree. Return None if the leaves are not sorted by namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Namespace and data of every leaf, in leaf order.
+    pub fn new(leaves: &[(u64, String)]) -> Option<Self> {
+        let mut level: Vec<NamespaceNode> = leaves
+            .iter()
+            .map(|(namespace, data)| NamespaceNode::leaf(*namespace, data))
+            .collect();
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => NamespaceNode::parent(left, right),
+                    [last] => Some(last.to_owned()), // Last node has no sibling.
+                    _ => unreachable!(),
+                })
+                .collect::<Option<Vec<NamespaceNode>>>()?;
+            levels.push(level);
+            level = parents;
+        }
+        levels.push(level);
+        Some(NamespacedTree {
+            leaves: leaves.to_vec(),
+            levels,
+        })
+    }
+
+    /// Root node, or None if there are no leaves.
+    pub fn root(&self) -> Option<&NamespaceNode> {
+        self.levels.last()?.first()
+    }
+
+    /// Data of every leaf of a namespace, in leaf order.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the data.
+    pub fn data(&self, namespace: u64) -> Vec<String> {
+        let (start, end) = self.range(namespace);
+        self.leaves[start..end]
+            .iter()
+            .map(|(_, data)| data.to_owned())
+            .collect()
+    }
+
+    /// Return the range of indices of the leaves of a namespace, empty at the index of the
+    /// first leaf of a larger namespace if there are none.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the leaves.
+    fn range(&self, namespace: u64) -> (usize, usize) {
+        let start = self.leaves.partition_point(|(other, _)| *other < namespace);
+        let end = self
+            .leaves
+            .partition_point(|(other, _)| *other <= namespace);
+        (start, end)
+    }
+
+    /// Generate a proof of all data of a namespace, or of its absence. Return None if there
+    /// are no leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace to prove.
+    pub fn prove_namespace(&self, namespace: u64) -> Option<NamespaceProof> {
+        let root = self.root()?;
+        let num_of_leaves = self.leaves.len();
+        if namespace < root.min || namespace > root.max {
+            return Some(NamespaceProof {
+                num_of_leaves,
+                start: 0,
+                end: 0,
+                absence_leaf: None,
+                nodes: Vec::new(),
+            });
+        }
+
+        // The namespace lies within the root interval, so if it is absent, a leaf of a larger
+        // namespace follows where it would be.
+        let (start, mut end) = self.range(namespace);
+        let mut absence_leaf = None;
+        if start == end {
+            absence_leaf = Some(self.levels[0][start].to_owned());
+            end += 1;
+        }
+        let mut nodes = Vec::new();
+        let (mut first, mut last) = (start, end);
+        for level in &self.levels[..self.levels.len() - 1] {
+            if first % 2 == 1 {
+                nodes.push(level[first - 1].to_owned());
+            }
+            if last % 2 == 1 && last < level.len() {
+                nodes.push(level[last].to_owned());
+            }
+            first /= 2;
+            last = last.div_ceil(2);
+        }
+        Some(NamespaceProof {
+            num_of_leaves,
+            start,
+            end,
+            absence_leaf,
+            nodes,
+        })
+    }
+
+    /// Verify that `data` is all data of a namespace, in leaf order. An empty `data` verifies
+    /// only with a proof that the namespace is absent.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the namespaced merkle tree.
+    /// * `namespace` - Namespace of the data.
+    /// * `data` - Data of the namespace.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_namespace(
+        root: &NamespaceNode,
+        namespace: u64,
+        data: &[String],
+        proof: &NamespaceProof,
+    ) -> bool {
+        let mut level: Vec<NamespaceNode> = match (&proof.absence_leaf, data.is_empty()) {
+            (None, false) => data
+                .iter()
+                .map(|data| NamespaceNode::leaf(namespace, data))
+                .collect(),
+            (Some(leaf), true) if leaf.min > namespace => vec![leaf.to_owned()],
+            (None, true) => {
+                return proof.start == proof.end
+                    && proof.nodes.is_empty()
+                    && (namespace < root.min || namespace > root.max);
+            }
+            _ => return false,
+        };
+        if proof.end > proof.num_of_leaves
+            || proof.end.checked_sub(proof.start) != Some(level.len())
+        {
+            return false;
+        }
+
+        // Nodes left of the covered leaves must all have smaller namespaces, and nodes right
+        // of them larger ones, so no leaf of the namespace is left out.
+        let mut nodes = proof.nodes.iter();
+        let (mut first, mut last) = (proof.start, proof.end);
+        let mut len = proof.num_of_leaves;
+        while len > 1 {
+            if first % 2 == 1 {
+                match nodes.next() {
+                    Some(node) if node.max < namespace => level.insert(0, node.to_owned()),
+                    _ => return false,
+                }
+            }
+            if last % 2 == 1 && last < len {
+                match nodes.next() {
+                    Some(node) if node.min > namespace => level.push(node.to_owned()),
+                    _ => return false,
+                }
+            }
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => NamespaceNode::parent(left, right),
+                    [last] => Some(last.to_owned()), // Last node has no sibling.
+                    _ => unreachable!(),
+                })
+                .collect::<Option<Vec<NamespaceNode>>>();
+            let Some(parents) = parents else {
+                return false;
+            };
+            level = parents;
+            first /= 2;
+            last = last.div_ceil(2);
+            len = len.div_ceil(2);
+        }
+        nodes.next().is_none() && level.len() == 1 && level[0] == *root
+    }
+}
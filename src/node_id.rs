@@ -0,0 +1,120 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::MerkleTree;
+use std::ops::Range;
+
+/// Position of a node in a merkle tree: its level, 0 for leaves, and its index within the
+/// level. The node at index `i` of level `l` is the root of the subtree over leaves `i << l`
+/// up to `(i + 1) << l`, exclusive, as in `MerkleTree::subtree_proof`.
+///
+/// Positions also convert to and from a single `u64` in in-order numbering, as Certificate
+/// Transparency logs number nodes: leaf `i` is `2 * i`, and every parent lies between its
+/// children, so the levels of a growing tree never renumber.
+///
+/// ```
+/// use merkle_tree::NodeId;
+///
+/// let node = NodeId::new(1, 2);
+/// assert_eq!(node.to_u64(), Some(9));
+/// assert_eq!(NodeId::from_u64(9), node);
+/// assert_eq!(node.leaves(), 4..6);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId {
+    /// Level of the node, 0 for leaves.
+    pub level: usize,
+
+    /// Index of the node within its level.
+    pub index: usize,
+}
+
+impl NodeId {
+    /// Initialize the position of a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, 0 for leaves.
+    /// * `index` - Index of the node within its level.
+    pub fn new(level: usize, index: usize) -> Self {
+        NodeId { level, index }
+    }
+
+    /// Position of a leaf.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node.
+    pub fn leaf(leaf_index: usize) -> Self {
+        NodeId::new(0, leaf_index)
+    }
+
+    /// Return the in-order number of the node, `(index << (level + 1)) + (1 << level) - 1`,
+    /// or None if it does not fit in a `u64`.
+    pub fn to_u64(&self) -> Option<u64> {
+        let index = u64::try_from(self.index).ok()?;
+        if self.level >= 64 || index.leading_zeros() < self.level as u32 + 1 {
+            return None;
+        }
+        let low = (1u64 << self.level) - 1;
+        Some(index.checked_shl(self.level as u32 + 1).unwrap_or(0) | low)
+    }
+
+    /// Return the node with an in-order number, whose level is the number of trailing one
+    /// bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - In-order number of the node.
+    pub fn from_u64(id: u64) -> Self {
+        let level = id.trailing_ones() as usize;
+        let index = id.checked_shr(level as u32 + 1).unwrap_or(0);
+        NodeId::new(level, index as usize)
+    }
+
+    /// Parent of the node.
+    pub fn parent(&self) -> Self {
+        NodeId::new(self.level + 1, self.index / 2)
+    }
+
+    /// Other child of the parent of the node.
+    pub fn sibling(&self) -> Self {
+        NodeId::new(self.level, self.index ^ 1)
+    }
+
+    /// Left and right children of the node, or None for a leaf.
+    pub fn children(&self) -> Option<(Self, Self)> {
+        let level = self.level.checked_sub(1)?;
+        Some((
+            NodeId::new(level, 2 * self.index),
+            NodeId::new(level, 2 * self.index + 1),
+        ))
+    }
+
+    /// Indices of the leaves below the node in a tree with enough leaves, exclusive of the
+    /// end. Leaves past the last leaf of a tree are not in it.
+    pub fn leaves(&self) -> Range<usize> {
+        self.index << self.level..(self.index + 1) << self.level
+    }
+}
+
+impl From<(usize, usize)> for NodeId {
+    fn from((level, index): (usize, usize)) -> Self {
+        NodeId::new(level, index)
+    }
+}
+
+impl From<NodeId> for (usize, usize) {
+    fn from(node: NodeId) -> Self {
+        (node.level, node.index)
+    }
+}
+
+impl MerkleTree {
+    /// Return the value of a node as hexdigest, or None if the tree has no such node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - Position of the node.
+    pub fn node(&self, node: NodeId) -> Option<String> {
+        Some(self.levels.get(node.level)?.get(node.index)?.to_owned())
+    }
+}
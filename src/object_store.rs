@@ -0,0 +1,164 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Merkle commitments to objects of an `ObjectStore`, split into fixed-size parts.
+//!
+//! An object is committed to by a merkle tree whose leaves are the hexdigests of its parts,
+//! in part order, where every part but the last has `part_size` bytes. The parts match those
+//! of a multipart upload with the same part size, so the commitment can be computed while
+//! uploading. A ranged GET of one part is then verified with the root and the proof of that
+//! part alone, without downloading the rest of the object.
+use crate::{Hash, MerkleProof, MerkleTree};
+use ::object_store::path::Path;
+use ::object_store::{ObjectStore, PutPayload};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+/// Merkle tree over the parts of an object.
+pub struct PartCommitment {
+    /// Number of bytes of every part but the last.
+    pub part_size: usize,
+
+    /// Number of bytes of the object.
+    pub size: usize,
+
+    /// Tree whose leaves are the hexdigests of the parts.
+    tree: MerkleTree,
+}
+
+impl PartCommitment {
+    /// Commit to the digests of the parts of an object.
+    ///
+    /// # Arguments
+    ///
+    /// * `part_size` - Number of bytes of every part but the last.
+    /// * `size` - Number of bytes of the object.
+    /// * `digests` - Hexdigests of the parts, in part order.
+    fn new(part_size: usize, size: usize, digests: &[String]) -> Self {
+        PartCommitment {
+            part_size,
+            size,
+            tree: MerkleTree::from_leaves(digests),
+        }
+    }
+
+    /// Number of parts of the object.
+    pub fn num_of_parts(&self) -> usize {
+        self.tree.num_of_leaves()
+    }
+
+    /// Return the root hash as hexdigest, or None if the object is empty.
+    pub fn root(&self) -> Option<String> {
+        self.tree.root().map(|root| root.borrow().value.to_owned())
+    }
+
+    /// Return the proof of a part, whose leaf content is the hexdigest of the part,
+    /// or None if there is no such part.
+    ///
+    /// # Arguments
+    ///
+    /// * `part` - Index of the part.
+    pub fn proof(&self, part: usize) -> Option<MerkleProof> {
+        self.tree.proof(part)
+    }
+}
+
+/// Upload an object in parts of `part_size` bytes with a multipart upload, and commit to
+/// its parts as they are uploaded.
+///
+/// # Arguments
+///
+/// * `store` - Object store.
+/// * `location` - Location of the object.
+/// * `data` - Content of the object.
+/// * `part_size` - Number of bytes of every part but the last.
+///
+/// # Panics
+///
+/// Panics if `part_size` is 0.
+pub async fn put_multipart(
+    store: &dyn ObjectStore,
+    location: &Path,
+    data: &[u8],
+    part_size: usize,
+) -> io::Result<PartCommitment> {
+    assert!(part_size > 0, "part size must be at least 1 byte");
+    let mut upload = store.put_multipart(location).await?;
+    let mut digests = Vec::new();
+    for part in data.chunks(part_size) {
+        digests.push(Hash::hash_reader(part)?);
+        upload.put_part(PutPayload::from(part.to_vec())).await?;
+    }
+    upload.complete().await?;
+    Ok(PartCommitment::new(part_size, data.len(), &digests))
+}
+
+/// Commit to the parts of an object already in a store, reading it with one ranged GET
+/// per part.
+///
+/// # Arguments
+///
+/// * `store` - Object store.
+/// * `location` - Location of the object.
+/// * `part_size` - Number of bytes of every part but the last.
+///
+/// # Panics
+///
+/// Panics if `part_size` is 0.
+pub async fn commit(
+    store: &dyn ObjectStore,
+    location: &Path,
+    part_size: usize,
+) -> io::Result<PartCommitment> {
+    assert!(part_size > 0, "part size must be at least 1 byte");
+    let size = store.head(location).await?.size;
+    let mut digests = Vec::new();
+    for start in (0..size).step_by(part_size) {
+        let part = store
+            .get_range(location, start..size.min(start + part_size))
+            .await?;
+        digests.push(Hash::hash_reader(&part[..])?);
+    }
+    Ok(PartCommitment::new(part_size, size, &digests))
+}
+
+/// Fetch the part proven by `proof` with a ranged GET and return its content once it is
+/// verified against `root`. Fail with `io::ErrorKind::InvalidData` if the part does not
+/// match the proof, or the proof does not match the root.
+///
+/// # Arguments
+///
+/// * `store` - Object store.
+/// * `location` - Location of the object.
+/// * `root` - Root hash of the object's commitment, as hexdigest.
+/// * `part_size` - Number of bytes of every part but the last.
+/// * `proof` - Proof of the part, as returned by `PartCommitment::proof`.
+///
+/// # Panics
+///
+/// Panics if `part_size` is 0.
+pub async fn get_verified(
+    store: &dyn ObjectStore,
+    location: &Path,
+    root: &str,
+    part_size: usize,
+    proof: &MerkleProof,
+) -> io::Result<Vec<u8>> {
+    assert!(part_size > 0, "part size must be at least 1 byte");
+    let start = proof.leaf_index * part_size;
+    let end = if proof.leaf_index + 1 < proof.num_of_leaves {
+        start + part_size
+    } else {
+        // Only the last part may be shorter than `part_size`.
+        store.head(location).await?.size.max(start)
+    };
+    let part = store.get_range(location, start..end).await?;
+    if Hash::hash_reader(&part[..])? != proof.leaf_content
+        || !MerkleTree::verify_proof(Rc::new(RefCell::new(Hash::new(root.to_owned()))), proof)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "part does not match its proof",
+        ));
+    }
+    Ok(part.to_vec())
+}
@@ -0,0 +1,542 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Anchoring of merkle roots into Bitcoin with OpenTimestamps.
+//!
+//! A root is wrapped into a detached timestamp, the content of an `.ots` file, whose digest is
+//! the root. The root is submitted to an OpenTimestamps calendar, which returns a timestamp
+//! committing to it with a pending attestation; once the calendar has anchored the commitment
+//! into a block, upgrading the pending commitment returns a Bitcoin attestation. Each
+//! attestation commits to a message derived from the root by the operations on its path, and
+//! a Bitcoin attestation is valid if that message is the merkle root of the attested block.
+//!
+//! Operations that need hash functions other than SHA-256 (SHA-1, RIPEMD-160 and Keccak-256)
+//! are rejected with `MerkleError::UnsupportedScheme`.
+use crate::{hex, provider, MerkleError};
+
+/// Magic bytes that start a detached timestamp.
+const HEADER_MAGIC: &[u8] = b"\x00OpenTimestamps\x00\x00Proof\x00\xbf\x89\xe2\xe8\x84\xe8\x92\x94";
+
+/// Major version of detached timestamps.
+const MAJOR_VERSION: u64 = 1;
+
+/// Tag of a SHA-256 operation, also identifying the hash function of the digest.
+const SHA256_TAG: u8 = 0x08;
+
+/// Tag of an attestation, instead of an operation.
+const ATTESTATION_TAG: u8 = 0x00;
+
+/// Tag preceding every branch of a timestamp but the last.
+const BRANCH_TAG: u8 = 0xff;
+
+/// Attestation tag of a pending attestation.
+const PENDING_TAG: [u8; 8] = [0x83, 0xdf, 0xe3, 0x0d, 0x2e, 0xf9, 0x0c, 0x8e];
+
+/// Attestation tag of a Bitcoin block header attestation.
+const BITCOIN_TAG: [u8; 8] = [0x05, 0x88, 0x96, 0x0d, 0x73, 0xd7, 0x19, 0x01];
+
+/// Largest argument of an operation and largest attestation payload, in bytes.
+const MAX_PAYLOAD: usize = 8192;
+
+/// Deepest nesting of operations accepted when parsing.
+const MAX_DEPTH: usize = 256;
+
+/// Operation transforming the message of a timestamp into the message of its child.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// Append bytes to the message.
+    Append(Vec<u8>),
+
+    /// Prepend bytes to the message.
+    Prepend(Vec<u8>),
+
+    /// Hash the message with SHA-256.
+    Sha256,
+
+    /// Reverse the bytes of the message.
+    Reverse,
+
+    /// Replace the message with its lowercase hex encoding.
+    Hexlify,
+}
+
+impl Op {
+    /// Apply the operation to a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - Message to transform.
+    fn apply(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            Op::Append(suffix) => [msg, suffix].concat(),
+            Op::Prepend(prefix) => [prefix, msg].concat(),
+            Op::Sha256 => provider::sha256(msg).to_vec(),
+            Op::Reverse => msg.iter().rev().copied().collect(),
+            Op::Hexlify => hex::encode(msg).into_bytes(),
+        }
+    }
+
+    /// Append the serialized operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Bytes to append to.
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Op::Append(suffix) => {
+                out.push(0xf0);
+                write_varbytes(out, suffix);
+            }
+            Op::Prepend(prefix) => {
+                out.push(0xf1);
+                write_varbytes(out, prefix);
+            }
+            Op::Sha256 => out.push(SHA256_TAG),
+            Op::Reverse => out.push(0xf3),
+            Op::Hexlify => out.push(0xf2),
+        }
+    }
+
+    /// Read an operation whose tag was already read.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader positioned after the tag.
+    /// * `tag` - Tag of the operation.
+    fn read(reader: &mut Reader, tag: u8) -> Result<Self, MerkleError> {
+        match tag {
+            0xf0 => Ok(Op::Append(reader.varbytes()?.to_vec())),
+            0xf1 => Ok(Op::Prepend(reader.varbytes()?.to_vec())),
+            SHA256_TAG => Ok(Op::Sha256),
+            0xf3 => Ok(Op::Reverse),
+            0xf2 => Ok(Op::Hexlify),
+            // SHA-1, RIPEMD-160 and Keccak-256.
+            0x02 | 0x03 | 0x67 => Err(MerkleError::UnsupportedScheme),
+            _ => Err(MerkleError::InvalidEncoding),
+        }
+    }
+}
+
+/// Claim that the message of a timestamp existed at some time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attestation {
+    /// The calendar at this URI will anchor the message; upgrade it later.
+    Pending(String),
+
+    /// The message is the merkle root of the Bitcoin block at this height.
+    Bitcoin(u64),
+
+    /// Attestation of another kind, kept as its tag and payload.
+    Unknown([u8; 8], Vec<u8>),
+}
+
+impl Attestation {
+    /// Append the serialized attestation.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Bytes to append to.
+    fn write(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        let tag = match self {
+            Attestation::Pending(uri) => {
+                write_varbytes(&mut payload, uri.as_bytes());
+                PENDING_TAG
+            }
+            Attestation::Bitcoin(height) => {
+                write_varuint(&mut payload, *height);
+                BITCOIN_TAG
+            }
+            Attestation::Unknown(tag, bytes) => {
+                payload.extend(bytes);
+                *tag
+            }
+        };
+        out.extend(tag);
+        write_varbytes(out, &payload);
+    }
+
+    /// Read an attestation after its attestation tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader positioned after the attestation tag.
+    fn read(reader: &mut Reader) -> Result<Self, MerkleError> {
+        let tag: [u8; 8] = reader.take(8)?.try_into().unwrap();
+        let payload = reader.varbytes()?;
+        let mut payload_reader = Reader::new(payload);
+        let attestation = match tag {
+            PENDING_TAG => Attestation::Pending(
+                String::from_utf8(payload_reader.varbytes()?.to_vec())
+                    .map_err(|_| MerkleError::InvalidEncoding)?,
+            ),
+            BITCOIN_TAG => Attestation::Bitcoin(payload_reader.varuint()?),
+            _ => return Ok(Attestation::Unknown(tag, payload.to_vec())),
+        };
+        payload_reader.finish()?;
+        Ok(attestation)
+    }
+}
+
+/// Tree of operations and attestations rooted at a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    /// Message this timestamp commits to.
+    pub msg: Vec<u8>,
+
+    /// Attestations of the message.
+    pub attestations: Vec<Attestation>,
+
+    /// Operations on the message, each with the timestamp of its result.
+    pub ops: Vec<(Op, Timestamp)>,
+}
+
+impl Timestamp {
+    /// Timestamp of a message without attestations.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - Message.
+    fn new(msg: Vec<u8>) -> Self {
+        Timestamp {
+            msg,
+            attestations: Vec::new(),
+            ops: Vec::new(),
+        }
+    }
+
+    /// Append the serialized timestamp. Every branch but the last is preceded by `BRANCH_TAG`.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Bytes to append to.
+    fn write(&self, out: &mut Vec<u8>) {
+        let num_of_branches = self.attestations.len() + self.ops.len();
+        for (index, attestation) in self.attestations.iter().enumerate() {
+            if index + 1 < num_of_branches {
+                out.push(BRANCH_TAG);
+            }
+            out.push(ATTESTATION_TAG);
+            attestation.write(out);
+        }
+        for (index, (op, timestamp)) in self.ops.iter().enumerate() {
+            if self.attestations.len() + index + 1 < num_of_branches {
+                out.push(BRANCH_TAG);
+            }
+            op.write(out);
+            timestamp.write(out);
+        }
+    }
+
+    /// Read the timestamp of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader positioned at the first branch.
+    /// * `msg` - Message of the timestamp.
+    /// * `depth` - Number of operations above this timestamp.
+    fn read(reader: &mut Reader, msg: Vec<u8>, depth: usize) -> Result<Self, MerkleError> {
+        if depth > MAX_DEPTH {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let mut timestamp = Timestamp::new(msg);
+        loop {
+            let mut tag = reader.byte()?;
+            let last = tag != BRANCH_TAG;
+            if !last {
+                tag = reader.byte()?;
+            }
+            if tag == ATTESTATION_TAG {
+                timestamp.attestations.push(Attestation::read(reader)?);
+            } else {
+                let op = Op::read(reader, tag)?;
+                let result = op.apply(&timestamp.msg);
+                let child = Timestamp::read(reader, result, depth + 1)?;
+                timestamp.ops.push((op, child));
+            }
+            if last {
+                return Ok(timestamp);
+            }
+        }
+    }
+
+    /// Return every attestation with the message it attests to.
+    pub fn all_attestations(&self) -> Vec<(&[u8], &Attestation)> {
+        let mut attestations: Vec<(&[u8], &Attestation)> = self
+            .attestations
+            .iter()
+            .map(|attestation| (self.msg.as_slice(), attestation))
+            .collect();
+        for (_, timestamp) in &self.ops {
+            attestations.extend(timestamp.all_attestations());
+        }
+        attestations
+    }
+
+    /// Return the timestamp of a message below this one, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - Message to look for.
+    fn find_mut(&mut self, msg: &[u8]) -> Option<&mut Timestamp> {
+        if self.msg == msg {
+            return Some(self);
+        }
+        self.ops
+            .iter_mut()
+            .find_map(|(_, timestamp)| timestamp.find_mut(msg))
+    }
+
+    /// Add the attestations and operations of another timestamp of the same message.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Timestamp of the same message.
+    fn merge(&mut self, other: Timestamp) {
+        for attestation in other.attestations {
+            if !self.attestations.contains(&attestation) {
+                self.attestations.push(attestation);
+            }
+        }
+        for (op, timestamp) in other.ops {
+            match self.ops.iter_mut().find(|(existing, _)| *existing == op) {
+                Some((_, existing)) => existing.merge(timestamp),
+                None => self.ops.push((op, timestamp)),
+            }
+        }
+    }
+}
+
+/// Timestamp of a merkle root, the content of an OpenTimestamps `.ots` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetachedTimestamp {
+    /// Timestamp whose message is the root.
+    pub timestamp: Timestamp,
+}
+
+impl DetachedTimestamp {
+    /// Wrap the root of a SHA-256 merkle tree into a timestamp without attestations.
+    /// Submit the root to calendars and add their responses with `merge`.
+    /// Return None if the root is not a 32-byte hexdigest.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root hash as hexdigest.
+    pub fn new(root: &str) -> Option<Self> {
+        let digest = hex::decode(root)?;
+        (digest.len() == 32).then(|| DetachedTimestamp {
+            timestamp: Timestamp::new(digest),
+        })
+    }
+
+    /// Root the timestamp commits to.
+    pub fn digest(&self) -> &[u8] {
+        &self.timestamp.msg
+    }
+
+    /// Add a calendar response, a serialized timestamp of `commitment`. Submitting the root
+    /// returns a timestamp of the root; upgrading a pending attestation returns a timestamp
+    /// of the commitment it attests to. Fail with `MerkleError::InvalidEncoding` if no message
+    /// of the timestamp is `commitment` or the response is malformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `commitment` - Message the response timestamps.
+    /// * `response` - Body of the calendar response.
+    pub fn merge(&mut self, commitment: &[u8], response: &[u8]) -> Result<(), MerkleError> {
+        let target = self
+            .timestamp
+            .find_mut(commitment)
+            .ok_or(MerkleError::InvalidEncoding)?;
+        let mut reader = Reader::new(response);
+        let timestamp = Timestamp::read(&mut reader, commitment.to_vec(), 0)?;
+        reader.finish()?;
+        target.merge(timestamp);
+        Ok(())
+    }
+
+    /// Return the pending attestations with the commitments to upgrade them with.
+    pub fn pending(&self) -> Vec<(Vec<u8>, String)> {
+        self.timestamp
+            .all_attestations()
+            .into_iter()
+            .filter_map(|(msg, attestation)| match attestation {
+                Attestation::Pending(uri) => Some((msg.to_vec(), uri.to_owned())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Return the heights of the Bitcoin blocks attesting to the root, each with the merkle
+    /// root the block header must have, in header byte order.
+    pub fn bitcoin_attestations(&self) -> Vec<(u64, Vec<u8>)> {
+        self.timestamp
+            .all_attestations()
+            .into_iter()
+            .filter_map(|(msg, attestation)| match attestation {
+                Attestation::Bitcoin(height) => Some((*height, msg.to_vec())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Verify that the root is attested by the Bitcoin block at `height`. The block merkle
+    /// root is in header byte order, the reverse of the hex shown by block explorers.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - Height of the block.
+    /// * `block_merkle_root` - Merkle root of the block header.
+    pub fn verify_bitcoin(&self, height: u64, block_merkle_root: &[u8; 32]) -> bool {
+        self.bitcoin_attestations()
+            .iter()
+            .any(|(attested, msg)| *attested == height && msg == block_merkle_root)
+    }
+
+    /// Serialize the timestamp into the content of an `.ots` file.
+    /// Fail with `MerkleError::IncompleteProof` if a branch ends without an attestation.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MerkleError> {
+        if !complete(&self.timestamp) {
+            return Err(MerkleError::IncompleteProof);
+        }
+        let mut out = HEADER_MAGIC.to_vec();
+        write_varuint(&mut out, MAJOR_VERSION);
+        out.push(SHA256_TAG);
+        out.extend(&self.timestamp.msg);
+        self.timestamp.write(&mut out);
+        Ok(out)
+    }
+
+    /// Deserialize the content of an `.ots` file whose digest is a SHA-256 digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Content of an `.ots` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(HEADER_MAGIC.len())? != HEADER_MAGIC {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let version = reader.varuint()?;
+        if version != MAJOR_VERSION {
+            return Err(MerkleError::UnsupportedVersion(version));
+        }
+        if reader.byte()? != SHA256_TAG {
+            return Err(MerkleError::UnsupportedScheme);
+        }
+        let digest = reader.take(32)?.to_vec();
+        let timestamp = Timestamp::read(&mut reader, digest, 0)?;
+        reader.finish()?;
+        Ok(DetachedTimestamp { timestamp })
+    }
+}
+
+/// Whether every branch of a timestamp ends with an attestation, as serialization requires.
+///
+/// # Arguments
+///
+/// * `timestamp` - Timestamp to check.
+fn complete(timestamp: &Timestamp) -> bool {
+    (!timestamp.attestations.is_empty() || !timestamp.ops.is_empty())
+        && timestamp.ops.iter().all(|(_, child)| complete(child))
+}
+
+/// Append an unsigned integer, 7 bits per byte from the least significant, with the high bit
+/// of every byte but the last set.
+///
+/// # Arguments
+///
+/// * `out` - Bytes to append to.
+/// * `value` - Integer to append.
+fn write_varuint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Append bytes prefixed by their length.
+///
+/// # Arguments
+///
+/// * `out` - Bytes to append to.
+/// * `value` - Bytes to append.
+fn write_varbytes(out: &mut Vec<u8>, value: &[u8]) {
+    write_varuint(out, value.len() as u64);
+    out.extend(value);
+}
+
+/// Reader of serialized timestamps.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Reader at the start of `bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Bytes to read.
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, position: 0 }
+    }
+
+    /// Read `num_of_bytes` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_of_bytes` - Number of bytes to read.
+    fn take(&mut self, num_of_bytes: usize) -> Result<&'a [u8], MerkleError> {
+        let end = self
+            .position
+            .checked_add(num_of_bytes)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(MerkleError::InvalidEncoding)?;
+        let value = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(value)
+    }
+
+    /// Read a byte.
+    fn byte(&mut self) -> Result<u8, MerkleError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read an unsigned integer written by `write_varuint`.
+    fn varuint(&mut self) -> Result<u64, MerkleError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f)
+                .checked_shl(shift)
+                .filter(|part| part >> shift == u64::from(byte & 0x7f))
+                .ok_or(MerkleError::InvalidEncoding)?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(MerkleError::InvalidEncoding)
+    }
+
+    /// Read bytes written by `write_varbytes`, at most `MAX_PAYLOAD` of them.
+    fn varbytes(&mut self) -> Result<&'a [u8], MerkleError> {
+        let len = self.varuint()?;
+        if len > MAX_PAYLOAD as u64 {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        self.take(len as usize)
+    }
+
+    /// Fail unless every byte was read.
+    fn finish(self) -> Result<(), MerkleError> {
+        if self.position == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(MerkleError::InvalidEncoding)
+        }
+    }
+}
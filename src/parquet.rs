@@ -0,0 +1,139 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Integrity manifest of the row groups of a Parquet file.
+//!
+//! Every row group occupies one contiguous byte range of the file, from the first page of its
+//! first column chunk to the end of its last. The manifest commits to the digest of every
+//! range with a merkle tree whose leaves are those digests as hexdigests, in row group order,
+//! so a consumer that downloads only some row groups can verify each one against the root.
+use crate::{Hash, MerkleProof, MerkleTree};
+use ::parquet::file::metadata::ParquetMetaDataReader;
+use ::parquet::file::reader::ChunkReader;
+use std::cell::RefCell;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+/// Byte range of a row group and the proof of its digest.
+pub struct RowGroupEntry {
+    /// Offset of the first byte of the row group in the file.
+    pub offset: u64,
+
+    /// Number of bytes of the row group.
+    pub length: u64,
+
+    /// Number of rows of the row group.
+    pub num_rows: i64,
+
+    /// Proof whose leaf content is the hexdigest of the bytes of the row group.
+    pub proof: MerkleProof,
+}
+
+/// Merkle root over the row groups of a Parquet file, with a proof for every row group.
+pub struct Manifest {
+    /// Root hash as hexdigest.
+    pub root: String,
+
+    /// Row groups in file order.
+    pub row_groups: Vec<RowGroupEntry>,
+}
+
+impl Manifest {
+    /// Generate the manifest of a Parquet file. Row groups are streamed from the file, so they
+    /// never need to be fully buffered. Return None for a file without row groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - Parquet file.
+    pub fn generate<R: ChunkReader>(file: &R) -> io::Result<Option<Self>> {
+        let metadata = ParquetMetaDataReader::new()
+            .parse_and_finish(file)
+            .map_err(io::Error::other)?;
+        let mut ranges = Vec::new();
+        let mut digests = Vec::new();
+        for row_group in metadata.row_groups() {
+            let (start, end) = row_group
+                .columns()
+                .iter()
+                .map(|column| column.byte_range())
+                .fold((u64::MAX, 0), |(start, end), (offset, length)| {
+                    (start.min(offset), end.max(offset + length))
+                });
+            if start >= end {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "row group without column chunks",
+                ));
+            }
+            let reader = file.get_read(start).map_err(io::Error::other)?;
+            digests.push(Hash::hash_reader(reader.take(end - start))?);
+            ranges.push((start, end - start, row_group.num_rows()));
+        }
+
+        let tree = MerkleTree::from_leaves(&digests);
+        let root = match tree.root() {
+            Some(root) => root.borrow().value.to_owned(),
+            None => return Ok(None),
+        };
+        let row_groups = ranges
+            .into_iter()
+            .enumerate()
+            .map(|(index, (offset, length, num_rows))| RowGroupEntry {
+                offset,
+                length,
+                num_rows,
+                proof: tree.proof(index).unwrap(),
+            })
+            .collect();
+        Ok(Some(Manifest { root, row_groups }))
+    }
+
+    /// Verify that downloaded bytes are the row group at `index`.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the row group.
+    /// * `bytes` - Downloaded bytes of the row group's byte range.
+    pub fn verify(&self, index: usize, bytes: &[u8]) -> bool {
+        let entry = match self.row_groups.get(index) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        bytes.len() as u64 == entry.length
+            && entry.proof.leaf_content == Hash::hash_reader(bytes).unwrap()
+            && MerkleTree::verify_proof(
+                Rc::new(RefCell::new(Hash::new(self.root.to_owned()))),
+                &entry.proof,
+            )
+    }
+
+    /// Return the manifest as JSON, with node values as hexdigests.
+    pub fn to_json(&self) -> String {
+        let row_groups: Vec<String> = self
+            .row_groups
+            .iter()
+            .map(|entry| {
+                let proof: Vec<String> = entry
+                    .proof
+                    .hashes
+                    .iter()
+                    .map(|hash| {
+                        let hash = hash.borrow();
+                        format!("{{\"value\":\"{}\",\"isLeft\":{}}}", hash.value, hash.is_left)
+                    })
+                    .collect();
+                format!(
+                    "{{\"offset\":{},\"length\":{},\"numRows\":{},\"digest\":\"{}\",\"proof\":[{}]}}",
+                    entry.offset,
+                    entry.length,
+                    entry.num_rows,
+                    entry.proof.leaf_content,
+                    proof.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"root\":\"{}\",\"rowGroups\":[{}]}}",
+            self.root,
+            row_groups.join(",")
+        )
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{Hash, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+
+/// Evidence that two trees hold the same multiset of leaves in different orders.
+///
+/// Every leaf of the first tree is mapped to a distinct leaf of the second tree with the same
+/// content, and each pair is proven in its tree. As both trees have the same number of leaves,
+/// an auditor checking the pairs learns that no leaf was added or dropped by the reordering.
+pub struct Permutation {
+    /// For every leaf index of the first tree, the index of the same leaf in the second tree.
+    pub map: Vec<usize>,
+
+    /// For every leaf index of the first tree, the proofs of the leaf in the first and second
+    /// tree.
+    pub proofs: Vec<(MerkleProof, MerkleProof)>,
+}
+
+impl Permutation {
+    /// Map the leaves of `first` to the leaves of `second`, pairing equal leaves in index order.
+    /// Return None if the trees do not hold the same multiset of leaves, or either has
+    /// removed leaves.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - Tree before reordering.
+    /// * `second` - Tree after reordering.
+    pub fn between(first: &MerkleTree, second: &MerkleTree) -> Option<Self> {
+        let num_of_leaves = first.num_of_leaves();
+        if second.num_of_leaves() != num_of_leaves {
+            return None;
+        }
+        let mut positions: BTreeMap<String, VecDeque<MerkleProof>> = BTreeMap::new();
+        for leaf_index in 0..num_of_leaves {
+            let proof = second.proof(leaf_index)?;
+            positions
+                .entry(proof.leaf_content.to_owned())
+                .or_default()
+                .push_back(proof);
+        }
+
+        let mut map = Vec::with_capacity(num_of_leaves);
+        let mut proofs = Vec::with_capacity(num_of_leaves);
+        for leaf_index in 0..num_of_leaves {
+            let proof = first.proof(leaf_index)?;
+            let paired = positions.get_mut(&proof.leaf_content)?.pop_front()?;
+            map.push(paired.leaf_index);
+            proofs.push((proof, paired));
+        }
+        Some(Permutation { map, proofs })
+    }
+
+    /// Verify that the permutation maps every leaf of the tree with root `first_root` to a
+    /// distinct leaf with the same content of the tree with root `second_root`, and that both
+    /// trees have exactly as many leaves as the permutation.
+    ///
+    /// # Arguments
+    ///
+    /// * `first_root` - Root of the tree before reordering.
+    /// * `second_root` - Root of the tree after reordering.
+    pub fn verify(&self, first_root: &str, second_root: &str) -> bool {
+        let num_of_leaves = self.map.len();
+        if self.proofs.len() != num_of_leaves {
+            return false;
+        }
+        let first_root = Rc::new(RefCell::new(Hash::new(first_root.to_owned())));
+        let second_root = Rc::new(RefCell::new(Hash::new(second_root.to_owned())));
+        let mut mapped = vec![false; num_of_leaves];
+        for (leaf_index, (target, (first, second))) in self.map.iter().zip(&self.proofs).enumerate()
+        {
+            if *target >= num_of_leaves || std::mem::replace(&mut mapped[*target], true) {
+                return false;
+            }
+            if first.leaf_index != leaf_index
+                || second.leaf_index != *target
+                || first.num_of_leaves != num_of_leaves
+                || second.num_of_leaves != num_of_leaves
+                || first.leaf_content != second.leaf_content
+                || !MerkleTree::verify_proof(first_root.to_owned(), first)
+                || !MerkleTree::verify_proof(second_root.to_owned(), second)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
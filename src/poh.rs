@@ -0,0 +1,241 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{Concatenation, HashAlgorithm, MerkleProof, MerkleTree};
+
+/// Entry of a `ProofOfHistory`: the root of a batch of events, mixed into the sequential hash
+/// after a number of ticks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Number of ticks hashed between the previous entry and this one.
+    pub num_hashes: u64,
+
+    /// Root of the tree over the events of the batch, as hexdigest.
+    pub root: String,
+
+    /// State of the sequential hash after mixing in `root`, as hexdigest.
+    pub hash: String,
+}
+
+/// Return the state after `num_hashes` ticks from `state`, each hashing the previous state
+/// as hexdigest text, followed by mixing in `root` like parents of a `Concatenation::Hex`
+/// merkle tree are hashed.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+/// * `state` - State before the ticks, as hexdigest.
+/// * `num_hashes` - Number of ticks.
+/// * `root` - Root of the batch, as hexdigest.
+fn advance(algorithm: HashAlgorithm, state: &str, num_hashes: u64, root: &str) -> String {
+    let mut state = state.to_owned();
+    for _ in 0..num_hashes {
+        state = algorithm.hash(state.as_bytes());
+    }
+    Concatenation::Hex.hash_with(algorithm, &state, root)
+}
+
+/// Sequential hash chain in the style of proof of history, with periodic merkle roots over
+/// batched events.
+///
+/// The state is hashed over and over by `tick`, so each state can only be computed after the
+/// previous one, and the root of every batch of events is mixed into the state when the
+/// batch is sealed. Every event of a batch was therefore recorded after the states before
+/// the batch and before the states after it, and `HistoryReceipt` proves both the membership
+/// of an event in its batch and the order of the batch in the chain.
+pub struct ProofOfHistory {
+    algorithm: HashAlgorithm,
+
+    /// State the chain started from, as hexdigest.
+    seed: String,
+
+    /// Current state, as hexdigest.
+    state: String,
+
+    /// Ticks hashed since the last entry.
+    num_hashes: u64,
+
+    /// Events recorded into the current, not yet sealed, batch.
+    events: Vec<String>,
+
+    /// Entries of every sealed batch, in order.
+    entries: Vec<HistoryEntry>,
+
+    /// Trees over the events of every sealed batch, in order.
+    batches: Vec<MerkleTree>,
+}
+
+impl ProofOfHistory {
+    /// Initialize a chain with no ticks and no events.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function for ticks, batches and mixing.
+    /// * `seed` - Content hashed into the first state, e.g. the head of a previous chain.
+    pub fn new(algorithm: HashAlgorithm, seed: &str) -> Self {
+        let seed = algorithm.hash(seed.as_bytes());
+        ProofOfHistory {
+            algorithm,
+            state: seed.to_owned(),
+            seed,
+            num_hashes: 0,
+            events: Vec::new(),
+            entries: Vec::new(),
+            batches: Vec::new(),
+        }
+    }
+
+    /// State the chain started from, as hexdigest.
+    pub fn seed(&self) -> &str {
+        &self.seed
+    }
+
+    /// Current state, including the ticks since the last sealed batch, as hexdigest.
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// State after the last sealed batch, or the seed if no batch was sealed, as hexdigest.
+    pub fn head(&self) -> &str {
+        self.entries
+            .last()
+            .map_or(self.seed.as_str(), |entry| entry.hash.as_str())
+    }
+
+    /// Entries of every sealed batch, in order.
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Hash the state `num_hashes` more times.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_hashes` - Number of ticks.
+    pub fn tick(&mut self, num_hashes: u64) {
+        for _ in 0..num_hashes {
+            self.state = self.algorithm.hash(self.state.as_bytes());
+        }
+        self.num_hashes += num_hashes;
+    }
+
+    /// Record an event into the current batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Content of event.
+    pub fn record(&mut self, event: String) {
+        self.events.push(event);
+    }
+
+    /// Seal the current batch, mixing the root of a tree over its events into the state, and
+    /// return its entry. Return None if no events were recorded into the current batch.
+    pub fn seal(&mut self) -> Option<&HistoryEntry> {
+        if self.events.is_empty() {
+            return None;
+        }
+        let tree = MerkleTree::builder()
+            .algorithm(self.algorithm)
+            .build(&std::mem::take(&mut self.events));
+        let root = tree.root()?.borrow().value.to_owned();
+        self.state = Concatenation::Hex.hash_with(self.algorithm, &self.state, &root);
+        self.entries.push(HistoryEntry {
+            num_hashes: std::mem::take(&mut self.num_hashes),
+            root,
+            hash: self.state.to_owned(),
+        });
+        self.batches.push(tree);
+        self.entries.last()
+    }
+
+    /// Generate a receipt that the event at `index` of sealed batch `batch` is in the chain
+    /// with the current head. Return None if either index is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - 0-based index of sealed batch.
+    /// * `index` - 0-based index of event within the batch.
+    pub fn receipt(&self, batch: usize, index: usize) -> Option<HistoryReceipt> {
+        let proof = self.batches.get(batch)?.proof(index)?;
+        let previous = match batch.checked_sub(1) {
+            Some(previous) => self.entries[previous].hash.to_owned(),
+            None => self.seed.to_owned(),
+        };
+        Some(HistoryReceipt {
+            previous,
+            entries: self.entries[batch..].to_vec(),
+            proof,
+            algorithm: self.algorithm,
+        })
+    }
+
+    /// Verify a chain of entries by replaying every tick from `seed`. Replaying takes as many
+    /// hashes as the chain did, which is what shows that the ticks elapsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function of the chain.
+    /// * `seed` - State the chain started from, as hexdigest.
+    /// * `entries` - Entries of every sealed batch, in order.
+    pub fn verify_entries(algorithm: HashAlgorithm, seed: &str, entries: &[HistoryEntry]) -> bool {
+        let mut state = seed.to_owned();
+        for entry in entries {
+            state = advance(algorithm, &state, entry.num_hashes, &entry.root);
+            if state != entry.hash {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Receipt that an event was recorded in a batch of a `ProofOfHistory` with a given head.
+///
+/// The receipt holds the entries of the batch and of every later batch, so its size grows
+/// with the number of batches sealed after it.
+pub struct HistoryReceipt {
+    /// State before the ticks of the batch of the event, as hexdigest.
+    pub previous: String,
+
+    /// Entries of the batch of the event and of every later batch, in order.
+    pub entries: Vec<HistoryEntry>,
+
+    /// Proof that the event is a leaf of the tree over its batch.
+    pub proof: MerkleProof,
+
+    /// Hash function of the chain.
+    pub algorithm: HashAlgorithm,
+}
+
+impl HistoryReceipt {
+    /// Content of the event.
+    pub fn event(&self) -> &str {
+        &self.proof.leaf_content
+    }
+
+    /// Verify that the event is in its batch and that the chain from `previous` through
+    /// every entry reaches `head`, replaying the ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `head` - State after the last sealed batch, as hexdigest.
+    pub fn verify(&self, head: &str) -> bool {
+        let Some(batch) = self.entries.first() else {
+            return false;
+        };
+        if self.proof.algorithm != self.algorithm || self.proof.root_value() != batch.root {
+            return false;
+        }
+        ProofOfHistory::verify_entries(self.algorithm, &self.previous, &self.entries)
+            && self.entries.last().map(|entry| entry.hash.as_str()) == Some(head)
+    }
+
+    /// Whether the event of this receipt was recorded before the event of `other`, given that
+    /// both verify against the same head. Events of later batches have fewer entries after
+    /// them, and events of the same batch are ordered by leaf index.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Receipt of another event.
+    pub fn precedes(&self, other: &HistoryReceipt) -> bool {
+        (self.entries.len(), other.proof.leaf_index) > (other.entries.len(), self.proof.leaf_index)
+    }
+}
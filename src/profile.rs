@@ -0,0 +1,108 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{hex, Concatenation, HashAlgorithm, LeafEncoding, MerkleTree, MerkleTreeBuilder};
+
+/// Named bundle of construction settings reproducing the trees of a known convention, so
+/// the hash function, concatenation, leaf encoding and chunk size need not be assembled
+/// option by option. Every profile promotes the last node of an odd level.
+///
+/// ```
+/// use merkle_tree::Profile;
+///
+/// let tree = Profile::RsMerkle.build(&[b"a", b"b", b"c"]).unwrap();
+/// assert_eq!(tree.num_of_leaves(), 3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    /// Defaults of this crate: SHA-256 over UTF-8 leaves, with parents hashing the
+    /// concatenated hexdigests. Leaves that are not valid UTF-8 are rejected.
+    #[default]
+    Plain,
+
+    /// Trees of the `rs_merkle` crate with its `Sha256` hasher, and of most libraries hashing
+    /// raw leaf bytes: SHA-256 over the bytes of each leaf, with parents hashing the
+    /// concatenated raw digests. Roots equal those of `rs_merkle::root`.
+    RsMerkle,
+
+    /// Trees verified on chain by the `solidity::verifier_source` library with the EVM
+    /// builtin `keccak256`: Keccak-256 over the bytes of each leaf, with parents hashing the
+    /// concatenated raw digests.
+    #[cfg(feature = "sha3")]
+    Keccak,
+
+    /// Blobs addressed by `cas::Cas` with 1 MiB chunks: the leaves are the hexdigests of the
+    /// chunks, an empty blob being one empty chunk, in a tree with the defaults of `Plain`.
+    /// Roots of `build_blob` equal those `Cas::put` returns for the same chunk size.
+    Blob,
+}
+
+impl Profile {
+    /// Builder configured with the settings of the profile, for further options such as
+    /// `max_leaves`.
+    pub fn builder(&self) -> MerkleTreeBuilder {
+        let builder = MerkleTree::builder();
+        match self {
+            Profile::Plain | Profile::Blob => builder,
+            Profile::RsMerkle => builder
+                .concatenation(Concatenation::Bytes)
+                .leaf_encoding(LeafEncoding::Hex),
+            #[cfg(feature = "sha3")]
+            Profile::Keccak => builder
+                .algorithm(HashAlgorithm::Keccak256)
+                .concatenation(Concatenation::Bytes)
+                .leaf_encoding(LeafEncoding::Hex),
+        }
+    }
+
+    /// Number of bytes per chunk `build_blob` splits blobs into, or None to keep a blob as a
+    /// single leaf.
+    pub fn chunk_size(&self) -> Option<usize> {
+        match self {
+            Profile::Blob => Some(1 << 20),
+            _ => None,
+        }
+    }
+
+    /// Return the leaf content hashing to the leaf node value of `bytes` under the profile,
+    /// or None if `bytes` cannot be a leaf of it (invalid UTF-8 for `Plain`).
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Bytes of a leaf, or of a chunk for `Blob`.
+    pub fn leaf(&self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Profile::Plain => String::from_utf8(bytes.to_vec()).ok(),
+            Profile::Blob => Some(HashAlgorithm::Sha256.hash(bytes)),
+            _ => Some(hex::encode(bytes)),
+        }
+    }
+
+    /// Build a tree over leaves given as bytes. Return None if a leaf cannot be a leaf of
+    /// the profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaves` - Bytes of each leaf.
+    pub fn build<B: AsRef<[u8]>>(&self, leaves: &[B]) -> Option<MerkleTree> {
+        let leaves = leaves
+            .iter()
+            .map(|leaf| self.leaf(leaf.as_ref()))
+            .collect::<Option<Vec<String>>>()?;
+        self.builder().try_build(&leaves).ok()
+    }
+
+    /// Build a tree over a blob split into chunks of `chunk_size` bytes, the last one possibly
+    /// shorter, or over the whole blob as one leaf if the profile does not chunk. An empty
+    /// blob is a single empty chunk. Return None like `build`.
+    ///
+    /// # Arguments
+    ///
+    /// * `blob` - Bytes of the blob.
+    pub fn build_blob(&self, blob: &[u8]) -> Option<MerkleTree> {
+        match self.chunk_size() {
+            Some(chunk_size) if !blob.is_empty() => {
+                self.build(&blob.chunks(chunk_size).collect::<Vec<&[u8]>>())
+            }
+            _ => self.build(&[blob]),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{Hash, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/// Proof held by a `ProofCache`, with the tick it was last used at.
+struct Entry {
+    proof: MerkleProof,
+    last_used: u64,
+}
+
+/// Cache of merkle proofs keyed by root and leaf index, so leaves served repeatedly do not
+/// have their audit paths recomputed. A root identifies the leaves of its tree, so a cached
+/// proof stays valid for every tree with that root, and proofs of a tree that changed are
+/// simply never hit again. Once `capacity` proofs are cached, the least recently used one is
+/// evicted.
+///
+/// ```
+/// use merkle_tree::{MerkleTree, ProofCache};
+///
+/// let tree = MerkleTree::from_leaves(&["a".to_string(), "b".to_string(), "c".to_string()]);
+/// let mut cache = ProofCache::new(16);
+/// for _ in 0..3 {
+///     assert!(MerkleTree::verify_proof(tree.root().unwrap(), &cache.proof(&tree, 1).unwrap()));
+/// }
+/// assert_eq!((cache.hits(), cache.misses()), (2, 1));
+/// ```
+pub struct ProofCache {
+    capacity: usize,
+    entries: HashMap<(String, usize), Entry>,
+
+    /// Keys of the cached proofs by the tick they were last used at, least recent first.
+    recency: BTreeMap<u64, (String, usize)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Return a copy of a proof that shares no audit hashes with it.
+///
+/// # Arguments
+///
+/// * `proof` - Proof to copy.
+fn copy(proof: &MerkleProof) -> MerkleProof {
+    MerkleProof {
+        hashes: proof
+            .hashes
+            .iter()
+            .map(|hash| {
+                let mut copy = Hash::new(hash.borrow().value.clone());
+                copy.is_left = hash.borrow().is_left;
+                Rc::new(RefCell::new(copy))
+            })
+            .collect(),
+        num_of_leaves: proof.num_of_leaves,
+        leaf_index: proof.leaf_index,
+        leaf_content: proof.leaf_content.clone(),
+        concatenation: proof.concatenation,
+        truncation: proof.truncation,
+        algorithm: proof.algorithm,
+        leaf_encoding: proof.leaf_encoding,
+        blinder: proof.blinder.clone(),
+    }
+}
+
+impl ProofCache {
+    /// Initialize an empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - Maximum number of proofs cached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be at least 1 proof");
+        ProofCache {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Maximum number of proofs cached.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of proofs cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no proofs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of proofs served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of proofs generated from a tree because they were not cached.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Return the merkle proof of a leaf of `tree`, from the cache if it holds the proof for
+    /// the current root of `tree`, or generated from `tree` and cached otherwise. Return None
+    /// if the tree has no leaves, `leaf_index` is out of range or the leaf was removed; such
+    /// lookups are counted as misses.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - Tree the leaf belongs to.
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&mut self, tree: &MerkleTree, leaf_index: usize) -> Option<MerkleProof> {
+        let root = tree.root().map(|root| root.borrow().value.clone());
+        let key = match root {
+            Some(root) => (root, leaf_index),
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        self.tick += 1;
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.hits += 1;
+            let key = self.recency.remove(&entry.last_used).unwrap();
+            entry.last_used = self.tick;
+            self.recency.insert(self.tick, key);
+            return Some(copy(&entry.proof));
+        }
+
+        self.misses += 1;
+        let proof = tree.proof(leaf_index)?;
+        if self.entries.len() == self.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap();
+            self.entries.remove(&evicted);
+        }
+        let copied = copy(&proof);
+        self.recency.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                proof,
+                last_used: self.tick,
+            },
+        );
+        Some(copied)
+    }
+
+    /// Evict every cached proof, keeping the hit and miss counts.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
@@ -0,0 +1,494 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Pluggable implementations of the hash functions.
+//!
+//! Every SHA-256 and SHA-3 digest computed by this crate goes through the process-wide
+//! `CryptoProvider`, so deployments with FIPS requirements can swap in a validated module
+//! without forking. `RustCrypto` is used until another provider is installed with
+//! `install_default`. Feature flags add providers backed by `ring`, `openssl` and
+//! `aws-lc-rs`. The Keccak-256 of the `mpt` feature always uses RustCrypto, and only
+//! RustCrypto implements the RIPEMD-160 and HASH160 of the `ripemd` feature.
+//!
+//! Builds of very wide trees can instead hand whole levels to a `BulkHasher` with
+//! `MerkleTreeBuilder::try_build_bulk`, so accelerators such as GPUs can hash many nodes at
+//! once. `CpuBulkHasher` is the reference implementation.
+use crate::{telemetry, HashAlgorithm};
+use std::sync::OnceLock;
+
+/// Incremental hash computation.
+pub trait Hasher: Send {
+    /// Feed more bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Bytes to hash.
+    fn update(&mut self, value: &[u8]);
+
+    /// Return the digest of all bytes fed.
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+}
+
+/// Implementation of the hash functions.
+pub trait CryptoProvider: Send + Sync {
+    /// Start an incremental hash computation. Return None if `algorithm` is not implemented.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>>;
+
+    /// Hash bytes to a raw digest in one shot. Return None if `algorithm` is not implemented.
+    /// Tree construction hashes every node this way, so providers should override the default,
+    /// which goes through a boxed `hasher`.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    /// * `value` - Bytes to hash.
+    fn digest(&self, algorithm: HashAlgorithm, value: &[u8]) -> Option<Vec<u8>> {
+        let mut hasher = self.hasher(algorithm)?;
+        hasher.update(value);
+        Some(hasher.finalize())
+    }
+}
+
+/// Provider installed with `install_default`.
+static PROVIDER: OnceLock<&'static dyn CryptoProvider> = OnceLock::new();
+
+/// Install the provider used by this crate for the rest of the process. Fail with the
+/// provider already installed if called more than once.
+///
+/// # Arguments
+///
+/// * `provider` - Provider to install.
+pub fn install_default(
+    provider: &'static dyn CryptoProvider,
+) -> Result<(), &'static dyn CryptoProvider> {
+    PROVIDER.set(provider).map_err(|_| *PROVIDER.get().unwrap())
+}
+
+/// Return the installed provider, or `RustCrypto` if none is installed.
+pub fn get_default() -> &'static dyn CryptoProvider {
+    PROVIDER.get().copied().unwrap_or(&RustCrypto)
+}
+
+/// Start an incremental hash computation with the installed provider.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+///
+/// # Panics
+///
+/// Panics if the installed provider does not implement `algorithm`, rather than silently
+/// falling back to another implementation.
+pub(crate) fn hasher(algorithm: HashAlgorithm) -> Box<dyn Hasher> {
+    telemetry::hash_op();
+    get_default()
+        .hasher(algorithm)
+        .unwrap_or_else(|| panic!("crypto provider does not implement {:?}", algorithm))
+}
+
+/// Hash bytes to a raw digest in one shot with the installed provider.
+///
+/// # Arguments
+///
+/// * `algorithm` - Hash function.
+/// * `value` - Bytes to hash.
+///
+/// # Panics
+///
+/// Panics if the installed provider does not implement `algorithm`, rather than silently
+/// falling back to another implementation.
+pub(crate) fn digest(algorithm: HashAlgorithm, value: &[u8]) -> Vec<u8> {
+    telemetry::hash_op();
+    if let HashAlgorithm::Custom(custom) = algorithm {
+        return (custom.digest)(value);
+    }
+    get_default()
+        .digest(algorithm, value)
+        .unwrap_or_else(|| panic!("crypto provider does not implement {:?}", algorithm))
+}
+
+/// Hash bytes to a raw SHA-256 digest with the installed provider.
+///
+/// # Arguments
+///
+/// * `value` - Bytes to hash.
+pub(crate) fn sha256(value: &[u8]) -> [u8; 32] {
+    digest(HashAlgorithm::Sha256, value).try_into().unwrap()
+}
+
+/// Hashing of many independent messages at once, for backends that are only efficient over
+/// large batches, such as GPU kernels. A backend may dispatch the batch asynchronously, and
+/// returns once every digest is available.
+pub trait BulkHasher: Send + Sync {
+    /// Return the raw digests of `messages` in order, or None if `algorithm` is not
+    /// implemented.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    /// * `messages` - Messages to hash.
+    fn hash_batch(&self, algorithm: HashAlgorithm, messages: &[Vec<u8>]) -> Option<Vec<Vec<u8>>>;
+}
+
+/// Bulk hasher that splits every batch across threads hashing with the installed provider.
+/// Implements the algorithms the installed provider implements.
+#[derive(Debug, Clone, Copy)]
+pub struct CpuBulkHasher {
+    num_of_threads: usize,
+}
+
+impl CpuBulkHasher {
+    /// Initialize a bulk hasher.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_of_threads` - Maximum number of threads hashing a batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_of_threads` is 0.
+    pub fn new(num_of_threads: usize) -> Self {
+        assert!(num_of_threads > 0, "bulk hasher needs at least 1 thread");
+        CpuBulkHasher { num_of_threads }
+    }
+}
+
+impl Default for CpuBulkHasher {
+    /// Initialize a bulk hasher with one thread per available core.
+    fn default() -> Self {
+        Self::new(std::thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+}
+
+impl BulkHasher for CpuBulkHasher {
+    fn hash_batch(&self, algorithm: HashAlgorithm, messages: &[Vec<u8>]) -> Option<Vec<Vec<u8>>> {
+        if !matches!(algorithm, HashAlgorithm::Custom(_)) {
+            get_default().hasher(algorithm)?;
+        }
+        let digest = |message: &Vec<u8>| digest(algorithm, message);
+        let chunk_size = messages.len().div_ceil(self.num_of_threads).max(1);
+        if chunk_size == messages.len() {
+            return Some(messages.iter().map(digest).collect());
+        }
+        Some(std::thread::scope(|scope| {
+            let threads: Vec<_> = messages
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(digest).collect::<Vec<_>>()))
+                .collect();
+            threads
+                .into_iter()
+                .flat_map(|thread| thread.join().unwrap())
+                .collect()
+        }))
+    }
+}
+
+/// Trait of the RustCrypto hash functions, re-exported so providers can adapt them with
+/// `DigestHasher`.
+pub use sha2::digest;
+
+/// Provider backed by the pure Rust `sha2`, `sha3` and `ripemd` crates.
+/// Implements every algorithm.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustCrypto;
+
+/// Hasher backed by any implementation of `digest::Digest`, so custom providers can back an
+/// algorithm with any hash function of the RustCrypto ecosystem, for example one accelerated
+/// for their platform. The digest must compute the algorithm it is returned for.
+///
+/// ```
+/// use merkle_tree::provider::{CryptoProvider, DigestHasher, Hasher};
+/// use merkle_tree::HashAlgorithm;
+///
+/// struct Sha256Only;
+///
+/// impl CryptoProvider for Sha256Only {
+///     fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+///         match algorithm {
+///             HashAlgorithm::Sha256 => Some(Box::new(DigestHasher::<sha2::Sha256>::default())),
+///             #[allow(unreachable_patterns)]
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DigestHasher<D>(pub D);
+
+impl<D: digest::Digest + Send> Hasher for DigestHasher<D> {
+    fn update(&mut self, value: &[u8]) {
+        self.0.update(value);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+}
+
+/// Hash function backed by any implementation of `digest::Digest`, for hash functions that are
+/// not built into `HashAlgorithm`. It is computed by the digest itself rather than the
+/// installed provider. Set it on a builder with `MerkleTreeBuilder::hasher`.
+///
+/// Two custom digests are equal if they are built from the same digest type.
+#[derive(Clone, Copy)]
+pub struct CustomDigest {
+    name: &'static str,
+    digest_size: usize,
+    digest: fn(&[u8]) -> Vec<u8>,
+}
+
+impl CustomDigest {
+    /// Return the hash function computed by the digest `D`.
+    pub fn new<D: digest::Digest>() -> Self {
+        CustomDigest {
+            name: std::any::type_name::<D>(),
+            digest_size: <D as digest::Digest>::output_size(),
+            digest: |value| D::digest(value).to_vec(),
+        }
+    }
+
+    /// Name of the digest type.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Number of bytes of a digest.
+    pub fn digest_size(&self) -> usize {
+        self.digest_size
+    }
+}
+
+impl PartialEq for CustomDigest {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomDigest {}
+
+impl std::fmt::Debug for CustomDigest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CustomDigest").field(&self.name).finish()
+    }
+}
+
+/// Hasher of RIPEMD-160 over the SHA-256 digest of all bytes fed.
+#[cfg(feature = "ripemd")]
+struct Hash160Hasher(sha2::Sha256);
+
+#[cfg(feature = "ripemd")]
+impl Hasher for Hash160Hasher {
+    fn update(&mut self, value: &[u8]) {
+        sha2::Digest::update(&mut self.0, value);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        use sha2::Digest;
+
+        ripemd::Ripemd160::digest(self.0.finalize()).to_vec()
+    }
+}
+
+impl CryptoProvider for RustCrypto {
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+        Some(match algorithm {
+            HashAlgorithm::Sha256 => Box::new(DigestHasher(sha2::Sha256::default())),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => Box::new(DigestHasher(sha3::Sha3_256::default())),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_512 => Box::new(DigestHasher(sha3::Sha3_512::default())),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => Box::new(DigestHasher(sha3::Keccak256::default())),
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Ripemd160 => Box::new(DigestHasher(ripemd::Ripemd160::default())),
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Hash160 => Box::new(Hash160Hasher(sha2::Sha256::default())),
+            HashAlgorithm::Custom(_) => return None,
+        })
+    }
+
+    fn digest(&self, algorithm: HashAlgorithm, value: &[u8]) -> Option<Vec<u8>> {
+        use sha2::Digest;
+
+        Some(match algorithm {
+            HashAlgorithm::Sha256 => sha2::Sha256::digest(value).to_vec(),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => sha3::Sha3_256::digest(value).to_vec(),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_512 => sha3::Sha3_512::digest(value).to_vec(),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => sha3::Keccak256::digest(value).to_vec(),
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Ripemd160 => ripemd::Ripemd160::digest(value).to_vec(),
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Hash160 => {
+                ripemd::Ripemd160::digest(sha2::Sha256::digest(value)).to_vec()
+            }
+            HashAlgorithm::Custom(_) => return None,
+        })
+    }
+}
+
+/// Provider backed by `ring`. Implements SHA-256 only.
+#[cfg(feature = "ring")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ring;
+
+#[cfg(feature = "ring")]
+struct RingHasher(ring::digest::Context);
+
+#[cfg(feature = "ring")]
+impl Hasher for RingHasher {
+    fn update(&mut self, value: &[u8]) {
+        self.0.update(value);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finish().as_ref().to_vec()
+    }
+}
+
+#[cfg(feature = "ring")]
+impl CryptoProvider for Ring {
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+        match algorithm {
+            HashAlgorithm::Sha256 => Some(Box::new(RingHasher(ring::digest::Context::new(
+                &ring::digest::SHA256,
+            )))),
+            _ => None,
+        }
+    }
+
+    fn digest(&self, algorithm: HashAlgorithm, value: &[u8]) -> Option<Vec<u8>> {
+        match algorithm {
+            HashAlgorithm::Sha256 => Some(
+                ring::digest::digest(&ring::digest::SHA256, value)
+                    .as_ref()
+                    .to_vec(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Provider backed by OpenSSL, which can be a FIPS-validated build.
+/// Implements every algorithm except Keccak-256, RIPEMD-160 and HASH160.
+#[cfg(feature = "openssl")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenSsl;
+
+#[cfg(feature = "openssl")]
+impl Hasher for openssl::hash::Hasher {
+    fn update(&mut self, value: &[u8]) {
+        openssl::hash::Hasher::update(self, value).expect("OpenSSL digest update failed");
+    }
+
+    fn finalize(mut self: Box<Self>) -> Vec<u8> {
+        self.finish().expect("OpenSSL digest failed").to_vec()
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl OpenSsl {
+    /// Return the OpenSSL digest of `algorithm`, or None if OpenSSL does not implement it.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    fn message_digest(algorithm: HashAlgorithm) -> Option<openssl::hash::MessageDigest> {
+        use openssl::hash::MessageDigest;
+
+        match algorithm {
+            HashAlgorithm::Sha256 => Some(MessageDigest::sha256()),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => Some(MessageDigest::sha3_256()),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_512 => Some(MessageDigest::sha3_512()),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => None,
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Ripemd160 | HashAlgorithm::Hash160 => None,
+            HashAlgorithm::Custom(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl CryptoProvider for OpenSsl {
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+        // Fails if the algorithm is not allowed, e.g. by the FIPS provider.
+        let hasher = openssl::hash::Hasher::new(Self::message_digest(algorithm)?).ok()?;
+        Some(Box::new(hasher))
+    }
+
+    fn digest(&self, algorithm: HashAlgorithm, value: &[u8]) -> Option<Vec<u8>> {
+        let digest = openssl::hash::hash(Self::message_digest(algorithm)?, value).ok()?;
+        Some(digest.to_vec())
+    }
+}
+
+/// Provider backed by AWS-LC, which has FIPS-validated builds.
+/// Implements every algorithm except Keccak-256, RIPEMD-160 and HASH160.
+#[cfg(feature = "aws-lc-rs")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AwsLc;
+
+#[cfg(feature = "aws-lc-rs")]
+struct AwsLcHasher(aws_lc_rs::digest::Context);
+
+#[cfg(feature = "aws-lc-rs")]
+impl Hasher for AwsLcHasher {
+    fn update(&mut self, value: &[u8]) {
+        self.0.update(value);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finish().as_ref().to_vec()
+    }
+}
+
+#[cfg(feature = "aws-lc-rs")]
+impl AwsLc {
+    /// Return the AWS-LC digest of `algorithm`, or None if AWS-LC does not implement it.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - Hash function.
+    fn digest_algorithm(algorithm: HashAlgorithm) -> Option<&'static aws_lc_rs::digest::Algorithm> {
+        use aws_lc_rs::digest;
+
+        match algorithm {
+            HashAlgorithm::Sha256 => Some(&digest::SHA256),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_256 => Some(&digest::SHA3_256),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_512 => Some(&digest::SHA3_512),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Keccak256 => None,
+            #[cfg(feature = "ripemd")]
+            HashAlgorithm::Ripemd160 | HashAlgorithm::Hash160 => None,
+            HashAlgorithm::Custom(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "aws-lc-rs")]
+impl CryptoProvider for AwsLc {
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+        let algorithm = Self::digest_algorithm(algorithm)?;
+        Some(Box::new(AwsLcHasher(aws_lc_rs::digest::Context::new(
+            algorithm,
+        ))))
+    }
+
+    fn digest(&self, algorithm: HashAlgorithm, value: &[u8]) -> Option<Vec<u8>> {
+        let algorithm = Self::digest_algorithm(algorithm)?;
+        Some(
+            aws_lc_rs::digest::digest(algorithm, value)
+                .as_ref()
+                .to_vec(),
+        )
+    }
+}
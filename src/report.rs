@@ -0,0 +1,284 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::telemetry;
+use crate::verify::sibling_is_left;
+use crate::{Hash, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// One level of a proof verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationStep {
+    /// Audit hash of this level as hexdigest.
+    pub sibling: String,
+
+    /// Whether the proof places the audit hash on the left.
+    pub sibling_is_left: bool,
+
+    /// Digest computed at this level as hexdigest.
+    pub computed: String,
+}
+
+/// Most likely reason a proof failed to verify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationFailure {
+    /// `leaf_index` is not less than `num_of_leaves`.
+    LeafIndexOutOfRange,
+
+    /// The proof has a different number of audit hashes than a tree of `num_of_leaves` leaves
+    /// needs for `leaf_index`.
+    ProofLength {
+        /// Number of audit hashes expected, without padding of odd levels.
+        expected: usize,
+
+        /// Number of audit hashes in the proof.
+        actual: usize,
+    },
+
+    /// The audit hash of this step is on the wrong side for `leaf_index`.
+    SiblingSide {
+        /// 0-based index into `VerificationReport::steps`.
+        step: usize,
+    },
+
+    /// The expected root is not a digest of the hash function and truncation of the proof,
+    /// so the tree was built with another hash function.
+    AlgorithmMismatch {
+        /// Number of hex digits of a node value of the proof.
+        expected: usize,
+
+        /// Number of hex digits of the expected root.
+        actual: usize,
+    },
+
+    /// The proof is well formed, but the computed root differs: the leaf content, an audit
+    /// hash, or the expected root is wrong, or the tree was built with another scheme.
+    RootMismatch,
+}
+
+impl fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationFailure::LeafIndexOutOfRange => {
+                write!(f, "leaf index is not less than the number of leaves")
+            }
+            VerificationFailure::ProofLength { expected, actual } => write!(
+                f,
+                "proof has {actual} audit hashes, but the leaf needs {expected}"
+            ),
+            VerificationFailure::SiblingSide { step } => write!(
+                f,
+                "audit hash {step} is on the wrong side for the leaf index"
+            ),
+            VerificationFailure::AlgorithmMismatch { expected, actual } => write!(
+                f,
+                "root has {actual} hex digits, but node values of the proof have {expected}; \
+                 the tree was built with another hash function or truncation"
+            ),
+            VerificationFailure::RootMismatch => write!(
+                f,
+                "proof does not reproduce the root; the leaf, an audit hash or the root is wrong"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerificationFailure {}
+
+/// Structured account of a proof verification, for triaging proofs that fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Leaf node value computed from the leaf content, as hexdigest.
+    pub leaf_value: String,
+
+    /// Levels from the bottom-most up to the top-most (closest to root node).
+    pub steps: Vec<VerificationStep>,
+
+    /// Root value computed from the proof, as hexdigest.
+    pub computed_root: String,
+
+    /// Root value the proof was verified against, as hexdigest.
+    pub expected_root: String,
+
+    /// Why verification failed, or None if it succeeded.
+    pub failure: Option<VerificationFailure>,
+}
+
+impl VerificationReport {
+    /// Whether the proof verified.
+    pub fn is_valid(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Order of child hashes under which a proof reproduced the root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcatenationOrder {
+    /// Every audit hash on the side the proof places it, as this crate concatenates.
+    AsProven,
+
+    /// Every audit hash on the opposite side, as implementations with the opposite
+    /// convention concatenate.
+    Reversed,
+}
+
+/// Reconstruct the root implied by a proof, as hexdigest, with every audit hash on the side
+/// the proof places it, or on the opposite side if `reversed` is set.
+///
+/// # Arguments
+///
+/// * `proof` - Proof to reconstruct the root of.
+/// * `reversed` - Whether to swap the side of every audit hash.
+fn ordered_root_value(proof: &MerkleProof, reversed: bool) -> String {
+    let mut result = proof.leaf_value();
+    for audit_hash in &proof.hashes {
+        let audit_hash = audit_hash.borrow();
+        let (left, right) = if audit_hash.is_left != reversed {
+            (&audit_hash.value, &result)
+        } else {
+            (&result, &audit_hash.value)
+        };
+        result = proof.parent_value(left, right);
+    }
+    result
+}
+
+/// Find where the sides of the audit hashes of `proof` depart from its `leaf_index`, or
+/// where `root` departs from its hash function. A proof of a padded tree has an audit hash
+/// on every level, otherwise the siblingless rightmost nodes are skipped, so both shapes are
+/// accepted.
+///
+/// # Arguments
+///
+/// * `root` - Expected root value as hexdigest.
+/// * `proof` - Proof to check.
+fn structural_failure(root: &str, proof: &MerkleProof) -> Option<VerificationFailure> {
+    if proof.leaf_index >= proof.num_of_leaves {
+        return Some(VerificationFailure::LeafIndexOutOfRange);
+    }
+    let digest_size = proof.algorithm.digest_size();
+    let node_len = 2 * proof.truncation.map_or(digest_size, |t| t.min(digest_size));
+    if root.len() != node_len {
+        return Some(VerificationFailure::AlgorithmMismatch {
+            expected: node_len,
+            actual: root.len(),
+        });
+    }
+    let promoted = sibling_is_left(proof.leaf_index, proof.num_of_leaves);
+    let promoted_len = promoted.len();
+    let height = proof.num_of_leaves.next_power_of_two().trailing_zeros() as usize;
+    let padded: Vec<bool> = (0..height)
+        .map(|level| (proof.leaf_index >> level) & 1 == 1)
+        .collect();
+    let actual: Vec<bool> = proof
+        .hashes
+        .iter()
+        .map(|hash| hash.borrow().is_left)
+        .collect();
+
+    let expected = [promoted, padded]
+        .into_iter()
+        .find(|expected| expected.len() == actual.len());
+    match expected {
+        None => Some(VerificationFailure::ProofLength {
+            expected: promoted_len,
+            actual: actual.len(),
+        }),
+        Some(expected) => expected
+            .iter()
+            .zip(&actual)
+            .position(|(expected, actual)| expected != actual)
+            .map(|step| VerificationFailure::SiblingSide { step }),
+    }
+}
+
+impl MerkleTree {
+    /// Same as `verify_proof`, but return a report of every intermediate digest and,
+    /// if verification fails, the most likely reason.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle tree.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_proof_verbose(
+        root: Rc<RefCell<Hash>>,
+        proof: &MerkleProof,
+    ) -> VerificationReport {
+        let leaf_value = proof.leaf_value();
+        let mut result = leaf_value.to_owned();
+        let mut steps = Vec::with_capacity(proof.hashes.len());
+        for audit_hash in &proof.hashes {
+            let audit_hash = audit_hash.borrow();
+            let (left, right) = if audit_hash.is_left {
+                (&audit_hash.value, &result)
+            } else {
+                (&result, &audit_hash.value)
+            };
+            result = proof.parent_value(left, right);
+            steps.push(VerificationStep {
+                sibling: audit_hash.value.to_owned(),
+                sibling_is_left: audit_hash.is_left,
+                computed: result.to_owned(),
+            });
+        }
+
+        let expected_root = root.borrow().value.to_owned();
+        let failure = (result != expected_root).then(|| {
+            structural_failure(&expected_root, proof).unwrap_or(VerificationFailure::RootMismatch)
+        });
+        VerificationReport {
+            leaf_value,
+            steps,
+            computed_root: result,
+            expected_root,
+            failure,
+        }
+    }
+
+    /// Same as `verify_proof`, but fail with the reason instead of returning false, so callers
+    /// can report precisely what is wrong: an out-of-range leaf index, a root of another hash
+    /// function, an audit path of the wrong length or shape, or otherwise a root the proof
+    /// does not reproduce. Unlike `verify_proof`, the shape of the proof is checked against
+    /// its leaf index before the root is, so a valid proof with a wrong index is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle tree.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_detailed(
+        root: Rc<RefCell<Hash>>,
+        proof: &MerkleProof,
+    ) -> Result<(), VerificationFailure> {
+        let root = &root.borrow().value;
+        let result = match structural_failure(root, proof) {
+            Some(failure) => Err(failure),
+            None if proof.root_value() != *root => Err(VerificationFailure::RootMismatch),
+            None => Ok(()),
+        };
+        telemetry::verification(result.is_ok());
+        result
+    }
+
+    /// Verify a proof with its audit hashes on the sides it places them and again on the
+    /// opposite sides, and return the order that reproduced the root, or None if neither
+    /// did. A proof that only verifies `Reversed` was generated by an implementation that
+    /// concatenates child hashes in the opposite order.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle tree.
+    /// * `proof` - Proof to be verified.
+    pub fn audit_concatenation_order(
+        root: Rc<RefCell<Hash>>,
+        proof: &MerkleProof,
+    ) -> Option<ConcatenationOrder> {
+        let root = &root.borrow().value;
+        let as_proven = ordered_root_value(proof, false) == *root;
+        let reversed = ordered_root_value(proof, true) == *root;
+        match (as_proven, reversed) {
+            (true, _) => Some(ConcatenationOrder::AsProven),
+            (false, true) => Some(ConcatenationOrder::Reversed),
+            (false, false) => None,
+        }
+    }
+}
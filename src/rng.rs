@@ -0,0 +1,50 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+
+/// Small deterministic pseudo-random number generator (SplitMix64).
+///
+/// Not suitable for cryptographic use. It only exists so that seeded routines
+/// produce the same sequence on every platform without extra dependencies.
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Initialize the generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Initial state.
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    /// Return the next pseudo-random `u64`.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Return a pseudo-random number in `0..bound`. `bound` must be non-zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `bound` - Exclusive upper bound.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Return a pseudo-random alphanumeric string of length `len`.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - Length of string.
+    pub(crate) fn alphanumeric(&mut self, len: usize) -> String {
+        const CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+        (0..len)
+            .map(|_| CHARSET[self.below(CHARSET.len())] as char)
+            .collect()
+    }
+}
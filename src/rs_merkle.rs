@@ -0,0 +1,128 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Compatibility adapter for roots and proofs produced by the `rs_merkle` crate
+//! with its `Sha256` hasher.
+//!
+//! `rs_merkle` uses the same tree shape as this crate (the last node of an odd level is
+//! promoted), but leaves are supplied already hashed, and parents are the hash of the
+//! concatenated raw 32-byte digests rather than of the concatenated hexdigests.
+//! Proofs are serialized as the concatenation of audit hashes, bottom-most first.
+use crate::verify::sibling_is_left;
+use crate::{provider, Hash, MerkleError};
+
+/// Size of a sha256 digest in bytes.
+pub const HASH_SIZE: usize = 32;
+
+/// Build the upper level from the nodes of the current level.
+fn next_level(nodes: &[[u8; HASH_SIZE]]) -> Vec<[u8; HASH_SIZE]> {
+    nodes
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => Hash::hash_pair_bytes(left, right),
+            [last] => *last, // Last node has no sibling.
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Hash leaf content the way `rs_merkle::algorithms::Sha256::hash` does.
+///
+/// # Arguments
+///
+/// * `leaf` - Leaf content.
+pub fn leaf_hash(leaf: &[u8]) -> [u8; HASH_SIZE] {
+    provider::sha256(leaf)
+}
+
+/// Compute the root of an `rs_merkle` tree. Return None if there are no leaves.
+///
+/// # Arguments
+///
+/// * `leaf_hashes` - Hashed leaves of merkle tree.
+pub fn root(leaf_hashes: &[[u8; HASH_SIZE]]) -> Option<[u8; HASH_SIZE]> {
+    let mut nodes = leaf_hashes.to_vec();
+    while nodes.len() > 1 {
+        nodes = next_level(&nodes);
+    }
+    nodes.first().copied()
+}
+
+/// Generate the audit hashes of an `rs_merkle` proof for a single leaf,
+/// arranged from the bottom-most hash up to the top-most hash (closest to root node).
+///
+/// # Arguments
+///
+/// * `leaf_hashes` - Hashed leaves of merkle tree.
+/// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+pub fn proof(leaf_hashes: &[[u8; HASH_SIZE]], mut leaf_index: usize) -> Vec<[u8; HASH_SIZE]> {
+    let mut nodes = leaf_hashes.to_vec();
+    let mut hashes = Vec::new();
+    while nodes.len() > 1 {
+        if let Some(sibling) = nodes.get(leaf_index ^ 1) {
+            hashes.push(*sibling);
+        } // Handle edge case for siblingless rightmost node on the level.
+        leaf_index /= 2;
+        nodes = next_level(&nodes);
+    }
+    hashes
+}
+
+/// Serialize audit hashes the way `rs_merkle::MerkleProof::to_bytes` does.
+///
+/// # Arguments
+///
+/// * `hashes` - Audit hashes.
+pub fn proof_to_bytes(hashes: &[[u8; HASH_SIZE]]) -> Vec<u8> {
+    hashes.concat()
+}
+
+/// Parse audit hashes serialized by `rs_merkle::MerkleProof::to_bytes`.
+///
+/// # Arguments
+///
+/// * `bytes` - Serialized proof.
+pub fn proof_from_bytes(bytes: &[u8]) -> Result<Vec<[u8; HASH_SIZE]>, MerkleError> {
+    if bytes.len() % HASH_SIZE != 0 {
+        return Err(MerkleError::InvalidProofLength(bytes.len()));
+    }
+    Ok(bytes
+        .chunks_exact(HASH_SIZE)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect())
+}
+
+/// Verify a single-leaf `rs_merkle` proof, like `rs_merkle::MerkleProof::verify`.
+///
+/// # Arguments
+///
+/// * `root` - Root of the merkle tree.
+/// * `leaf_index` - 0-based index of leaf node to be verified.
+/// * `leaf_hash` - Hashed leaf to be verified.
+/// * `total_leaves_count` - Number of leaves in the merkle tree.
+/// * `proof_hashes` - Audit hashes, arranged from the bottom-most hash up to the top-most hash.
+pub fn verify(
+    root: &[u8; HASH_SIZE],
+    leaf_index: usize,
+    leaf_hash: &[u8; HASH_SIZE],
+    total_leaves_count: usize,
+    proof_hashes: &[[u8; HASH_SIZE]],
+) -> bool {
+    if leaf_index >= total_leaves_count {
+        return false;
+    }
+    let directions = sibling_is_left(leaf_index, total_leaves_count);
+    if directions.len() != proof_hashes.len() {
+        return false;
+    }
+    let result =
+        proof_hashes
+            .iter()
+            .zip(directions)
+            .fold(*leaf_hash, |result, (sibling, is_left)| {
+                if is_left {
+                    Hash::hash_pair_bytes(sibling, &result)
+                } else {
+                    Hash::hash_pair_bytes(&result, sibling)
+                }
+            });
+    result == *root
+}
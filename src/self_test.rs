@@ -0,0 +1,57 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::rng::SplitMix64;
+use crate::MerkleTree;
+
+/// Known-answer leaves and root, taken from the README example.
+const KAT_LEAVES: [&str; 5] = ["abc", "bcd", "cde", "def", "efg"];
+const KAT_ROOT: &str = "b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073";
+
+/// Largest number of leaves in a randomly generated tree.
+const MAX_LEAVES: usize = 64;
+
+/// Run internal consistency checks and return true if all of them pass.
+///
+/// Intended as a cheap startup sanity check against miscompiles or hardware faults.
+/// The checks are deterministic for a given `seed`:
+///
+/// - The root of a known-answer tree matches its expected value.
+/// - Building the same tree twice yields the same root.
+/// - A proof for a randomly chosen leaf is accepted.
+/// - The same proof is rejected once its leaf content is tampered with.
+///
+/// # Arguments
+///
+/// * `seed` - Seed for generating random trees.
+/// * `iterations` - Number of random trees to check.
+pub fn self_test(seed: u64, iterations: usize) -> bool {
+    let kat_leaves: Vec<String> = KAT_LEAVES.iter().map(|leaf| leaf.to_string()).collect();
+    if MerkleTree::merkle_root(&kat_leaves).borrow().value != KAT_ROOT {
+        return false;
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    for _ in 0..iterations {
+        let num_of_leaves = 1 + rng.below(MAX_LEAVES);
+        let leaves: Vec<String> = (0..num_of_leaves)
+            .map(|_| {
+                let len = rng.below(32);
+                rng.alphanumeric(len)
+            })
+            .collect();
+
+        let root = MerkleTree::merkle_root(&leaves);
+        if MerkleTree::merkle_root(&leaves).borrow().value != root.borrow().value {
+            return false;
+        }
+
+        let mut proof = MerkleTree::merkle_proof(&leaves, rng.below(num_of_leaves));
+        if !MerkleTree::verify_proof(root.to_owned(), &proof) {
+            return false;
+        }
+        proof.leaf_content += "tainted";
+        if MerkleTree::verify_proof(root, &proof) {
+            return false;
+        }
+    }
+    true
+}
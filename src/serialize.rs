@@ -0,0 +1,306 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::codec::{Artifact, Decoder, Encoder};
+use crate::{base64, hex};
+use crate::{
+    Concatenation, Hash, HashAlgorithm, LeafEncoding, LeafOrdering, MerkleError, MerkleProof,
+    MerkleTree, Padding, TreeScheme,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Append how child hashes are concatenated.
+fn put_concatenation(encoder: &mut Encoder, concatenation: Concatenation) {
+    encoder.put_u64(match concatenation {
+        Concatenation::Hex => 0,
+        Concatenation::Bytes => 1,
+    });
+}
+
+/// Append how leaf contents encode the bytes hashed into leaf node values.
+fn put_leaf_encoding(encoder: &mut Encoder, leaf_encoding: LeafEncoding) {
+    encoder.put_u64(match leaf_encoding {
+        LeafEncoding::Utf8 => 0,
+        LeafEncoding::RawBytes => 1,
+        LeafEncoding::Hex => 2,
+        LeafEncoding::Base64 => 3,
+    });
+}
+
+/// Read how leaf contents encode the bytes hashed into leaf node values.
+fn leaf_encoding(decoder: &mut Decoder) -> Result<LeafEncoding, MerkleError> {
+    match decoder.u64()? {
+        0 => Ok(LeafEncoding::Utf8),
+        1 => Ok(LeafEncoding::RawBytes),
+        2 => Ok(LeafEncoding::Hex),
+        3 => Ok(LeafEncoding::Base64),
+        _ => Err(MerkleError::InvalidEncoding),
+    }
+}
+
+/// Append the number of bytes node values are truncated to, 0 for full digests.
+fn put_truncation(encoder: &mut Encoder, truncation: Option<usize>) {
+    encoder.put_usize(truncation.unwrap_or(0));
+}
+
+/// Read the number of bytes node values are truncated to, which must be shorter than
+/// a digest of `algorithm`.
+fn truncation(
+    decoder: &mut Decoder,
+    algorithm: HashAlgorithm,
+) -> Result<Option<usize>, MerkleError> {
+    match decoder.usize()? {
+        0 => Ok(None),
+        num_of_bytes if num_of_bytes < algorithm.digest_size() => Ok(Some(num_of_bytes)),
+        _ => Err(MerkleError::InvalidEncoding),
+    }
+}
+
+/// Read how child hashes are concatenated.
+fn concatenation(decoder: &mut Decoder) -> Result<Concatenation, MerkleError> {
+    match decoder.u64()? {
+        0 => Ok(Concatenation::Hex),
+        1 => Ok(Concatenation::Bytes),
+        _ => Err(MerkleError::InvalidEncoding),
+    }
+}
+
+/// Append how a tree is constructed, apart from its hash function which is in the header.
+pub(crate) fn put_scheme(encoder: &mut Encoder, scheme: &TreeScheme) {
+    put_concatenation(encoder, scheme.concatenation);
+    match &scheme.padding {
+        Padding::Promote => encoder.put_u64(0),
+        Padding::DuplicateLast => encoder.put_u64(1),
+        Padding::Constant(value) => {
+            encoder.put_u64(2);
+            encoder.put_str(value);
+        }
+    }
+    encoder.put_u64(match scheme.ordering {
+        LeafOrdering::Identity => 0,
+        LeafOrdering::SortedByHash => 1,
+        LeafOrdering::Custom => 2,
+    });
+    put_truncation(encoder, scheme.truncation);
+    put_leaf_encoding(encoder, scheme.leaf_encoding);
+}
+
+/// Read how a tree is constructed.
+pub(crate) fn scheme(
+    decoder: &mut Decoder,
+    algorithm: HashAlgorithm,
+) -> Result<TreeScheme, MerkleError> {
+    let concatenation = concatenation(decoder)?;
+    let padding = match decoder.u64()? {
+        0 => Padding::Promote,
+        1 => Padding::DuplicateLast,
+        2 => Padding::Constant(decoder.string()?),
+        _ => return Err(MerkleError::InvalidEncoding),
+    };
+    let ordering = match decoder.u64()? {
+        0 => LeafOrdering::Identity,
+        1 => LeafOrdering::SortedByHash,
+        2 => LeafOrdering::Custom,
+        _ => return Err(MerkleError::InvalidEncoding),
+    };
+    Ok(TreeScheme {
+        concatenation,
+        padding,
+        ordering,
+        truncation: truncation(decoder, algorithm)?,
+        leaf_encoding: leaf_encoding(decoder)?,
+    })
+}
+
+impl MerkleTree {
+    /// Serialize the tree into bytes. After the versioned header, the encoding records how the
+    /// tree was constructed (concatenation, padding, leaf ordering, truncation, leaf encoding
+    /// and blinding key),
+    /// followed by the leaves in tree order and the indices of removed leaves. The blinding key
+    /// is secret, so are the bytes of a tree with blinded leaves.
+    /// Inner nodes are not stored, they are recomputed by `from_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::Tree, self.algorithm);
+        put_scheme(&mut encoder, &self.scheme());
+        match self.blinding_key() {
+            None => encoder.put_u64(0),
+            Some(key) => {
+                encoder.put_u64(1);
+                encoder.put_bytes(&key);
+            }
+        }
+
+        encoder.put_usize(self.leaves.len());
+        for index in 0..self.leaves.len() {
+            encoder.put_str(self.leaves.get(index).unwrap());
+        }
+        encoder.put_usize(self.removed.len());
+        for index in self.removed.iter() {
+            encoder.put_usize(*index);
+        }
+        encoder.finish()
+    }
+
+    /// Deserialize a tree serialized by `to_bytes`. Leaves keep their serialized order.
+    /// Fail if the remaining leaves of a tree recorded as `LeafOrdering::SortedByHash`
+    /// are not sorted.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized tree.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::Tree)?;
+        let TreeScheme {
+            concatenation,
+            padding,
+            ordering,
+            truncation,
+            leaf_encoding,
+        } = scheme(&mut decoder, algorithm)?;
+        let blinding_key = match decoder.u64()? {
+            0 => None,
+            1 => Some(
+                <[u8; 32]>::try_from(decoder.bytes()?).map_err(|_| MerkleError::InvalidEncoding)?,
+            ),
+            _ => return Err(MerkleError::InvalidEncoding),
+        };
+        let leaves = (0..decoder.usize()?)
+            .map(|_| decoder.string())
+            .collect::<Result<Vec<String>, MerkleError>>()?;
+        let removed = (0..decoder.usize()?)
+            .map(|_| decoder.usize())
+            .collect::<Result<Vec<usize>, MerkleError>>()?;
+        decoder.finish()?;
+
+        if removed.iter().any(|index| *index >= leaves.len()) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let mut builder = MerkleTree::builder()
+            .algorithm(algorithm)
+            .concatenation(concatenation)
+            .padding(padding)
+            .leaf_encoding(leaf_encoding);
+        if let Some(num_of_bytes) = truncation {
+            builder = builder.truncation(num_of_bytes);
+        }
+        if let Some(key) = blinding_key {
+            builder = builder.blinding(key);
+        }
+        // Contents not valid in the leaf encoding cannot come from a tree.
+        let mut tree = builder
+            .try_build(&leaves)
+            .map_err(|_| MerkleError::InvalidEncoding)?;
+        tree.remove_batch(&removed);
+        let kept: Vec<String> = (0..leaves.len())
+            .filter(|index| !tree.is_removed(*index))
+            .map(|index| tree.leaf_value(&leaves[index]))
+            .collect();
+        if ordering == LeafOrdering::SortedByHash && kept.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        tree.ordering = ordering;
+        Ok(tree)
+    }
+}
+
+impl MerkleProof {
+    /// Serialize the proof into bytes, after a versioned header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::with_header(Artifact::Proof, self.algorithm);
+        put_concatenation(&mut encoder, self.concatenation);
+        put_truncation(&mut encoder, self.truncation);
+        put_leaf_encoding(&mut encoder, self.leaf_encoding);
+        encoder.put_usize(self.num_of_leaves);
+        encoder.put_usize(self.leaf_index);
+        encoder.put_str(&self.leaf_content);
+        match &self.blinder {
+            None => encoder.put_u64(0),
+            Some(blinder) => {
+                encoder.put_u64(1);
+                encoder.put_str(blinder);
+            }
+        }
+        encoder.put_usize(self.hashes.len());
+        for hash in &self.hashes {
+            encoder.put_u64(hash.borrow().is_left as u64);
+            encoder.put_str(&hash.borrow().value);
+        }
+        encoder.finish()
+    }
+
+    /// Deserialize a proof serialized by `to_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Serialized proof.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        let (mut decoder, algorithm) = Decoder::with_header(bytes, Artifact::Proof)?;
+        let concatenation = concatenation(&mut decoder)?;
+        let truncation = truncation(&mut decoder, algorithm)?;
+        let leaf_encoding = leaf_encoding(&mut decoder)?;
+        let num_of_leaves = decoder.usize()?;
+        let leaf_index = decoder.usize()?;
+        let leaf_content = decoder.string()?;
+        let blinder = match decoder.u64()? {
+            0 => None,
+            1 => Some(decoder.string()?),
+            _ => return Err(MerkleError::InvalidEncoding),
+        };
+        let hashes = (0..decoder.usize()?)
+            .map(|_| {
+                let is_left = match decoder.u64()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(MerkleError::InvalidEncoding),
+                };
+                let mut hash = Hash::new(decoder.string()?);
+                hash.is_left = is_left;
+                Ok(Rc::new(RefCell::new(hash)))
+            })
+            .collect::<Result<Vec<_>, MerkleError>>()?;
+        decoder.finish()?;
+
+        if leaf_index >= num_of_leaves {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        Ok(MerkleProof {
+            hashes,
+            num_of_leaves,
+            leaf_index,
+            leaf_content,
+            concatenation,
+            truncation,
+            algorithm,
+            leaf_encoding,
+            blinder,
+        })
+    }
+
+    /// Serialize the proof like `to_bytes`, as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.to_bytes())
+    }
+
+    /// Deserialize a proof serialized by `to_hex`. Hex strings of either case are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Serialized proof as hex.
+    pub fn from_hex(value: &str) -> Result<Self, MerkleError> {
+        Self::from_bytes(&hex::decode(value).ok_or(MerkleError::InvalidEncoding)?)
+    }
+
+    /// Serialize the proof like `to_bytes`, as unpadded base64 of the URL and filename safe
+    /// alphabet, so it can be sent in HTTP headers and query parameters without escaping.
+    pub fn to_base64(&self) -> String {
+        base64::encode_url_safe(&self.to_bytes())
+    }
+
+    /// Deserialize a proof serialized by `to_base64`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Serialized proof as base64.
+    pub fn from_base64(value: &str) -> Result<Self, MerkleError> {
+        Self::from_bytes(&base64::decode_url_safe(value).ok_or(MerkleError::InvalidEncoding)?)
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Solidity source for verifying proofs of a merkle tree on chain.
+//!
+//! Proofs carry the position of every audit hash, so the only settings of a tree that change
+//! verification are its hash function, `Concatenation` and truncation. Generating the
+//! verifier from the tree itself keeps both sides in agreement. Only hash functions with an
+//! EVM builtin and 32-byte digests are supported: SHA-256 and Keccak-256.
+//! Node values are passed as `bytes32`; truncated values are left-aligned and zero-padded.
+use crate::{Concatenation, HashAlgorithm, MerkleProof, MerkleTree};
+
+/// Return the source of a Solidity library `MerkleTreeVerifier` whose `verify` function
+/// accepts exactly the proofs generated by `tree`. Pass the arguments from `to_bytes32`
+/// and `proof_arguments`. For blinded leaves, pass the leaf content followed by its blinder
+/// as `leaf`. Return None if the hash function of `tree` has no EVM builtin.
+///
+/// # Arguments
+///
+/// * `tree` - Merkle tree whose configuration the verifier must match.
+pub fn verifier_source(tree: &MerkleTree) -> Option<String> {
+    let (hash, hash_name) = match tree.algorithm() {
+        HashAlgorithm::Sha256 => ("sha256", "SHA-256"),
+        #[cfg(feature = "sha3")]
+        HashAlgorithm::Keccak256 => ("keccak256", "Keccak-256"),
+        _ => return None,
+    };
+    let num_of_bytes = tree.truncation().unwrap_or(32);
+    let hash_pair = match tree.concatenation {
+        Concatenation::Hex => format!(
+            r#"    function hashPair(bytes32 left, bytes32 right) private pure returns (bytes32) {{
+        return truncate({hash}(abi.encodePacked(toHex(left), toHex(right))));
+    }}
+
+    function toHex(bytes32 value) private pure returns (bytes memory out) {{
+        bytes16 digits = "0123456789abcdef";
+        out = new bytes({hex_len});
+        for (uint256 i = 0; i < {num_of_bytes}; i++) {{
+            out[2 * i] = digits[uint8(value[i]) >> 4];
+            out[2 * i + 1] = digits[uint8(value[i]) & 0x0f];
+        }}
+    }}
+"#,
+            hex_len = 2 * num_of_bytes
+        ),
+        Concatenation::Bytes => {
+            let operands = if num_of_bytes < 32 {
+                format!("bytes{num_of_bytes}(left), bytes{num_of_bytes}(right)")
+            } else {
+                "left, right".to_owned()
+            };
+            format!(
+                r#"    function hashPair(bytes32 left, bytes32 right) private pure returns (bytes32) {{
+        return truncate({hash}(abi.encodePacked({operands})));
+    }}
+"#
+            )
+        }
+    };
+    let truncate = if num_of_bytes < 32 {
+        let mask = format!(
+            "{}{}",
+            "ff".repeat(num_of_bytes),
+            "00".repeat(32 - num_of_bytes)
+        );
+        format!(
+            r#"    function truncate(bytes32 value) private pure returns (bytes32) {{
+        return value & 0x{mask};
+    }}
+"#
+        )
+    } else {
+        r#"    function truncate(bytes32 value) private pure returns (bytes32) {
+        return value;
+    }
+"#
+        .to_owned()
+    };
+    let name = match tree.concatenation {
+        Concatenation::Hex => "hexdigest",
+        Concatenation::Bytes => "raw digest",
+    };
+    Some(format!(
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Verifies merkle proofs of a {hash_name} merkle tree with {num_of_bytes}-byte node values,
+/// whose parents hash the {name} concatenation of their children.
+library MerkleTreeVerifier {{
+    function verify(
+        bytes32 root,
+        bytes memory leaf,
+        bytes32[] memory proof,
+        bool[] memory isLeft
+    ) internal pure returns (bool) {{
+        require(proof.length == isLeft.length, "MerkleTreeVerifier: length mismatch");
+        bytes32 node = truncate({hash}(leaf));
+        for (uint256 i = 0; i < proof.length; i++) {{
+            node = isLeft[i] ? hashPair(proof[i], node) : hashPair(node, proof[i]);
+        }}
+        return node == root;
+    }}
+
+{hash_pair}
+{truncate}}}
+"#
+    ))
+}
+
+/// Return a node value as a 0x-prefixed `bytes32` argument, zero-padding truncated values.
+///
+/// # Arguments
+///
+/// * `value` - Node value as hexdigest.
+pub fn to_bytes32(value: &str) -> String {
+    format!("0x{:0<64}", value)
+}
+
+/// Return the `proof` and `isLeft` arguments of the generated `verify` function.
+///
+/// # Arguments
+///
+/// * `proof` - Merkle proof to pass on chain.
+pub fn proof_arguments(proof: &MerkleProof) -> (Vec<String>, Vec<bool>) {
+    proof
+        .hashes
+        .iter()
+        .map(|hash| {
+            let hash = hash.borrow();
+            (to_bytes32(&hash.value), hash.is_left)
+        })
+        .unzip()
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Export of merkle trees as directories of static files, which object storage or a CDN can
+//! serve without running any code, and a reader verifying everything it reads from them.
+//!
+//! A site holds `root.bin` with the canonical bytes of the `CommittedRoot`, `root.json` with
+//! the same root for humans and scripts, the tiles of the tree under `tile/` in the layout of
+//! the `tiles` module, and the serialized proof of every leaf under `proof/`, its index split
+//! into groups of 3 digits like tile indices, so leaf 1234067 is `proof/x001/x234/067`.
+use crate::tiles::{index_path, tiles_of, Tile, TileFetcher, TiledTree};
+use crate::{CommittedRoot, MerkleError, MerkleProof, MerkleTree};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Return an `io::ErrorKind::InvalidData` error for a file that does not decode or verify.
+///
+/// # Arguments
+///
+/// * `error` - Decoding or verification error.
+fn invalid_data(error: MerkleError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Write `bytes` to `path` under `dir`, creating missing parent directories.
+///
+/// # Arguments
+///
+/// * `dir` - Directory of the site.
+/// * `path` - Path of the file relative to `dir`.
+/// * `bytes` - Contents of the file.
+fn write_file(dir: &Path, path: &str, bytes: &[u8]) -> io::Result<()> {
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)
+}
+
+/// Write a tree as a static site into `dir`, replacing files of a previous export. Removed
+/// leaves have no proof file. Fail with `io::ErrorKind::InvalidInput` if the tree has no
+/// leaves.
+///
+/// # Arguments
+///
+/// * `dir` - Directory to write to, created if it does not exist.
+/// * `tree` - Tree to export.
+/// * `built_at` - When the tree was built, in seconds since the Unix epoch.
+/// * `tile_height` - Number of tree levels a tile spans.
+///
+/// # Panics
+///
+/// Panics if `tile_height` is 0 or above 30.
+pub fn export<P: AsRef<Path>>(
+    dir: P,
+    tree: &MerkleTree,
+    built_at: u64,
+    tile_height: u8,
+) -> io::Result<()> {
+    let dir = dir.as_ref();
+    let root = CommittedRoot::of(tree, built_at)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "tree has no leaves"))?;
+    write_file(dir, "root.bin", &root.to_bytes())?;
+    let json = format!(
+        "{{\"root\":\"{}\",\"leafCount\":{},\"builtAt\":{},\"tileHeight\":{}}}",
+        root.root, root.leaf_count, root.built_at, tile_height
+    );
+    write_file(dir, "root.json", json.as_bytes())?;
+    for (tile, bytes) in tiles_of(tree, tile_height) {
+        write_file(dir, &tile.path(), &bytes)?;
+    }
+    for leaf_index in 0..tree.num_of_leaves() {
+        if let Some(proof) = tree.proof(leaf_index) {
+            let path = format!("proof/{}", index_path(leaf_index));
+            write_file(dir, &path, &proof.to_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Tiles read from the `tile/` directory of a static site.
+#[derive(Debug, Clone)]
+pub struct DirectoryTiles {
+    dir: PathBuf,
+}
+
+impl DirectoryTiles {
+    /// Initialize a source of the tiles of a static site.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory of the site.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        DirectoryTiles {
+            dir: dir.as_ref().to_owned(),
+        }
+    }
+}
+
+impl TileFetcher for DirectoryTiles {
+    fn fetch(&self, tile: &Tile) -> Option<Vec<u8>> {
+        fs::read(self.dir.join(tile.path())).ok()
+    }
+}
+
+/// Static site written by `export`, whose proofs are verified against its root as they are
+/// read. Compare `root` with a root obtained from a trusted source before relying on it.
+#[derive(Debug, Clone)]
+pub struct StaticSite {
+    dir: PathBuf,
+    root: CommittedRoot,
+}
+
+impl StaticSite {
+    /// Open a static site by reading its root. Fail with `io::ErrorKind::InvalidData` if
+    /// `root.bin` does not decode.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory of the site.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref().to_owned();
+        let bytes = fs::read(dir.join("root.bin"))?;
+        let root = CommittedRoot::from_bytes(&bytes).map_err(invalid_data)?;
+        Ok(StaticSite { dir, root })
+    }
+
+    /// Published root of the site.
+    pub fn root(&self) -> &CommittedRoot {
+        &self.root
+    }
+
+    /// Read the proof of a leaf and verify it against the root. Fail with
+    /// `io::ErrorKind::NotFound` if the leaf has no proof file, and with
+    /// `io::ErrorKind::InvalidData` if it does not decode, is the proof of another leaf or
+    /// does not verify.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&self, leaf_index: usize) -> io::Result<MerkleProof> {
+        let path = self.dir.join(format!("proof/{}", index_path(leaf_index)));
+        let proof = MerkleProof::from_bytes(&fs::read(path)?).map_err(invalid_data)?;
+        if proof.leaf_index != leaf_index {
+            return Err(invalid_data(MerkleError::InvalidEncoding));
+        }
+        self.root.verify_proof(&proof).map_err(invalid_data)?;
+        Ok(proof)
+    }
+
+    /// Tiled tree assembling proofs from the tiles of the site, for leaves whose contents are
+    /// known from elsewhere.
+    ///
+    /// # Arguments
+    ///
+    /// * `tile_height` - Number of tree levels a tile spans, as given to `export`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tile_height` is 0 or above 30.
+    pub fn tiled(&self, tile_height: u8) -> TiledTree<DirectoryTiles> {
+        TiledTree::new(
+            self.root.clone(),
+            tile_height,
+            DirectoryTiles::new(&self.dir),
+        )
+    }
+}
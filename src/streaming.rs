@@ -0,0 +1,214 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::builder::LeafLimits;
+use crate::{Hash, MerkleError, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+/// Root of a perfect subtree over consecutive leaves pushed to a `StreamingProver`.
+struct Subtree {
+    /// Number of levels above the leaves, so the subtree holds `1 << height` leaves.
+    height: usize,
+
+    /// Node value as hexdigest.
+    value: String,
+
+    /// Indices into `StreamingProver::proofs` of the targets under this subtree.
+    targets: Vec<usize>,
+}
+
+/// Generate proofs for a set of leaves registered in advance during a single pass over all
+/// leaves, without building the tree. Only the roots of the perfect subtrees formed so far
+/// and the audit hashes of the targets are kept, so memory grows with the logarithm of the
+/// number of leaves for each target.
+///
+/// ```
+/// use merkle_tree::{MerkleTree, StreamingProver};
+///
+/// let mut prover = StreamingProver::new(&[1]);
+/// for leaf in ["a", "b", "c"] {
+///     prover.push(leaf);
+/// }
+/// let (root, proofs) = prover.finish();
+/// let tree = MerkleTree::from_leaves(&["a".to_string(), "b".to_string(), "c".to_string()]);
+/// assert_eq!(root.unwrap(), tree.root().unwrap().borrow().value);
+/// assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proofs[0].1));
+/// ```
+pub struct StreamingProver {
+    scheme: MerkleTree,
+    limits: LeafLimits,
+    targets: BTreeSet<usize>,
+    num_of_leaves: usize,
+    stack: Vec<Subtree>,
+    proofs: Vec<MerkleProof>,
+}
+
+impl StreamingProver {
+    /// Initialize a prover with the default scheme.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets` - 0-based indices of leaves to prove.
+    pub fn new(targets: &[usize]) -> Self {
+        Self::with_scheme(MerkleTree::new(), targets)
+    }
+
+    /// Initialize a prover that builds proofs like `scheme` does.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - Empty tree whose configuration is used.
+    /// * `targets` - 0-based indices of leaves to prove.
+    pub(crate) fn with_scheme(scheme: MerkleTree, targets: &[usize]) -> Self {
+        StreamingProver {
+            scheme,
+            limits: LeafLimits::default(),
+            targets: targets.iter().copied().collect(),
+            num_of_leaves: 0,
+            stack: Vec::new(),
+            proofs: Vec::new(),
+        }
+    }
+
+    /// Refuse leaves past `limits` when they are pushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - Limits on the leaves.
+    pub(crate) fn with_limits(mut self, limits: LeafLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Number of leaves pushed so far.
+    pub fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+
+    /// Push the next leaf.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the leaf is not valid in the leaf encoding of the scheme, or if it exceeds a
+    /// limit of the builder the prover was created with. Use `try_push` to handle the latter.
+    pub fn push(&mut self, leaf: &str) {
+        if let Err(error) = self.try_push(leaf) {
+            panic!("{error}");
+        }
+    }
+
+    /// Push the next leaf. Fail with `MerkleError::TooManyLeaves` if it is past `max_leaves`
+    /// of the builder the prover was created with, or with `MerkleError::LeafTooLarge` if it
+    /// is longer than `max_leaf_size`. A refused leaf is not pushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf` - Content of leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the leaf is not valid in the leaf encoding of the scheme.
+    pub fn try_push(&mut self, leaf: &str) -> Result<(), MerkleError> {
+        self.limits.check(self.num_of_leaves, leaf)?;
+        let leaf_index = self.num_of_leaves;
+        self.num_of_leaves += 1;
+        let mut subtree = Subtree {
+            height: 0,
+            value: self.scheme.leaf_value(leaf),
+            targets: Vec::new(),
+        };
+        if self.targets.remove(&leaf_index) {
+            subtree.targets.push(self.proofs.len());
+            self.proofs.push(MerkleProof {
+                hashes: Vec::new(),
+                num_of_leaves: 0,
+                leaf_index,
+                leaf_content: leaf.to_owned(),
+                concatenation: self.scheme.concatenation,
+                truncation: self.scheme.truncation,
+                algorithm: self.scheme.algorithm,
+                leaf_encoding: self.scheme.leaf_encoding,
+                blinder: self.scheme.blinder(leaf),
+            });
+        }
+
+        while let Some(left) = self.stack.pop() {
+            if left.height != subtree.height {
+                self.stack.push(left);
+                break;
+            }
+            subtree = self.join(left, subtree);
+        }
+        self.stack.push(subtree);
+        Ok(())
+    }
+
+    /// Finish the pass and return the root as hexdigest, or None if no leaves were pushed,
+    /// and the proofs of the targets in leaf order. Targets past the last leaf are skipped.
+    pub fn finish(mut self) -> (Option<String>, Vec<(usize, MerkleProof)>) {
+        let mut subtree = match self.stack.pop() {
+            Some(subtree) => subtree,
+            None => return (None, Vec::new()),
+        };
+        // Smaller subtrees on the right are combined first, and padded up to the height of
+        // their left neighbour as the siblingless rightmost node of each level would be.
+        while let Some(left) = self.stack.pop() {
+            while subtree.height < left.height {
+                if let Some(padding) = self.scheme.padding_sibling(&subtree.value) {
+                    self.audit(&subtree.targets, &padding, false);
+                }
+                subtree.value = self.scheme.parent_value(&subtree.value, None);
+                subtree.height += 1;
+            }
+            subtree = self.join(left, subtree);
+        }
+
+        let num_of_leaves = self.num_of_leaves;
+        let proofs = self
+            .proofs
+            .into_iter()
+            .map(|mut proof| {
+                proof.num_of_leaves = num_of_leaves;
+                (proof.leaf_index, proof)
+            })
+            .collect();
+        (Some(subtree.value), proofs)
+    }
+
+    /// Join two adjacent subtrees of the same height under their parent, adding each one to
+    /// the audit hashes of the targets under the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left subtree.
+    /// * `right` - Right subtree.
+    fn join(&mut self, mut left: Subtree, right: Subtree) -> Subtree {
+        self.audit(&left.targets, &right.value, false);
+        self.audit(&right.targets, &left.value, true);
+        left.targets.extend(right.targets);
+        Subtree {
+            height: left.height + 1,
+            value: self.scheme.parent_value(&left.value, Some(&right.value)),
+            targets: left.targets,
+        }
+    }
+
+    /// Append an audit hash to the proofs of targets.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets` - Indices into `proofs` of the targets.
+    /// * `value` - Audit hash as hexdigest.
+    /// * `is_left` - Whether the audit hash is a left child.
+    fn audit(&mut self, targets: &[usize], value: &str, is_left: bool) {
+        for &target in targets {
+            let mut hash = Hash::new(value.to_owned());
+            hash.is_left = is_left;
+            self.proofs[target].hashes.push(Rc::new(RefCell::new(hash)));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::verify::sibling_is_left;
+use crate::{proof_parent_value, telemetry, Concatenation, Hash, HashAlgorithm, MerkleTree};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Hold information needed to verify that an interior node, such as the root of one shard,
+/// is included at a specific position of a merkle tree.
+pub struct SubtreeProof {
+    /// List of audit hashes from the level of the subtree root up to the root node,
+    /// arranged from the bottom-most hash up to the top-most hash (closest to root node).
+    pub hashes: Vec<Rc<RefCell<Hash>>>,
+
+    /// Number of leaves in the merkle tree.
+    pub num_of_leaves: usize,
+
+    /// How child hashes are concatenated in the merkle tree.
+    pub concatenation: Concatenation,
+
+    /// Number of bytes node values are truncated to in the merkle tree, or None for full digests.
+    pub truncation: Option<usize>,
+
+    /// Hash function of the merkle tree.
+    pub algorithm: HashAlgorithm,
+}
+
+impl SubtreeProof {
+    /// Reconstruct the root node value implied by this proof for a subtree root, as hexdigest.
+    ///
+    /// # Arguments
+    ///
+    /// * `subtree_root` - Value of the subtree root as hexdigest.
+    pub fn root_value(&self, subtree_root: &str) -> String {
+        let mut result = subtree_root.to_owned();
+        for audit_hash in &self.hashes {
+            let audit_hash = audit_hash.borrow();
+            let (left, right) = if audit_hash.is_left {
+                (&audit_hash.value, &result)
+            } else {
+                (&result, &audit_hash.value)
+            };
+            result = proof_parent_value(
+                self.concatenation,
+                self.algorithm,
+                self.truncation,
+                left,
+                right,
+            );
+        }
+        result
+    }
+}
+
+/// Return the number of nodes on `level` of a merkle tree, or None if the tree has no
+/// such level.
+///
+/// # Arguments
+///
+/// * `num_of_leaves` - Number of leaves in the merkle tree.
+/// * `level` - Level, 0 for leaves.
+fn level_size(num_of_leaves: usize, level: usize) -> Option<usize> {
+    let mut size = num_of_leaves;
+    for _ in 0..level {
+        if size <= 1 {
+            return None;
+        }
+        size = size.div_ceil(2);
+    }
+    (size > 0).then_some(size)
+}
+
+impl MerkleTree {
+    /// Generate a proof that the node at `index` of `level` is included in this tree. The
+    /// node at index `i` of level `l` is the root of the subtree over leaves `i << l` up to
+    /// `(i + 1) << l`, exclusive. Return None if the tree has no such node.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the subtree root, 0 for leaves.
+    /// * `index` - Index of the subtree root within its level.
+    pub fn subtree_proof(&self, level: usize, index: usize) -> Option<SubtreeProof> {
+        let value = self.levels.get(level)?.get(index)?;
+        let top = self.levels.len() - 1;
+        let hashes = self.audit_hashes(level, top, index);
+        self.debug_check_audit_path(&hashes, level, top, index, value);
+        telemetry::proof_served(hashes.len());
+        Some(SubtreeProof {
+            hashes,
+            num_of_leaves: self.num_of_leaves(),
+            concatenation: self.concatenation,
+            truncation: self.truncation,
+            algorithm: self.algorithm,
+        })
+    }
+
+    /// Verify that `subtree_root` is the node at `subtree_position` of the merkle tree with
+    /// root `root`. Besides reconstructing the root, the sides of the audit hashes must match
+    /// the position, so a proof for one position does not verify at another.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle tree.
+    /// * `subtree_root` - Value of the subtree root as hexdigest.
+    /// * `subtree_position` - Level of the subtree root (0 for leaves) and its index within
+    ///   the level.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_subtree(
+        root: Rc<RefCell<Hash>>,
+        subtree_root: &str,
+        subtree_position: (usize, usize),
+        proof: &SubtreeProof,
+    ) -> bool {
+        let (level, index) = subtree_position;
+        let size = match level_size(proof.num_of_leaves, level) {
+            Some(size) if index < size => size,
+            _ => return false,
+        };
+        // A proof of a padded tree has an audit hash on every level, otherwise the siblingless
+        // rightmost nodes are skipped, so both shapes are accepted.
+        let promoted = sibling_is_left(index, size);
+        let padded: Vec<bool> = (0..size.next_power_of_two().trailing_zeros())
+            .map(|level| (index >> level) & 1 == 1)
+            .collect();
+        let actual: Vec<bool> = proof
+            .hashes
+            .iter()
+            .map(|hash| hash.borrow().is_left)
+            .collect();
+        (actual == promoted || actual == padded)
+            && proof.root_value(subtree_root) == root.borrow().value
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Merkle sum tree for proofs of reserves.
+//!
+//! Every node carries a hash and the sum of the amounts of all leaves below it. Parents commit
+//! to both the hashes and the sums of their children, so a proof shows that a leaf is included
+//! and that its amount is counted in the total at the root. Amounts are unsigned, so no leaf
+//! can offset the liabilities of another.
+use crate::Hash;
+
+/// Node of a merkle sum tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumNode {
+    /// Hash value as hexdigest.
+    pub value: String,
+
+    /// Sum of the amounts of all leaves below this node.
+    pub sum: u64,
+}
+
+impl SumNode {
+    /// Leaf node for an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Account identifier.
+    /// * `amount` - Account amount.
+    fn leaf(id: &str, amount: u64) -> Self {
+        SumNode {
+            value: Hash::hash(&format!("{}:{}:{:016x}", id.len(), id, amount)),
+            sum: amount,
+        }
+    }
+
+    /// Parent node of two children. Return None if the sum overflows.
+    ///
+    /// # Arguments
+    ///
+    /// * `left` - Left child node.
+    /// * `right` - Right child node.
+    fn parent(left: &SumNode, right: &SumNode) -> Option<Self> {
+        Some(SumNode {
+            value: Hash::hash(&format!(
+                "{}{:016x}{}{:016x}",
+                left.value, left.sum, right.value, right.sum
+            )),
+            sum: left.sum.checked_add(right.sum)?,
+        })
+    }
+}
+
+/// Hold information needed to verify that an account is included in a merkle sum tree.
+#[derive(Debug, Clone)]
+pub struct SumProof {
+    /// Audit nodes with whether each is a left child, arranged from the bottom-most node
+    /// up to the top-most node (closest to root node).
+    pub nodes: Vec<(SumNode, bool)>,
+
+    /// 0-based index of leaf node to be verified.
+    pub leaf_index: usize,
+
+    /// Account identifier of leaf node to be verified.
+    pub id: String,
+
+    /// Account amount of leaf node to be verified.
+    pub amount: u64,
+}
+
+/// Merkle sum tree over accounts and their amounts.
+pub struct SumTree {
+    /// Accounts in leaf order.
+    accounts: Vec<(String, u64)>,
+
+    /// Nodes from the bottom level (leaves) up to the top level (root node).
+    levels: Vec<Vec<SumNode>>,
+}
+
+impl SumTree {
+    /// Build a merkle sum tree. Return None if the total amount overflows `u64`.
+    ///
+    /// # Arguments
+    ///
+    /// * `accounts` - Account identifiers and amounts, in leaf order.
+    pub fn new(accounts: &[(String, u64)]) -> Option<Self> {
+        let mut level: Vec<SumNode> = accounts
+            .iter()
+            .map(|(id, amount)| SumNode::leaf(id, *amount))
+            .collect();
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => SumNode::parent(left, right),
+                    [last] => Some(last.to_owned()), // Last node has no sibling.
+                    _ => unreachable!(),
+                })
+                .collect::<Option<Vec<SumNode>>>()?;
+            levels.push(level);
+            level = parents;
+        }
+        levels.push(level);
+        Some(SumTree {
+            accounts: accounts.to_vec(),
+            levels,
+        })
+    }
+
+    /// Root node, holding the total amount. None if there are no accounts.
+    pub fn root(&self) -> Option<&SumNode> {
+        self.levels.last()?.first()
+    }
+
+    /// Generate a proof for an account. Return None if `leaf_index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    pub fn proof(&self, leaf_index: usize) -> Option<SumProof> {
+        let (id, amount) = self.accounts.get(leaf_index)?.to_owned();
+        let mut nodes = Vec::new();
+        let mut index = leaf_index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling_index = index ^ 1;
+            if let Some(sibling) = level.get(sibling_index) {
+                nodes.push((sibling.to_owned(), sibling_index < index));
+            } // Handle edge case for siblingless rightmost node on the level.
+            index /= 2;
+        }
+        Some(SumProof {
+            nodes,
+            leaf_index,
+            id,
+            amount,
+        })
+    }
+
+    /// Given a root node, verify that a proof reconstructs both its hash and its total.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Root node of the merkle sum tree.
+    /// * `proof` - Proof to be verified.
+    pub fn verify_proof(root: &SumNode, proof: &SumProof) -> bool {
+        let mut result = SumNode::leaf(&proof.id, proof.amount);
+        for (node, is_left) in &proof.nodes {
+            let parent = if *is_left {
+                SumNode::parent(node, &result)
+            } else {
+                SumNode::parent(&result, node)
+            };
+            match parent {
+                Some(parent) => result = parent,
+                None => return false,
+            }
+        }
+        result == *root
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Metrics emitted through the `metrics` facade when the `metrics` feature is enabled, to the
+//! recorder the application installs. Without the feature every function compiles to nothing.
+//!
+//! * `merkle_tree.leaves_appended` - Counter of leaves added by construction or `append`.
+//! * `merkle_tree.proofs_served` - Counter of proofs generated from trees.
+//! * `merkle_tree.proof_length` - Histogram of the number of audit hashes of those proofs.
+//! * `merkle_tree.verification_failures` - Counter of proofs that failed `verify_proof`.
+//! * `merkle_tree.hash_ops` - Counter of hash computations started with the provider.
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+/// Record leaves added to a tree.
+///
+/// # Arguments
+///
+/// * `num_of_leaves` - Number of leaves added.
+pub(crate) fn leaves_appended(num_of_leaves: usize) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("merkle_tree.leaves_appended").increment(num_of_leaves as u64);
+}
+
+/// Record a proof generated from a tree.
+///
+/// # Arguments
+///
+/// * `num_of_hashes` - Number of audit hashes of the proof.
+pub(crate) fn proof_served(num_of_hashes: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        ::metrics::counter!("merkle_tree.proofs_served").increment(1);
+        ::metrics::histogram!("merkle_tree.proof_length").record(num_of_hashes as f64);
+    }
+}
+
+/// Record the outcome of a proof verification.
+///
+/// # Arguments
+///
+/// * `verified` - Whether the proof verified.
+pub(crate) fn verification(verified: bool) {
+    #[cfg(feature = "metrics")]
+    if !verified {
+        ::metrics::counter!("merkle_tree.verification_failures").increment(1);
+    }
+}
+
+/// Record a hash computation started with the provider.
+pub(crate) fn hash_op() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("merkle_tree.hash_ops").increment(1);
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Deterministic generators of trees and proofs, so downstream crates can write reproducible
+//! tests against realistic trees without copying fixtures. Enabled by the `test-util`
+//! feature.
+//!
+//! Generated values depend only on the seed and are the same on every platform.
+use crate::rng::SplitMix64;
+use crate::{MerkleProof, MerkleTree};
+
+/// Largest number of leaves in a tree generated by `random_proof`.
+const MAX_LEAVES: usize = 64;
+
+/// Largest length of a generated leaf.
+const MAX_LEAF_LEN: usize = 32;
+
+/// Return `n` pseudo-random alphanumeric leaves of up to 31 characters, derived from `seed`.
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the generator.
+/// * `n` - Number of leaves.
+pub fn random_leaves(seed: u64, n: usize) -> Vec<String> {
+    let mut rng = SplitMix64::new(seed);
+    (0..n)
+        .map(|_| {
+            let len = rng.below(MAX_LEAF_LEN);
+            rng.alphanumeric(len)
+        })
+        .collect()
+}
+
+/// Build a tree with the default configuration from `random_leaves(seed, n)`.
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the generator.
+/// * `n` - Number of leaves.
+pub fn random_tree(seed: u64, n: usize) -> MerkleTree {
+    MerkleTree::from_leaves(&random_leaves(seed, n))
+}
+
+/// Build a tree of 1 to 64 pseudo-random leaves and return it with the proof of a
+/// pseudo-random leaf, all derived from `seed`.
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the generator.
+pub fn random_proof(seed: u64) -> (MerkleTree, MerkleProof) {
+    let mut rng = SplitMix64::new(seed);
+    let tree = random_tree(rng.next_u64(), 1 + rng.below(MAX_LEAVES));
+    let proof = tree.proof(rng.below(tree.num_of_leaves())).unwrap();
+    (tree, proof)
+}
@@ -0,0 +1,367 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Tiled storage of merkle trees, in the layout of Go sumdb and tiled transparency logs, so
+//! proofs can be assembled from static files served by a CDN instead of by a live tree.
+//!
+//! A tile of height `h` at tile level `L` holds up to `1 << h` consecutive node values of tree
+//! level `L * h`, as raw bytes, and only nodes whose subtree is complete, that is covers
+//! `1 << (L * h)` leaves. Nodes between tile levels are recomputed from the tile below them,
+//! and nodes on the right edge of a tree from their children, applying its padding rule.
+//! Complete nodes never change as leaves are appended, so full tiles can be cached forever;
+//! the rightmost tile of a level is partial until it fills up.
+use crate::{hex, CommittedRoot, Hash, MerkleError, MerkleProof, MerkleTree};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Coordinates of a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile {
+    /// Number of tree levels a tile spans, from 1 to 30.
+    pub height: u8,
+
+    /// Tile level, holding nodes of tree level `level * height`.
+    pub level: usize,
+
+    /// Index of the tile within its tile level.
+    pub index: usize,
+
+    /// Number of nodes in the tile, `1 << height` unless it is partial.
+    pub width: usize,
+}
+
+impl Tile {
+    /// Path of the tile relative to the root of a tile server, `tile/<height>/<level>/<index>`
+    /// with `.p/<width>` appended if it is partial. The index is split into groups of 3
+    /// digits, all but the last prefixed with `x`, so no directory holds more than 1000
+    /// entries: index 1234067 is `x001/x234/067`.
+    pub fn path(&self) -> String {
+        let mut path = format!(
+            "tile/{}/{}/{}",
+            self.height,
+            self.level,
+            index_path(self.index)
+        );
+        if self.width != 1 << self.height {
+            path.push_str(&format!(".p/{}", self.width));
+        }
+        path
+    }
+
+    /// Parse the path of a tile. Fail with `MerkleError::InvalidEncoding` if it is not a path
+    /// returned by `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the tile.
+    pub fn from_path(path: &str) -> Result<Self, MerkleError> {
+        let number = |value: &str| match value.parse::<usize>() {
+            Ok(number) if value == number.to_string() => Ok(number),
+            _ => Err(MerkleError::InvalidEncoding),
+        };
+        let mut parts: Vec<&str> = path.split('/').collect();
+        let width = match parts.iter().position(|part| part.ends_with(".p")) {
+            Some(position) if position + 2 == parts.len() => {
+                let width = number(parts.pop().unwrap())?;
+                let last = parts.last_mut().unwrap();
+                *last = last.strip_suffix(".p").unwrap();
+                Some(width)
+            }
+            Some(_) => return Err(MerkleError::InvalidEncoding),
+            None => None,
+        };
+        let [prefix, height, level, groups @ ..] = &parts[..] else {
+            return Err(MerkleError::InvalidEncoding);
+        };
+        let height = number(height)?;
+        if *prefix != "tile" || !(1..=30).contains(&height) || groups.is_empty() {
+            return Err(MerkleError::InvalidEncoding);
+        }
+        let mut index = 0usize;
+        for (position, group) in groups.iter().enumerate() {
+            let digits = match position + 1 == groups.len() {
+                true => Some(*group),
+                false => group.strip_prefix('x'),
+            };
+            let digits = digits
+                .filter(|digits| digits.len() == 3 && digits.bytes().all(|b| b.is_ascii_digit()))
+                .ok_or(MerkleError::InvalidEncoding)?;
+            index = index
+                .checked_mul(1000)
+                .and_then(|index| index.checked_add(digits.parse().unwrap()))
+                .ok_or(MerkleError::InvalidEncoding)?;
+        }
+        let full_width = 1 << height;
+        let width = match width {
+            Some(width) if width == 0 || width >= full_width => {
+                return Err(MerkleError::InvalidEncoding)
+            }
+            Some(width) => width,
+            None => full_width,
+        };
+        Ok(Tile {
+            height: height as u8,
+            level: number(level)?,
+            index,
+            width,
+        })
+    }
+}
+
+/// Split an index into groups of 3 digits, all but the last prefixed with `x`, for paths
+/// that keep directories small.
+///
+/// # Arguments
+///
+/// * `index` - Index to split.
+pub(crate) fn index_path(index: usize) -> String {
+    let mut path = format!("{:03}", index % 1000);
+    let mut rest = index / 1000;
+    while rest > 0 {
+        path = format!("x{:03}/{path}", rest % 1000);
+        rest /= 1000;
+    }
+    path
+}
+
+/// Source of tiles, such as a CDN or a directory of static files.
+pub trait TileFetcher {
+    /// Return the bytes of a tile, or None if it cannot be fetched. The bytes need not be
+    /// trusted, as proofs assembled from them are verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `tile` - Coordinates of the tile.
+    fn fetch(&self, tile: &Tile) -> Option<Vec<u8>>;
+}
+
+/// Tiles kept in memory, keyed by their paths.
+impl TileFetcher for HashMap<String, Vec<u8>> {
+    fn fetch(&self, tile: &Tile) -> Option<Vec<u8>> {
+        self.get(&tile.path()).cloned()
+    }
+}
+
+/// Return every tile of a tree with their bytes, in order of tile level and index, including
+/// the partial rightmost tile of every tile level.
+///
+/// # Arguments
+///
+/// * `tree` - Merkle tree.
+/// * `height` - Number of tree levels a tile spans.
+///
+/// # Panics
+///
+/// Panics if `height` is 0 or above 30.
+pub fn tiles_of(tree: &MerkleTree, height: u8) -> Vec<(Tile, Vec<u8>)> {
+    assert!(
+        (1..=30).contains(&height),
+        "tile height must be from 1 to 30"
+    );
+    let num_of_leaves = tree.levels.first().map_or(0, |leaves| leaves.len());
+    let mut tiles = Vec::new();
+    for level in 0.. {
+        let row = level * height as usize;
+        let num_of_complete = match tree.levels.get(row) {
+            Some(_) => num_of_leaves >> row,
+            None => 0,
+        };
+        if num_of_complete == 0 {
+            break;
+        }
+        for index in 0..num_of_complete.div_ceil(1 << height) {
+            let first = index << height;
+            let width = (num_of_complete - first).min(1 << height);
+            let bytes = (first..first + width)
+                .flat_map(|node| hex::decode(tree.levels[row].get(node).unwrap()).unwrap())
+                .collect();
+            let tile = Tile {
+                height,
+                level,
+                index,
+                width,
+            };
+            tiles.push((tile, bytes));
+        }
+    }
+    tiles
+}
+
+/// Merkle tree of a published root, whose proofs are assembled from tiles fetched on demand.
+/// Fetched tiles are kept for later proofs. Trees built with a blinding key are not
+/// supported, as their proofs need blinders that tiles do not hold.
+pub struct TiledTree<F: TileFetcher> {
+    root: CommittedRoot,
+
+    /// Empty tree with the configuration of the published tree.
+    scheme: MerkleTree,
+    height: u8,
+    fetcher: F,
+
+    /// Number of nodes of every level, from the leaf level up.
+    level_lengths: Vec<usize>,
+
+    /// Tiles fetched so far.
+    tiles: RefCell<HashMap<Tile, Vec<u8>>>,
+}
+
+impl<F: TileFetcher> TiledTree<F> {
+    /// Initialize a tiled tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Published root of the tree, which assembled proofs are verified against.
+    /// * `height` - Number of tree levels a tile spans.
+    /// * `fetcher` - Source of the tiles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `height` is 0 or above 30.
+    pub fn new(root: CommittedRoot, height: u8, fetcher: F) -> Self {
+        assert!(
+            (1..=30).contains(&height),
+            "tile height must be from 1 to 30"
+        );
+        let scheme = MerkleTree {
+            algorithm: root.algorithm,
+            concatenation: root.scheme.concatenation,
+            padding: root.scheme.padding.clone(),
+            ordering: root.scheme.ordering,
+            truncation: root.scheme.truncation,
+            leaf_encoding: root.scheme.leaf_encoding,
+            ..MerkleTree::default()
+        };
+        let mut level_lengths = Vec::new();
+        let mut len = root.leaf_count;
+        while len > 0 {
+            level_lengths.push(len);
+            len = if len == 1 { 0 } else { len.div_ceil(2) };
+        }
+        TiledTree {
+            root,
+            scheme,
+            height,
+            fetcher,
+            level_lengths,
+            tiles: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Source of the tiles.
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
+    /// Number of tiles fetched and kept so far.
+    pub fn num_of_cached_tiles(&self) -> usize {
+        self.tiles.borrow().len()
+    }
+
+    /// Assemble a merkle proof of a leaf from tiles and verify it against the published root.
+    /// Fail with `MerkleError::MissingTile` if a tile cannot be fetched, with
+    /// `MerkleError::InvalidEncoding` if a tile has the wrong size, and with
+    /// `MerkleError::ProofHashMismatch` if the proof does not reproduce the root, because a
+    /// tile or the leaf content is wrong.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - 0-based index of leaf node that needs to be verified.
+    /// * `leaf_content` - Content of the leaf, which tiles do not hold.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_index` is out of range.
+    pub fn proof(&self, leaf_index: usize, leaf_content: &str) -> Result<MerkleProof, MerkleError> {
+        assert!(
+            leaf_index < self.root.leaf_count,
+            "leaf index is out of range"
+        );
+        let mut hashes = Vec::new();
+        let mut index = leaf_index;
+        for level in 0..self.level_lengths.len() - 1 {
+            let sibling_index = index ^ 1;
+            let sibling = match sibling_index < self.level_lengths[level] {
+                true => Some(self.node(level, sibling_index)?),
+                false => self.scheme.padding_sibling(&self.node(level, index)?),
+            };
+            if let Some(sibling) = sibling {
+                let mut hash = Hash::new(sibling);
+                hash.is_left = sibling_index < index;
+                hashes.push(Rc::new(RefCell::new(hash)));
+            } // Handle edge case for promoted siblingless rightmost node on the level.
+            index /= 2;
+        }
+
+        let proof = MerkleProof {
+            hashes,
+            num_of_leaves: self.root.leaf_count,
+            leaf_index,
+            leaf_content: leaf_content.to_owned(),
+            concatenation: self.scheme.concatenation,
+            truncation: self.scheme.truncation,
+            algorithm: self.scheme.algorithm,
+            leaf_encoding: self.scheme.leaf_encoding,
+            blinder: None,
+        };
+        self.root.verify_proof(&proof)?;
+        Ok(proof)
+    }
+
+    /// Return the value of an existing node as hexdigest, from the tile holding the nodes
+    /// below it if its subtree is complete, or else from its children.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level of the node, 0 for leaves.
+    /// * `index` - Index of the node within its level.
+    fn node(&self, level: usize, index: usize) -> Result<String, MerkleError> {
+        if (index + 1) << level > self.root.leaf_count {
+            let left = self.node(level - 1, 2 * index)?;
+            let right = match 2 * index + 1 < self.level_lengths[level - 1] {
+                true => Some(self.node(level - 1, 2 * index + 1)?),
+                false => None,
+            };
+            return Ok(self.scheme.parent_value(&left, right.as_ref()));
+        }
+
+        let height = self.height as usize;
+        let row = level / height * height;
+        let first = index << (level - row);
+        let tile_index = first >> height;
+        let tile = Tile {
+            height: self.height,
+            level: level / height,
+            index: tile_index,
+            width: ((self.root.leaf_count >> row) - (tile_index << height)).min(1 << height),
+        };
+        let node_size = self
+            .scheme
+            .truncation
+            .unwrap_or(self.scheme.algorithm.digest_size());
+        if !self.tiles.borrow().contains_key(&tile) {
+            let bytes = self
+                .fetcher
+                .fetch(&tile)
+                .ok_or_else(|| MerkleError::MissingTile(tile.path()))?;
+            if bytes.len() != tile.width * node_size {
+                return Err(MerkleError::InvalidEncoding);
+            }
+            self.tiles.borrow_mut().insert(tile, bytes);
+        }
+
+        let tiles = self.tiles.borrow();
+        let offset = first - (tile_index << height);
+        let mut nodes: Vec<String> = tiles[&tile]
+            .chunks(node_size)
+            .skip(offset)
+            .take(1 << (level - row))
+            .map(hex::encode)
+            .collect();
+        while nodes.len() > 1 {
+            nodes = nodes
+                .chunks(2)
+                .map(|pair| self.scheme.parent_value(&pair[0], Some(&pair[1])))
+                .collect();
+        }
+        Ok(nodes.pop().unwrap())
+    }
+}
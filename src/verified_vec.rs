@@ -0,0 +1,129 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::{MerkleProof, MerkleTree};
+
+/// Vector that maintains a merkle root over its elements and proves every element it returns.
+///
+/// The leaf of an element is its `to_string()`, at the element's index. Pushing appends a leaf
+/// and only updates the path to the root; other mutations rebuild the tree.
+///
+/// ```
+/// use merkle_tree::{MerkleTree, VerifiedVec};
+///
+/// let mut vec = VerifiedVec::new();
+/// vec.push(7);
+/// vec.push(8);
+/// let (value, proof) = vec.get(1).unwrap();
+/// assert_eq!(*value, 8);
+/// assert!(MerkleTree::verify_proof(vec.tree().root().unwrap(), &proof));
+/// ```
+#[derive(Clone)]
+pub struct VerifiedVec<T: ToString> {
+    values: Vec<T>,
+    tree: MerkleTree,
+}
+
+impl<T: ToString> VerifiedVec<T> {
+    /// Create an empty vector.
+    pub fn new() -> Self {
+        VerifiedVec {
+            values: Vec::new(),
+            tree: MerkleTree::new(),
+        }
+    }
+
+    /// Create a vector holding `values`.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Elements in order.
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let tree = Self::build(&values);
+        VerifiedVec { values, tree }
+    }
+
+    /// Build the tree over elements.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Elements in order.
+    fn build(values: &[T]) -> MerkleTree {
+        let leaves: Vec<String> = values.iter().map(T::to_string).collect();
+        MerkleTree::from_leaves(&leaves)
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Elements in order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Merkle tree over the elements.
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Return the root hash as hexdigest, or None if there are no elements.
+    pub fn root(&self) -> Option<String> {
+        self.tree.root().map(|root| root.borrow().value.to_owned())
+    }
+
+    /// Return an element and the proof of its leaf, or None if `index` is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the element.
+    pub fn get(&self, index: usize) -> Option<(&T, MerkleProof)> {
+        Some((self.values.get(index)?, self.tree.proof(index)?))
+    }
+
+    /// Append an element.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Element to append.
+    pub fn push(&mut self, value: T) {
+        self.tree.append(value.to_string());
+        self.values.push(value);
+    }
+
+    /// Replace the element at `index` and return the previous one, or None if `index` is out
+    /// of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the element.
+    /// * `value` - New element.
+    pub fn set(&mut self, index: usize, value: T) -> Option<T> {
+        let previous = std::mem::replace(self.values.get_mut(index)?, value);
+        self.tree = Self::build(&self.values);
+        Some(previous)
+    }
+
+    /// Remove and return the last element, or None if there are no elements.
+    pub fn pop(&mut self) -> Option<T> {
+        let value = self.values.pop()?;
+        self.tree = Self::build(&self.values);
+        Some(value)
+    }
+}
+
+impl<T: ToString> Default for VerifiedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ToString> From<Vec<T>> for VerifiedVec<T> {
+    fn from(values: Vec<T>) -> Self {
+        Self::from_vec(values)
+    }
+}
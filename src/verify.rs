@@ -0,0 +1,169 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#[cfg(feature = "tree")]
+use crate::{Concatenation, Hash, HashAlgorithm, LeafEncoding, MerkleProof, MerkleTree};
+#[cfg(feature = "tree")]
+use std::cell::RefCell;
+#[cfg(feature = "tree")]
+use std::rc::Rc;
+
+/// For each level of the audit path of a leaf, return whether the sibling is a left child,
+/// arranged from the bottom-most level up to the top-most level (closest to root node).
+/// Levels where the target node has no sibling (promoted rightmost node) are skipped.
+///
+/// # Arguments
+///
+/// * `leaf_index` - 0-based index of leaf node.
+/// * `num_of_leaves` - Number of leaves in the merkle tree.
+#[cfg(feature = "tree")]
+pub(crate) fn sibling_is_left(leaf_index: usize, num_of_leaves: usize) -> Vec<bool> {
+    path_positions(leaf_index, num_of_leaves)
+        .into_iter()
+        .map(|(_, _, is_left)| is_left)
+        .collect()
+}
+
+/// Return the position of every audit node of a leaf as `(level, index, is_left)`, arranged
+/// from the bottom-most level up to the top-most level (closest to root node), where `level`
+/// is 0 for leaves, `index` is the index of the sibling within its level and `is_left` is
+/// whether the sibling is a left child. Only index arithmetic is done, no hashing, so storage
+/// systems can plan which nodes to fetch before fetching any.
+///
+/// Levels where the target node has no sibling (rightmost node of an odd level) are skipped,
+/// as they are in proofs of trees with `Padding::Promote`. Trees with other padding place a
+/// padding hash there instead, which is not a stored node. Return no positions if
+/// `leaf_index` is out of range.
+///
+/// # Arguments
+///
+/// * `leaf_index` - 0-based index of leaf node.
+/// * `num_of_leaves` - Number of leaves in the merkle tree.
+pub fn audit_path_positions(leaf_index: usize, num_of_leaves: usize) -> Vec<(usize, usize, bool)> {
+    if leaf_index >= num_of_leaves {
+        return Vec::new();
+    }
+    path_positions(leaf_index, num_of_leaves)
+}
+
+/// Same as `audit_path_positions`, but for any `leaf_index`, following the path an in-range
+/// index with the same bits would take.
+///
+/// # Arguments
+///
+/// * `leaf_index` - 0-based index of leaf node.
+/// * `num_of_leaves` - Number of leaves in the merkle tree.
+fn path_positions(mut leaf_index: usize, mut num_of_leaves: usize) -> Vec<(usize, usize, bool)> {
+    let mut positions = Vec::new();
+    let mut level = 0;
+    while num_of_leaves > 1 {
+        let sibling_index = leaf_index ^ 1;
+        if sibling_index < num_of_leaves {
+            positions.push((level, sibling_index, sibling_index < leaf_index));
+        }
+        leaf_index /= 2;
+        num_of_leaves = num_of_leaves.div_ceil(2);
+        level += 1;
+    }
+    positions
+}
+
+/// Verify that a leaf belongs to a merkle tree using only primitive values.
+///
+/// Returns false if `index` is out of range, if the number of `siblings` does not match
+/// the audit path length for a tree of `num_of_leaves` leaves, or if `leaf_bytes` is not
+/// valid UTF-8 (leaves of this merkle tree are strings).
+///
+/// # Arguments
+///
+/// * `root_hex` - Root node value as hexdigest.
+/// * `leaf_bytes` - Content of leaf node to be verified.
+/// * `index` - 0-based index of leaf node to be verified.
+/// * `num_of_leaves` - Number of leaves in the merkle tree. Needed to tell which levels
+///   have a siblingless rightmost node, and must come from the same trusted source as
+///   `root_hex`. It cannot be derived from `siblings`: the same leaf and siblings can
+///   reconstruct the same root at more than one index, e.g. leaf 2 of a 3-leaf tree also
+///   verifies as leaf 1 of a 2-leaf tree.
+/// * `siblings` - Audit hashes as hexdigests, arranged from the bottom-most hash up to the
+///   top-most hash (closest to root node).
+#[cfg(feature = "tree")]
+pub fn verify(
+    root_hex: &str,
+    leaf_bytes: &[u8],
+    index: usize,
+    num_of_leaves: usize,
+    siblings: &[&str],
+) -> bool {
+    if index >= num_of_leaves {
+        return false;
+    }
+    let directions = sibling_is_left(index, num_of_leaves);
+    if directions.len() != siblings.len() {
+        return false;
+    }
+    let Ok(leaf_content) = String::from_utf8(leaf_bytes.to_vec()) else {
+        return false;
+    };
+
+    let hashes = siblings
+        .iter()
+        .zip(directions)
+        .map(|(sibling, is_left)| {
+            let mut hash = Hash::new(sibling.to_string());
+            hash.is_left = is_left;
+            Rc::new(RefCell::new(hash))
+        })
+        .collect();
+    let proof = MerkleProof {
+        hashes,
+        num_of_leaves,
+        leaf_index: index,
+        leaf_content,
+        concatenation: Concatenation::Hex,
+        truncation: None,
+        algorithm: HashAlgorithm::Sha256,
+        leaf_encoding: LeafEncoding::Utf8,
+        blinder: None,
+    };
+    let root = Rc::new(RefCell::new(Hash::new(root_hex.to_string())));
+    MerkleTree::verify_proof(root, &proof)
+}
+
+/// Verify a proof against a SHA-256 merkle tree whose child digests are concatenated as raw
+/// bytes (`Concatenation::Bytes`), without allocating. Suitable for hot paths and embedded
+/// verifiers. Digests are computed with the built-in SHA-256 implementation rather than the
+/// installed `provider::CryptoProvider`, which allocates every digest.
+///
+/// Returns false if there are more than 64 siblings, or if `directions` has bits set beyond
+/// the number of siblings, so every proof has exactly one valid encoding.
+///
+/// # Arguments
+///
+/// * `root` - Root node value.
+/// * `leaf` - Bytes of the leaf content, hashed into the leaf node value.
+/// * `siblings` - Audit hashes, arranged from the bottom-most hash up to the top-most hash
+///   (closest to root node).
+/// * `directions` - Bit `i` is set if `siblings[i]` is a left child.
+pub fn verify_proof_in_place(
+    root: &[u8; 32],
+    leaf: &[u8],
+    siblings: &[[u8; 32]],
+    directions: u64,
+) -> bool {
+    use sha2::{Digest, Sha256};
+
+    if siblings.len() > 64 || directions.checked_shr(siblings.len() as u32).unwrap_or(0) != 0 {
+        return false;
+    }
+    let mut node: [u8; 32] = Sha256::digest(leaf).into();
+    for (level, sibling) in siblings.iter().enumerate() {
+        let (left, right) = match directions >> level & 1 {
+            1 => (sibling, &node),
+            _ => (&node, sibling),
+        };
+        node = Sha256::new()
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .into();
+    }
+    node == *root
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+use crate::provider::{self, Hasher};
+use crate::{hex, HashAlgorithm, StreamingProver};
+use std::io::{self, Write};
+
+/// Sink that splits everything written to it into chunks of `chunk_size` bytes, the last one
+/// possibly shorter, and computes the root of a merkle tree whose leaves are the SHA-256
+/// hexdigests of the chunks, in chunk order. Writing nothing yields a single empty chunk, so
+/// the root matches the one `cas::Cas` addresses the same bytes with. Chunks are hashed as
+/// they arrive, and only the roots of the perfect subtrees formed so far are kept.
+///
+/// ```
+/// use merkle_tree::MerkleWriter;
+/// use std::io;
+///
+/// let mut writer = MerkleWriter::new(4);
+/// io::copy(&mut &b"abcdefghij"[..], &mut writer).unwrap();
+/// assert_eq!(writer.num_of_chunks(), 3);
+/// let root = writer.finish();
+/// ```
+pub struct MerkleWriter {
+    chunk_size: usize,
+    hasher: Box<dyn Hasher>,
+    filled: usize,
+    num_of_chunks: usize,
+    prover: StreamingProver,
+}
+
+impl MerkleWriter {
+    /// Initialize a writer with no bytes written.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_size` - Number of bytes of every chunk but the last.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn new(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be at least 1 byte");
+        MerkleWriter {
+            chunk_size,
+            hasher: provider::hasher(HashAlgorithm::Sha256),
+            filled: 0,
+            num_of_chunks: 0,
+            prover: StreamingProver::new(&[]),
+        }
+    }
+
+    /// Number of chunks written so far, including the chunk being filled.
+    pub fn num_of_chunks(&self) -> usize {
+        self.num_of_chunks + usize::from(self.filled > 0)
+    }
+
+    /// Hash the chunk being filled into a leaf and start the next chunk.
+    fn end_chunk(&mut self) {
+        let hasher = std::mem::replace(&mut self.hasher, provider::hasher(HashAlgorithm::Sha256));
+        self.prover.push(&hex::encode(&hasher.finalize()));
+        self.filled = 0;
+        self.num_of_chunks += 1;
+    }
+
+    /// Hash the last chunk and return the root as hexdigest.
+    pub fn finish(mut self) -> String {
+        if self.filled > 0 || self.num_of_chunks == 0 {
+            self.end_chunk();
+        }
+        self.prover.finish().0.unwrap()
+    }
+}
+
+impl Write for MerkleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let (head, tail) = rest.split_at(rest.len().min(self.chunk_size - self.filled));
+            self.hasher.update(head);
+            self.filled += head.len();
+            if self.filled == self.chunk_size {
+                self.end_chunk();
+            }
+            rest = tail;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::accumulator::{Accumulator, AccumulatorProof, Forest};
+
+#[test]
+fn test_add_and_verify() {
+    let mut forest = Forest::new();
+    for i in 0..33 {
+        assert_eq!(forest.add(&format!("utxo{}", i)), i);
+        let accumulator = forest.accumulator();
+        assert_eq!(
+            accumulator.roots().len(),
+            accumulator.num_of_leaves().count_ones() as usize
+        );
+        for j in 0..=i {
+            let proof = forest.proof(j).unwrap();
+            assert!(accumulator.verify(&format!("utxo{}", j), &proof));
+            assert!(!accumulator.verify(&format!("utxo{}", j + 1), &proof));
+        }
+    }
+    assert!(forest.proof(33).is_none());
+}
+
+#[test]
+fn test_delete() {
+    let mut forest = Forest::new();
+    for i in 0..13 {
+        forest.add(&format!("utxo{}", i));
+    }
+    let mut accumulator = forest.accumulator().clone();
+    for i in [3, 0, 12, 8, 1, 2] {
+        let proof = forest.proof(i).unwrap();
+        assert!(accumulator.delete(&format!("utxo{}", i), &proof));
+        assert!(!accumulator.delete(&format!("utxo{}", i), &proof));
+        assert!(forest.delete(i));
+        assert!(!forest.delete(i));
+        assert_eq!(&accumulator, forest.accumulator());
+        assert!(forest.proof(i).is_none());
+        for j in 0..13 {
+            if let Some(proof) = forest.proof(j) {
+                assert!(accumulator.verify(&format!("utxo{}", j), &proof));
+            }
+        }
+    }
+    // Deleting every leaf of a tree empties its root.
+    for i in [4, 5, 6, 7, 9, 10, 11] {
+        forest.delete(i);
+    }
+    assert!(forest.accumulator().roots().iter().all(Option::is_none));
+}
+
+#[test]
+fn test_proof_updates() {
+    // A light client keeps only the accumulator and proofs of its own leaves.
+    let mut forest = Forest::new();
+    let mut accumulator = Accumulator::new();
+    let mut owned: Vec<(String, AccumulatorProof)> = Vec::new();
+    for i in 0..40u64 {
+        let leaf = format!("utxo{}", i);
+        for (_, proof) in owned.iter_mut() {
+            proof.update_after_add(&accumulator, &leaf);
+        }
+        accumulator.add(&leaf);
+        forest.add(&leaf);
+        if i % 3 == 0 {
+            owned.push((leaf, forest.proof(i).unwrap()));
+        }
+        if i % 5 == 4 {
+            // Someone else spends a leaf.
+            let spent = i - 2;
+            if let Some(spent_proof) = forest.proof(spent) {
+                assert!(accumulator.delete(&format!("utxo{}", spent), &spent_proof));
+                forest.delete(spent);
+                owned.retain(|(_, proof)| proof.position != spent);
+                for (_, proof) in owned.iter_mut() {
+                    proof.update_after_delete(&spent_proof);
+                }
+            }
+        }
+        for (leaf, proof) in &owned {
+            assert!(accumulator.verify(leaf, proof));
+            assert_eq!(Some(proof), forest.proof(proof.position).as_ref());
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "sha3")]
+use merkle_tree::{HashAlgorithm, MerkleError, MerkleProof, MerkleTree, TreeDelta};
+
+fn data() -> Vec<String> {
+    ["abc", "bcd", "cde", "def", "efg"]
+        .iter()
+        .map(|leaf| leaf.to_string())
+        .collect()
+}
+
+const ALGORITHMS: [HashAlgorithm; 4] = [
+    HashAlgorithm::Sha256,
+    HashAlgorithm::Sha3_256,
+    HashAlgorithm::Sha3_512,
+    HashAlgorithm::Keccak256,
+];
+
+// Digests of "abc" from FIPS 180-4, FIPS 202 and the Keccak reference implementation.
+#[test]
+fn test_digest_vectors() {
+    for (algorithm, digest) in [
+        (
+            HashAlgorithm::Sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            HashAlgorithm::Sha3_256,
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        ),
+        (
+            HashAlgorithm::Sha3_512,
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+        ),
+        (
+            HashAlgorithm::Keccak256,
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+        ),
+    ] {
+        assert_eq!(algorithm.hash(b"abc"), digest);
+        assert_eq!(algorithm.digest_size() * 2, digest.len());
+    }
+}
+
+#[test]
+fn test_root_vectors() {
+    for (algorithm, root) in [
+        (
+            HashAlgorithm::Sha256,
+            "b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073",
+        ),
+        (
+            HashAlgorithm::Sha3_256,
+            "d3b666b62f00a6203a0ca56a23944a96c143ab7f3f300fa59458eb00671f8aa6",
+        ),
+        (
+            HashAlgorithm::Sha3_512,
+            "f707485c0f5115a33250115a4163306edb626088adc57a19c109e25ca890cd04ad385fd7bb16da0b59ffda8126fad488b0107b04109b82293330736d07d13885",
+        ),
+    ] {
+        let tree = MerkleTree::builder().algorithm(algorithm).build(&data());
+        assert_eq!(tree.root().unwrap().borrow().value, root);
+    }
+}
+
+#[test]
+fn test_proofs_and_serialization() {
+    for algorithm in ALGORITHMS {
+        let tree = MerkleTree::builder()
+            .algorithm(algorithm)
+            .truncation(40)
+            .build(&data());
+        assert_eq!(
+            tree.truncation(),
+            Some(40).filter(|_| algorithm == HashAlgorithm::Sha3_512)
+        );
+        let root = tree.root().unwrap();
+        for leaf_index in 0..data().len() {
+            let proof = tree.proof(leaf_index).unwrap();
+            assert_eq!(proof.algorithm, algorithm);
+            assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+
+            let restored = MerkleProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(restored.algorithm, algorithm);
+            assert!(MerkleTree::verify_proof(root.to_owned(), &restored));
+        }
+
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(restored.algorithm(), algorithm);
+        assert_eq!(restored.root().unwrap().borrow().value, root.borrow().value);
+
+        // Trees of different hash functions are never mixed up.
+        let other = MerkleTree::builder()
+            .algorithm(if algorithm == HashAlgorithm::Sha256 {
+                HashAlgorithm::Keccak256
+            } else {
+                HashAlgorithm::Sha256
+            })
+            .build(&[]);
+        let mut target = other.clone();
+        assert_eq!(
+            TreeDelta::between(&other, &tree).apply(&mut target),
+            Err(MerkleError::DeltaBaseMismatch)
+        );
+    }
+}
+
+#[test]
+fn test_solidity_verifier() {
+    let tree = MerkleTree::builder()
+        .algorithm(HashAlgorithm::Keccak256)
+        .build(&data());
+    let source = merkle_tree::solidity::verifier_source(&tree).unwrap();
+    assert!(source.contains("bytes32 node = truncate(keccak256(leaf));"));
+
+    for algorithm in [HashAlgorithm::Sha3_256, HashAlgorithm::Sha3_512] {
+        let tree = MerkleTree::builder().algorithm(algorithm).build(&data());
+        assert!(merkle_tree::solidity::verifier_source(&tree).is_none());
+    }
+}
+
+#[test]
+fn test_tombstone_digest_size() {
+    assert_eq!(HashAlgorithm::Sha256.tombstone(), merkle_tree::TOMBSTONE);
+    for algorithm in ALGORITHMS {
+        let mut tree = MerkleTree::builder().algorithm(algorithm).build(&data());
+        assert!(tree.remove(1));
+        assert!(tree.is_removed(1));
+        assert_eq!(algorithm.tombstone().len(), 2 * algorithm.digest_size());
+        assert_eq!(tree.levels()[0][1], algorithm.tombstone());
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert!(restored.is_removed(1));
+        assert!(tree.proof(1).is_none());
+    }
+}
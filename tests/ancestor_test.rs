@@ -0,0 +1,52 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{Hash, MerkleTree, Padding};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn node(value: &str) -> Rc<RefCell<Hash>> {
+    Rc::new(RefCell::new(Hash {
+        parent: None,
+        left: None,
+        right: None,
+        value: value.to_owned(),
+        is_left: true,
+    }))
+}
+
+#[test]
+fn test_proof_to_ancestor() {
+    for padding in [Padding::Promote, Padding::DuplicateLast] {
+        let tree = MerkleTree::builder().padding(padding).build(&leaves(7));
+        let levels = tree.levels();
+        for leaf_index in 0..7 {
+            for (level, nodes) in levels.iter().enumerate() {
+                let proof = tree.proof_to_ancestor(leaf_index, level).unwrap();
+                assert!(proof.hashes.len() <= level);
+                assert!(MerkleTree::verify_proof(
+                    node(&nodes[leaf_index >> level]),
+                    &proof
+                ));
+            }
+            assert_eq!(
+                tree.proof_to_ancestor(leaf_index, levels.len() - 1)
+                    .unwrap()
+                    .root_value(),
+                tree.proof(leaf_index).unwrap().root_value()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_proof_to_ancestor_out_of_range() {
+    let mut tree = MerkleTree::from_leaves(&leaves(4));
+    assert!(tree.proof_to_ancestor(4, 1).is_none());
+    assert!(tree.proof_to_ancestor(0, 3).is_none());
+    tree.remove(1);
+    assert!(tree.proof_to_ancestor(1, 1).is_none());
+    assert!(MerkleTree::new().proof_to_ancestor(0, 0).is_none());
+}
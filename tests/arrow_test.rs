@@ -0,0 +1,59 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, Date32Array, Float64Array, Int64Array, RecordBatch, StringArray};
+use merkle_tree::{arrow, MerkleError, MerkleTree};
+use std::sync::Arc;
+
+fn columns() -> Vec<(&'static str, ArrayRef)> {
+    vec![
+        ("id", Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef),
+        (
+            "name",
+            Arc::new(StringArray::from(vec![Some("alice"), None, Some("carol")])),
+        ),
+        ("score", Arc::new(Float64Array::from(vec![1.5, 2.0, -0.0]))),
+    ]
+}
+
+#[test]
+fn test_row_leaf() {
+    let batch = RecordBatch::try_from_iter(columns()).unwrap();
+    assert_eq!(
+        arrow::row_leaf(&batch, 0).unwrap(),
+        "2:id5:Int64=1:14:name4:Utf8=5:alice5:score7:Float64=16:3ff8000000000000"
+    );
+    assert_eq!(
+        arrow::row_leaf(&batch, 1).unwrap(),
+        "2:id5:Int64=1:24:name4:Utf8-5:score7:Float64=16:4000000000000000"
+    );
+
+    // Columns are hashed in canonical order, whatever the order of the schema.
+    let reordered = RecordBatch::try_from_iter(columns().into_iter().rev()).unwrap();
+    assert_eq!(arrow::leaves(&reordered), arrow::leaves(&batch));
+}
+
+#[test]
+fn test_row_proofs() {
+    let batch = RecordBatch::try_from_iter(columns()).unwrap();
+    let tree = arrow::tree(&batch).unwrap();
+    let root = tree.root().unwrap();
+    assert_eq!(
+        root.borrow().value,
+        MerkleTree::merkle_root(&arrow::leaves(&batch).unwrap())
+            .borrow()
+            .value
+    );
+    for row in 0..batch.num_rows() {
+        let proof = tree.proof(row).unwrap();
+        assert_eq!(proof.leaf_content, arrow::row_leaf(&batch, row).unwrap());
+        assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+    }
+
+    let mut unsupported = columns();
+    unsupported.push(("day", Arc::new(Date32Array::from(vec![0, 1, 2]))));
+    let batch = RecordBatch::try_from_iter(unsupported).unwrap();
+    assert_eq!(
+        arrow::tree(&batch).err(),
+        Some(MerkleError::UnsupportedColumn("day".to_string()))
+    );
+}
@@ -0,0 +1,26 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tokio")]
+use merkle_tree::{Hash, MerkleTree};
+
+#[tokio::test]
+async fn test_hash_async_reader() {
+    for value in ["", "0", "abc", &"x".repeat(100_000)] {
+        assert_eq!(
+            Hash::hash_async_reader(value.as_bytes()).await.unwrap(),
+            Hash::hash(value)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_merkle_root_from_async_readers() {
+    let leaves: Vec<String> = (0..=8).map(|i| i.to_string()).collect();
+    let root =
+        MerkleTree::merkle_root_from_async_readers(leaves.iter().map(|leaf| leaf.as_bytes()))
+            .await
+            .unwrap();
+    assert_eq!(
+        root.borrow().value,
+        MerkleTree::merkle_root(&leaves).borrow().value
+    );
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::attestation::{statement, PREDICATE_TYPE, STATEMENT_TYPE};
+use merkle_tree::json_tree::flatten;
+use merkle_tree::manifest::Manifest;
+use merkle_tree::HashAlgorithm;
+
+#[test]
+fn test_statement() {
+    let files = [("b \"quoted\".bin", b"b".as_slice()), ("a.txt", b"abc")];
+    let manifest = Manifest::of_files(HashAlgorithm::Sha256, &files).unwrap();
+    let statement = statement(&manifest).unwrap();
+
+    let fields = flatten(&statement).unwrap();
+    let field = |pointer: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == pointer)
+            .map(|(_, value)| value.as_str())
+            .unwrap()
+    };
+    assert_eq!(field("/_type"), format!("\"{}\"", STATEMENT_TYPE));
+    assert_eq!(field("/predicateType"), format!("\"{}\"", PREDICATE_TYPE));
+    assert_eq!(field("/subject/0/name"), "\"a.txt\"");
+    assert_eq!(
+        field("/subject/0/digest/sha256"),
+        "\"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\""
+    );
+    assert_eq!(field("/subject/1/name"), "\"b \\\"quoted\\\".bin\"");
+    assert_eq!(field("/predicate/root"), format!("\"{}\"", manifest.root));
+    assert_eq!(field("/predicate/algorithm"), "\"sha256\"");
+    assert_eq!(field("/predicate/leafCount"), "2");
+    assert_eq!(fields.len(), 12);
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_unsupported_algorithm() {
+    use merkle_tree::MerkleError;
+
+    let manifest = Manifest::of_files(HashAlgorithm::Keccak256, &[("a", b"abc")]).unwrap();
+    assert_eq!(statement(&manifest), Err(MerkleError::UnsupportedScheme));
+    let manifest = Manifest::of_files(HashAlgorithm::Sha3_256, &[("a", b"abc")]).unwrap();
+    assert!(statement(&manifest)
+        .unwrap()
+        .contains("\"digest\":{\"sha3_256\":"));
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{Hash, LeafOrdering, MerkleProof, MerkleTree};
+use sha2::{Digest, Sha256};
+
+const KEY: [u8; 32] = [7; 32];
+
+fn leaves() -> Vec<String> {
+    ["yes", "no", "no", "yes", "abstain"]
+        .iter()
+        .map(|leaf| leaf.to_string())
+        .collect()
+}
+
+#[test]
+fn test_blinding() {
+    let tree = MerkleTree::builder().blinding(KEY).build(&leaves());
+    let root = tree.root().unwrap();
+    assert_ne!(
+        root.borrow().value,
+        MerkleTree::merkle_root(&leaves()).borrow().value
+    );
+    assert_eq!(tree.blinding_key(), Some(KEY));
+
+    for (leaf_index, leaf) in leaves().iter().enumerate() {
+        let proof = tree.proof(leaf_index).unwrap();
+        let blinder = format!("{:x}", Sha256::digest([&KEY[..], leaf.as_bytes()].concat()));
+        assert_eq!(proof.blinder.as_deref(), Some(blinder.as_str()));
+        assert_eq!(
+            proof.leaf_value(),
+            Hash::hash(&format!("{}{}", leaf, blinder))
+        );
+        assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+
+        let restored = MerkleProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(restored.blinder, proof.blinder);
+        assert!(MerkleTree::verify_proof(root.to_owned(), &restored));
+
+        let mut unblinded = proof;
+        unblinded.blinder = None;
+        assert!(!MerkleTree::verify_proof(root.to_owned(), &unblinded));
+    }
+
+    // The audit hash of a sibling is not the plain hash of any candidate content.
+    let proof = tree.proof(0).unwrap();
+    let sibling = proof.hashes[0].borrow().value.to_owned();
+    for candidate in ["yes", "no", "abstain"] {
+        assert_ne!(sibling, Hash::hash(candidate));
+    }
+}
+
+#[test]
+fn test_blinding_serialization() {
+    let mut tree = MerkleTree::builder()
+        .blinding(KEY)
+        .ordering(LeafOrdering::SortedByHash)
+        .build(&leaves());
+    tree.remove(1);
+    let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+    assert_eq!(restored.blinding_key(), Some(KEY));
+    assert_eq!(
+        restored.root().unwrap().borrow().value,
+        tree.root().unwrap().borrow().value
+    );
+    assert!(
+        MerkleTree::from_bytes(&MerkleTree::from_leaves(&leaves()).to_bytes())
+            .unwrap()
+            .blinding_key()
+            .is_none()
+    );
+}
@@ -0,0 +1,391 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::provider::CustomDigest;
+use merkle_tree::{Concatenation, Hash, HashAlgorithm, MerkleError, MerkleTree, Padding};
+use sha2::{Digest, Sha512};
+
+fn data() -> Vec<String> {
+    ["abc", "bcd", "cde", "def", "efg"]
+        .iter()
+        .map(|leaf| leaf.to_string())
+        .collect()
+}
+
+#[test]
+fn test_concatenation() {
+    let (h0, h1) = (Hash::hash("0"), Hash::hash("1"));
+    assert_eq!(
+        Concatenation::Hex.hash(&h0, &h1),
+        Hash::hash(&format!("{}{}", h0, h1))
+    );
+    assert_eq!(
+        Concatenation::Bytes.hash(&h0, &h1),
+        "b9b10a1bc77d2a241d120324db7f3b81b2edb67eb8e9cf02af9c95d30329aef5"
+    );
+
+    for (concatenation, root) in [
+        (
+            Concatenation::Hex,
+            "b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073",
+        ),
+        (
+            Concatenation::Bytes,
+            "5ef8b5df330a94e9af18e6d9e638629c0592b135abd02323a894a3adcf27b65a",
+        ),
+    ] {
+        let tree = MerkleTree::builder()
+            .concatenation(concatenation)
+            .build(&data());
+        assert_eq!(tree.root().unwrap().borrow().value, root);
+
+        let mut appended = MerkleTree::builder()
+            .concatenation(concatenation)
+            .build(&[]);
+        for leaf in data() {
+            appended.append(leaf);
+        }
+        assert_eq!(appended.root().unwrap().borrow().value, root);
+
+        for leaf_index in 0..data().len() {
+            let mut proof = tree.proof(leaf_index).unwrap();
+            assert_eq!(proof.concatenation, concatenation);
+            assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+            proof.leaf_content += "tainted";
+            assert!(!MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+        }
+    }
+}
+
+#[test]
+fn test_concatenation_not_hex() {
+    let tree = MerkleTree::builder()
+        .concatenation(Concatenation::Bytes)
+        .build(&data());
+    let proof = tree.proof(0).unwrap();
+    // Under the raw bytes fallback, the text "zz" hashed like the hexdigest "7a7a".
+    proof.hashes[0].borrow_mut().value = "zz".to_string();
+    assert!(!MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+    assert!(!MerkleTree::verify_proof_verbose(tree.root().unwrap(), &proof).is_valid());
+}
+
+#[test]
+#[should_panic(expected = "child hash is not a hexdigest")]
+fn test_concatenation_not_hex_panics() {
+    Concatenation::Bytes.hash("zz", &Hash::hash("0"));
+}
+
+#[test]
+fn test_padding() {
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    for (padding, root) in [
+        (
+            Padding::Promote,
+            "a45a2925b7c95556029e0b7e0636a74741354feef1d9078a01d5d4c4c4584f6d",
+        ),
+        (
+            Padding::DuplicateLast,
+            "7f277729978cc43411d2ebad2ff023e66a50f00625810c416ca16846909306ad",
+        ),
+        (
+            Padding::Constant("0".repeat(64)),
+            "8df3af2b861dc17a657d0dd13ddf9b21581da344cce867cb70b99cd9d48ee621",
+        ),
+    ] {
+        let tree = MerkleTree::builder()
+            .padding(padding.to_owned())
+            .build(&leaves);
+        assert_eq!(tree.root().unwrap().borrow().value, root);
+
+        for num_of_leaves in 1..=20 {
+            let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+            let tree = MerkleTree::builder()
+                .padding(padding.to_owned())
+                .build(&leaves);
+            let mut appended = MerkleTree::builder().padding(padding.to_owned()).build(&[]);
+            for leaf in &leaves {
+                appended.append(leaf.to_owned());
+            }
+            let root = tree.root().unwrap();
+            assert_eq!(appended.root().unwrap().borrow().value, root.borrow().value);
+            for leaf_index in 0..num_of_leaves {
+                let proof = appended.proof(leaf_index).unwrap();
+                assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_padding_constant_invalid() {
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    for (truncation, constant) in [
+        (None, "0".repeat(62)),
+        (None, "0".repeat(66)),
+        (None, "A".repeat(64)),
+        (None, "z".repeat(64)),
+        (Some(20), "0".repeat(64)),
+    ] {
+        let mut builder = MerkleTree::builder().padding(Padding::Constant(constant));
+        if let Some(num_of_bytes) = truncation {
+            builder = builder.truncation(num_of_bytes);
+        }
+        assert_eq!(
+            builder.try_build(&leaves).err(),
+            Some(MerkleError::InvalidEncoding)
+        );
+    }
+
+    let tree = MerkleTree::builder()
+        .padding(Padding::Constant("0".repeat(40)))
+        .truncation(20)
+        .build(&leaves);
+    assert_eq!(tree.root().unwrap().borrow().value.len(), 40);
+}
+
+#[test]
+#[should_panic(expected = "padding constant is not a hexdigest of the node size")]
+fn test_padding_constant_invalid_panics() {
+    MerkleTree::builder()
+        .padding(Padding::Constant("0".repeat(62)))
+        .build(&data());
+}
+
+#[test]
+fn test_hasher() {
+    let sha512 = |value: &[u8]| format!("{:x}", Sha512::digest(value));
+    let (h0, h1) = (sha512(b"0"), sha512(b"1"));
+    let tree = MerkleTree::builder()
+        .hasher::<Sha512>()
+        .build(&["0".to_string(), "1".to_string()]);
+    assert_eq!(
+        tree.algorithm(),
+        HashAlgorithm::Custom(CustomDigest::new::<Sha512>())
+    );
+    assert_eq!(tree.algorithm().digest_size(), 64);
+    assert_eq!(
+        tree.root().unwrap().borrow().value,
+        sha512(format!("{}{}", h0, h1).as_bytes())
+    );
+
+    let tree = MerkleTree::builder().hasher::<Sha512>().build(&data());
+    let mut appended = MerkleTree::builder().hasher::<Sha512>().build(&[]);
+    for leaf in data() {
+        appended.append(leaf);
+    }
+    assert_eq!(
+        appended.root().unwrap().borrow().value,
+        tree.root().unwrap().borrow().value
+    );
+    for leaf_index in 0..data().len() {
+        let mut proof = tree.proof(leaf_index).unwrap();
+        assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+        proof.leaf_content += "tainted";
+        assert!(!MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+    }
+
+    // The digest is not recorded, so it cannot be deserialized.
+    assert_eq!(
+        MerkleTree::from_bytes(&tree.to_bytes()).err(),
+        Some(MerkleError::UnsupportedScheme)
+    );
+}
+
+#[test]
+fn test_truncation() {
+    let leaves = data();
+    for (concatenation, root) in [
+        (
+            Concatenation::Hex,
+            "4724d24e5f12d4b06bd98ae42ac6cae89aa26a47",
+        ),
+        (
+            Concatenation::Bytes,
+            "7190acfd74a204526b671e0b0bb45dfe49dd7b79",
+        ),
+    ] {
+        let tree = MerkleTree::builder()
+            .concatenation(concatenation)
+            .truncation(20)
+            .build(&leaves);
+        assert_eq!(tree.truncation(), Some(20));
+        assert_eq!(tree.root().unwrap().borrow().value, root);
+
+        let mut appended = MerkleTree::builder()
+            .concatenation(concatenation)
+            .truncation(20)
+            .build(&[]);
+        for leaf in &leaves {
+            appended.append(leaf.to_owned());
+        }
+        assert_eq!(appended.root().unwrap().borrow().value, root);
+
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(restored.truncation(), Some(20));
+        assert_eq!(restored.root().unwrap().borrow().value, root);
+
+        for leaf_index in 0..leaves.len() {
+            let proof = tree.proof(leaf_index).unwrap();
+            assert!(proof
+                .hashes
+                .iter()
+                .all(|hash| hash.borrow().value.len() == 40));
+            assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+
+            let mut untruncated = proof;
+            untruncated.truncation = None;
+            assert!(!MerkleTree::verify_proof(
+                tree.root().unwrap(),
+                &untruncated
+            ));
+        }
+    }
+
+    // Truncating to the full digest is the same as not truncating.
+    assert_eq!(
+        MerkleTree::builder()
+            .truncation(32)
+            .build(&leaves)
+            .truncation(),
+        None
+    );
+}
+
+#[test]
+#[should_panic(expected = "truncation must be at least 1 byte")]
+fn test_truncation_out_of_range() {
+    MerkleTree::builder().truncation(0);
+}
+
+#[test]
+fn test_empty_leaves() {
+    let empty_digest = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let leaves: Vec<String> = vec!["abc".to_string(), "".to_string(), "cde".to_string()];
+
+    // Empty leaves are hashed as the digest of zero bytes.
+    let tree = MerkleTree::from_leaves(&["".to_string()]);
+    assert_eq!(tree.root().unwrap().borrow().value, empty_digest);
+    let tree = MerkleTree::from_leaves(&leaves);
+    let proof = tree.proof(1).unwrap();
+    assert_eq!(proof.leaf_value(), empty_digest);
+    assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+    assert_eq!(
+        tree.root().unwrap().borrow().value,
+        MerkleTree::merkle_root(&leaves).borrow().value
+    );
+
+    let builder = MerkleTree::builder().reject_empty_leaves();
+    assert_eq!(
+        builder.try_build(&leaves).err(),
+        Some(MerkleError::EmptyLeaf(1))
+    );
+    assert_eq!(
+        builder
+            .try_build(&data())
+            .unwrap()
+            .root()
+            .unwrap()
+            .borrow()
+            .value,
+        MerkleTree::from_leaves(&data())
+            .root()
+            .unwrap()
+            .borrow()
+            .value
+    );
+}
+
+#[test]
+#[should_panic(expected = "leaf 1 is empty")]
+fn test_reject_empty_leaves() {
+    MerkleTree::builder()
+        .reject_empty_leaves()
+        .build(&["abc".to_string(), "".to_string()]);
+}
+
+#[test]
+fn test_leaf_limits() {
+    let leaves: Vec<String> = vec!["a".to_string(), "bcd".to_string(), "ef".to_string()];
+    let builder = MerkleTree::builder().max_leaves(3).max_leaf_size(3);
+    assert_eq!(
+        builder
+            .try_build(&leaves)
+            .unwrap()
+            .root()
+            .unwrap()
+            .borrow()
+            .value,
+        MerkleTree::from_leaves(&leaves)
+            .root()
+            .unwrap()
+            .borrow()
+            .value
+    );
+
+    assert_eq!(
+        MerkleTree::builder().max_leaves(2).try_build(&leaves).err(),
+        Some(MerkleError::TooManyLeaves(3))
+    );
+    assert_eq!(
+        MerkleTree::builder()
+            .max_leaf_size(2)
+            .try_build(&leaves)
+            .err(),
+        Some(MerkleError::LeafTooLarge(1))
+    );
+
+    let tree = MerkleTree::from_leaves(&leaves);
+    assert_eq!(
+        MerkleTree::builder()
+            .max_leaves(2)
+            .build_from_levels(&leaves, tree.levels())
+            .err(),
+        Some(MerkleError::TooManyLeaves(3))
+    );
+    assert!(builder.build_from_levels(&leaves, tree.levels()).is_ok());
+}
+
+#[test]
+fn test_leaf_limits_iter() {
+    let leaves: Vec<String> = vec!["a".to_string(), "bcd".to_string(), "ef".to_string()];
+    let builder = MerkleTree::builder().max_leaves(3).max_leaf_size(3);
+    assert_eq!(
+        builder
+            .try_build_iter(leaves.iter().map(String::as_str))
+            .unwrap()
+            .root()
+            .unwrap()
+            .borrow()
+            .value,
+        MerkleTree::from_leaves(&leaves)
+            .root()
+            .unwrap()
+            .borrow()
+            .value
+    );
+
+    // Consumption stops at the first leaf past a limit, so unbounded iterators are refused.
+    let mut consumed = 0;
+    let unbounded = std::iter::repeat("a").inspect(|_| consumed += 1);
+    assert_eq!(
+        builder.try_build_iter(unbounded).err(),
+        Some(MerkleError::TooManyLeaves(4))
+    );
+    assert_eq!(consumed, 4);
+    let mut consumed = 0;
+    let growing = (1..).map(|len| "a".repeat(len)).inspect(|_| consumed += 1);
+    assert_eq!(
+        MerkleTree::builder()
+            .max_leaf_size(3)
+            .try_build_iter(growing)
+            .err(),
+        Some(MerkleError::LeafTooLarge(3))
+    );
+    assert_eq!(consumed, 4);
+}
+
+#[test]
+#[should_panic(expected = "leaf 1 exceeds the maximum leaf size")]
+fn test_leaf_too_large_panics() {
+    let leaves: Vec<String> = vec!["a".to_string(), "bcd".to_string()];
+    MerkleTree::builder().max_leaf_size(2).build(&leaves);
+}
@@ -0,0 +1,99 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::provider::{BulkHasher, CpuBulkHasher};
+use merkle_tree::{Concatenation, HashAlgorithm, LeafOrdering, MerkleTree, Padding};
+use std::sync::Mutex;
+
+/// Bulk hasher recording the size of every batch.
+#[derive(Default)]
+struct Recording(Mutex<Vec<usize>>);
+
+impl BulkHasher for Recording {
+    fn hash_batch(&self, algorithm: HashAlgorithm, messages: &[Vec<u8>]) -> Option<Vec<Vec<u8>>> {
+        self.0.lock().unwrap().push(messages.len());
+        Some(
+            messages
+                .iter()
+                .map(|message| algorithm.digest(message))
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_bulk_build() {
+    let builders = [
+        MerkleTree::builder(),
+        MerkleTree::builder().padding(Padding::DuplicateLast),
+        MerkleTree::builder()
+            .padding(Padding::Constant("00".repeat(32)))
+            .concatenation(Concatenation::Bytes),
+        MerkleTree::builder()
+            .ordering(LeafOrdering::SortedByHash)
+            .truncation(20),
+        MerkleTree::builder().blinding([7; 32]),
+    ];
+    let hashers = [CpuBulkHasher::new(1), CpuBulkHasher::new(3)];
+    for builder in &builders {
+        for num_of_leaves in [0, 1, 2, 3, 7, 16, 33] {
+            let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+            let tree = builder.build(&leaves);
+            for hasher in &hashers {
+                let bulk = builder.try_build_bulk(&leaves, hasher).unwrap();
+                assert_eq!(bulk.levels(), tree.levels());
+                for leaf_index in 0..num_of_leaves {
+                    assert_eq!(
+                        bulk.proof(leaf_index).unwrap().leaf_content,
+                        tree.proof(leaf_index).unwrap().leaf_content
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_one_batch_per_level() {
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    let hasher = Recording::default();
+    MerkleTree::builder()
+        .try_build_bulk(&leaves, &hasher)
+        .unwrap();
+    // The siblingless rightmost node of the leaf level is promoted without hashing.
+    assert_eq!(*hasher.0.lock().unwrap(), vec![5, 2, 1, 1]);
+
+    let hasher = Recording::default();
+    MerkleTree::builder()
+        .padding(Padding::DuplicateLast)
+        .try_build_bulk(&leaves, &hasher)
+        .unwrap();
+    assert_eq!(*hasher.0.lock().unwrap(), vec![5, 3, 2, 1]);
+}
+
+#[test]
+fn test_cpu_bulk_hasher() {
+    let messages: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; i as usize]).collect();
+    let expected: Vec<Vec<u8>> = messages
+        .iter()
+        .map(|message| HashAlgorithm::Sha256.digest(message))
+        .collect();
+    for num_of_threads in [1, 2, 4, 16] {
+        let hasher = CpuBulkHasher::new(num_of_threads);
+        assert_eq!(
+            hasher.hash_batch(HashAlgorithm::Sha256, &messages),
+            Some(expected.clone())
+        );
+        assert_eq!(hasher.hash_batch(HashAlgorithm::Sha256, &[]), Some(vec![]));
+    }
+    let hasher = CpuBulkHasher::default();
+    assert_eq!(
+        hasher.hash_batch(HashAlgorithm::Sha256, &messages),
+        Some(expected)
+    );
+}
+
+#[test]
+#[should_panic(expected = "bulk hasher needs at least 1 thread")]
+fn test_zero_threads() {
+    CpuBulkHasher::new(0);
+}
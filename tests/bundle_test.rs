@@ -0,0 +1,67 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{verify_bundle, Concatenation, ExportBundle, MerkleError, MerkleTree};
+
+#[test]
+fn test_bundle() {
+    let tree = MerkleTree::builder()
+        .concatenation(Concatenation::Bytes)
+        .truncation(20)
+        .build(&leaves(9));
+    let bundle = ExportBundle::of(&tree, &[8, 0, 3], 1_700_000_000, 1_700_000_100).unwrap();
+    assert!(bundle.verify().is_ok());
+
+    let bytes = bundle.to_bytes();
+    let verified = verify_bundle(&bytes).unwrap();
+    assert_eq!(verified.root, bundle.root);
+    assert_eq!(verified.created_at, 1_700_000_100);
+    let indices: Vec<usize> = verified
+        .proofs
+        .iter()
+        .map(|proof| proof.leaf_index)
+        .collect();
+    assert_eq!(indices, vec![8, 0, 3]);
+    assert_eq!(verified.proofs[0].leaf_content, "leaf8");
+
+    // Canonical: equal bundles give equal bytes.
+    assert_eq!(verified.to_bytes(), bytes);
+    assert_eq!(
+        ExportBundle::of(&tree, &[8, 0, 3], 1_700_000_000, 1_700_000_100)
+            .unwrap()
+            .to_bytes(),
+        bytes
+    );
+
+    assert!(ExportBundle::of(&tree, &[9], 0, 0).is_none());
+    assert!(ExportBundle::of(&MerkleTree::new(), &[], 0, 0).is_none());
+}
+
+#[test]
+fn test_bundle_rejected() {
+    let tree = MerkleTree::from_leaves(&leaves(5));
+    let other = MerkleTree::from_leaves(&leaves(6));
+
+    let mut bundle = ExportBundle::of(&tree, &[1, 2], 0, 0).unwrap();
+    bundle.proofs[1].leaf_content = "tampered".to_string();
+    assert!(matches!(
+        verify_bundle(&bundle.to_bytes()),
+        Err(MerkleError::ProofHashMismatch)
+    ));
+
+    let mut bundle = ExportBundle::of(&tree, &[1], 0, 0).unwrap();
+    bundle.proofs.push(other.proof(1).unwrap());
+    assert!(matches!(
+        verify_bundle(&bundle.to_bytes()),
+        Err(MerkleError::SchemeMismatch)
+    ));
+
+    let bytes = ExportBundle::of(&tree, &[1], 0, 0).unwrap().to_bytes();
+    assert!(matches!(
+        verify_bundle(&bytes[..bytes.len() - 1]),
+        Err(MerkleError::InvalidEncoding)
+    ));
+    assert!(verify_bundle(&tree.to_bytes()).is_err());
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::cas::{verify_chunk, Cas, Download, MemoryStorage, Storage};
+use merkle_tree::MerkleTree;
+use sha2::{Digest, Sha256};
+
+#[test]
+fn test_put_get() {
+    let mut cas = Cas::new(MemoryStorage::default(), 4);
+    let blobs: Vec<&[u8]> = vec![b"", b"abc", b"abcd", b"abcdefghij"];
+    let roots: Vec<String> = blobs.iter().map(|blob| cas.put(blob)).collect();
+    for (blob, root) in blobs.iter().zip(&roots) {
+        assert_eq!(cas.get(root).as_deref(), Some(*blob));
+    }
+
+    // The root is the merkle root of the chunk hexdigests.
+    let digests: Vec<String> = ["abcd", "efgh", "ij"]
+        .iter()
+        .map(|chunk| format!("{:x}", Sha256::digest(chunk)))
+        .collect();
+    assert_eq!(roots[3], MerkleTree::merkle_root(&digests).borrow().value);
+    assert_eq!(cas.put(b"abcdefghij"), roots[3]);
+    assert_eq!(cas.get(&digests[0]), None);
+}
+
+#[test]
+fn test_prove_chunk() {
+    let mut cas = Cas::new(MemoryStorage::default(), 4);
+    let root = cas.put(b"abcdefghij");
+    for (index, expected) in [&b"abcd"[..], b"efgh", b"ij"].into_iter().enumerate() {
+        let (chunk, proof) = cas.prove_chunk(&root, index).unwrap();
+        assert_eq!(chunk, expected);
+        assert!(verify_chunk(&root, &chunk, &proof));
+        assert!(!verify_chunk(&root, b"abce", &proof));
+    }
+    assert!(cas.prove_chunk(&root, 3).is_none());
+
+    // Tampered storage is detected.
+    let mut storage = cas.storage().clone();
+    let digest = format!("{:x}", Sha256::digest("efgh"));
+    storage.put(format!("chunk:{}", digest), b"efgi".to_vec());
+    let tampered = Cas::new(storage, 4);
+    assert_eq!(tampered.get(&root), None);
+    assert!(tampered.prove_chunk(&root, 1).is_none());
+    assert!(tampered.prove_chunk(&root, 0).is_some());
+}
+
+#[test]
+fn test_download() {
+    let mut cas = Cas::new(MemoryStorage::default(), 4);
+    let blob = b"abcdefghijklmn";
+    let root = cas.put(blob);
+    let mut download = Download::new(&root, cas.chunk_proofs(&root).unwrap()).unwrap();
+    assert_eq!(download.num_of_chunks(), 4);
+    assert_eq!(download.missing(), vec![0, 1, 2, 3]);
+
+    assert!(download.receive(2, b"ijkl".to_vec()));
+    assert!(!download.receive(0, b"abce".to_vec()));
+    assert!(!download.receive(3, b"mno".to_vec()));
+    assert!(!download.receive(4, b"".to_vec()));
+    assert_eq!(download.missing(), vec![0, 1, 3]);
+    assert_eq!(download.corrupted(), vec![0, 3]);
+    assert!(!download.is_complete());
+
+    assert!(download.receive(0, b"abcd".to_vec()));
+    assert!(!download.receive(0, b"abce".to_vec()));
+    assert!(download.receive(3, b"mn".to_vec()));
+    assert!(download.receive(1, b"efgh".to_vec()));
+    assert!(download.missing().is_empty());
+    assert!(download.corrupted().is_empty());
+    assert!(download.is_complete());
+    assert_eq!(download.into_bytes().unwrap(), blob);
+}
+
+#[test]
+fn test_download_proofs() {
+    let mut cas = Cas::new(MemoryStorage::default(), 4);
+    let root = cas.put(b"abcdefghij");
+    let other = cas.put(b"abcdefghik");
+    assert!(Download::new(&other, cas.chunk_proofs(&root).unwrap()).is_none());
+    let mut proofs = cas.chunk_proofs(&root).unwrap();
+    proofs.swap(0, 1);
+    assert!(Download::new(&root, proofs).is_none());
+    let mut proofs = cas.chunk_proofs(&root).unwrap();
+    proofs.pop();
+    assert!(Download::new(&root, proofs).is_none());
+    assert!(Download::new(&root, Vec::new()).is_none());
+    assert!(cas.chunk_proofs(&"0".repeat(64)).is_none());
+
+    let download = Download::new(&root, cas.chunk_proofs(&root).unwrap()).unwrap();
+    assert!(download.into_bytes().is_none());
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::cbergoon;
+
+// Vectors follow cbergoon/merkletree's sha256 construction over the leaves below.
+const LEAVES: [&str; 5] = ["Hello", "Hi", "Hey", "Hola", "Bonjour"];
+const ROOT: &str = "a21820b40b9451fa1d775b25bc7abd716d3ed20ccfca883ca18a6558867aff45";
+const SINGLE_LEAF_ROOT: &str = "e1c72e4395428dfeb7d5ba8733bb704610e9981d7c2c349b48129dac9344601e";
+const PATHS: [(usize, [&str; 3], [i64; 3]); 3] = [
+    (
+        0,
+        [
+            "3639efcd08abb273b1619e82e78c29a7df02c1051b1820e99fc395dcaa3326b8",
+            "67b8901ac30135e74d42036dfa604336e1f978e49ee0d6bf484a46ff27a2ae9c",
+            "5def38b032150d03cd5335cbc0cf2184d2bb2a52b61774e1005923e3d2ba29f7",
+        ],
+        [1, 1, 1],
+    ),
+    (
+        3,
+        [
+            "581d43745726e0ee62911178bfb3887c3fe295d29eeb741f0e40f91e8a70907a",
+            "d123f97da25da4b08b962e64b8042bda739950443f172132e5a50cde10e7b6bc",
+            "5def38b032150d03cd5335cbc0cf2184d2bb2a52b61774e1005923e3d2ba29f7",
+        ],
+        [0, 0, 1],
+    ),
+    (
+        4,
+        [
+            "9172e8eec99f144f72eca9a568759580edadb2cfd154857f07e657569493bc44",
+            "c85e8c3c6867a85cf1da035b4e2cd4ffb89d384ad2027ef95fe795c0cc70b332",
+            "5f30cc80133b9394156e24b233f0c4be32b24e44bb3381f02c7ba52619d0febc",
+        ],
+        [1, 1, 0],
+    ),
+];
+
+fn from_hex(value: &str) -> [u8; 32] {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+        .collect::<Vec<u8>>()
+        .try_into()
+        .unwrap()
+}
+
+fn leaf_hashes() -> Vec<[u8; 32]> {
+    LEAVES
+        .iter()
+        .map(|leaf| cbergoon::leaf_hash(leaf.as_bytes()))
+        .collect()
+}
+
+#[test]
+fn test_cbergoon_root() {
+    assert_eq!(cbergoon::root(&leaf_hashes()), Some(from_hex(ROOT)));
+    assert_eq!(
+        cbergoon::root(&leaf_hashes()[..1]),
+        Some(from_hex(SINGLE_LEAF_ROOT))
+    );
+    assert_eq!(cbergoon::root(&[]), None);
+}
+
+#[test]
+fn test_cbergoon_merkle_path() {
+    let leaf_hashes = leaf_hashes();
+    let root = from_hex(ROOT);
+    for (leaf_index, expected_path, expected_indexes) in PATHS {
+        let (path, indexes) = cbergoon::merkle_path(&leaf_hashes, leaf_index).unwrap();
+        assert_eq!(path, expected_path.map(from_hex));
+        assert_eq!(indexes, expected_indexes);
+        assert!(cbergoon::verify_path(
+            &root,
+            &leaf_hashes[leaf_index],
+            &path,
+            &indexes
+        ));
+        assert!(!cbergoon::verify_path(
+            &root,
+            &cbergoon::leaf_hash(b"tainted"),
+            &path,
+            &indexes
+        ));
+    }
+    assert!(cbergoon::merkle_path(&leaf_hashes, 5).is_none());
+}
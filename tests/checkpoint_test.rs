@@ -0,0 +1,168 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::checkpoint::{Checkpoint, NoteSignature, SignedCheckpoint};
+use merkle_tree::{CommittedRoot, MerkleError, MerkleTree};
+
+/// Checkpoint from the C2SP tlog-checkpoint specification.
+const SPEC_CHECKPOINT: &str =
+    "example.com/behind-the-sofa\n20852163\nCsUYapGGPo4dkMgIAUqom/Xajj7h2fB2MPA3j2jxq2I=\n";
+
+#[test]
+fn test_checkpoint_text() {
+    let checkpoint = Checkpoint::from_text(SPEC_CHECKPOINT).unwrap();
+    assert_eq!(checkpoint.origin, "example.com/behind-the-sofa");
+    assert_eq!(checkpoint.size, 20852163);
+    assert_eq!(
+        checkpoint.root,
+        "0ac5186a91863e8e1d90c808014aa89bf5da8e3ee1d9f07630f0378f68f1ab62"
+    );
+    assert!(checkpoint.extensions.is_empty());
+    assert_eq!(checkpoint.to_text(), SPEC_CHECKPOINT);
+
+    let extended = format!("{SPEC_CHECKPOINT}ext one\next two\n");
+    let checkpoint = Checkpoint::from_text(&extended).unwrap();
+    assert_eq!(checkpoint.extensions, ["ext one", "ext two"]);
+    assert_eq!(checkpoint.to_text(), extended);
+
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    let tree = MerkleTree::from_leaves(&leaves);
+    let checkpoint = Checkpoint::of(&CommittedRoot::of(&tree, 0).unwrap(), "example.com/log");
+    assert_eq!(checkpoint.size, 5);
+    assert_eq!(checkpoint.root, tree.root().unwrap().borrow().value);
+    assert_eq!(Checkpoint::from_text(&checkpoint.to_text()), Ok(checkpoint));
+}
+
+#[test]
+fn test_invalid_checkpoint_text() {
+    let root = "CsUYapGGPo4dkMgIAUqom/Xajj7h2fB2MPA3j2jxq2I=";
+    for invalid in [
+        "".to_owned(),
+        format!("origin\n1\n{root}"),
+        format!("\n1\n{root}\n"),
+        format!("origin\n01\n{root}\n"),
+        format!("origin\n+1\n{root}\n"),
+        format!("origin\n-1\n{root}\n"),
+        format!("origin\nx\n{root}\n"),
+        "origin\n1\nnot base64\n".to_owned(),
+        "origin\n1\n\n".to_owned(),
+        format!("origin\n1\n{root}\n\n"),
+        format!("origin\n1\n{root}\next\n\nmore\n"),
+    ] {
+        assert_eq!(
+            Checkpoint::from_text(&invalid),
+            Err(MerkleError::InvalidEncoding)
+        );
+    }
+    assert!(Checkpoint::from_text(&format!("origin\n0\n{root}\n")).is_ok());
+}
+
+#[test]
+fn test_signed_note() {
+    let signed = SignedCheckpoint {
+        checkpoint: Checkpoint::from_text(SPEC_CHECKPOINT).unwrap(),
+        signatures: vec![
+            NoteSignature {
+                name: "log".to_owned(),
+                key_hash: [1, 2, 3, 4],
+                signature: vec![5; 64],
+            },
+            NoteSignature {
+                name: "witness.example".to_owned(),
+                key_hash: [6, 7, 8, 9],
+                signature: vec![10; 72],
+            },
+        ],
+    };
+    let note = signed.to_note();
+    assert!(note.starts_with(&format!("{SPEC_CHECKPOINT}\n\u{2014} log AQIDBAUF")));
+    assert_eq!(note.lines().count(), 6);
+    assert_eq!(SignedCheckpoint::from_note(&note), Ok(signed));
+
+    for invalid in [
+        SPEC_CHECKPOINT.to_owned(),
+        format!("{SPEC_CHECKPOINT}\n"),
+        format!("{SPEC_CHECKPOINT}\n- log AQIDBAUF\n"),
+        format!("{SPEC_CHECKPOINT}\n\u{2014} log AQIDBAUF"),
+        format!("{SPEC_CHECKPOINT}\n\u{2014} log AQIDBA==\n"),
+        format!("{SPEC_CHECKPOINT}\n\u{2014} a+b AQIDBAUF\n"),
+        format!("{SPEC_CHECKPOINT}\n\u{2014}  AQIDBAUF\n"),
+    ] {
+        assert_eq!(
+            SignedCheckpoint::from_note(&invalid),
+            Err(MerkleError::InvalidEncoding)
+        );
+    }
+}
+
+#[cfg(feature = "ring")]
+#[test]
+fn test_go_note_interop() {
+    use merkle_tree::cosign::{Ed25519WitnessKey, WitnessKey};
+    use merkle_tree::LeafEncoding;
+
+    // Example key and signed note from the documentation of golang.org/x/mod/sumdb/note.
+    let verifier_key = "PeterNeumann+c74f20a3+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW";
+    let key = Ed25519WitnessKey::from_verifier_key(verifier_key).unwrap();
+    assert_eq!(key.name(), "PeterNeumann");
+    assert_eq!(key.key_hash(), [0xc7, 0x4f, 0x20, 0xa3]);
+    assert_eq!(key.verifier_key(), verifier_key);
+    let text = b"If you think cryptography is the answer to your problem,\n\
+        then you don't know what your problem is.\n";
+    let signature = LeafEncoding::Base64
+        .decode("x08go/ZJkuBS9UG/SffcvIAQxVBtiFupLLr8pAcElZInNIuGUgYN1FFYC2pZSNXgKvqfqdngotpRZb6KE6RyyBwJnAM=")
+        .unwrap();
+    assert_eq!(signature[..4], key.key_hash());
+    assert!(key.verify(text, &signature[4..]));
+
+    for invalid in [
+        "PeterNeumann+c74f20a4+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW",
+        "PeterNeumann+c74f20a3+Ahpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW",
+        "PeterNeumann+c74f20a3",
+        "Peter+Neumann+c74f20a3+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW",
+    ] {
+        assert_eq!(
+            Ed25519WitnessKey::from_verifier_key(invalid),
+            Err(MerkleError::InvalidEncoding)
+        );
+    }
+}
+
+#[cfg(feature = "ring")]
+#[test]
+fn test_signed_checkpoint_quorum() {
+    use merkle_tree::cosign::{Ed25519Witness, WitnessKey};
+
+    let log = Ed25519Witness::from_seed("example.com/log", &[1; 32]);
+    let witness = Ed25519Witness::from_seed("witness.example", &[2; 32]);
+    let keys = [log.key(), witness.key()];
+    let keys: Vec<&dyn WitnessKey> = keys.iter().map(|k| k as &dyn WitnessKey).collect();
+
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    let root = CommittedRoot::of(&MerkleTree::from_leaves(&leaves), 0).unwrap();
+    let checkpoint = Checkpoint::of(&root, "example.com/log");
+    let signed = SignedCheckpoint {
+        signatures: vec![
+            log.sign_checkpoint(&checkpoint),
+            witness.sign_checkpoint(&checkpoint),
+        ],
+        checkpoint,
+    };
+    assert_eq!(signed.signatures[0].key_hash, log.key().key_hash());
+
+    let mut parsed = SignedCheckpoint::from_note(&signed.to_note()).unwrap();
+    assert_eq!(parsed.verify(&keys, 2), Ok(2));
+
+    // A signature line naming a trusted witness with the hash of another key is ignored.
+    let mut mislabeled = parsed.clone();
+    mislabeled.signatures[1].key_hash = log.key().key_hash();
+    assert_eq!(
+        mislabeled.verify(&keys, 2),
+        Err(MerkleError::QuorumNotReached(1))
+    );
+
+    parsed.checkpoint.size += 1;
+    assert_eq!(
+        parsed.verify(&keys, 1),
+        Err(MerkleError::QuorumNotReached(0))
+    );
+}
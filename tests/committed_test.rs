@@ -0,0 +1,70 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{CommittedRoot, Concatenation, MerkleError, MerkleTree, Padding};
+
+#[test]
+fn test_committed_root() {
+    let tree = MerkleTree::builder()
+        .padding(Padding::DuplicateLast)
+        .truncation(20)
+        .build(&leaves(5));
+    let committed = CommittedRoot::of(&tree, 1_700_000_000).unwrap();
+    assert_eq!(committed.root, tree.root().unwrap().borrow().value);
+    assert_eq!(committed.leaf_count, 5);
+    assert_eq!(committed.scheme, tree.scheme());
+    assert!(CommittedRoot::of(&MerkleTree::new(), 0).is_none());
+
+    // Canonical: equal commitments give equal bytes and digests.
+    let bytes = committed.to_bytes();
+    let restored = CommittedRoot::from_bytes(&bytes).unwrap();
+    assert_eq!(restored, committed);
+    assert_eq!(restored.to_bytes(), bytes);
+    assert_eq!(restored.hash(), committed.hash());
+    assert_eq!(
+        CommittedRoot::of(&tree, 1_700_000_000).unwrap().hash(),
+        committed.hash()
+    );
+    assert_ne!(
+        CommittedRoot::of(&tree, 0).unwrap().hash(),
+        committed.hash()
+    );
+    assert_eq!(
+        CommittedRoot::from_bytes(&tree.to_bytes()).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+
+    for leaf_index in 0..5 {
+        assert_eq!(
+            committed.verify_proof(&tree.proof(leaf_index).unwrap()),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn test_scheme_mismatch() {
+    let tree = MerkleTree::from_leaves(&leaves(4));
+    let committed = CommittedRoot::of(&tree, 0).unwrap();
+
+    let bytes_tree = MerkleTree::builder()
+        .concatenation(Concatenation::Bytes)
+        .build(&leaves(4));
+    assert_eq!(
+        committed.verify_proof(&bytes_tree.proof(0).unwrap()),
+        Err(MerkleError::SchemeMismatch)
+    );
+    assert_eq!(
+        committed.verify_proof(&MerkleTree::from_leaves(&leaves(5)).proof(0).unwrap()),
+        Err(MerkleError::SchemeMismatch)
+    );
+
+    let mut tampered = tree.proof(0).unwrap();
+    tampered.leaf_content = "tampered".to_string();
+    assert_eq!(
+        committed.verify_proof(&tampered),
+        Err(MerkleError::ProofHashMismatch)
+    );
+}
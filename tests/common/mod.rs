@@ -0,0 +1,7 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Helpers shared by the integration tests.
+
+/// Return `n` distinct leaves, from `leaf0` up to `leaf{n - 1}`.
+pub fn leaves(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("leaf{}", i)).collect()
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{MerkleError, MerkleProof, MerkleTree};
+
+fn leaves(prefix: &str, n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("{}{}", prefix, i)).collect()
+}
+
+#[test]
+fn test_compose() {
+    // Three shards, whose roots are the leaves of a global tree.
+    let shards: Vec<MerkleTree> = (0..3)
+        .map(|shard| MerkleTree::from_leaves(&leaves(&format!("shard{}.", shard), 5)))
+        .collect();
+    let shard_roots: Vec<String> = shards
+        .iter()
+        .map(|shard| shard.root().unwrap().borrow().value.to_owned())
+        .collect();
+    let global = MerkleTree::from_leaves(&shard_roots);
+    let root = global.root().unwrap();
+
+    for (shard_index, shard) in shards.iter().enumerate() {
+        for leaf_index in 0..5 {
+            let inner = shard.proof(leaf_index).unwrap();
+            let outer = global.proof(shard_index).unwrap();
+            let composed = MerkleProof::compose(inner, outer).unwrap();
+            assert_eq!(
+                composed.leaf_content(),
+                format!("shard{}.{}", shard_index, leaf_index)
+            );
+            assert_eq!(composed.root_value(), root.borrow().value);
+            assert_eq!(composed.proofs().len(), 2);
+            assert!(composed.verify(root.to_owned()));
+            assert!(!composed.verify(shard.root().unwrap()));
+        }
+    }
+
+    // The outer proof must prove the root of the inner tree.
+    let inner = shards[0].proof(0).unwrap();
+    let outer = global.proof(1).unwrap();
+    assert_eq!(
+        MerkleProof::compose(inner, outer).err(),
+        Some(MerkleError::ProofHashMismatch)
+    );
+    let mut inner = shards[0].proof(0).unwrap();
+    inner.leaf_content += "tainted";
+    let outer = global.proof(0).unwrap();
+    assert_eq!(
+        MerkleProof::compose(inner, outer).err(),
+        Some(MerkleError::ProofHashMismatch)
+    );
+}
+
+#[test]
+fn test_compose_three_levels() {
+    let blocks = MerkleTree::from_leaves(&leaves("tx", 4));
+    let block_root = blocks.root().unwrap().borrow().value.to_owned();
+    let epochs = MerkleTree::from_leaves(&[
+        "block0".to_owned(),
+        block_root.to_owned(),
+        "block2".to_owned(),
+    ]);
+    let epoch_root = epochs.root().unwrap().borrow().value.to_owned();
+    let chain = MerkleTree::from_leaves(&[epoch_root, "epoch1".to_owned()]);
+
+    let composed = MerkleProof::compose(blocks.proof(3).unwrap(), epochs.proof(1).unwrap())
+        .unwrap()
+        .then(chain.proof(0).unwrap())
+        .unwrap();
+    assert_eq!(composed.proofs().len(), 3);
+    assert_eq!(composed.leaf_content(), "tx3");
+    assert!(composed.verify(chain.root().unwrap()));
+
+    let composed = MerkleProof::compose(blocks.proof(3).unwrap(), epochs.proof(1).unwrap())
+        .unwrap()
+        .then(chain.proof(1).unwrap());
+    assert_eq!(composed.err(), Some(MerkleError::ProofHashMismatch));
+}
@@ -0,0 +1,58 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{HashAlgorithm, LeafEncoding, MerkleError, MerkleTree};
+
+fn root(tree: &MerkleTree) -> String {
+    tree.root().unwrap().borrow().value.clone()
+}
+
+#[test]
+fn test_from_iterator() {
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    let expected = root(&MerkleTree::from_leaves(&leaves));
+
+    let tree: MerkleTree = (0..5).map(|i| i.to_string()).collect();
+    assert_eq!(root(&tree), expected);
+    let tree: MerkleTree = leaves.iter().map(String::as_str).collect();
+    assert_eq!(root(&tree), expected);
+    let tree = MerkleTree::from_iter(&leaves);
+    assert_eq!(root(&tree), expected);
+
+    let tree: MerkleTree = std::iter::empty::<&str>().collect();
+    assert!(tree.root().is_none());
+
+    // Leaves of any bytes, including bytes that are not valid UTF-8.
+    let text = ["abc", "", "é"];
+    let expected = root(&MerkleTree::try_from(&text[..]).unwrap());
+    let tree: MerkleTree = text.iter().map(|leaf| leaf.as_bytes().to_vec()).collect();
+    assert_eq!(root(&tree), expected);
+    assert_eq!(tree.scheme().leaf_encoding, LeafEncoding::Hex);
+    assert_eq!(tree.proof(2).unwrap().leaf_content, "c3a9");
+    let bytes: [&[u8]; 2] = [&[0xff, 0xfe], b"abc"];
+    let tree: MerkleTree = bytes.iter().collect();
+    let proof = tree.proof(0).unwrap();
+    assert_eq!(proof.leaf_content, "fffe");
+    assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+    assert_eq!(
+        proof.leaf_value(),
+        HashAlgorithm::Sha256.hash(&[0xff, 0xfe])
+    );
+}
+
+#[test]
+fn test_try_from_bytes() {
+    let leaves: Vec<String> = vec!["abc".to_string(), "".to_string(), "é".to_string()];
+    let expected = root(&MerkleTree::from_leaves(&leaves));
+
+    assert_eq!(root(&MerkleTree::try_from(&leaves[..]).unwrap()), expected);
+    let bytes: Vec<Vec<u8>> = leaves.iter().map(|leaf| leaf.as_bytes().to_vec()).collect();
+    assert_eq!(root(&MerkleTree::try_from(&bytes[..]).unwrap()), expected);
+    let slices: [&[u8]; 3] = [b"abc", b"", "é".as_bytes()];
+    assert_eq!(root(&MerkleTree::try_from(&slices[..]).unwrap()), expected);
+
+    let invalid: [&[u8]; 2] = [b"abc", &[0xff, 0xfe]];
+    assert_eq!(
+        MerkleTree::try_from(&invalid[..]).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
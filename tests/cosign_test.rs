@@ -0,0 +1,118 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::cosign::{cosigned_message, verify_cosigned_root, Cosignature, WitnessKey};
+use merkle_tree::{CommittedRoot, HashAlgorithm, MerkleError, MerkleTree};
+
+/// Witness signing with the SHA-256 digest of its secret followed by the message. Insecure, as
+/// verifying needs the secret, but enough to exercise quorum counting.
+struct ToyWitness {
+    name: &'static str,
+    secret: &'static str,
+}
+
+impl ToyWitness {
+    fn cosign(&self, root: &CommittedRoot) -> Cosignature {
+        let message = [self.secret.as_bytes(), &cosigned_message(root)].concat();
+        Cosignature {
+            witness: self.name.to_owned(),
+            signature: HashAlgorithm::Sha256.digest(&message),
+        }
+    }
+}
+
+impl WitnessKey for ToyWitness {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        HashAlgorithm::Sha256.digest(&[self.secret.as_bytes(), message].concat()) == signature
+    }
+}
+
+fn root(num_of_leaves: usize) -> CommittedRoot {
+    let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+    CommittedRoot::of(&MerkleTree::from_leaves(&leaves), 1700000000).unwrap()
+}
+
+#[test]
+fn test_quorum() {
+    let witnesses = [
+        ToyWitness {
+            name: "a",
+            secret: "1",
+        },
+        ToyWitness {
+            name: "b",
+            secret: "2",
+        },
+        ToyWitness {
+            name: "c",
+            secret: "3",
+        },
+    ];
+    let keys: Vec<&dyn WitnessKey> = witnesses.iter().map(|w| w as &dyn WitnessKey).collect();
+    let root = root(5);
+
+    let signatures: Vec<Cosignature> = witnesses[..2].iter().map(|w| w.cosign(&root)).collect();
+    assert_eq!(verify_cosigned_root(&root, &signatures, &keys, 2), Ok(2));
+    assert_eq!(
+        verify_cosigned_root(&root, &signatures, &keys, 3),
+        Err(MerkleError::QuorumNotReached(2))
+    );
+
+    // A witness counts once, however many cosignatures name it.
+    let repeated = vec![signatures[0].clone(); 3];
+    assert_eq!(
+        verify_cosigned_root(&root, &repeated, &keys, 2),
+        Err(MerkleError::QuorumNotReached(1))
+    );
+
+    // Untrusted witnesses and signatures over other roots are ignored.
+    let untrusted = ToyWitness {
+        name: "d",
+        secret: "4",
+    };
+    let mut forged = witnesses[2].cosign(&root);
+    forged.witness = "a".to_owned();
+    let other = witnesses[1].cosign(&self::root(6));
+    let signatures = vec![untrusted.cosign(&root), forged, other];
+    assert_eq!(
+        verify_cosigned_root(&root, &signatures, &keys, 1),
+        Err(MerkleError::QuorumNotReached(0))
+    );
+    assert_eq!(
+        verify_cosigned_root(&root, &[], &keys, 0),
+        Err(MerkleError::InvalidThreshold)
+    );
+
+    // A witness whose key is listed twice also counts once.
+    let listed_twice = vec![keys[0], keys[0]];
+    let signatures = vec![witnesses[0].cosign(&root)];
+    assert_eq!(
+        verify_cosigned_root(&root, &signatures, &listed_twice, 2),
+        Err(MerkleError::QuorumNotReached(1))
+    );
+}
+
+#[cfg(feature = "ring")]
+#[test]
+fn test_ed25519() {
+    use merkle_tree::cosign::Ed25519Witness;
+
+    let witnesses: Vec<Ed25519Witness> = (0..3)
+        .map(|i| Ed25519Witness::from_seed(&format!("witness{i}"), &[i; 32]))
+        .collect();
+    let keys: Vec<_> = witnesses.iter().map(Ed25519Witness::key).collect();
+    let keys: Vec<&dyn WitnessKey> = keys.iter().map(|k| k as &dyn WitnessKey).collect();
+    let root = root(5);
+
+    let mut signatures: Vec<Cosignature> = witnesses.iter().map(|w| w.cosign(&root)).collect();
+    assert_eq!(signatures[0].signature.len(), 64);
+    assert_eq!(verify_cosigned_root(&root, &signatures, &keys, 3), Ok(3));
+    signatures[2].signature[0] ^= 1;
+    assert_eq!(
+        verify_cosigned_root(&root, &signatures, &keys, 3),
+        Err(MerkleError::QuorumNotReached(2))
+    );
+}
@@ -0,0 +1,171 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::cosign::{cosigned_message, Cosignature, WitnessKey};
+use merkle_tree::credential::{Claim, Credential, DisclosureBundle};
+use merkle_tree::{CommittedRoot, HashAlgorithm, MerkleError};
+
+/// Issuer signing with the SHA-256 digest of its secret followed by the message. Insecure, as
+/// verifying needs the secret, but enough to exercise the bundle.
+struct ToyIssuer {
+    name: &'static str,
+    secret: &'static str,
+}
+
+impl ToyIssuer {
+    fn sign(&self, root: &CommittedRoot) -> Cosignature {
+        let message = [self.secret.as_bytes(), &cosigned_message(root)].concat();
+        Cosignature {
+            witness: self.name.to_owned(),
+            signature: HashAlgorithm::Sha256.digest(&message),
+        }
+    }
+}
+
+impl WitnessKey for ToyIssuer {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        HashAlgorithm::Sha256.digest(&[self.secret.as_bytes(), message].concat()) == signature
+    }
+}
+
+const ISSUER: ToyIssuer = ToyIssuer {
+    name: "issuer.example",
+    secret: "issuer secret",
+};
+
+const DOCUMENT: &str = r#"{
+    "given_name": "Erika",
+    "family_name": "Mustermann",
+    "birthdate": "1963-08-12",
+    "address": {"locality": "Berlin", "country": "DE"},
+    "over_18": true
+}"#;
+
+fn issue() -> (Credential, CommittedRoot, Cosignature) {
+    let credential = Credential::issue(DOCUMENT, &[7; 32]).unwrap();
+    let root = CommittedRoot::of(credential.tree(), 1700000000).unwrap();
+    let signature = ISSUER.sign(&root);
+    (credential, root, signature)
+}
+
+#[test]
+fn test_claims() {
+    let (credential, _, _) = issue();
+    let pointers: Vec<&str> = credential
+        .claims()
+        .iter()
+        .map(|claim| claim.pointer.as_str())
+        .collect();
+    assert_eq!(
+        pointers,
+        [
+            "/address/country",
+            "/address/locality",
+            "/birthdate",
+            "/family_name",
+            "/given_name",
+            "/over_18"
+        ]
+    );
+    for claim in credential.claims() {
+        // 16 bytes of salt.
+        assert_eq!(claim.salt.len(), 22);
+        assert_eq!(Claim::from_leaf(&claim.leaf()).as_ref(), Ok(claim));
+    }
+    let claim = &credential.claims()[5];
+    assert_eq!(
+        claim.leaf(),
+        format!("[\"{}\",\"/over_18\",true]", claim.salt)
+    );
+
+    // Salts depend on the key, so roots of the same claims differ between credentials.
+    let other = Credential::issue(DOCUMENT, &[8; 32]).unwrap();
+    assert_ne!(other.claims()[0].salt, credential.claims()[0].salt);
+    assert_ne!(
+        other.tree().root().unwrap().borrow().value,
+        credential.tree().root().unwrap().borrow().value
+    );
+
+    for leaf in [
+        "[\"s\",\"/a\"]",
+        "[\"s\", \"/a\",1]",
+        "[1,\"/a\",1]",
+        "{\"a\":1}",
+    ] {
+        assert_eq!(Claim::from_leaf(leaf), Err(MerkleError::InvalidEncoding));
+    }
+}
+
+#[test]
+fn test_disclosure() {
+    let (credential, root, signature) = issue();
+    let bundle = credential
+        .disclose(&["/over_18", "/address/country"], &root, &signature)
+        .unwrap();
+    let claims = bundle.verify(&ISSUER).unwrap();
+    let disclosed: Vec<(&str, &str)> = claims
+        .iter()
+        .map(|claim| (claim.pointer.as_str(), claim.value.as_str()))
+        .collect();
+    assert_eq!(
+        disclosed,
+        [("/over_18", "true"), ("/address/country", "\"DE\"")]
+    );
+
+    // Undisclosed claims appear nowhere in the presentation.
+    let text = bundle.to_text();
+    let decoded = DisclosureBundle::from_text(&text).unwrap();
+    assert_eq!(decoded.verify(&ISSUER).unwrap(), claims);
+    for proof in &decoded.proofs {
+        assert!(!proof.leaf_content.contains("Mustermann"));
+    }
+
+    assert!(credential
+        .disclose(&["/address"], &root, &signature)
+        .is_none());
+    let empty = credential.disclose(&[], &root, &signature).unwrap();
+    assert_eq!(empty.verify(&ISSUER).unwrap(), vec![]);
+}
+
+#[test]
+fn test_tampered_disclosure() {
+    let (credential, root, signature) = issue();
+
+    let other_issuer = ToyIssuer {
+        name: "issuer.example",
+        secret: "other secret",
+    };
+    let bundle = credential
+        .disclose(&["/over_18"], &root, &signature)
+        .unwrap();
+    assert_eq!(
+        bundle.verify(&other_issuer).err(),
+        Some(MerkleError::QuorumNotReached(0))
+    );
+
+    let mut bundle = credential
+        .disclose(&["/over_18"], &root, &signature)
+        .unwrap();
+    let salt = &credential.claims()[5].salt;
+    bundle.proofs[0].leaf_content = format!("[\"{}\",\"/over_18\",false]", salt);
+    assert_eq!(
+        bundle.verify(&ISSUER).err(),
+        Some(MerkleError::ProofHashMismatch)
+    );
+
+    let bundle = credential
+        .disclose(&["/over_18", "/over_18"], &root, &signature)
+        .unwrap();
+    assert_eq!(
+        bundle.verify(&ISSUER).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+
+    assert_eq!(
+        DisclosureBundle::from_text("not~a~bundle").err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
@@ -0,0 +1,220 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{MerkleError, MerkleTree, Padding, TreeDelta};
+
+fn tree(leaves: &[&str]) -> MerkleTree {
+    MerkleTree::from_leaves(
+        &leaves
+            .iter()
+            .map(|leaf| leaf.to_string())
+            .collect::<Vec<String>>(),
+    )
+}
+
+fn removed(leaves: &[&str], leaf_index: usize) -> MerkleTree {
+    let mut tree = tree(leaves);
+    tree.remove(leaf_index);
+    tree
+}
+
+fn root_value(tree: &MerkleTree) -> Option<String> {
+    tree.root().map(|root| root.borrow().value.to_owned())
+}
+
+#[test]
+fn test_tree_delta() {
+    let versions = [
+        tree(&[]),
+        tree(&["0"]),
+        tree(&["0", "1", "2"]),
+        tree(&["0", "1", "2", "3", "4", "5", "6", "7", "8"]),
+        tree(&["0", "1", "x", "3", "4"]),
+        tree(&["0", "1"]),
+        removed(&["0", "1", "2", "3"], 2),
+    ];
+    for old in &versions {
+        for new in &versions {
+            let delta = TreeDelta::between(old, new);
+            let delta = TreeDelta::from_bytes(&delta.to_bytes()).unwrap();
+
+            let mut replica = old.clone();
+            delta.apply(&mut replica).unwrap();
+            assert_eq!(root_value(&replica), root_value(new));
+            assert_eq!(replica.num_of_leaves(), new.num_of_leaves());
+            for leaf_index in 0..new.num_of_leaves() {
+                assert_eq!(replica.is_removed(leaf_index), new.is_removed(leaf_index));
+                if new.is_removed(leaf_index) {
+                    continue;
+                }
+                let proof = replica.proof(leaf_index).unwrap();
+                assert_eq!(
+                    proof.leaf_content,
+                    new.proof(leaf_index).unwrap().leaf_content
+                );
+                assert!(MerkleTree::verify_proof(new.root().unwrap(), &proof));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tree_delta_appended() {
+    let old = tree(&["0", "1", "2", "3", "4", "5", "6", "7"]);
+    let mut new = old.clone();
+    new.append("8".to_string());
+    let delta = TreeDelta::between(&old, &new);
+    // Only the new leaf, its hash promoted up three levels, and the new root.
+    assert_eq!(delta.leaves, vec![(8, "8".to_string())]);
+    assert_eq!(
+        delta
+            .nodes
+            .iter()
+            .map(|(level, index, _)| (*level, *index))
+            .collect::<Vec<(usize, usize)>>(),
+        vec![(0, 8), (1, 4), (2, 2), (3, 1), (4, 0)]
+    );
+
+    let mut stale = tree(&["0"]);
+    assert_eq!(delta.apply(&mut stale), Err(MerkleError::DeltaBaseMismatch));
+    assert_eq!(root_value(&stale), root_value(&tree(&["0"])));
+}
+
+#[test]
+fn test_tree_delta_from_bytes() {
+    let delta = TreeDelta::between(&tree(&["0"]), &tree(&["0", "1", "2"]));
+    let bytes = delta.to_bytes();
+    assert_eq!(
+        TreeDelta::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(MerkleError::InvalidEncoding)
+    );
+    assert_eq!(
+        TreeDelta::from_bytes(&[bytes.to_owned(), vec![0]].concat()),
+        Err(MerkleError::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_tree_delta_rejected() {
+    let old = tree(&["0", "1", "2"]);
+    let new = tree(&["0", "1", "x", "3", "4"]);
+    let delta = TreeDelta::between(&old, &new);
+    let rejected = |delta: &TreeDelta, error: MerkleError| {
+        let mut replica = old.clone();
+        assert_eq!(delta.apply(&mut replica), Err(error));
+        assert_eq!(root_value(&replica), root_value(&old));
+        assert_eq!(replica.num_of_leaves(), old.num_of_leaves());
+    };
+
+    // Nodes that are not the hashes of their leaf or children.
+    let mut forged = delta.clone();
+    forged.leaves[0].1 = "y".to_string();
+    rejected(&forged, MerkleError::InvalidEncoding);
+    let mut forged = delta.clone();
+    let (level, index, _) = forged.nodes.pop().unwrap();
+    forged.nodes.push((level, index, root_value(&old).unwrap()));
+    rejected(&forged, MerkleError::InvalidEncoding);
+
+    // Leaves or nodes that would leave a gap in a level.
+    let mut gap = delta.clone();
+    gap.leaves.retain(|(index, _)| *index != 3);
+    rejected(&gap, MerkleError::InvalidEncoding);
+    let mut gap = delta.clone();
+    gap.nodes
+        .retain(|(level, index, _)| (*level, *index) != (0, 3));
+    rejected(&gap, MerkleError::InvalidEncoding);
+
+    // Another scheme with the same hash function.
+    let mut other = delta.clone();
+    other.scheme.padding = Padding::DuplicateLast;
+    rejected(&other, MerkleError::DeltaBaseMismatch);
+
+    // Entries out of order are not decoded.
+    let mut unsorted = delta.clone();
+    unsorted.leaves.reverse();
+    assert_eq!(
+        TreeDelta::from_bytes(&unsorted.to_bytes()),
+        Err(MerkleError::InvalidEncoding)
+    );
+    let mut repeated = delta.clone();
+    repeated
+        .nodes
+        .push(repeated.nodes.last().unwrap().to_owned());
+    assert_eq!(
+        TreeDelta::from_bytes(&repeated.to_bytes()),
+        Err(MerkleError::InvalidEncoding)
+    );
+    let mut shape = delta;
+    shape.level_lengths[1] += 1;
+    assert_eq!(
+        TreeDelta::from_bytes(&shape.to_bytes()),
+        Err(MerkleError::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_upgrade_proof() {
+    let leaves: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+    for old_len in 1..=9 {
+        let old = MerkleTree::from_leaves(&leaves[..old_len]);
+        for new_len in old_len..=20 {
+            let new = MerkleTree::from_leaves(&leaves[..new_len]);
+            let delta = TreeDelta::between(&old, &new);
+            for leaf_index in 0..old_len {
+                let upgraded = delta
+                    .upgrade_proof(&old.proof(leaf_index).unwrap())
+                    .unwrap();
+                assert_eq!(upgraded.num_of_leaves, new_len);
+                assert_eq!(
+                    upgraded.to_bytes(),
+                    new.proof(leaf_index).unwrap().to_bytes()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_upgrade_proof_rejected() {
+    let old = tree(&["0", "1", "2"]);
+    let new = tree(&["0", "1", "2", "3", "4"]);
+    let delta = TreeDelta::between(&old, &new);
+
+    let mut proof = old.proof(1).unwrap();
+    proof.leaf_content = "tampered".to_string();
+    assert_eq!(
+        delta.upgrade_proof(&proof).err(),
+        Some(MerkleError::DeltaBaseMismatch)
+    );
+
+    let mut proof = old.proof(2).unwrap();
+    proof.hashes.pop();
+    assert_eq!(
+        delta.upgrade_proof(&proof).err(),
+        Some(MerkleError::SchemeMismatch)
+    );
+
+    let rewritten = TreeDelta::between(&old, &tree(&["0", "x", "2", "3"]));
+    assert_eq!(
+        rewritten.upgrade_proof(&old.proof(0).unwrap()).err(),
+        Some(MerkleError::NotAppendOnly)
+    );
+    let shrunk = TreeDelta::between(&old, &tree(&["0", "1"]));
+    assert_eq!(
+        shrunk.upgrade_proof(&old.proof(0).unwrap()).err(),
+        Some(MerkleError::NotAppendOnly)
+    );
+
+    // A delta whose new nodes were forged does not reproduce its own new root. Node 1 of
+    // level 2 is the audit hash of leaf 0 closest to the root.
+    let mut forged = delta.to_owned();
+    let node = forged
+        .nodes
+        .iter_mut()
+        .find(|(level, index, _)| (*level, *index) == (2, 1))
+        .unwrap();
+    node.2 = "00".repeat(32);
+    assert_eq!(
+        forged.upgrade_proof(&old.proof(0).unwrap()).err(),
+        Some(MerkleError::ProofHashMismatch)
+    );
+}
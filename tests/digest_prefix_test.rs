@@ -0,0 +1,43 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::MerkleTree;
+
+fn leaf_value(tree: &MerkleTree, leaf_index: usize) -> String {
+    tree.levels()[0][leaf_index].to_owned()
+}
+
+#[test]
+fn test_find_by_digest_prefix() {
+    let leaves: Vec<String> = (0..300).map(|i| i.to_string()).collect();
+    let mut tree = MerkleTree::from_leaves(&leaves);
+    for leaf_index in [0, 17, 299] {
+        let value = leaf_value(&tree, leaf_index);
+        assert_eq!(tree.find_by_digest_prefix(&value), [leaf_index]);
+        assert_eq!(tree.find_by_digest_prefix(&value[..8]), [leaf_index]);
+        assert_eq!(
+            tree.find_by_digest_prefix(&value[..8].to_uppercase()),
+            [leaf_index]
+        );
+    }
+
+    // A one-digit prefix matches about a sixteenth of the leaves, in ascending order.
+    let expected: Vec<usize> = (0..300)
+        .filter(|i| leaf_value(&tree, *i).starts_with('a'))
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(tree.find_by_digest_prefix("a"), expected);
+    assert_eq!(tree.find_by_digest_prefix("").len(), 300);
+    assert!(tree.find_by_digest_prefix("not hex").is_empty());
+
+    // The index follows appends and removals.
+    let value = leaf_value(&tree, 17);
+    tree.remove(17);
+    assert!(tree.find_by_digest_prefix(&value).is_empty());
+    tree.append("17".to_owned());
+    assert_eq!(tree.find_by_digest_prefix(&value), [300]);
+
+    // Duplicate leaves share a leaf value.
+    let tree = MerkleTree::from_leaves(&["x".to_string(), "y".to_string(), "x".to_string()]);
+    assert_eq!(tree.find_by_digest_prefix(&leaf_value(&tree, 0)), [0, 2]);
+    assert!(MerkleTree::new().find_by_digest_prefix("").is_empty());
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "mpt")]
+use merkle_tree::eip1186::{Account, AccountProof, StorageProof};
+use merkle_tree::mpt::{self, PatriciaTrie, EMPTY_ROOT};
+use sha3::{Digest, Keccak256};
+
+fn keccak256(value: &[u8]) -> [u8; 32] {
+    Keccak256::digest(value).into()
+}
+
+fn slot(index: u8) -> Vec<u8> {
+    let mut key = vec![0; 32];
+    key[31] = index;
+    key
+}
+
+#[test]
+fn test_account_proof() {
+    let mut storage = PatriciaTrie::new();
+    storage.insert(&keccak256(&slot(0)), vec![0x2a]); // RLP of 0x2a.
+    storage.insert(&keccak256(&slot(1)), vec![0x82, 0x01, 0x00]); // RLP of 0x0100.
+
+    let address = [0x11; 20];
+    let account = Account {
+        nonce: 1,
+        balance: 0,
+        code_hash: keccak256(&[]),
+    };
+    let mut state = PatriciaTrie::new();
+    state.insert(&keccak256(&address), account.rlp(&storage.root_hash()));
+    state.insert(&keccak256(&[0x22; 20]), account.rlp(&EMPTY_ROOT));
+
+    let proof = AccountProof::from_tries(
+        &state,
+        &address,
+        account.to_owned(),
+        &storage,
+        &[&slot(0), &slot(1), &slot(2)],
+    );
+    assert_eq!(
+        mpt::verify_proof(
+            &state.root_hash(),
+            &keccak256(&address),
+            &proof.account_proof
+        ),
+        Ok(Some(account.rlp(&proof.storage_hash)))
+    );
+    let values: Vec<Vec<u8>> = proof
+        .storage_proof
+        .iter()
+        .map(|storage_proof| storage_proof.value.to_owned())
+        .collect();
+    assert_eq!(values, vec![vec![0x2a], vec![0x01, 0x00], vec![]]);
+    for storage_proof in &proof.storage_proof {
+        assert!(mpt::verify_proof(
+            &proof.storage_hash,
+            &keccak256(&storage_proof.key),
+            &storage_proof.proof
+        )
+        .is_ok());
+    }
+
+    let json = proof.to_json();
+    assert!(json.starts_with(
+        "{\"address\":\"0x1111111111111111111111111111111111111111\",\"accountProof\":[\"0x"
+    ));
+    assert!(json.contains("\"balance\":\"0x0\",\"codeHash\":\"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470\",\"nonce\":\"0x1\""));
+    assert!(json.contains(&format!(
+        "\"storageProof\":[{},",
+        StorageProof::from_trie(&storage, &slot(0)).to_json()
+    )));
+    assert!(json.contains("\"value\":\"0x100\""));
+    assert!(json.ends_with(&format!(
+        "{{\"key\":\"0x{}02\",\"value\":\"0x0\",\"proof\":{}}}]}}",
+        "00".repeat(31),
+        "[".to_owned()
+            + &proof.storage_proof[2]
+                .proof
+                .iter()
+                .map(|node| format!("\"0x{}\"", hex(node)))
+                .collect::<Vec<String>>()
+                .join(",")
+            + "]"
+    )));
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
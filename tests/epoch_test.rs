@@ -0,0 +1,68 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{EpochTree, Hash, MerkleTree};
+
+fn minute_roots(epoch: usize, n: usize) -> Vec<String> {
+    (0..n)
+        .map(|minute| Hash::hash(&format!("{}:{}", epoch, minute)))
+        .collect()
+}
+
+#[test]
+fn test_epoch_tree() {
+    let mut tree = EpochTree::new();
+    assert_eq!(tree.roll_over(), None);
+    assert_eq!(tree.super_root(), None);
+
+    let epochs: Vec<Vec<String>> = (0..3)
+        .map(|epoch| minute_roots(epoch, 60 - epoch))
+        .collect();
+    for roots in &epochs {
+        for root in roots {
+            tree.push(root.to_owned());
+        }
+        assert_eq!(
+            tree.roll_over(),
+            Some(MerkleTree::merkle_root(roots).borrow().value.to_owned())
+        );
+    }
+    assert_eq!(tree.num_of_epochs(), 3);
+
+    let super_root = tree.super_root().unwrap();
+    for (epoch, roots) in epochs.iter().enumerate() {
+        for index in [0, 17, roots.len() - 1] {
+            let proof = tree.prove(epoch, index).unwrap();
+            assert_eq!(proof.epoch_proof.leaf_content, roots[index]);
+            assert!(EpochTree::verify(&super_root, &proof));
+        }
+    }
+    assert!(tree.prove(3, 0).is_none());
+    assert!(tree.prove(0, 60).is_none());
+
+    let mut proof = tree.prove(1, 5).unwrap();
+    proof.epoch_proof.leaf_content = Hash::hash("tainted");
+    assert!(!EpochTree::verify(&super_root, &proof));
+}
+
+#[test]
+fn test_epoch_tree_reanchor() {
+    let mut tree = EpochTree::new();
+    for root in minute_roots(0, 60) {
+        tree.push(root);
+    }
+    tree.roll_over();
+    let old_super_root = tree.super_root().unwrap();
+    let proof = tree.prove(0, 42).unwrap();
+    assert!(EpochTree::verify(&old_super_root, &proof));
+
+    for root in minute_roots(1, 60) {
+        tree.push(root);
+    }
+    tree.roll_over();
+    let new_super_root = tree.super_root().unwrap();
+    assert!(!EpochTree::verify(&new_super_root, &proof));
+
+    let proof = tree.reanchor(proof).unwrap();
+    assert!(EpochTree::verify(&new_super_root, &proof));
+    assert!(!EpochTree::verify(&old_super_root, &proof));
+}
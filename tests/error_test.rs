@@ -0,0 +1,38 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{MerkleError, MerkleProof};
+use std::error::Error;
+
+fn parse(bytes: &[u8]) -> Result<MerkleProof, Box<dyn Error + Send + Sync>> {
+    Ok(MerkleProof::from_bytes(bytes)?)
+}
+
+#[test]
+fn test_error_trait() {
+    let error = parse(b"not a proof").err().unwrap();
+    assert_eq!(
+        error.downcast_ref::<MerkleError>(),
+        Some(&MerkleError::InvalidEncoding)
+    );
+    assert!(error
+        .to_string()
+        .starts_with("serialized data is truncated or malformed"));
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(
+        MerkleError::EmptyLeaf(3).to_string(),
+        "leaf 3 is empty, which the builder was configured to reject"
+    );
+    assert_eq!(
+        MerkleError::LeafDigestMismatch("ab12".to_owned()).to_string(),
+        "leaf content hashes to ab12, not the expected leaf digest"
+    );
+    assert!(MerkleError::UnsupportedVersion(9)
+        .to_string()
+        .contains("format version 9"));
+    assert!(MerkleError::MissingTile("tile/8/0/000".to_owned())
+        .to_string()
+        .contains("tile tile/8/0/000 could not be fetched"));
+}
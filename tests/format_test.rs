@@ -0,0 +1,114 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{MerkleError, MerkleProof, MerkleTree, TreeDelta};
+
+#[test]
+fn test_header() {
+    let bytes = MerkleTree::from_leaves(&leaves(3)).to_bytes();
+    assert_eq!(&bytes[..4], b"MRKL");
+    assert_eq!(bytes[4..12], 1u64.to_le_bytes()); // Format version.
+    assert_eq!(bytes[12..20], 0u64.to_le_bytes()); // Tree artifact.
+    assert_eq!(bytes[20..28], 0u64.to_le_bytes()); // SHA-256.
+    assert_eq!(bytes[28..36], 2u64.to_le_bytes()); // Binary tree.
+
+    let mut future = bytes.to_owned();
+    future[4] = 2;
+    assert_eq!(
+        MerkleTree::from_bytes(&future).err(),
+        Some(MerkleError::UnsupportedVersion(2))
+    );
+    let mut other_hash = bytes.to_owned();
+    other_hash[20] = 99; // Unknown hash algorithm.
+    assert_eq!(
+        MerkleTree::from_bytes(&other_hash).err(),
+        Some(MerkleError::UnsupportedScheme)
+    );
+    let mut other_arity = bytes.to_owned();
+    other_arity[28] = 4;
+    assert_eq!(
+        MerkleTree::from_bytes(&other_arity).err(),
+        Some(MerkleError::UnsupportedScheme)
+    );
+    assert_eq!(
+        MerkleTree::from_bytes(&bytes[4..]).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+
+    // Artifacts of one kind are not mistaken for another.
+    let proof = MerkleTree::merkle_proof(&leaves(3), 1).to_bytes();
+    assert_eq!(
+        MerkleTree::from_bytes(&proof).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+    assert_eq!(
+        TreeDelta::from_bytes(&bytes).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_proof_serialization() {
+    for n in 1..=9 {
+        let tree = MerkleTree::from_leaves(&leaves(n));
+        let root = tree.root().unwrap();
+        for index in 0..n {
+            let bytes = tree.proof(index).unwrap().to_bytes();
+            let proof = MerkleProof::from_bytes(&bytes).unwrap();
+            assert_eq!(proof.leaf_index, index);
+            assert_eq!(proof.num_of_leaves, n);
+            assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+            assert_eq!(proof.to_bytes(), bytes);
+            assert_eq!(
+                MerkleProof::from_bytes(&bytes[..bytes.len() - 1]).err(),
+                Some(MerkleError::InvalidEncoding)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_proof_text_interchange() {
+    let tree = MerkleTree::from_leaves(&leaves(5));
+    let root = tree.root().unwrap();
+    for index in 0..5 {
+        let proof = tree.proof(index).unwrap();
+        let bytes = proof.to_bytes();
+
+        let hex = proof.to_hex();
+        assert_eq!(hex.len(), 2 * bytes.len());
+        assert_eq!(MerkleProof::from_hex(&hex).unwrap().to_bytes(), bytes);
+        let upper = MerkleProof::from_hex(&hex.to_uppercase()).unwrap();
+        assert!(MerkleTree::verify_proof(root.to_owned(), &upper));
+
+        let base64 = proof.to_base64();
+        assert!(base64
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_'));
+        assert_eq!(base64.len(), (4 * bytes.len()).div_ceil(3));
+        let decoded = MerkleProof::from_base64(&base64).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(MerkleTree::verify_proof(root.to_owned(), &decoded));
+    }
+
+    let proof = tree.proof(0).unwrap();
+    let base64 = proof.to_base64();
+    for invalid in [
+        format!("{base64}="),
+        format!("{}+", &base64[..base64.len() - 1]),
+        base64[..base64.len() - 4].to_owned(),
+    ] {
+        assert_eq!(
+            MerkleProof::from_base64(&invalid).err(),
+            Some(MerkleError::InvalidEncoding)
+        );
+    }
+    for invalid in ["0", "zz", ""] {
+        assert_eq!(
+            MerkleProof::from_hex(invalid).err(),
+            Some(MerkleError::InvalidEncoding)
+        );
+    }
+}
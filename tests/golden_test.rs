@@ -0,0 +1,77 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{MerkleError, MerkleTree, Padding};
+
+const HEADER: &str = "merkle-tree golden v1
+algorithm Sha256
+concatenation Hex
+padding Promote
+ordering Identity
+truncation None
+leaf_encoding Utf8
+blinded false
+leaves 3
+";
+
+fn leaves() -> Vec<String> {
+    ["abc", "bcd", "cde"].map(String::from).to_vec()
+}
+
+#[test]
+fn test_to_golden() {
+    let tree = MerkleTree::from_leaves(&leaves());
+    let golden = tree.to_golden();
+    assert!(golden.starts_with(HEADER));
+    assert!(golden.ends_with('\n'));
+    let levels = tree.levels();
+    let lines: Vec<&str> = golden.lines().collect();
+    assert_eq!(
+        lines.len(),
+        9 + levels.len() + levels.iter().map(Vec::len).sum::<usize>()
+    );
+    assert_eq!(lines[9], "level 0 3");
+    assert_eq!(lines[10], levels[0][0]);
+    assert_eq!(lines[13], "level 1 2");
+    assert_eq!(lines[16], "level 2 1");
+    assert_eq!(lines[17], tree.root().unwrap().borrow().value);
+    assert_eq!(
+        MerkleTree::new().to_golden().lines().last(),
+        Some("leaves 0")
+    );
+}
+
+#[test]
+fn test_verify_golden() {
+    let tree = MerkleTree::from_leaves(&leaves());
+    let golden = tree.to_golden();
+    assert_eq!(tree.verify_golden(&golden), Ok(()));
+    assert_eq!(tree.verify_golden(&golden.replace('\n', "\r\n")), Ok(()));
+
+    // A change of configuration or of any node is reported at its line.
+    let duplicated = MerkleTree::builder()
+        .padding(Padding::DuplicateLast)
+        .build(&leaves());
+    assert_eq!(
+        duplicated.verify_golden(&golden),
+        Err(MerkleError::GoldenMismatch(4))
+    );
+    let mut changed = leaves();
+    changed[2] = "cdf".to_owned();
+    assert_eq!(
+        MerkleTree::from_leaves(&changed).verify_golden(&golden),
+        Err(MerkleError::GoldenMismatch(13))
+    );
+    let truncated: String = golden
+        .lines()
+        .take(12)
+        .map(|line| format!("{line}\n"))
+        .collect();
+    assert_eq!(
+        tree.verify_golden(&truncated),
+        Err(MerkleError::GoldenMismatch(13))
+    );
+    assert_eq!(
+        tree.verify_golden(&format!("{golden}extra\n")),
+        Err(MerkleError::GoldenMismatch(19))
+    );
+}
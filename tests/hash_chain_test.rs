@@ -0,0 +1,55 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{ChainReceipt, Hash, HashAlgorithm, HashChain, MerkleError};
+
+fn chain(n: usize) -> HashChain {
+    let mut chain = HashChain::new(HashAlgorithm::Sha256);
+    for i in 0..n {
+        chain.append(format!("entry{}", i));
+    }
+    chain
+}
+
+#[test]
+fn test_head() {
+    let mut chain = HashChain::new(HashAlgorithm::Sha256);
+    assert!(chain.is_empty());
+    assert_eq!(chain.head(), None);
+    assert_eq!(chain.append("a".to_string()), Hash::hash("a"));
+    let expected = Hash::hash(&format!("{}{}", Hash::hash("a"), Hash::hash("b")));
+    assert_eq!(chain.append("b".to_string()), expected);
+    assert_eq!(chain.len(), 2);
+}
+
+#[test]
+fn test_receipts() {
+    for n in 1..=8 {
+        let chain = chain(n);
+        let head = chain.head().unwrap().to_owned();
+        for index in 0..n {
+            let receipt = chain.receipt(index).unwrap();
+            assert_eq!(receipt.entry, format!("entry{}", index));
+            assert_eq!(receipt.later.len(), n - index - 1);
+            assert!(receipt.verify(&head));
+            assert!(receipt.verify(&head.to_uppercase()));
+
+            let restored = ChainReceipt::from_bytes(&receipt.to_bytes()).unwrap();
+            assert_eq!(restored, receipt);
+
+            let mut tampered = receipt;
+            tampered.entry.push('!');
+            assert!(!tampered.verify(&head));
+        }
+        assert!(chain.receipt(n).is_none());
+        assert_eq!(HashChain::from_bytes(&chain.to_bytes()).unwrap(), chain);
+    }
+
+    // A receipt for a later entry does not verify against an earlier head.
+    let long = chain(5);
+    let short = chain(3);
+    assert!(!long.receipt(1).unwrap().verify(short.head().unwrap()));
+    assert_eq!(
+        HashChain::from_bytes(&long.receipt(1).unwrap().to_bytes()).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
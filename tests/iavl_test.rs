@@ -0,0 +1,121 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::iavl::{empty_root_hash, verify_range_proof, RangeProof, VersionedTree};
+use merkle_tree::MerkleError;
+
+fn key(i: usize) -> Vec<u8> {
+    format!("key{:04}", i).into_bytes()
+}
+
+#[test]
+fn test_versions() {
+    let mut tree = VersionedTree::new();
+    assert!(tree.is_empty());
+    assert_eq!(tree.save_version(), (1, empty_root_hash()));
+
+    // Insert in a scrambled order to exercise rotations.
+    for i in 0..100 {
+        let i = (i * 37) % 100;
+        tree.set(&key(i), b"v2".to_vec());
+    }
+    let (version, hash) = tree.save_version();
+    assert_eq!(version, 2);
+    assert_eq!(tree.len(), 100);
+
+    for i in (0..100).step_by(3) {
+        tree.set(&key(i), b"v3".to_vec());
+    }
+    tree.set(b"zzz", b"v3".to_vec());
+    assert_eq!(tree.save_version().0, 3);
+
+    assert_eq!(tree.root_hash(2), Some(hash));
+    assert_ne!(tree.root_hash(3), Some(hash));
+    assert_eq!(tree.root_hash(4), None);
+    assert_eq!(tree.latest_version(), Some(3));
+
+    assert_eq!(tree.get_versioned(&key(3), 1), None);
+    assert_eq!(tree.get_versioned(&key(3), 2), Some(&b"v2"[..]));
+    assert_eq!(tree.get_versioned(&key(3), 3), Some(&b"v3"[..]));
+    assert_eq!(tree.get_versioned(&key(4), 3), Some(&b"v2"[..]));
+    assert_eq!(tree.get_versioned(b"zzz", 2), None);
+    assert_eq!(tree.get(b"zzz"), Some(&b"v3"[..]));
+
+    // The same contents give the same root hash regardless of insertion order.
+    let mut sorted = VersionedTree::new();
+    for i in 0..100 {
+        sorted.set(&key(i), b"v2".to_vec());
+    }
+    let mut reversed = VersionedTree::new();
+    for i in (0..100).rev() {
+        reversed.set(&key(i), b"v2".to_vec());
+    }
+    assert_eq!(sorted.get(&key(50)), reversed.get(&key(50)));
+    assert_eq!(sorted.len(), reversed.len());
+}
+
+#[test]
+fn test_range_proof() {
+    let mut tree = VersionedTree::new();
+    for i in (0..200).step_by(2) {
+        tree.set(&key(i), key(i * 10));
+    }
+    let (version, hash) = tree.save_version();
+
+    for (start, end) in [(0, 200), (10, 21), (11, 12), (199, 250), (13, 13)] {
+        let proof = tree
+            .range_proof(&key(start), Some(&key(end)), version)
+            .unwrap();
+        let entries = verify_range_proof(&hash, &key(start), Some(&key(end)), &proof).unwrap();
+        let expected: Vec<(Vec<u8>, Vec<u8>)> = (start..end.min(200))
+            .filter(|i| i % 2 == 0)
+            .map(|i| (key(i), key(i * 10)))
+            .collect();
+        assert_eq!(entries, expected);
+    }
+    let proof = tree.range_proof(&key(150), None, version).unwrap();
+    assert_eq!(
+        verify_range_proof(&hash, &key(150), None, &proof)
+            .unwrap()
+            .len(),
+        25
+    );
+
+    // A proof for a narrower range cannot vouch for a wider one.
+    let proof = tree.range_proof(&key(10), Some(&key(20)), version).unwrap();
+    assert_eq!(
+        verify_range_proof(&hash, &key(10), Some(&key(30)), &proof),
+        Err(MerkleError::IncompleteProof)
+    );
+    assert_eq!(
+        verify_range_proof(&empty_root_hash(), &key(10), Some(&key(20)), &proof),
+        Err(MerkleError::ProofHashMismatch)
+    );
+}
+
+#[test]
+fn test_key_proof() {
+    let mut tree = VersionedTree::new();
+    let (empty_version, empty_hash) = tree.save_version();
+    tree.set(b"a", b"1".to_vec());
+    tree.set(b"c", b"3".to_vec());
+    let (version, hash) = tree.save_version();
+
+    let proof = tree.key_proof(b"a", version).unwrap();
+    assert_eq!(
+        verify_range_proof(&hash, b"a", Some(b"a\0"), &proof),
+        Ok(vec![(b"a".to_vec(), b"1".to_vec())])
+    );
+    let proof = tree.key_proof(b"b", version).unwrap();
+    assert_eq!(
+        verify_range_proof(&hash, b"b", Some(b"b\0"), &proof),
+        Ok(vec![])
+    );
+
+    let proof = tree.key_proof(b"a", empty_version).unwrap();
+    assert_eq!(proof, RangeProof::Empty);
+    assert_eq!(
+        verify_range_proof(&empty_hash, b"a", Some(b"a\0"), &proof),
+        Ok(vec![])
+    );
+    assert!(tree.key_proof(b"a", 3).is_none());
+}
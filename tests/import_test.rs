@@ -0,0 +1,122 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "import")]
+use merkle_tree::{MerkleError, MerkleTree};
+use std::io;
+use std::path::PathBuf;
+
+fn write(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn root(tree: &MerkleTree) -> String {
+    tree.root().unwrap().borrow().value.to_owned()
+}
+
+#[test]
+fn test_from_csv() {
+    let path = write(
+        "export.csv",
+        "id,name,note\r\n1,Alice,\"says \"\"hi\"\"\"\r\n2,\"Bob, Jr.\",\"two\nlines\"\r\n",
+    );
+    let tree = MerkleTree::from_csv(&path, &["name", "id"]).unwrap();
+    let expected = MerkleTree::from_leaves(&[
+        "[\"Alice\",\"1\"]".to_string(),
+        "[\"Bob, Jr.\",\"2\"]".to_string(),
+    ]);
+    assert_eq!(root(&tree), root(&expected));
+
+    // Quoting and line endings do not change the leaves.
+    let path = write(
+        "export-lf.csv",
+        "note,id,name\n\"x\",\"1\",\"Alice\"\n,2,\"Bob, Jr.\"",
+    );
+    assert_eq!(
+        root(&MerkleTree::from_csv(&path, &["name", "id"]).unwrap()),
+        root(&expected)
+    );
+
+    let tree = MerkleTree::from_csv(&path, &["note"]).unwrap();
+    assert_eq!(tree.proof(1).unwrap().leaf_content, "[\"\"]");
+}
+
+#[test]
+fn test_invalid_csv() {
+    let path = write("header.csv", "id,name\n1,Alice\n");
+    let error = MerkleTree::from_csv(&path, &["email"]).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+    for (name, contents, message) in [
+        ("empty.csv", "", "line 1: missing header"),
+        ("count.csv", "id,name\n1,Alice\n2\n", "line 3: field count"),
+        ("quote.csv", "id,name\n1,\"Alice\n", "line 2: unterminated"),
+        (
+            "stray.csv",
+            "id,name\n1,Al\"ice\n",
+            "line 2: quote in unquoted",
+        ),
+        (
+            "after.csv",
+            "id,name\n1,\"Al\"ice\n",
+            "line 2: unexpected character",
+        ),
+    ] {
+        let path = write(name, contents);
+        let error = MerkleTree::from_csv(&path, &["id"]).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with(message), "{}", error);
+    }
+}
+
+#[test]
+fn test_from_ndjson() {
+    let path = write(
+        "export.ndjson",
+        "{\"id\": 1, \"user\": {\"name\": \"Alice\"}, \"tags\": []}\n\n{\"user\":{\"name\":\"Bob\"},\"id\":2,\"tags\":[\"x\"]}\n",
+    );
+    let tree = MerkleTree::from_ndjson(&path, &["/user/name", "/id"]).unwrap();
+    let expected =
+        MerkleTree::from_leaves(&["[\"Alice\",1]".to_string(), "[\"Bob\",2]".to_string()]);
+    assert_eq!(root(&tree), root(&expected));
+
+    let error = MerkleTree::from_ndjson(&path, &["/tags"]).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "line 3: no field at /tags");
+
+    let path = write("invalid.ndjson", "{\"id\": 1}\n{\"id\": \n");
+    let error = MerkleTree::from_ndjson(&path, &["/id"]).err().unwrap();
+    assert_eq!(error.to_string(), "line 2: invalid JSON");
+}
+
+#[test]
+fn test_import_limits() {
+    let path = write("limits.csv", "id\n1\n2\n3\n");
+    let builder = MerkleTree::builder().max_leaves(2);
+    let error = builder.build_from_csv(&path, &["id"]).err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        error.into_inner().unwrap().downcast_ref::<MerkleError>(),
+        Some(&MerkleError::TooManyLeaves(3))
+    );
+    let tree = MerkleTree::builder()
+        .max_leaves(3)
+        .build_from_csv(&path, &["id"])
+        .unwrap();
+    assert_eq!(
+        root(&tree),
+        root(&MerkleTree::from_csv(&path, &["id"]).unwrap())
+    );
+
+    let path = write("limits.ndjson", "{\"id\": 1}\n{\"id\": 22}\n{\"id\": \n");
+    let error = MerkleTree::builder()
+        .max_leaf_size(3)
+        .build_from_ndjson(&path, &["/id"])
+        .err()
+        .unwrap();
+    // The invalid line after the refused leaf is not parsed.
+    assert_eq!(
+        error.into_inner().unwrap().downcast_ref::<MerkleError>(),
+        Some(&MerkleError::LeafTooLarge(1))
+    );
+}
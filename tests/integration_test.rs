@@ -1,7 +1,8 @@
 // Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
 use std::borrow::BorrowMut;
 
-use merkle_tree::MerkleTree;
+use merkle_tree::{Hash, MerkleTree};
 
 #[test]
 fn test_integration() {
@@ -18,7 +19,107 @@ fn test_integration() {
         "b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073"
     );
     let mut proof = MerkleTree::merkle_proof(&data, 1);
-    assert_eq!(MerkleTree::verify_proof(root.to_owned(), &proof), true);
+    assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
     proof.borrow_mut().leaf_content += "tainted";
-    assert_eq!(MerkleTree::verify_proof(root.to_owned(), &proof), false);
+    assert!(!MerkleTree::verify_proof(root.to_owned(), &proof));
+}
+
+#[test]
+fn test_diff() {
+    let write = |name: &str, contents: &str| {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    };
+    let diff = |a: &std::path::Path, b: &std::path::Path| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_merkle-tree"))
+            .arg("diff")
+            .args([a, b])
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap()
+    };
+    let a = write("a.leaves", "abc\nbcd\ncde\ndef\n");
+    let b = write("b.leaves", "abc\nBCD\ncde\n");
+    let c = write("c.leaves", "abc\nbcd\ncde\ndef\nefg\n");
+
+    let output = diff(&a, &a);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let output = diff(&a, &b);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!(
+            "1 {} {}\n3 {} -\n",
+            Hash::hash("bcd"),
+            Hash::hash("BCD"),
+            Hash::hash("def")
+        )
+    );
+
+    let output = diff(&a, &c);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("4 - {}\n", Hash::hash("efg"))
+    );
+
+    let missing = std::env::temp_dir().join("missing.leaves");
+    assert_eq!(diff(&a, &missing).status.code(), Some(2));
+    for path in [a, b, c] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_watch() {
+    use std::io::BufRead;
+
+    let dir = std::env::temp_dir().join(format!("{}-watch", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("a.txt"), "abc").unwrap();
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_merkle-tree"))
+        .arg("watch")
+        .arg(&dir)
+        .arg("20")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Files are written elsewhere and moved in, so no scan sees them partly written.
+    let put = |path: std::path::PathBuf, contents: &str| {
+        let staged = std::env::temp_dir().join(format!("{}-watch-staged", std::process::id()));
+        std::fs::write(&staged, contents).unwrap();
+        std::fs::rename(staged, path).unwrap();
+    };
+    let mut lines = std::io::BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut next = || lines.next().unwrap().unwrap();
+    let root = |leaves: &[String]| {
+        format!(
+            "root {}",
+            MerkleTree::merkle_root(&leaves.to_vec()).borrow().value
+        )
+    };
+
+    let a = format!("{}  a.txt", Hash::hash("abc"));
+    assert_eq!(next(), root(&[a.to_owned()]));
+
+    put(dir.join("sub").join("b.txt"), "bcd");
+    assert_eq!(next(), "A sub/b.txt");
+    let b = format!("{}  sub/b.txt", Hash::hash("bcd"));
+    assert_eq!(next(), root(&[a, b.to_owned()]));
+
+    put(dir.join("a.txt"), "abcd");
+    assert_eq!(next(), "M a.txt");
+    let a = format!("{}  a.txt", Hash::hash("abcd"));
+    assert_eq!(next(), root(&[a.to_owned(), b]));
+
+    std::fs::remove_file(dir.join("sub").join("b.txt")).unwrap();
+    assert_eq!(next(), "D sub/b.txt");
+    assert_eq!(next(), root(&[a]));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
 }
@@ -0,0 +1,57 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::interval_tree::IntervalTree;
+
+fn keys(n: usize) -> Vec<String> {
+    // Even numbers only, leaving a gap after every key.
+    (0..n).map(|i| format!("k{:03}", i * 2)).collect()
+}
+
+#[test]
+fn test_membership() {
+    for n in 1..=12 {
+        let tree = IntervalTree::new(&keys(n));
+        let root = tree.root().unwrap();
+        for key in keys(n) {
+            let proof = tree.prove_membership(&key).unwrap();
+            assert!(IntervalTree::verify_membership(root, &key, &proof));
+            assert!(!IntervalTree::verify_membership(root, "k999", &proof));
+        }
+        assert!(tree.prove_membership("k001").is_none());
+    }
+}
+
+#[test]
+fn test_absence() {
+    for n in 1..=12 {
+        let tree = IntervalTree::new(&keys(n));
+        let root = tree.root().unwrap();
+        for i in 0..(2 * n + 2) {
+            let key = format!("k{:03}", i);
+            let proof = tree.prove_absence(&key, &key);
+            if i % 2 == 0 && i < 2 * n {
+                assert!(proof.is_none());
+                continue;
+            }
+            let proof = proof.unwrap();
+            assert!(IntervalTree::verify_absence(root, &key, &key, &proof));
+            // A gap proof cannot vouch for a wider range reaching an existing key.
+            assert!(!IntervalTree::verify_absence(root, "k000", &key, &proof));
+        }
+        let proof = tree.prove_absence("a", "b").unwrap();
+        assert!(IntervalTree::verify_absence(root, "a", "b", &proof));
+        assert!(!IntervalTree::verify_absence(root, "a", "k000", &proof));
+        assert!(tree.prove_absence("k001", "k002").is_none() || n == 1);
+    }
+}
+
+#[test]
+fn test_unsorted_input() {
+    let mut shuffled = keys(9);
+    shuffled.reverse();
+    shuffled.push("k004".to_string());
+    assert_eq!(
+        IntervalTree::new(&shuffled).root(),
+        IntervalTree::new(&keys(9)).root()
+    );
+}
@@ -0,0 +1,42 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(all(feature = "tree", debug_assertions))]
+mod common;
+
+use common::leaves;
+use merkle_tree::{MerkleError, MerkleTree};
+
+#[test]
+fn test_corrupted_node() {
+    let mut levels = MerkleTree::from_leaves(&leaves(4)).levels();
+    levels[1][1] = levels[1][0].to_owned();
+    assert_eq!(
+        MerkleTree::from_levels(&leaves(4), levels).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_corrupted_leaf() {
+    let levels = MerkleTree::from_leaves(&leaves(3)).levels();
+    let mut contents = leaves(3);
+    contents[2] = "other".to_string();
+    assert_eq!(
+        MerkleTree::from_levels(&contents, levels).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
+
+#[test]
+fn test_operations() {
+    let mut tree = MerkleTree::new();
+    for leaf in leaves(9) {
+        tree.append(leaf);
+    }
+    tree.remove_batch(&[1, 4, 8, 20]);
+    for leaf_index in 0..9 {
+        for level in 0..5 {
+            tree.proof_to_ancestor(leaf_index, level);
+            tree.subtree_proof(level, leaf_index);
+        }
+    }
+}
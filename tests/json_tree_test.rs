@@ -0,0 +1,116 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::json_tree::{field_leaf, flatten, JsonTree};
+use merkle_tree::MerkleError;
+
+const DOCUMENT: &str = r#"{
+    "name": "Alice \"Al\" Smith",
+    "age": 42,
+    "address": {"city": "Zürich", "zip/code": "8001"},
+    "tags": ["a", true, null, -1.5e3],
+    "empty": {},
+    "none": [],
+    "a~b": "\u00e9\ud83d\ude00\n"
+}"#;
+
+#[test]
+fn test_flatten() {
+    let fields = flatten(DOCUMENT).unwrap();
+    let expected = [
+        ("/address/city", "\"Zürich\""),
+        ("/address/zip~1code", "\"8001\""),
+        ("/age", "42"),
+        ("/a~0b", "\"é😀\\n\""),
+        ("/empty", "{}"),
+        ("/name", "\"Alice \\\"Al\\\" Smith\""),
+        ("/none", "[]"),
+        ("/tags/0", "\"a\""),
+        ("/tags/1", "true"),
+        ("/tags/2", "null"),
+        ("/tags/3", "-1.5e3"),
+    ];
+    let expected: Vec<(String, String)> = expected
+        .iter()
+        .map(|(pointer, value)| (pointer.to_string(), value.to_string()))
+        .collect();
+    assert_eq!(fields, expected);
+
+    assert_eq!(
+        flatten(" \"scalar\" ").unwrap(),
+        vec![(String::new(), "\"scalar\"".to_string())]
+    );
+    assert_eq!(field_leaf("/age", "42"), "[\"/age\",42]");
+}
+
+#[test]
+fn test_invalid_documents() {
+    let deep = "[".repeat(200) + &"]".repeat(200);
+    for document in [
+        "",
+        "{",
+        "{\"a\":1,}",
+        "[1 2]",
+        "{\"a\":1,\"a\":2}",
+        "01",
+        "1.",
+        "-",
+        "tru",
+        "\"\\x\"",
+        "\"\\ud800\"",
+        "\"tab\there\"",
+        "1 1",
+        &deep,
+    ] {
+        assert_eq!(
+            flatten(document),
+            Err(MerkleError::InvalidEncoding),
+            "{}",
+            document
+        );
+    }
+}
+
+#[test]
+fn test_canonical_root() {
+    let tree = JsonTree::new(DOCUMENT).unwrap();
+    let reordered = JsonTree::new(
+        r#"{"none":[],"empty":{},"tags":["a",true,null,-1.5e3],"a~b":"é😀\n",
+        "address":{"zip/code":"8001","city":"Z\u00fcrich"},"age":42,"name":"Alice \"Al\" Smith"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        tree.tree().root().unwrap().borrow().value,
+        reordered.tree().root().unwrap().borrow().value
+    );
+
+    let changed = JsonTree::new(&DOCUMENT.replace("42", "43")).unwrap();
+    assert_ne!(
+        tree.tree().root().unwrap().borrow().value,
+        changed.tree().root().unwrap().borrow().value
+    );
+}
+
+#[test]
+fn test_field_proofs() {
+    let tree = JsonTree::new(DOCUMENT).unwrap();
+    let root = tree.tree().root().unwrap();
+    assert_eq!(tree.fields().len(), 11);
+    for (pointer, value) in tree.fields() {
+        let proof = tree.prove(pointer).unwrap();
+        assert_eq!(tree.value(pointer), Some(value.as_str()));
+        assert_eq!(&proof.value, value);
+        assert!(proof.verify(root.to_owned()));
+    }
+
+    // Only leaves are fields, not the objects and arrays holding them.
+    assert!(tree.prove("/address").is_none());
+    assert!(tree.prove("/missing").is_none());
+
+    let mut proof = tree.prove("/age").unwrap();
+    proof.value = "18".to_string();
+    assert!(!proof.verify(root.to_owned()));
+
+    let mut proof = tree.prove("/age").unwrap();
+    proof.pointer = "/tags/3".to_string();
+    assert!(!proof.verify(root.to_owned()));
+}
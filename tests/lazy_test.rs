@@ -0,0 +1,89 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{LazyTree, LeafOrdering, MerkleError, MerkleProof, MerkleTree, Padding};
+
+fn audit_hashes(proof: &MerkleProof) -> Vec<(String, bool)> {
+    proof
+        .hashes
+        .iter()
+        .map(|hash| (hash.borrow().value.clone(), hash.borrow().is_left))
+        .collect()
+}
+
+#[test]
+fn test_lazy_tree() {
+    for padding in [
+        Padding::Promote,
+        Padding::DuplicateLast,
+        Padding::Constant("00".repeat(32)),
+    ] {
+        for num_of_leaves in 0..20 {
+            let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+            let builder = MerkleTree::builder()
+                .padding(padding.clone())
+                .ordering(LeafOrdering::SortedByHash);
+            let tree = builder.build(&leaves);
+            let lazy = builder.build_lazy(&leaves).unwrap();
+            assert_eq!(lazy.num_of_leaves(), num_of_leaves);
+            assert_eq!(lazy.num_of_cached_nodes(), 0);
+
+            for leaf_index in 0..num_of_leaves {
+                let expected = tree.proof(leaf_index).unwrap();
+                let proof = lazy.proof(leaf_index).unwrap();
+                assert_eq!(lazy.leaf(leaf_index), Some(expected.leaf_content.as_str()));
+                assert_eq!(proof.leaf_content, expected.leaf_content);
+                assert_eq!(audit_hashes(&proof), audit_hashes(&expected));
+                assert_eq!(proof.num_of_leaves, num_of_leaves);
+            }
+            assert!(lazy.proof(num_of_leaves).is_none());
+            assert_eq!(
+                lazy.root(),
+                tree.root().map(|root| root.borrow().value.clone())
+            );
+            assert_eq!(lazy.to_tree().levels(), tree.levels());
+
+            let levels = tree.levels();
+            for (level, values) in levels.iter().enumerate() {
+                for (index, value) in values.iter().enumerate() {
+                    assert_eq!(lazy.node(level, index).as_ref(), Some(value));
+                }
+                assert!(lazy.node(level, values.len()).is_none());
+            }
+            assert!(lazy.node(levels.len(), 0).is_none());
+        }
+    }
+}
+
+#[test]
+fn test_nodes_computed_on_demand() {
+    let leaves: Vec<String> = (0..1024).map(|i| i.to_string()).collect();
+    let tree = LazyTree::from_leaves(&leaves);
+
+    // A proof keeps only its audit hashes above the leaf level.
+    tree.proof(0).unwrap();
+    assert_eq!(tree.num_of_cached_nodes(), 9);
+    // Proofs of neighbouring leaves share their upper audit hashes.
+    tree.proof(2).unwrap();
+    assert_eq!(tree.num_of_cached_nodes(), 10);
+    tree.root().unwrap();
+    assert_eq!(tree.num_of_cached_nodes(), 11);
+}
+
+#[test]
+fn test_build_lazy_errors() {
+    let leaves: Vec<String> = vec!["a".to_string(), "".to_string()];
+    assert_eq!(
+        MerkleTree::builder()
+            .reject_empty_leaves()
+            .build_lazy(&leaves)
+            .err(),
+        Some(MerkleError::EmptyLeaf(1))
+    );
+    assert_eq!(
+        MerkleTree::builder()
+            .max_leaves(1)
+            .build_lazy(&leaves)
+            .err(),
+        Some(MerkleError::TooManyLeaves(2))
+    );
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{Endianness, LeafEncode, LeafEncoding, MerkleTree};
+
+#[test]
+fn test_fixed_width() {
+    assert_eq!(LeafEncode::u32_be(0x01020304), "01020304");
+    assert_eq!(LeafEncode::u32_le(0x01020304), "04030201");
+    assert_eq!(LeafEncode::u64_be(1), "0000000000000001");
+    assert_eq!(LeafEncode::u64_le(1), "0100000000000000");
+    assert_eq!(LeafEncode::u128_be(1), format!("{:032x}", 1));
+    assert_eq!(LeafEncode::u128_le(u128::MAX), "f".repeat(32));
+    assert_eq!(LeafEncode::i64_be(-2), "fffffffffffffffe");
+    assert_eq!(LeafEncode::i64_le(-2), "feffffffffffffff");
+}
+
+#[test]
+fn test_variable_width() {
+    assert_eq!(
+        LeafEncode::unsigned(0x0102, 3, Endianness::Big).as_deref(),
+        Some("000102")
+    );
+    assert_eq!(
+        LeafEncode::unsigned(0x0102, 3, Endianness::Little).as_deref(),
+        Some("020100")
+    );
+    assert_eq!(
+        LeafEncode::unsigned(u64::MAX as u128, 8, Endianness::Big),
+        Some(LeafEncode::u64_be(u64::MAX))
+    );
+    assert_eq!(
+        LeafEncode::unsigned(u128::MAX, 16, Endianness::Little),
+        Some(LeafEncode::u128_le(u128::MAX))
+    );
+    assert_eq!(LeafEncode::unsigned(256, 1, Endianness::Big), None);
+    assert_eq!(LeafEncode::unsigned(0, 0, Endianness::Big), None);
+    assert_eq!(LeafEncode::unsigned(0, 17, Endianness::Big), None);
+
+    assert_eq!(
+        LeafEncode::signed(-2, 8, Endianness::Little),
+        Some(LeafEncode::i64_le(-2))
+    );
+    assert_eq!(
+        LeafEncode::signed(-128, 1, Endianness::Big).as_deref(),
+        Some("80")
+    );
+    assert_eq!(
+        LeafEncode::signed(127, 1, Endianness::Big).as_deref(),
+        Some("7f")
+    );
+    assert_eq!(LeafEncode::signed(128, 1, Endianness::Big), None);
+    assert_eq!(LeafEncode::signed(-129, 1, Endianness::Big), None);
+    assert_eq!(
+        LeafEncode::signed(i128::MIN, 16, Endianness::Big),
+        Some(format!("8{}", "0".repeat(31)))
+    );
+}
+
+#[test]
+fn test_length_prefixed() {
+    assert_eq!(
+        LeafEncode::length_prefixed(b"ab", 2, Endianness::Big).as_deref(),
+        Some("00026162")
+    );
+    assert_eq!(
+        LeafEncode::length_prefixed(b"ab", 4, Endianness::Little).as_deref(),
+        Some("020000006162")
+    );
+    assert_eq!(LeafEncode::length_prefixed(b"", 0, Endianness::Big), None);
+    assert_eq!(
+        LeafEncode::length_prefixed(&[0; 256], 1, Endianness::Big),
+        None
+    );
+    assert_eq!(LeafEncode::length_prefixed(b"", 9, Endianness::Big), None);
+}
+
+#[test]
+fn test_leaves_hash_encoded_bytes() {
+    let leaves = vec![
+        LeafEncode::u64_be(7) + &LeafEncode::u32_be(1),
+        LeafEncode::u64_be(8) + &LeafEncode::u32_be(2),
+    ];
+    let tree = MerkleTree::builder()
+        .leaf_encoding(LeafEncoding::Hex)
+        .build(&leaves);
+    let proof = tree.proof(1).unwrap();
+    assert_eq!(
+        LeafEncoding::Hex.decode(&proof.leaf_content),
+        Some([8u64.to_be_bytes().as_slice(), &2u32.to_be_bytes()].concat())
+    );
+    assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+}
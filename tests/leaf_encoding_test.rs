@@ -0,0 +1,114 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{
+    CommittedRoot, LeafEncoding, MerkleError, MerkleProof, MerkleTree, INVALID_LEAF,
+};
+
+#[test]
+fn test_decode() {
+    let bytes = vec![0x00, 0x7f, 0xe9, 0xff];
+    assert_eq!(LeafEncoding::Utf8.decode("aé"), Some(b"a\xc3\xa9".to_vec()));
+    assert_eq!(
+        LeafEncoding::RawBytes.decode("\u{0}\u{7f}\u{e9}\u{ff}"),
+        Some(bytes.clone())
+    );
+    assert_eq!(LeafEncoding::RawBytes.decode("\u{100}"), None);
+    assert_eq!(LeafEncoding::Hex.decode("007fe9ff"), Some(bytes.clone()));
+    assert_eq!(LeafEncoding::Hex.decode("007FE9FF"), Some(bytes.clone()));
+    for invalid in ["007", "0g", "+f", " f"] {
+        assert_eq!(LeafEncoding::Hex.decode(invalid), None);
+    }
+    assert_eq!(LeafEncoding::Base64.decode("AH/p/w=="), Some(bytes));
+    assert_eq!(LeafEncoding::Base64.decode("YWJj"), Some(b"abc".to_vec()));
+    assert_eq!(LeafEncoding::Base64.decode("YWI="), Some(b"ab".to_vec()));
+    assert_eq!(LeafEncoding::Base64.decode(""), Some(vec![]));
+    // Unused bits must be zero, so every byte string has exactly one encoding.
+    for invalid in ["AH/p/x==", "YWJ=", "YWI", "YW=j", "YQ==YQ==", "YW-j"] {
+        assert_eq!(LeafEncoding::Base64.decode(invalid), None);
+    }
+}
+
+#[test]
+fn test_encodings_hash_the_same_bytes() {
+    let contents = [
+        (LeafEncoding::Utf8, ["a", "é", ""]),
+        (LeafEncoding::RawBytes, ["a", "\u{c3}\u{a9}", ""]),
+        (LeafEncoding::Hex, ["61", "C3a9", ""]),
+        (LeafEncoding::Base64, ["YQ==", "w6k=", ""]),
+    ];
+    let expected = MerkleTree::from_leaves(&["a".to_string(), "é".to_string(), "".to_string()]);
+    for (leaf_encoding, leaves) in contents {
+        let leaves: Vec<String> = leaves.map(String::from).to_vec();
+        let builder = MerkleTree::builder().leaf_encoding(leaf_encoding);
+        let tree = builder.build(&leaves);
+        assert_eq!(tree.leaf_encoding(), leaf_encoding);
+        assert_eq!(tree.levels(), expected.levels());
+
+        let proof = tree.proof(1).unwrap();
+        assert_eq!(proof.leaf_encoding, leaf_encoding);
+        assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+        let proof = MerkleProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(proof.leaf_encoding, leaf_encoding);
+        assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(restored.leaf_encoding(), leaf_encoding);
+        assert_eq!(restored.levels(), tree.levels());
+
+        let committed = CommittedRoot::of(&tree, 0).unwrap();
+        assert_eq!(committed.scheme.leaf_encoding, leaf_encoding);
+        assert_eq!(committed.verify_proof(&tree.proof(0).unwrap()), Ok(()));
+    }
+}
+
+#[test]
+fn test_encoding_honored_during_verification() {
+    let leaves: Vec<String> = vec!["616263".to_string(), "646566".to_string()];
+    let tree = MerkleTree::builder()
+        .leaf_encoding(LeafEncoding::Hex)
+        .build(&leaves);
+    let root = tree.root().unwrap();
+
+    // The same proof read as UTF-8 hashes other bytes.
+    let mut proof = tree.proof(0).unwrap();
+    proof.leaf_encoding = LeafEncoding::Utf8;
+    assert!(!MerkleTree::verify_proof(root.clone(), &proof));
+    assert_eq!(
+        CommittedRoot::of(&tree, 0).unwrap().verify_proof(&proof),
+        Err(MerkleError::SchemeMismatch)
+    );
+
+    // Contents that are not valid in the encoding verify against no tree.
+    let mut proof = tree.proof(0).unwrap();
+    proof.leaf_content = "abc".to_owned();
+    assert_eq!(proof.leaf_value(), INVALID_LEAF);
+    assert!(!MerkleTree::verify_proof(root, &proof));
+    assert_eq!(
+        MerkleTree::verify_proof_pinned(tree.root().unwrap(), &proof, INVALID_LEAF),
+        Err(MerkleError::ProofHashMismatch)
+    );
+}
+
+#[test]
+fn test_invalid_leaves() {
+    let leaves: Vec<String> = vec!["00".to_string(), "xyz".to_string()];
+    let builder = MerkleTree::builder().leaf_encoding(LeafEncoding::Hex);
+    assert_eq!(
+        builder.try_build(&leaves).err(),
+        Some(MerkleError::InvalidLeafEncoding(1))
+    );
+    assert_eq!(
+        builder.build_lazy(&leaves).err(),
+        Some(MerkleError::InvalidLeafEncoding(1))
+    );
+    assert!(builder.try_build(&leaves[..1]).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "leaf is not valid Base64")]
+fn test_append_invalid_leaf() {
+    let mut tree = MerkleTree::builder()
+        .leaf_encoding(LeafEncoding::Base64)
+        .build(&[]);
+    tree.append("YQ".to_owned());
+}
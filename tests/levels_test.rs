@@ -0,0 +1,85 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{Concatenation, MerkleError, MerkleTree, TOMBSTONE};
+
+#[test]
+fn test_levels() {
+    let tree = MerkleTree::from_leaves(&leaves(5));
+    let levels = tree.levels();
+    let sizes: Vec<usize> = levels.iter().map(Vec::len).collect();
+    assert_eq!(sizes, vec![5, 3, 2, 1]);
+    assert_eq!(levels[3][0], tree.root().unwrap().borrow().value);
+    assert!(MerkleTree::new().levels().is_empty());
+}
+
+#[test]
+fn test_from_levels() {
+    for n in 0..9 {
+        let tree = MerkleTree::from_leaves(&leaves(n));
+        let restored = MerkleTree::from_levels(&leaves(n), tree.levels()).unwrap();
+        assert_eq!(restored.num_of_leaves(), n);
+        assert_eq!(restored.levels(), tree.levels());
+        for leaf_index in 0..n {
+            let proof = restored.proof(leaf_index).unwrap();
+            assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+        }
+    }
+}
+
+#[test]
+fn test_build_from_levels() {
+    let builder = MerkleTree::builder()
+        .concatenation(Concatenation::Bytes)
+        .truncation(8);
+    let mut tree = builder.build(&leaves(6));
+    tree.remove(2);
+    let mut contents = leaves(6);
+    contents[2] = String::new();
+    let levels = tree.levels();
+    assert_eq!(levels[0][2], TOMBSTONE[..16]);
+
+    let mut restored = builder
+        .clone()
+        .reject_empty_leaves()
+        .build_from_levels(&contents, levels)
+        .unwrap();
+    assert!(restored.is_removed(2));
+    restored.append("leaf6".to_string());
+    tree.append("leaf6".to_string());
+    assert_eq!(restored.levels(), tree.levels());
+}
+
+#[test]
+fn test_from_levels_shape() {
+    let levels = MerkleTree::from_leaves(&leaves(4)).levels();
+    assert!(matches!(
+        MerkleTree::from_levels(&leaves(3), levels.clone()),
+        Err(MerkleError::InvalidEncoding)
+    ));
+    assert!(matches!(
+        MerkleTree::from_levels(&leaves(4), levels[..2].to_vec()),
+        Err(MerkleError::InvalidEncoding)
+    ));
+    let mut extra = levels.clone();
+    extra.push(extra[2].clone());
+    assert!(matches!(
+        MerkleTree::from_levels(&leaves(4), extra),
+        Err(MerkleError::InvalidEncoding)
+    ));
+    assert!(matches!(
+        MerkleTree::from_levels(&leaves(1), Vec::new()),
+        Err(MerkleError::InvalidEncoding)
+    ));
+    assert!(matches!(
+        MerkleTree::builder()
+            .reject_empty_leaves()
+            .build_from_levels(
+                &[String::new()],
+                MerkleTree::from_leaves(&[String::new()]).levels()
+            ),
+        Err(MerkleError::EmptyLeaf(0))
+    ));
+}
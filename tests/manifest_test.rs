@@ -0,0 +1,123 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::manifest::Manifest;
+use merkle_tree::{HashAlgorithm, MerkleError, MerkleTree};
+
+fn files() -> Vec<(String, Vec<u8>)> {
+    (0..10)
+        .rev()
+        .map(|i| {
+            (
+                format!("bin/tool{}", i),
+                format!("contents {}", i).into_bytes(),
+            )
+        })
+        .chain([("a.txt".to_string(), b"abc".to_vec())])
+        .collect()
+}
+
+#[test]
+fn test_manifest() {
+    let manifest = Manifest::of_files(HashAlgorithm::Sha256, &files()).unwrap();
+    assert_eq!(manifest.entries.len(), 11);
+    assert_eq!(manifest.entries[0].name, "a.txt");
+    assert_eq!(manifest.entries[1].name, "bin/tool0");
+    assert_eq!(
+        manifest.entries[0].leaf(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  a.txt"
+    );
+    let leaves: Vec<String> = manifest.entries.iter().map(|entry| entry.leaf()).collect();
+    assert_eq!(
+        MerkleTree::merkle_root(&leaves).borrow().value,
+        manifest.root
+    );
+    assert_eq!(manifest.verify(), Ok(()));
+
+    assert!(manifest.contains_file("a.txt", b"abc"));
+    assert!(!manifest.contains_file("a.txt", b"abd"));
+    assert!(!manifest.contains_file("b.txt", b"abc"));
+
+    // A single file is proven without the rest of the listing.
+    let tree = manifest.tree();
+    let proof = tree.proof(0).unwrap();
+    assert_eq!(proof.leaf_content, manifest.entries[0].leaf());
+    assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+
+    let duplicated = [("a", b"1"), ("a", b"2")];
+    assert!(Manifest::of_files(HashAlgorithm::Sha256, &duplicated).is_none());
+    let empty: [(&str, &[u8]); 0] = [];
+    assert!(Manifest::of_files(HashAlgorithm::Sha256, &empty).is_none());
+}
+
+#[test]
+fn test_tampered_manifest() {
+    let mut manifest = Manifest::of_files(HashAlgorithm::Sha256, &files()).unwrap();
+    manifest.entries[3].digest = HashAlgorithm::Sha256.hash(b"malware");
+    assert_eq!(manifest.verify(), Err(MerkleError::ProofHashMismatch));
+
+    let mut manifest = Manifest::of_files(HashAlgorithm::Sha256, &files()).unwrap();
+    manifest.entries.swap(0, 1);
+    assert_eq!(manifest.verify(), Err(MerkleError::InvalidEncoding));
+}
+
+#[test]
+fn test_der() {
+    let manifest = Manifest::of_files(HashAlgorithm::Sha256, &[("a", b"abc")]).unwrap();
+    let der = manifest.to_der().unwrap();
+    let expected = [
+        "305b",                       // IntegrityManifest
+        "020101",                     // version
+        "300b0609608648016503040201", // sha256
+        "0420",
+        &manifest.root,
+        "3027",   // entries
+        "3025",   // entry
+        "0c0161", // "a"
+        "0420ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ]
+    .concat();
+    assert_eq!(to_hex(&der), expected);
+    assert_eq!(Manifest::from_der(&der), Ok(manifest.to_owned()));
+
+    let info = manifest.to_encapsulated_content_info().unwrap();
+    assert_eq!(to_hex(&info[..17]), "306c06092a864886f70d010701a05f045d");
+    assert_eq!(&info[info.len() - der.len()..], &der[..]);
+
+    // Listings longer than 127 bytes use the long form of lengths.
+    let manifest = Manifest::of_files(HashAlgorithm::Sha256, &files()).unwrap();
+    let der = manifest.to_der().unwrap();
+    assert_eq!(&der[..2], &[0x30, 0x82]);
+    assert_eq!(Manifest::from_der(&der), Ok(manifest));
+}
+
+#[test]
+fn test_invalid_der() {
+    let manifest = Manifest::of_files(HashAlgorithm::Sha256, &[("a", b"abc")]).unwrap();
+    let der = manifest.to_der().unwrap();
+
+    let mut version = der.to_owned();
+    version[4] = 2;
+    assert_eq!(
+        Manifest::from_der(&version),
+        Err(MerkleError::UnsupportedVersion(2))
+    );
+
+    let mut algorithm = der.to_owned();
+    algorithm[17] = 0x09;
+    assert_eq!(
+        Manifest::from_der(&algorithm),
+        Err(MerkleError::UnsupportedScheme)
+    );
+
+    for bytes in [
+        &der[..der.len() - 1],
+        &[der.as_slice(), &[0]].concat(),
+        &[0x30, 0x81, 0x05],
+    ] {
+        assert_eq!(Manifest::from_der(bytes), Err(MerkleError::InvalidEncoding));
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
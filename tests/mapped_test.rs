@@ -0,0 +1,132 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "mmap")]
+mod common;
+
+use common::leaves;
+use merkle_tree::mapped::MappedTree;
+use merkle_tree::{MerkleTree, Padding};
+use std::path::PathBuf;
+
+fn path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}", std::process::id(), name))
+}
+
+fn assert_same_proofs(tree: &MerkleTree, mapped: &MappedTree) {
+    assert_eq!(mapped.num_of_leaves(), tree.num_of_leaves());
+    assert_eq!(
+        mapped.root(),
+        tree.root().map(|root| root.borrow().value.to_owned())
+    );
+    for leaf_index in 0..tree.num_of_leaves() + 1 {
+        match (tree.proof(leaf_index), mapped.proof(leaf_index)) {
+            (None, None) => {}
+            (Some(expected), Some(proof)) => {
+                assert_eq!(proof.leaf_content, expected.leaf_content);
+                assert_eq!(proof.root_value(), expected.root_value());
+                assert_eq!(proof.hashes.len(), expected.hashes.len());
+                assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+            }
+            _ => panic!("proofs of leaf {} differ", leaf_index),
+        }
+    }
+}
+
+#[test]
+fn test_mapped_tree() {
+    for n in [0, 1, 2, 5, 16, 33] {
+        let path = path(&format!("mapped-{}.mrkl", n));
+        let tree = MerkleTree::from_leaves(&leaves(n));
+        let created = MappedTree::create(&path, &tree).unwrap();
+        assert_same_proofs(&tree, &created);
+        let opened = MappedTree::open(&path).unwrap();
+        assert_same_proofs(&tree, &opened);
+        assert_eq!(
+            opened.leaf(n.saturating_sub(1)),
+            leaves(n).last().map(String::as_str)
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn test_mapped_scheme() {
+    let path = path("mapped-scheme.mrkl");
+    let mut tree = MerkleTree::builder()
+        .padding(Padding::DuplicateLast)
+        .truncation(16)
+        .blinding([3; 32])
+        .build(&leaves(7));
+    tree.remove(3);
+    let mapped = MappedTree::create(&path, &tree).unwrap();
+    assert_same_proofs(&tree, &mapped);
+    assert!(mapped.proof(3).is_none());
+    assert_eq!(mapped.leaf(3), Some(""));
+    assert_eq!(mapped.scheme(), tree.scheme());
+    assert_eq!(mapped.node(1, 3), Some(tree.levels()[1][3].to_owned()));
+    assert_eq!(mapped.node(1, 4), None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_mapped_compact() {
+    let compacted_path = path("mapped-compacted.mrkl");
+    let path = path("mapped-compact.mrkl");
+    let mut tree = MerkleTree::builder()
+        .padding(Padding::DuplicateLast)
+        .blinding([3; 32])
+        .build(&leaves(7));
+    tree.remove_batch(&[0, 3, 4]);
+    let mapped = MappedTree::create(&path, &tree).unwrap();
+    let (compacted, remap) = mapped.compact(&compacted_path).unwrap();
+    assert_eq!(
+        remap,
+        vec![None, Some(0), Some(1), None, None, Some(2), Some(3)]
+    );
+
+    let kept: Vec<String> = [1, 2, 5, 6]
+        .iter()
+        .map(|i| leaves(7)[*i].to_owned())
+        .collect();
+    let expected = MerkleTree::builder()
+        .padding(Padding::DuplicateLast)
+        .blinding([3; 32])
+        .build(&kept);
+    assert_same_proofs(&expected, &compacted);
+    assert_eq!(compacted.scheme(), tree.scheme());
+    for (leaf_index, new_index) in remap.iter().enumerate() {
+        if let Some(new_index) = new_index {
+            assert_eq!(compacted.leaf(*new_index), mapped.leaf(leaf_index));
+        }
+    }
+    assert_same_proofs(&expected, &MappedTree::open(&compacted_path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&compacted_path).unwrap();
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_mapped_removed_digest_size() {
+    let path = path("mapped-sha3-512.mrkl");
+    let mut tree = MerkleTree::builder()
+        .algorithm(merkle_tree::HashAlgorithm::Sha3_512)
+        .build(&leaves(5));
+    tree.remove(2);
+    let mapped = MappedTree::create(&path, &tree).unwrap();
+    assert_same_proofs(&tree, &mapped);
+    assert!(mapped.proof(2).is_none());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_mapped_invalid() {
+    let path = path("mapped-invalid.mrkl");
+    let tree = MerkleTree::from_leaves(&leaves(4));
+    MappedTree::create(&path, &tree).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    let error = MappedTree::open(&path).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::write(&path, tree.to_bytes()).unwrap();
+    assert!(MappedTree::open(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,6 +1,8 @@
 // Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
-use merkle_tree::{Hash, MerkleTree};
+#![cfg(feature = "tree")]
+use merkle_tree::{Hash, MerkleError, MerkleTree};
 use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
 use std::borrow::BorrowMut;
 
 static H0: Lazy<String> = Lazy::new(|| Hash::hash("0"));
@@ -20,10 +22,10 @@ static H_H6_H7: Lazy<String> = Lazy::new(|| Hash::hash(&format!("{}{}", *H6, *H7
 #[test]
 fn test_merkle_root() {
     let test_cases: Vec<(Vec<String>, String)> = vec![
-        ((0..=0).map(|i| i.to_string()).collect(), format!("{}", *H0)),
+        ((0..=0).map(|i| i.to_string()).collect(), H0.to_string()),
         (
             (0..=1).map(|i| i.to_string()).collect(),
-            format!("{}", *H_H0_H1),
+            H_H0_H1.to_string(),
         ),
         (
             (0..=2).map(|i| i.to_string()).collect(),
@@ -127,17 +129,15 @@ fn test_merkle_proof() {
             vec![
                 H5.to_string(),
                 H6.to_string(),
-                Hash::hash(&format!("{}{}", H_H0_H1.to_string(), H_H2_H3.to_string())),
+                Hash::hash(&format!("{}{}", *H_H0_H1, *H_H2_H3)),
             ],
         ),
     ];
     for (leaves, leaf_index, expected_proof_nodes) in &test_cases {
-        let proof = MerkleTree::merkle_proof(&leaves, leaf_index.to_owned());
+        let proof = MerkleTree::merkle_proof(leaves, leaf_index.to_owned());
         assert_eq!(proof.hashes.len(), expected_proof_nodes.len());
-        let mut i = 0;
-        for hash in &proof.hashes {
+        for (i, hash) in proof.hashes.iter().enumerate() {
             assert_eq!(hash.borrow().value, expected_proof_nodes[i]);
-            i += 1;
         }
     }
 }
@@ -151,9 +151,115 @@ fn test_verify_proof() {
         for leaf_index in 0..leaves.len() {
             let root = MerkleTree::merkle_root(&leaves);
             let mut proof = MerkleTree::merkle_proof(&leaves, leaf_index);
-            assert_eq!(MerkleTree::verify_proof(root.to_owned(), &proof), true);
+            assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
             proof.borrow_mut().leaf_content += "tainted";
-            assert_eq!(MerkleTree::verify_proof(root, &proof), false);
+            assert!(!MerkleTree::verify_proof(root, &proof));
+        }
+    }
+}
+
+#[test]
+fn test_all_proofs() {
+    assert!(MerkleTree::all_proofs(&[]).is_empty());
+    let leaves_sets: Vec<Vec<String>> = (1..=10)
+        .map(|i| (0..i).map(|j| j.to_string()).collect())
+        .collect();
+    for leaves in leaves_sets {
+        let root = MerkleTree::merkle_root(&leaves);
+        let proofs = MerkleTree::all_proofs(&leaves);
+        assert_eq!(proofs.len(), leaves.len());
+        for (leaf_index, proof) in proofs.iter().enumerate() {
+            let expected = MerkleTree::merkle_proof(&leaves, leaf_index);
+            assert_eq!(proof.leaf_index, leaf_index);
+            assert_eq!(proof.num_of_leaves, leaves.len());
+            assert_eq!(proof.leaf_content, leaves[leaf_index]);
+            assert_eq!(
+                proof
+                    .hashes
+                    .iter()
+                    .map(|hash| (hash.borrow().value.to_owned(), hash.borrow().is_left))
+                    .collect::<Vec<(String, bool)>>(),
+                expected
+                    .hashes
+                    .iter()
+                    .map(|hash| (hash.borrow().value.to_owned(), hash.borrow().is_left))
+                    .collect::<Vec<(String, bool)>>()
+            );
+            assert!(MerkleTree::verify_proof(root.to_owned(), proof));
+        }
+    }
+}
+
+#[test]
+fn test_all_proofs_repeated() {
+    for num_of_leaves in 1..=10 {
+        let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+        let root = MerkleTree::from_leaves(&leaves).root().unwrap();
+        let first = MerkleTree::all_proofs(&leaves);
+        let second = MerkleTree::all_proofs(&leaves);
+        assert_eq!(
+            first
+                .iter()
+                .map(|proof| proof.to_bytes())
+                .collect::<Vec<_>>(),
+            second
+                .iter()
+                .map(|proof| proof.to_bytes())
+                .collect::<Vec<_>>()
+        );
+        for proof in &first {
+            assert!(MerkleTree::verify_proof(root.to_owned(), proof));
+        }
+    }
+}
+
+#[test]
+fn test_verify_proof_counted() {
+    let mut steps = 0;
+    for num_of_leaves in 1..=10 {
+        let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+        let root = MerkleTree::merkle_root(&leaves);
+        for leaf_index in 0..leaves.len() {
+            let proof = MerkleTree::merkle_proof(&leaves, leaf_index);
+            let before = steps;
+            assert!(MerkleTree::verify_proof_counted(
+                root.to_owned(),
+                &proof,
+                &mut steps
+            ));
+            // One hash for the leaf, and one per audit hash.
+            assert_eq!(steps - before, 1 + proof.hashes.len() as u64);
         }
     }
 }
+
+#[test]
+fn test_verify_proof_pinned() {
+    let leaves: Vec<String> = vec!["héllo".to_string(), "world".to_string()];
+    let root = MerkleTree::merkle_root(&leaves);
+    let proof = MerkleTree::merkle_proof(&leaves, 0);
+    let digest = Hash::hash("héllo");
+    assert_eq!(proof.leaf_value(), digest);
+
+    assert_eq!(
+        MerkleTree::verify_proof_pinned(root.to_owned(), &proof, &digest),
+        Ok(())
+    );
+    assert_eq!(
+        MerkleTree::verify_proof_pinned(root.to_owned(), &proof, &digest.to_uppercase()),
+        Ok(())
+    );
+
+    // Digest of the Latin-1 encoding of the same text, as a mismatched client would compute.
+    let latin1 = format!("{:x}", Sha256::digest([0x68, 0xe9, 0x6c, 0x6c, 0x6f]));
+    assert_eq!(
+        MerkleTree::verify_proof_pinned(root.to_owned(), &proof, &latin1),
+        Err(MerkleError::LeafDigestMismatch(digest.to_owned()))
+    );
+
+    let other_root = MerkleTree::merkle_root(&leaves[..1].to_vec());
+    assert_eq!(
+        MerkleTree::verify_proof_pinned(other_root, &proof, &digest),
+        Err(MerkleError::ProofHashMismatch)
+    );
+}
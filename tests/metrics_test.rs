@@ -0,0 +1,96 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "metrics")]
+use merkle_tree::MerkleTree;
+use metrics::{
+    Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Value of a metric, shared with the handles registered for it.
+#[derive(Default)]
+struct Value(Mutex<Vec<f64>>);
+
+impl CounterFn for Value {
+    fn increment(&self, value: u64) {
+        self.0.lock().unwrap().push(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        *self.0.lock().unwrap() = vec![value as f64];
+    }
+}
+
+impl HistogramFn for Value {
+    fn record(&self, value: f64) {
+        self.0.lock().unwrap().push(value);
+    }
+}
+
+/// Recorder keeping every value recorded under each metric name.
+#[derive(Default)]
+struct TestRecorder(Mutex<HashMap<String, Arc<Value>>>);
+
+impl TestRecorder {
+    fn value(&self, key: &Key) -> Arc<Value> {
+        let mut values = self.0.lock().unwrap();
+        values.entry(key.name().to_owned()).or_default().clone()
+    }
+
+    /// Sum of the increments of a counter.
+    fn counter(&self, name: &str) -> u64 {
+        self.samples(name).iter().sum::<f64>() as u64
+    }
+
+    /// Values recorded under a metric.
+    fn samples(&self, name: &str) -> Vec<f64> {
+        match self.0.lock().unwrap().get(name) {
+            Some(value) => value.0.lock().unwrap().clone(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.value(key))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.value(key))
+    }
+}
+
+#[test]
+fn test_metrics() {
+    let recorder = TestRecorder::default();
+    metrics::with_local_recorder(&recorder, || {
+        let leaves: Vec<String> = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        let mut tree = MerkleTree::from_leaves(&leaves);
+        tree.append("f".to_owned());
+        let root = tree.root().unwrap();
+
+        let proof = tree.proof(0).unwrap();
+        assert!(MerkleTree::verify_proof(root.clone(), &proof));
+        let mut tampered = tree.proof(4).unwrap();
+        tampered.leaf_content = "z".to_owned();
+        assert!(!MerkleTree::verify_proof(root, &tampered));
+    });
+
+    assert_eq!(recorder.counter("merkle_tree.leaves_appended"), 6);
+    assert_eq!(recorder.counter("merkle_tree.proofs_served"), 2);
+    assert_eq!(recorder.samples("merkle_tree.proof_length"), vec![3.0, 2.0]);
+    assert_eq!(recorder.counter("merkle_tree.verification_failures"), 1);
+    assert!(recorder.counter("merkle_tree.hash_ops") > 0);
+}
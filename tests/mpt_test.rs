@@ -0,0 +1,122 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "mpt")]
+use merkle_tree::mpt::{self, PatriciaTrie, EMPTY_ROOT};
+use merkle_tree::MerkleError;
+
+fn from_hex(value: &str) -> [u8; 32] {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+        .collect::<Vec<u8>>()
+        .try_into()
+        .unwrap()
+}
+
+fn trie(entries: &[(&str, &str)]) -> PatriciaTrie {
+    let mut trie = PatriciaTrie::new();
+    for (key, value) in entries {
+        trie.insert(key.as_bytes(), value.as_bytes().to_vec());
+    }
+    trie
+}
+
+// Root hashes from the Ethereum trie test suite.
+#[test]
+fn test_root_hash() {
+    assert_eq!(PatriciaTrie::new().root_hash(), EMPTY_ROOT);
+    assert_eq!(
+        trie(&[
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat")
+        ])
+        .root_hash(),
+        from_hex("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+    );
+    assert_eq!(
+        trie(&[
+            ("do", "verb"),
+            ("horse", "stallion"),
+            ("doge", "coin"),
+            ("dog", "puppy")
+        ])
+        .root_hash(),
+        from_hex("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+    );
+    // Insertion order does not matter, and a replaced value leaves no trace.
+    assert_eq!(
+        trie(&[
+            ("dog", "puppy"),
+            ("horse", "pony"),
+            ("doge", "coin"),
+            ("do", "verb"),
+            ("horse", "stallion"),
+        ])
+        .root_hash(),
+        from_hex("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+    );
+}
+
+#[test]
+fn test_get() {
+    let trie = trie(&[
+        ("do", "verb"),
+        ("horse", "stallion"),
+        ("doge", "coin"),
+        ("dog", "puppy"),
+    ]);
+    assert_eq!(trie.get(b"do"), Some(&b"verb"[..]));
+    assert_eq!(trie.get(b"dog"), Some(&b"puppy"[..]));
+    assert_eq!(trie.get(b"doge"), Some(&b"coin"[..]));
+    assert_eq!(trie.get(b"horse"), Some(&b"stallion"[..]));
+    assert_eq!(trie.get(b"d"), None);
+    assert_eq!(trie.get(b"dogs"), None);
+    assert_eq!(trie.get(b"cat"), None);
+}
+
+#[test]
+fn test_proof() {
+    let entries: Vec<(String, String)> = (0..64)
+        .map(|i| {
+            (
+                format!("key{}", i * 7),
+                format!("value{}", i).repeat(i % 5 + 1),
+            )
+        })
+        .collect();
+    let mut trie = PatriciaTrie::new();
+    for (key, value) in &entries {
+        trie.insert(key.as_bytes(), value.as_bytes().to_vec());
+    }
+    let root_hash = trie.root_hash();
+
+    for (key, value) in &entries {
+        let proof = trie.proof(key.as_bytes());
+        assert_eq!(
+            mpt::verify_proof(&root_hash, key.as_bytes(), &proof),
+            Ok(Some(value.as_bytes().to_vec()))
+        );
+    }
+    for key in ["key1", "key", "missing", "key13999"] {
+        let proof = trie.proof(key.as_bytes());
+        assert_eq!(
+            mpt::verify_proof(&root_hash, key.as_bytes(), &proof),
+            Ok(None)
+        );
+    }
+
+    let mut proof = trie.proof(b"key7");
+    proof.pop();
+    assert_eq!(
+        mpt::verify_proof(&root_hash, b"key7", &proof),
+        Err(MerkleError::IncompleteProof)
+    );
+    assert_eq!(
+        mpt::verify_proof(&EMPTY_ROOT, b"key7", &trie.proof(b"key7")),
+        Err(MerkleError::ProofHashMismatch)
+    );
+    assert_eq!(
+        mpt::verify_proof(&EMPTY_ROOT, b"key7", &PatriciaTrie::new().proof(b"key7")),
+        Ok(None)
+    );
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::namespaced_tree::{NamespaceNode, NamespacedTree};
+
+fn leaves(namespaces: &[u64]) -> Vec<(u64, String)> {
+    namespaces
+        .iter()
+        .enumerate()
+        .map(|(i, namespace)| (*namespace, format!("share{}", i)))
+        .collect()
+}
+
+#[test]
+fn test_namespace_proofs() {
+    assert!(NamespacedTree::new(&[])
+        .unwrap()
+        .prove_namespace(1)
+        .is_none());
+    assert!(NamespacedTree::new(&leaves(&[2, 1])).is_none());
+
+    for namespaces in [
+        vec![5],
+        vec![1, 1, 2, 4, 4, 4, 7],
+        vec![2, 3, 3, 3, 3, 6, 6, 8, 9, 9],
+        vec![1, 2, 3, 4, 5, 6, 7, 8],
+    ] {
+        let tree = NamespacedTree::new(&leaves(&namespaces)).unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(root.min, namespaces[0]);
+        assert_eq!(root.max, *namespaces.last().unwrap());
+
+        for namespace in 0..=10 {
+            let data = tree.data(namespace);
+            assert_eq!(
+                data.len(),
+                namespaces
+                    .iter()
+                    .filter(|other| **other == namespace)
+                    .count()
+            );
+            let proof = tree.prove_namespace(namespace).unwrap();
+            assert!(NamespacedTree::verify_namespace(
+                root, namespace, &data, &proof
+            ));
+
+            // Withholding or adding data is detected.
+            if let Some((_, rest)) = data.split_last() {
+                assert!(!NamespacedTree::verify_namespace(
+                    root, namespace, rest, &proof
+                ));
+                assert!(!NamespacedTree::verify_namespace(
+                    root,
+                    namespace,
+                    &[],
+                    &proof
+                ));
+                let mut tampered = data.clone();
+                tampered[0] += "x";
+                assert!(!NamespacedTree::verify_namespace(
+                    root, namespace, &tampered, &proof
+                ));
+            } else {
+                assert!(!NamespacedTree::verify_namespace(
+                    root,
+                    namespace,
+                    &["share".to_owned()],
+                    &proof
+                ));
+            }
+            // Data of a namespace does not verify as data of another.
+            if !data.is_empty() {
+                assert!(!NamespacedTree::verify_namespace(
+                    root,
+                    namespace + 1,
+                    &data,
+                    &proof
+                ));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_partial_namespace_rejected() {
+    let tree = NamespacedTree::new(&leaves(&[1, 3, 3, 3, 5])).unwrap();
+    let root = tree.root().unwrap();
+    let data = tree.data(3);
+
+    // Proving only a part of the namespace as if it were all of it fails, as the audit node
+    // next to the part covers leaves of the same namespace.
+    let mut proof = tree.prove_namespace(3).unwrap();
+    proof.end -= 1;
+    assert!(!NamespacedTree::verify_namespace(
+        root,
+        3,
+        &data[..2],
+        &proof
+    ));
+
+    // An absence proof cannot use a leaf of the namespace itself.
+    let mut proof = tree.prove_namespace(2).unwrap();
+    assert!(NamespacedTree::verify_namespace(root, 2, &[], &proof));
+    proof.absence_leaf = Some(NamespaceNode {
+        value: String::new(),
+        min: 2,
+        max: 2,
+    });
+    assert!(!NamespacedTree::verify_namespace(root, 2, &[], &proof));
+
+    // Namespaces outside the root interval are absent without audit nodes.
+    let proof = tree.prove_namespace(9).unwrap();
+    assert!(proof.nodes.is_empty());
+    assert!(NamespacedTree::verify_namespace(root, 9, &[], &proof));
+    assert!(!NamespacedTree::verify_namespace(root, 4, &[], &proof));
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{audit_path_positions, MerkleTree, NodeId};
+
+#[test]
+fn test_in_order_numbering() {
+    // Leaves are even, and each parent lies between its children.
+    assert_eq!(NodeId::leaf(0).to_u64(), Some(0));
+    assert_eq!(NodeId::leaf(3).to_u64(), Some(6));
+    assert_eq!(NodeId::new(1, 0).to_u64(), Some(1));
+    assert_eq!(NodeId::new(2, 0).to_u64(), Some(3));
+    assert_eq!(NodeId::new(2, 1).to_u64(), Some(11));
+    for id in 0..1000 {
+        assert_eq!(NodeId::from_u64(id).to_u64(), Some(id));
+    }
+    for node in [NodeId::new(63, 0), NodeId::new(0, 1 << 62)] {
+        assert_eq!(NodeId::from_u64(node.to_u64().unwrap()), node);
+    }
+    assert_eq!(NodeId::new(63, 1).to_u64(), None);
+    assert_eq!(NodeId::new(64, 0).to_u64(), None);
+    assert_eq!(NodeId::leaf(1 << 63).to_u64(), None);
+}
+
+#[test]
+fn test_navigation() {
+    let node = NodeId::new(2, 3);
+    assert_eq!(node.parent(), NodeId::new(3, 1));
+    assert_eq!(node.sibling(), NodeId::new(2, 2));
+    assert_eq!(
+        node.children(),
+        Some((NodeId::new(1, 6), NodeId::new(1, 7)))
+    );
+    assert_eq!(NodeId::leaf(5).children(), None);
+    assert_eq!(node.leaves(), 12..16);
+    for id in 1..200u64 {
+        let node = NodeId::from_u64(id);
+        let parent = node.parent().to_u64().unwrap();
+        let (left, right) = node.parent().children().unwrap();
+        assert!(left.to_u64().unwrap() < parent && parent < right.to_u64().unwrap());
+    }
+
+    let tuple: (usize, usize) = node.into();
+    assert_eq!(tuple, (2, 3));
+    assert_eq!(NodeId::from(tuple), node);
+}
+
+#[test]
+fn test_tree_nodes() {
+    let leaves: Vec<String> = (0..7).map(|i| i.to_string()).collect();
+    let tree = MerkleTree::from_leaves(&leaves);
+    let levels = tree.levels();
+    assert_eq!(tree.node(NodeId::leaf(6)), Some(levels[0][6].to_owned()));
+    assert_eq!(
+        tree.node(NodeId::new(3, 0)),
+        Some(tree.root().unwrap().borrow().value.to_owned())
+    );
+    assert_eq!(tree.node(NodeId::leaf(7)), None);
+    assert_eq!(tree.node(NodeId::new(4, 0)), None);
+
+    // Audit path positions address the nodes of a proof.
+    let proof = tree.proof(4).unwrap();
+    for ((level, index, _), hash) in audit_path_positions(4, 7).into_iter().zip(&proof.hashes) {
+        assert_eq!(
+            tree.node(NodeId::new(level, index)),
+            Some(hash.borrow().value.to_owned())
+        );
+    }
+}
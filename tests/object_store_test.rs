@@ -0,0 +1,68 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "object_store")]
+use merkle_tree::object_store::{commit, get_verified, put_multipart};
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload};
+use std::io;
+
+#[tokio::test]
+async fn test_multipart_commitment() {
+    let store = InMemory::new();
+    for size in [1usize, 4096, 10_000, 12_288] {
+        let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+        let location = Path::from(format!("blob{}", size));
+        let uploaded = put_multipart(&store, &location, &data, 4096).await.unwrap();
+        assert_eq!(uploaded.num_of_parts(), size.div_ceil(4096));
+        assert_eq!(uploaded.size, size);
+
+        let committed = commit(&store, &location, 4096).await.unwrap();
+        let root = uploaded.root().unwrap();
+        assert_eq!(committed.root(), Some(root.to_owned()));
+
+        for part in 0..uploaded.num_of_parts() {
+            let proof = uploaded.proof(part).unwrap();
+            let content = get_verified(&store, &location, &root, 4096, &proof)
+                .await
+                .unwrap();
+            assert_eq!(content, data[part * 4096..size.min((part + 1) * 4096)]);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_tampered_part() {
+    let store = InMemory::new();
+    let location = Path::from("blob");
+    let mut data = vec![7u8; 10_000];
+    let commitment = put_multipart(&store, &location, &data, 4096).await.unwrap();
+    let root = commitment.root().unwrap();
+
+    data[5000] ^= 1;
+    store
+        .put(&location, PutPayload::from(data.to_owned()))
+        .await
+        .unwrap();
+    for (part, valid) in [(0, true), (1, false), (2, true)] {
+        let proof = commitment.proof(part).unwrap();
+        let result = get_verified(&store, &location, &root, 4096, &proof).await;
+        assert_eq!(result.is_ok(), valid);
+        if let Err(error) = result {
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    // A proof of another object does not verify against this root.
+    let other = put_multipart(&store, &Path::from("other"), &[1u8; 10_000], 4096)
+        .await
+        .unwrap();
+    let proof = other.proof(0).unwrap();
+    assert!(get_verified(&store, &location, &root, 4096, &proof)
+        .await
+        .is_err());
+
+    let empty = put_multipart(&store, &Path::from("empty"), &[], 4096)
+        .await
+        .unwrap();
+    assert_eq!(empty.root(), None);
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::opentimestamps::{Attestation, DetachedTimestamp, Op};
+use merkle_tree::{MerkleError, MerkleTree};
+use sha2::{Digest, Sha256};
+
+const CALENDAR: &str = "https://alice.btc.calendar.opentimestamps.org";
+
+fn root() -> String {
+    let leaves: Vec<String> = vec!["a".to_string(), "b".to_string()];
+    MerkleTree::merkle_root(&leaves).borrow().value.to_owned()
+}
+
+/// Calendar response to submitting `digest`: append a nonce, hash and mark as pending.
+fn submit_response() -> Vec<u8> {
+    let mut response = vec![0xf0, 0x04, 0x01, 0x02, 0x03, 0x04, 0x08, 0x00];
+    response.extend([0x83, 0xdf, 0xe3, 0x0d, 0x2e, 0xf9, 0x0c, 0x8e]);
+    response.push(CALENDAR.len() as u8 + 1);
+    response.push(CALENDAR.len() as u8);
+    response.extend(CALENDAR.as_bytes());
+    response
+}
+
+/// Calendar response to upgrading a pending commitment: prepend, hash and attest in block
+/// 358391.
+fn upgrade_response() -> Vec<u8> {
+    let mut response = vec![0xf1, 0x02, 0xaa, 0xbb, 0x08, 0x00];
+    response.extend([0x05, 0x88, 0x96, 0x0d, 0x73, 0xd7, 0x19, 0x01]);
+    response.extend([0x03, 0xf7, 0xef, 0x15]);
+    response
+}
+
+#[test]
+fn test_anchor() {
+    let root = root();
+    let mut timestamp = DetachedTimestamp::new(&root).unwrap();
+    assert_eq!(
+        timestamp.to_bytes().err(),
+        Some(MerkleError::IncompleteProof)
+    );
+
+    let digest = timestamp.digest().to_vec();
+    timestamp.merge(&digest, &submit_response()).unwrap();
+    let commitment: Vec<u8> = Sha256::digest([&digest[..], &[1, 2, 3, 4]].concat()).to_vec();
+    assert_eq!(
+        timestamp.pending(),
+        vec![(commitment.to_owned(), CALENDAR.to_string())]
+    );
+    assert!(timestamp.bitcoin_attestations().is_empty());
+
+    timestamp.merge(&commitment, &upgrade_response()).unwrap();
+    let block_merkle_root: [u8; 32] =
+        Sha256::digest([&[0xaa, 0xbb], &commitment[..]].concat()).into();
+    assert_eq!(
+        timestamp.bitcoin_attestations(),
+        vec![(358391, block_merkle_root.to_vec())]
+    );
+    assert!(timestamp.verify_bitcoin(358391, &block_merkle_root));
+    assert!(!timestamp.verify_bitcoin(358392, &block_merkle_root));
+    assert!(!timestamp.verify_bitcoin(358391, &[0; 32]));
+
+    // The pending attestation is kept next to the upgrade.
+    let bytes = timestamp.to_bytes().unwrap();
+    let mut expected =
+        b"\x00OpenTimestamps\x00\x00Proof\x00\xbf\x89\xe2\xe8\x84\xe8\x92\x94".to_vec();
+    expected.extend([0x01, 0x08]);
+    expected.extend(&digest);
+    let submit = submit_response();
+    expected.extend(&submit[..7]);
+    expected.push(0xff);
+    expected.extend(&submit[7..]);
+    expected.extend(upgrade_response());
+    assert_eq!(bytes, expected);
+    assert_eq!(DetachedTimestamp::from_bytes(&bytes).unwrap(), timestamp);
+
+    // Merging the same response again changes nothing.
+    timestamp.merge(&commitment, &upgrade_response()).unwrap();
+    assert_eq!(timestamp.to_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_invalid() {
+    assert!(DetachedTimestamp::new("abcd").is_none());
+    assert!(DetachedTimestamp::new(&"zz".repeat(32)).is_none());
+
+    let mut timestamp = DetachedTimestamp::new(&root()).unwrap();
+    let digest = timestamp.digest().to_vec();
+    assert_eq!(
+        timestamp.merge(&[0; 32], &submit_response()).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+    let response = submit_response();
+    assert_eq!(
+        timestamp
+            .merge(&digest, &response[..response.len() - 1])
+            .err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+    // SHA-1 is not supported.
+    assert_eq!(
+        timestamp.merge(&digest, &[0x02, 0x00]).err(),
+        Some(MerkleError::UnsupportedScheme)
+    );
+
+    timestamp.merge(&digest, &submit_response()).unwrap();
+    let bytes = timestamp.to_bytes().unwrap();
+    let mut future = bytes.to_owned();
+    future[31] = 2;
+    assert_eq!(
+        DetachedTimestamp::from_bytes(&future).err(),
+        Some(MerkleError::UnsupportedVersion(2))
+    );
+    assert_eq!(
+        DetachedTimestamp::from_bytes(&bytes[1..]).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+
+    // Attestations of unknown kinds are kept.
+    let mut unknown = bytes[..65].to_vec();
+    unknown.extend([0x00, 1, 2, 3, 4, 5, 6, 7, 8, 0x02, 0xca, 0xfe]);
+    let parsed = DetachedTimestamp::from_bytes(&unknown).unwrap();
+    assert_eq!(
+        parsed.timestamp.attestations,
+        vec![Attestation::Unknown(
+            [1, 2, 3, 4, 5, 6, 7, 8],
+            vec![0xca, 0xfe]
+        )]
+    );
+    assert_eq!(parsed.to_bytes().unwrap(), unknown);
+    assert_eq!(timestamp.timestamp.ops[0].0, Op::Append(vec![1, 2, 3, 4]));
+}
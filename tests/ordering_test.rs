@@ -0,0 +1,84 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{Concatenation, Hash, LeafOrdering, MerkleError, MerkleTree, Padding};
+
+fn leaves(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn root_value(tree: &MerkleTree) -> String {
+    tree.root().unwrap().borrow().value.to_owned()
+}
+
+#[test]
+fn test_leaf_ordering() {
+    let forward = leaves(&["a", "bb", "ccc", "dddd", "eeeee"]);
+    let backward: Vec<String> = forward.iter().rev().cloned().collect();
+
+    let identity = MerkleTree::from_leaves(&backward);
+    assert_eq!(identity.ordering(), LeafOrdering::Identity);
+    assert_ne!(
+        root_value(&identity),
+        root_value(&MerkleTree::from_leaves(&forward))
+    );
+
+    let sorted = |leaves: &[String]| {
+        MerkleTree::builder()
+            .ordering(LeafOrdering::SortedByHash)
+            .build(leaves)
+    };
+    assert_eq!(
+        root_value(&sorted(&forward)),
+        root_value(&sorted(&backward))
+    );
+    let mut by_hash = forward.to_owned();
+    by_hash.sort_by_key(|leaf| Hash::hash(leaf));
+    assert_eq!(
+        root_value(&sorted(&backward)),
+        MerkleTree::merkle_root(&by_hash).borrow().value
+    );
+
+    let by_length = MerkleTree::builder()
+        .custom_ordering(|a, b| a.len().cmp(&b.len()))
+        .build(&backward);
+    assert_eq!(by_length.ordering(), LeafOrdering::Custom);
+    assert_eq!(
+        root_value(&by_length),
+        root_value(&MerkleTree::from_leaves(&forward))
+    );
+    assert_eq!(by_length.proof(0).unwrap().leaf_content, "a");
+}
+
+#[test]
+fn test_tree_serialization() {
+    let values = leaves(&["0", "1", "2", "3", "4"]);
+    for tree in [
+        MerkleTree::from_leaves(&[]),
+        MerkleTree::from_leaves(&values),
+        MerkleTree::builder()
+            .concatenation(Concatenation::Bytes)
+            .padding(Padding::Constant("0".repeat(64)))
+            .ordering(LeafOrdering::SortedByHash)
+            .build(&values),
+        MerkleTree::builder()
+            .padding(Padding::DuplicateLast)
+            .custom_ordering(|a, b| b.cmp(a))
+            .build(&values),
+    ] {
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(restored.ordering(), tree.ordering());
+        assert_eq!(restored.num_of_leaves(), tree.num_of_leaves());
+        assert_eq!(
+            restored.root().map(|root| root.borrow().value.to_owned()),
+            tree.root().map(|root| root.borrow().value.to_owned())
+        );
+        assert_eq!(restored.to_bytes(), tree.to_bytes());
+    }
+
+    let mut bytes = MerkleTree::from_leaves(&values).to_bytes();
+    bytes[36 + 16] = 1; // Claim that the unsorted leaves are sorted by hash.
+    assert_eq!(
+        MerkleTree::from_bytes(&bytes).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+}
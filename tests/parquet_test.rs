@@ -0,0 +1,79 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "parquet")]
+use merkle_tree::parquet::Manifest;
+use merkle_tree::MerkleTree;
+use parquet::data_type::Int64Type;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::sync::Arc;
+
+/// Write a Parquet file with one row group per batch and return its bytes.
+fn write(name: &str, batches: &[Vec<i64>]) -> (File, Vec<u8>) {
+    let schema = Arc::new(parse_message_type("message test { REQUIRED INT64 id; }").unwrap());
+    let mut bytes = Vec::new();
+    let mut writer =
+        SerializedFileWriter::new(&mut bytes, schema, Arc::new(WriterProperties::default()))
+            .unwrap();
+    for batch in batches {
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(batch, None, None)
+            .unwrap();
+        column.close().unwrap();
+        row_group.close().unwrap();
+    }
+    writer.close().unwrap();
+
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, &bytes).unwrap();
+    (File::open(path).unwrap(), bytes)
+}
+
+#[test]
+fn test_manifest() {
+    let batches: Vec<Vec<i64>> = (0..5).map(|i| (i * 10..i * 10 + 3 + i).collect()).collect();
+    let (file, bytes) = write("merkle_tree_parquet_test.parquet", &batches);
+    let manifest = Manifest::generate(&file).unwrap().unwrap();
+    assert_eq!(manifest.row_groups.len(), 5);
+
+    let digests: Vec<String> = manifest
+        .row_groups
+        .iter()
+        .map(|entry| {
+            let range = &bytes[entry.offset as usize..(entry.offset + entry.length) as usize];
+            format!("{:x}", Sha256::digest(range))
+        })
+        .collect();
+    assert_eq!(
+        manifest.root,
+        MerkleTree::merkle_root(&digests).borrow().value
+    );
+    for (index, entry) in manifest.row_groups.iter().enumerate() {
+        assert_eq!(entry.num_rows, 3 + index as i64);
+        let mut range =
+            bytes[entry.offset as usize..(entry.offset + entry.length) as usize].to_vec();
+        assert!(manifest.verify(index, &range));
+        assert!(!manifest.verify((index + 1) % 5, &range));
+        range[0] ^= 1;
+        assert!(!manifest.verify(index, &range));
+    }
+    assert!(!manifest.verify(5, &[]));
+
+    let json = manifest.to_json();
+    assert!(json.starts_with(&format!(
+        "{{\"root\":\"{}\",\"rowGroups\":[{{",
+        manifest.root
+    )));
+    assert!(json.contains(&format!("\"digest\":\"{}\"", digests[0])));
+}
+
+#[test]
+fn test_manifest_without_row_groups() {
+    let (file, _) = write("merkle_tree_parquet_empty_test.parquet", &[]);
+    assert!(Manifest::generate(&file).unwrap().is_none());
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{LeafOrdering, MerkleTree, Permutation};
+
+fn leaves() -> Vec<String> {
+    ["d", "a", "c", "a", "b", "e", "a"]
+        .iter()
+        .map(|leaf| leaf.to_string())
+        .collect()
+}
+
+fn root(tree: &MerkleTree) -> String {
+    tree.root().unwrap().borrow().value.to_owned()
+}
+
+#[test]
+fn test_permutation() {
+    let first = MerkleTree::from_leaves(&leaves());
+    let second = MerkleTree::builder()
+        .ordering(LeafOrdering::SortedByHash)
+        .build(&leaves());
+    let permutation = Permutation::between(&first, &second).unwrap();
+    assert!(permutation.verify(&root(&first), &root(&second)));
+    assert!(!permutation.verify(&root(&second), &root(&first)));
+
+    let mut targets = permutation.map.to_owned();
+    targets.sort();
+    assert_eq!(targets, (0..leaves().len()).collect::<Vec<usize>>());
+    for (leaf_index, target) in permutation.map.iter().enumerate() {
+        assert_eq!(
+            second.proof(*target).unwrap().leaf_content,
+            leaves()[leaf_index]
+        );
+    }
+
+    // Mapping two leaves to the same position is rejected.
+    let mut duplicated = Permutation::between(&first, &second).unwrap();
+    let (index_a, index_b) = (1, 3); // Both "a".
+    duplicated.map[index_b] = duplicated.map[index_a];
+    duplicated.proofs[index_b].1 = second.proof(duplicated.map[index_a]).unwrap();
+    assert!(!duplicated.verify(&root(&first), &root(&second)));
+}
+
+#[test]
+fn test_different_multisets() {
+    let first = MerkleTree::from_leaves(&leaves());
+    let mut changed = leaves();
+    changed[0] = "a".to_string();
+    assert!(Permutation::between(&first, &MerkleTree::from_leaves(&changed)).is_none());
+    assert!(Permutation::between(&first, &MerkleTree::from_leaves(&leaves()[1..])).is_none());
+
+    let mut removed = MerkleTree::from_leaves(&leaves());
+    removed.remove(2);
+    assert!(Permutation::between(&first, &removed).is_none());
+}
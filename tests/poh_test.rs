@@ -0,0 +1,83 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{HashAlgorithm, ProofOfHistory};
+
+fn history() -> ProofOfHistory {
+    let mut history = ProofOfHistory::new(HashAlgorithm::Sha256, "genesis");
+    for batch in 0..4 {
+        history.tick(batch * 3);
+        for event in 0..=batch {
+            history.record(format!("event{}.{}", batch, event));
+        }
+        history.seal().unwrap();
+    }
+    history
+}
+
+#[test]
+fn test_entries() {
+    let mut history = history();
+    assert_eq!(history.seed(), HashAlgorithm::Sha256.hash(b"genesis"));
+    assert_eq!(history.entries().len(), 4);
+    assert_eq!(history.entries()[2].num_hashes, 6);
+    assert!(ProofOfHistory::verify_entries(
+        HashAlgorithm::Sha256,
+        history.seed(),
+        history.entries()
+    ));
+
+    // Ticks without events move the state but not the head.
+    let head = history.head().to_owned();
+    assert!(history.seal().is_none());
+    history.tick(2);
+    assert_ne!(history.state(), head);
+    assert_eq!(history.head(), head);
+
+    // Claiming fewer ticks, or swapping batches, does not verify.
+    let mut entries = history.entries().to_vec();
+    entries[1].num_hashes -= 1;
+    assert!(!ProofOfHistory::verify_entries(
+        HashAlgorithm::Sha256,
+        history.seed(),
+        &entries
+    ));
+    let mut entries = history.entries().to_vec();
+    entries.swap(1, 2);
+    assert!(!ProofOfHistory::verify_entries(
+        HashAlgorithm::Sha256,
+        history.seed(),
+        &entries
+    ));
+}
+
+#[test]
+fn test_receipts() {
+    let history = history();
+    let head = history.head();
+    assert!(history.receipt(4, 0).is_none());
+    assert!(history.receipt(1, 2).is_none());
+
+    let receipts: Vec<_> = (0..4)
+        .flat_map(|batch| (0..=batch).map(move |event| (batch, event)))
+        .map(|(batch, event)| history.receipt(batch, event).unwrap())
+        .collect();
+    for (i, receipt) in receipts.iter().enumerate() {
+        assert!(receipt.verify(head));
+        assert!(!receipt.verify(history.seed()));
+        for (j, other) in receipts.iter().enumerate() {
+            assert_eq!(receipt.precedes(other), i < j);
+        }
+    }
+    assert_eq!(receipts[4].event(), "event2.1");
+
+    // Tampering with the event or the chain is detected.
+    let mut receipt = history.receipt(2, 1).unwrap();
+    receipt.proof.leaf_content += "x";
+    assert!(!receipt.verify(head));
+    let mut receipt = history.receipt(2, 1).unwrap();
+    receipt.entries[1].num_hashes += 1;
+    assert!(!receipt.verify(head));
+    let mut receipt = history.receipt(2, 1).unwrap();
+    receipt.previous = history.seed().to_owned();
+    assert!(!receipt.verify(head));
+}
@@ -0,0 +1,77 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::cas::{Cas, MemoryStorage};
+use merkle_tree::{rs_merkle, MerkleTree, Profile};
+
+#[test]
+fn test_plain() {
+    let tree = Profile::Plain
+        .build(&["abc", "bcd", "cde", "def", "efg"])
+        .unwrap();
+    assert_eq!(
+        tree.root().unwrap().borrow().value,
+        "b12bb480c5d29242ab22fe53c199c26a5a5bd1ac66ac2702099855ceaf006073"
+    );
+    assert_eq!(Profile::default(), Profile::Plain);
+    assert_eq!(Profile::Plain.chunk_size(), None);
+    assert!(Profile::Plain.build(&[b"\xff".as_slice()]).is_none());
+}
+
+#[test]
+fn test_rs_merkle() {
+    let leaves = [b"a", b"b", b"c", b"d", b"e"];
+    let tree = Profile::RsMerkle.build(&leaves).unwrap();
+    let leaf_hashes: Vec<[u8; 32]> = leaves
+        .iter()
+        .map(|leaf| rs_merkle::leaf_hash(*leaf))
+        .collect();
+    let root = rs_merkle::root(&leaf_hashes).unwrap();
+    assert_eq!(
+        tree.root().unwrap().borrow().value,
+        root.iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+    );
+
+    let proof = tree.proof(3).unwrap();
+    assert_eq!(proof.leaf_content, "64");
+    assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+}
+
+#[test]
+fn test_blob() {
+    let blob: Vec<u8> = (0..(5 << 19) + 7).map(|i| i as u8).collect();
+    assert_eq!(Profile::Blob.chunk_size(), Some(1 << 20));
+    let tree = Profile::Blob.build_blob(&blob).unwrap();
+    assert_eq!(tree.num_of_leaves(), 3);
+
+    let mut cas = Cas::new(MemoryStorage::default(), 1 << 20);
+    assert_eq!(tree.root().unwrap().borrow().value, cas.put(&blob));
+    assert_eq!(
+        Profile::Blob
+            .build_blob(&[])
+            .unwrap()
+            .root()
+            .unwrap()
+            .borrow()
+            .value,
+        cas.put(&[])
+    );
+
+    // Profiles without chunking keep a blob as one leaf.
+    assert_eq!(
+        Profile::Plain.build_blob(b"abc").unwrap().num_of_leaves(),
+        1
+    );
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_keccak() {
+    use merkle_tree::{Concatenation, HashAlgorithm};
+
+    let tree = Profile::Keccak.build(&[b"a", b"b", b"c"]).unwrap();
+    assert_eq!(tree.algorithm(), HashAlgorithm::Keccak256);
+    assert_eq!(tree.scheme().concatenation, Concatenation::Bytes);
+    assert!(merkle_tree::solidity::verifier_source(&tree).is_some());
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{MerkleProof, MerkleTree, ProofCache};
+
+fn audit_hashes(proof: &MerkleProof) -> Vec<(String, bool)> {
+    proof
+        .hashes
+        .iter()
+        .map(|hash| (hash.borrow().value.clone(), hash.borrow().is_left))
+        .collect()
+}
+
+#[test]
+fn test_proof_cache() {
+    let leaves: Vec<String> = (0..7).map(|i| i.to_string()).collect();
+    let mut tree = MerkleTree::from_leaves(&leaves);
+    let mut cache = ProofCache::new(4);
+    assert!(cache.is_empty());
+    assert_eq!(cache.capacity(), 4);
+
+    for leaf_index in 0..7 {
+        let expected = tree.proof(leaf_index).unwrap();
+        for _ in 0..2 {
+            let proof = cache.proof(&tree, leaf_index).unwrap();
+            assert_eq!(audit_hashes(&proof), audit_hashes(&expected));
+            assert_eq!(proof.leaf_content, expected.leaf_content);
+            assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+        }
+    }
+    assert_eq!((cache.hits(), cache.misses()), (7, 7));
+    assert_eq!(cache.len(), 4);
+
+    // Callers modifying a served proof do not modify the cached one.
+    let proof = cache.proof(&tree, 6).unwrap();
+    proof.hashes[0].borrow_mut().value = "0".repeat(64);
+    assert!(MerkleTree::verify_proof(
+        tree.root().unwrap(),
+        &cache.proof(&tree, 6).unwrap()
+    ));
+
+    // Proofs of a tree that changed are not served for its new root.
+    tree.append("7".to_owned());
+    let proof = cache.proof(&tree, 6).unwrap();
+    assert_eq!(proof.num_of_leaves, 8);
+    assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+    assert_eq!((cache.hits(), cache.misses()), (9, 8));
+
+    assert!(cache.proof(&tree, 8).is_none());
+    assert!(cache.proof(&MerkleTree::new(), 0).is_none());
+    assert_eq!(cache.misses(), 10);
+
+    cache.clear();
+    assert!(cache.is_empty());
+    cache.proof(&tree, 6).unwrap();
+    assert_eq!((cache.hits(), cache.misses()), (9, 11));
+}
+
+#[test]
+fn test_least_recently_used_eviction() {
+    let leaves: Vec<String> = (0..4).map(|i| i.to_string()).collect();
+    let tree = MerkleTree::from_leaves(&leaves);
+    let mut cache = ProofCache::new(2);
+    cache.proof(&tree, 0);
+    cache.proof(&tree, 1);
+    cache.proof(&tree, 0);
+    cache.proof(&tree, 2); // Evicts 1, used less recently than 0.
+    assert_eq!((cache.hits(), cache.misses()), (1, 3));
+    cache.proof(&tree, 0);
+    assert_eq!(cache.hits(), 2);
+    cache.proof(&tree, 1);
+    assert_eq!(cache.misses(), 4);
+}
+
+#[test]
+#[should_panic(expected = "capacity must be at least 1 proof")]
+fn test_zero_capacity() {
+    ProofCache::new(0);
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::provider::{self, CryptoProvider, DigestHasher, Hasher, RustCrypto};
+use merkle_tree::{HashAlgorithm, MerkleTree};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Provider that counts the hash computations it starts, delegating to `RustCrypto`.
+struct Counting(AtomicUsize);
+
+impl CryptoProvider for Counting {
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        RustCrypto.hasher(algorithm)
+    }
+}
+
+static COUNTING: Counting = Counting(AtomicUsize::new(0));
+
+fn digest(provider: &dyn CryptoProvider, algorithm: HashAlgorithm, value: &[u8]) -> Vec<u8> {
+    let mut hasher = provider.hasher(algorithm).unwrap();
+    for chunk in value.chunks(7) {
+        hasher.update(chunk);
+    }
+    hasher.finalize()
+}
+
+#[test]
+fn test_providers_agree() {
+    let value = b"The quick brown fox jumps over the lazy dog";
+    #[allow(unused_mut)]
+    let mut providers: Vec<&dyn CryptoProvider> = vec![&RustCrypto];
+    #[cfg(feature = "ring")]
+    providers.push(&provider::Ring);
+    #[cfg(feature = "openssl")]
+    providers.push(&provider::OpenSsl);
+    #[cfg(feature = "aws-lc-rs")]
+    providers.push(&provider::AwsLc);
+
+    #[allow(unused_mut)]
+    let mut algorithms = vec![HashAlgorithm::Sha256];
+    #[cfg(feature = "sha3")]
+    algorithms.extend([
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Sha3_512,
+        HashAlgorithm::Keccak256,
+    ]);
+
+    for provider in providers {
+        for algorithm in &algorithms {
+            if provider.hasher(*algorithm).is_some() {
+                assert_eq!(
+                    digest(provider, *algorithm, value),
+                    digest(&RustCrypto, *algorithm, value)
+                );
+                assert_eq!(
+                    provider.digest(*algorithm, value),
+                    Some(digest(&RustCrypto, *algorithm, value))
+                );
+            }
+        }
+        assert!(provider.hasher(HashAlgorithm::Sha256).is_some());
+    }
+}
+
+/// Provider that adapts RustCrypto digests with `DigestHasher`.
+struct Adapted;
+
+impl CryptoProvider for Adapted {
+    fn hasher(&self, algorithm: HashAlgorithm) -> Option<Box<dyn Hasher>> {
+        match algorithm {
+            HashAlgorithm::Sha256 => Some(Box::new(DigestHasher::<sha2::Sha256>::default())),
+            #[cfg(feature = "sha3")]
+            HashAlgorithm::Sha3_512 => Some(Box::new(DigestHasher(
+                <sha3::Sha3_512 as provider::digest::Digest>::new(),
+            ))),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_digest_hasher() {
+    let value = b"The quick brown fox jumps over the lazy dog";
+    assert_eq!(
+        digest(&Adapted, HashAlgorithm::Sha256, value),
+        digest(&RustCrypto, HashAlgorithm::Sha256, value)
+    );
+    #[cfg(feature = "sha3")]
+    assert_eq!(
+        digest(&Adapted, HashAlgorithm::Sha3_512, value),
+        digest(&RustCrypto, HashAlgorithm::Sha3_512, value)
+    );
+}
+
+// The only test in this file that installs a provider, as it is process-wide.
+#[test]
+fn test_install_default() {
+    let leaves: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+    let expected = MerkleTree::merkle_root(&leaves).borrow().value.to_owned();
+
+    assert!(provider::install_default(&COUNTING).is_ok());
+    assert!(provider::install_default(&RustCrypto).is_err());
+
+    let before = COUNTING.0.load(Ordering::SeqCst);
+    let root = MerkleTree::from_leaves(&leaves).root().unwrap();
+    assert_eq!(root.borrow().value, expected);
+    assert!(COUNTING.0.load(Ordering::SeqCst) - before >= 9); // 5 leaves and 4 parents.
+}
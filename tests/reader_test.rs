@@ -0,0 +1,53 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{Hash, MerkleTree};
+use std::io::{self, Read};
+
+/// Reader yielding `len` copies of `byte`, a few bytes at a time.
+struct Repeat {
+    byte: u8,
+    len: usize,
+}
+
+impl Read for Repeat {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.len).min(7);
+        buf[..n].fill(self.byte);
+        self.len -= n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_hash_reader() {
+    for value in ["", "0", "abc", &"x".repeat(100_000)] {
+        assert_eq!(
+            Hash::hash_reader(value.as_bytes()).unwrap(),
+            Hash::hash(value)
+        );
+    }
+    assert_eq!(
+        Hash::hash_reader(Repeat {
+            byte: b'y',
+            len: 1000
+        })
+        .unwrap(),
+        Hash::hash(&"y".repeat(1000))
+    );
+}
+
+#[test]
+fn test_merkle_root_from_readers() {
+    let leaves: Vec<String> = (0..=8).map(|i| i.to_string()).collect();
+    let readers: Vec<Box<dyn Read>> = leaves
+        .iter()
+        .map(|leaf| Box::new(io::Cursor::new(leaf.to_owned().into_bytes())) as Box<dyn Read>)
+        .collect();
+    assert_eq!(
+        MerkleTree::merkle_root_from_readers(readers)
+            .unwrap()
+            .borrow()
+            .value,
+        MerkleTree::merkle_root(&leaves).borrow().value
+    );
+}
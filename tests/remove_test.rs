@@ -0,0 +1,111 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{MerkleTree, Padding, TOMBSTONE};
+
+#[test]
+fn test_remove() {
+    let mut tree = MerkleTree::from_leaves(&leaves(7));
+    let old_root = tree.root().unwrap().borrow().value.to_owned();
+    assert!(tree.remove(2));
+    assert!(!tree.remove(2));
+    assert!(!tree.remove(7));
+    assert!(tree.is_removed(2));
+    assert_eq!(tree.num_of_leaves(), 7);
+    assert!(tree.proof(2).is_none());
+
+    let root = tree.root().unwrap();
+    assert_ne!(root.borrow().value, old_root);
+    for index in [0, 1, 3, 4, 5, 6] {
+        let proof = tree.proof(index).unwrap();
+        assert_eq!(proof.leaf_index, index);
+        assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+    }
+
+    // A removed leaf hashes to the tombstone.
+    let mut expected = leaves(7);
+    expected[2] = String::new();
+    let mut reference = MerkleTree::from_leaves(&expected);
+    reference.remove(2);
+    assert_eq!(
+        reference.root().unwrap().borrow().value,
+        root.borrow().value
+    );
+    assert_eq!(TOMBSTONE.len(), 64);
+}
+
+#[test]
+fn test_remove_batch() {
+    for padding in [Padding::Promote, Padding::DuplicateLast] {
+        for n in 1..=17 {
+            let removed: Vec<usize> = (0..n).filter(|i| i % 3 == 1).collect();
+            let mut batched = MerkleTree::builder()
+                .padding(padding.to_owned())
+                .build(&leaves(n));
+            let mut one_by_one = batched.clone();
+            assert_eq!(batched.remove_batch(&removed), removed.len());
+            for index in &removed {
+                assert!(one_by_one.remove(*index));
+            }
+            assert_eq!(
+                batched.root().unwrap().borrow().value,
+                one_by_one.root().unwrap().borrow().value
+            );
+
+            let root = batched.root().unwrap();
+            for index in (0..n).filter(|i| !removed.contains(i)) {
+                let proof = batched.proof(index).unwrap();
+                assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+            }
+
+            // Removal survives serialization and appends keep working.
+            let mut restored = MerkleTree::from_bytes(&batched.to_bytes()).unwrap();
+            assert_eq!(restored.root().unwrap().borrow().value, root.borrow().value);
+            restored.append("new".to_string());
+            batched.append("new".to_string());
+            assert_eq!(
+                restored.root().unwrap().borrow().value,
+                batched.root().unwrap().borrow().value
+            );
+        }
+    }
+    assert_eq!(MerkleTree::new().remove_batch(&[0]), 0);
+}
+
+#[test]
+fn test_remove_truncated_collision() {
+    // A live leaf whose truncated value is all zero bytes is not a removed leaf.
+    let mut tree = MerkleTree::builder().truncation(1).build(&leaves(1024));
+    let levels = tree.levels();
+    let zeros: Vec<usize> = (0..1024)
+        .filter(|index| levels[0][*index] == "00")
+        .collect();
+    let live = zeros[0];
+    assert!(!tree.is_removed(live));
+    assert!(tree.proof(live).is_some());
+
+    let removed = (0..1024).find(|index| !zeros.contains(index)).unwrap();
+    assert!(tree.remove(removed));
+    assert_eq!(tree.find_by_digest_prefix("00"), zeros);
+    for restored in [
+        MerkleTree::from_bytes(&tree.to_bytes()).unwrap(),
+        MerkleTree::builder()
+            .truncation(1)
+            .build_from_levels(
+                &(0..1024)
+                    .map(|index| match index == removed {
+                        true => String::new(),
+                        false => format!("leaf{}", index),
+                    })
+                    .collect::<Vec<String>>(),
+                tree.levels(),
+            )
+            .unwrap(),
+    ] {
+        assert!(!restored.is_removed(live));
+        assert!(restored.is_removed(removed));
+        assert!(restored.proof(live).is_some());
+    }
+}
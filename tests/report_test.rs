@@ -0,0 +1,150 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{ConcatenationOrder, Hash, MerkleTree, Padding, VerificationFailure};
+
+#[test]
+fn test_valid_report() {
+    for padding in [Padding::Promote, Padding::DuplicateLast] {
+        let tree = MerkleTree::builder()
+            .padding(padding.to_owned())
+            .build(&leaves(7));
+        let root = tree.root().unwrap();
+        for leaf_index in 0..7 {
+            let proof = tree.proof(leaf_index).unwrap();
+            let report = MerkleTree::verify_proof_verbose(root.to_owned(), &proof);
+            assert!(report.is_valid());
+            assert_eq!(
+                report.leaf_value,
+                Hash::hash(&format!("leaf{}", leaf_index))
+            );
+            assert_eq!(report.steps.len(), proof.hashes.len());
+            assert_eq!(report.steps.last().unwrap().computed, report.computed_root);
+            assert_eq!(report.computed_root, root.borrow().value);
+        }
+    }
+}
+
+#[test]
+fn test_failure_diagnosis() {
+    let tree = MerkleTree::from_leaves(&leaves(7));
+    let root = tree.root().unwrap();
+
+    let mut proof = tree.proof(2).unwrap();
+    proof.leaf_content = "tampered".to_string();
+    let report = MerkleTree::verify_proof_verbose(root.to_owned(), &proof);
+    assert_eq!(report.failure, Some(VerificationFailure::RootMismatch));
+    assert_eq!(report.expected_root, root.borrow().value);
+    assert_ne!(report.computed_root, report.expected_root);
+
+    let proof = tree.proof(2).unwrap();
+    proof.hashes[1].borrow_mut().is_left = false;
+    assert_eq!(
+        MerkleTree::verify_proof_verbose(root.to_owned(), &proof).failure,
+        Some(VerificationFailure::SiblingSide { step: 1 })
+    );
+
+    let mut proof = tree.proof(2).unwrap();
+    proof.hashes.pop();
+    assert_eq!(
+        MerkleTree::verify_proof_verbose(root.to_owned(), &proof).failure,
+        Some(VerificationFailure::ProofLength {
+            expected: 3,
+            actual: 2
+        })
+    );
+
+    let mut proof = tree.proof(2).unwrap();
+    proof.leaf_index = 7;
+    proof.leaf_content = "tampered".to_string();
+    assert_eq!(
+        MerkleTree::verify_proof_verbose(root.to_owned(), &proof).failure,
+        Some(VerificationFailure::LeafIndexOutOfRange)
+    );
+}
+
+#[test]
+fn test_audit_concatenation_order() {
+    let tree = MerkleTree::from_leaves(&leaves(7));
+    let root = tree.root().unwrap();
+    for leaf_index in 0..7 {
+        let proof = tree.proof(leaf_index).unwrap();
+        assert_eq!(
+            MerkleTree::audit_concatenation_order(root.to_owned(), &proof),
+            Some(ConcatenationOrder::AsProven)
+        );
+
+        // A proof of an implementation that swaps children places every audit hash on the
+        // other side.
+        for audit_hash in &proof.hashes {
+            let is_left = audit_hash.borrow().is_left;
+            audit_hash.borrow_mut().is_left = !is_left;
+        }
+        assert!(!MerkleTree::verify_proof(root.to_owned(), &proof));
+        assert_eq!(
+            MerkleTree::audit_concatenation_order(root.to_owned(), &proof),
+            Some(ConcatenationOrder::Reversed)
+        );
+    }
+
+    let mut proof = tree.proof(0).unwrap();
+    proof.leaf_content = "tampered".to_string();
+    assert_eq!(
+        MerkleTree::audit_concatenation_order(root.to_owned(), &proof),
+        None
+    );
+}
+
+#[test]
+fn test_verify_detailed() {
+    let tree = MerkleTree::from_leaves(&leaves(7));
+    let root = tree.root().unwrap();
+    for leaf_index in 0..7 {
+        let proof = tree.proof(leaf_index).unwrap();
+        assert_eq!(MerkleTree::verify_detailed(root.to_owned(), &proof), Ok(()));
+    }
+
+    let mut proof = tree.proof(2).unwrap();
+    proof.leaf_content = "tampered".to_string();
+    let failure = MerkleTree::verify_detailed(root.to_owned(), &proof).unwrap_err();
+    assert_eq!(failure, VerificationFailure::RootMismatch);
+    assert!(failure
+        .to_string()
+        .starts_with("proof does not reproduce the root"));
+
+    let mut proof = tree.proof(2).unwrap();
+    proof.hashes.pop();
+    assert_eq!(
+        MerkleTree::verify_detailed(root.to_owned(), &proof),
+        Err(VerificationFailure::ProofLength {
+            expected: 3,
+            actual: 2
+        })
+    );
+
+    let mut proof = tree.proof(2).unwrap();
+    proof.leaf_index = 7;
+    assert_eq!(
+        MerkleTree::verify_detailed(root.to_owned(), &proof),
+        Err(VerificationFailure::LeafIndexOutOfRange)
+    );
+
+    // A RIPEMD-160 root has 40 hex digits, not the 64 of SHA-256.
+    let proof = tree.proof(2).unwrap();
+    root.borrow_mut().value = "ab".repeat(20);
+    let failure = MerkleTree::verify_detailed(root, &proof).unwrap_err();
+    assert_eq!(
+        failure,
+        VerificationFailure::AlgorithmMismatch {
+            expected: 64,
+            actual: 40
+        }
+    );
+    assert_eq!(
+        failure.to_string(),
+        "root has 40 hex digits, but node values of the proof have 64; the tree was built \
+         with another hash function or truncation"
+    );
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "ripemd")]
+use merkle_tree::{HashAlgorithm, MerkleProof, MerkleTree};
+
+fn data() -> Vec<String> {
+    ["abc", "bcd", "cde", "def", "efg"]
+        .iter()
+        .map(|leaf| leaf.to_string())
+        .collect()
+}
+
+#[test]
+fn test_digest_vectors() {
+    assert_eq!(
+        HashAlgorithm::Ripemd160.hash(b"abc"),
+        "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+    );
+    assert_eq!(
+        HashAlgorithm::Hash160.hash(b"abc"),
+        "bb1be98c142444d7a56aa3981c3942a978e4dc33"
+    );
+    // HASH160 of the compressed public key of private key 1, as in its Bitcoin address.
+    let public_key = [
+        0x02, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87,
+        0x0b, 0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16,
+        0xf8, 0x17, 0x98,
+    ];
+    assert_eq!(
+        HashAlgorithm::Hash160.hash(&public_key),
+        "751e76e8199196d454941c45d1b3a323f1433bd6"
+    );
+    assert_eq!(HashAlgorithm::Ripemd160.digest_size(), 20);
+    assert_eq!(HashAlgorithm::Hash160.digest_size(), 20);
+}
+
+#[test]
+fn test_proofs() {
+    for algorithm in [HashAlgorithm::Ripemd160, HashAlgorithm::Hash160] {
+        let tree = MerkleTree::builder().algorithm(algorithm).build(&data());
+        let root = tree.root().unwrap();
+        assert_eq!(root.borrow().value.len(), 40);
+        for leaf_index in 0..data().len() {
+            let proof = tree.proof(leaf_index).unwrap();
+            assert!(MerkleTree::verify_proof(root.clone(), &proof));
+            let decoded = MerkleProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(decoded.algorithm, algorithm);
+            assert!(MerkleTree::verify_proof(root.clone(), &decoded));
+        }
+        let restored = MerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+        assert_eq!(restored.algorithm(), algorithm);
+        assert_eq!(restored.root().unwrap().borrow().value, root.borrow().value);
+    }
+}
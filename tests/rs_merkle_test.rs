@@ -0,0 +1,72 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::rs_merkle;
+use merkle_tree::MerkleError;
+
+// Vectors produced by rs_merkle 1.4 with `algorithms::Sha256` over leaves "a" to "e".
+const ROOT: &str = "d71f8983ad4ee170f8129f1ebcdd7440be7798d8e1c80420bf11f1eced610dba";
+const PROOFS: [(usize, &str); 3] = [
+    (0, "3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009dbffe0b34dba16bc6fac17c08bac55d676cded5a4ade41fe2c9924a5dde8f3e5b3f79bb7b435b05321651daefd374cdc681dc06faa65e374e38337b88ca046dea"),
+    (2, "18ac3e7343f016890c510e93f935261169d9e3f565436429830faf0934f4f8e4e5a01fee14e0ed5c48714f22180f25ad8365b53f9779f79dc4a3d7e93963f94a3f79bb7b435b05321651daefd374cdc681dc06faa65e374e38337b88ca046dea"),
+    (4, "14ede5e8e97ad9372327728f5099b95604a39593cac3bd38a343ad76205213e7"),
+];
+
+fn from_hex(value: &str) -> Vec<u8> {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn leaf_hashes() -> Vec<[u8; 32]> {
+    ["a", "b", "c", "d", "e"]
+        .iter()
+        .map(|leaf| rs_merkle::leaf_hash(leaf.as_bytes()))
+        .collect()
+}
+
+#[test]
+fn test_rs_merkle_root() {
+    let root = rs_merkle::root(&leaf_hashes()).unwrap();
+    assert_eq!(root.to_vec(), from_hex(ROOT));
+    assert_eq!(rs_merkle::root(&leaf_hashes()[..1]), Some(leaf_hashes()[0]));
+    assert_eq!(rs_merkle::root(&[]), None);
+}
+
+#[test]
+fn test_rs_merkle_proof() {
+    let leaf_hashes = leaf_hashes();
+    let root: [u8; 32] = from_hex(ROOT).try_into().unwrap();
+    for (leaf_index, serialized) in PROOFS {
+        let serialized = from_hex(serialized);
+        let hashes = rs_merkle::proof_from_bytes(&serialized).unwrap();
+        assert_eq!(rs_merkle::proof(&leaf_hashes, leaf_index), hashes);
+        assert_eq!(rs_merkle::proof_to_bytes(&hashes), serialized);
+
+        assert!(rs_merkle::verify(
+            &root,
+            leaf_index,
+            &leaf_hashes[leaf_index],
+            leaf_hashes.len(),
+            &hashes
+        ));
+        assert!(!rs_merkle::verify(
+            &root,
+            leaf_index,
+            &rs_merkle::leaf_hash(b"tainted"),
+            leaf_hashes.len(),
+            &hashes
+        ));
+        assert!(!rs_merkle::verify(
+            &root,
+            leaf_index ^ 1,
+            &leaf_hashes[leaf_index],
+            leaf_hashes.len(),
+            &hashes
+        ));
+    }
+    assert_eq!(
+        rs_merkle::proof_from_bytes(&[0; 33]),
+        Err(MerkleError::InvalidProofLength(33))
+    );
+}
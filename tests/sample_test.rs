@@ -0,0 +1,63 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::MerkleTree;
+use std::collections::BTreeSet;
+
+#[test]
+fn test_sample_proofs() {
+    let tree = MerkleTree::from_leaves(&leaves(100));
+    let root = tree.root().unwrap();
+    let proofs = tree.sample_proofs(b"challenge", 10);
+    assert_eq!(proofs.len(), 10);
+
+    // An auditor reproduces the sample from the seed and the root alone.
+    let indices = MerkleTree::sample_indices(&root.borrow().value, 100, b"challenge", 10);
+    assert_eq!(
+        proofs
+            .iter()
+            .map(|proof| proof.leaf_index)
+            .collect::<Vec<usize>>(),
+        indices
+    );
+    assert_eq!(indices.iter().collect::<BTreeSet<_>>().len(), 10);
+    for proof in &proofs {
+        assert!(MerkleTree::verify_proof(root.to_owned(), proof));
+    }
+
+    // The sample depends on both the seed and the root.
+    assert_ne!(
+        MerkleTree::sample_indices(&root.borrow().value, 100, b"challenge2", 10),
+        indices
+    );
+    let other = MerkleTree::from_leaves(&leaves(101)).root().unwrap();
+    assert_ne!(
+        MerkleTree::sample_indices(&other.borrow().value, 100, b"challenge", 10),
+        indices
+    );
+    assert_eq!(
+        MerkleTree::sample_indices(&root.borrow().value, 100, b"challenge", 3),
+        indices[..3]
+    );
+}
+
+#[test]
+fn test_sample_bounds() {
+    let tree = MerkleTree::from_leaves(&leaves(5));
+    let mut sampled: Vec<usize> = tree
+        .sample_proofs(b"seed", 9)
+        .iter()
+        .map(|proof| proof.leaf_index)
+        .collect();
+    sampled.sort();
+    assert_eq!(sampled, vec![0, 1, 2, 3, 4]);
+    assert!(MerkleTree::new().sample_proofs(b"seed", 3).is_empty());
+
+    let mut tree = MerkleTree::from_leaves(&leaves(5));
+    tree.remove(2);
+    let proofs = tree.sample_proofs(b"seed", 5);
+    assert_eq!(proofs.len(), 4);
+    assert!(proofs.iter().all(|proof| proof.leaf_index != 2));
+}
@@ -0,0 +1,11 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::self_test;
+
+#[test]
+fn test_self_test() {
+    for seed in 0..4 {
+        assert!(self_test(seed, 16));
+    }
+    assert!(self_test(u64::MAX, 0));
+}
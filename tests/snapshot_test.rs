@@ -0,0 +1,84 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::MerkleTree;
+
+#[test]
+fn test_append() {
+    let mut tree = MerkleTree::new();
+    assert!(tree.root().is_none());
+    assert!(tree.proof(0).is_none());
+
+    let mut leaves = Vec::new();
+    for i in 0..200 {
+        leaves.push(i.to_string());
+        tree.append(i.to_string());
+
+        let root = MerkleTree::merkle_root(&leaves);
+        assert_eq!(tree.num_of_leaves(), leaves.len());
+        assert_eq!(tree.root().unwrap().borrow().value, root.borrow().value);
+        assert_eq!(
+            MerkleTree::from_leaves(&leaves)
+                .root()
+                .unwrap()
+                .borrow()
+                .value,
+            root.borrow().value
+        );
+        for leaf_index in [0, i / 2, i] {
+            let proof = tree.proof(leaf_index).unwrap();
+            let expected = MerkleTree::merkle_proof(&leaves, leaf_index);
+            assert_eq!(proof.hashes.len(), expected.hashes.len());
+            for (hash, expected_hash) in proof.hashes.iter().zip(&expected.hashes) {
+                assert_eq!(hash.borrow().value, expected_hash.borrow().value);
+                assert_eq!(hash.borrow().is_left, expected_hash.borrow().is_left);
+            }
+            assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+        }
+        assert!(tree.proof(i + 1).is_none());
+    }
+}
+
+#[test]
+fn test_with_capacity() {
+    for capacity in [0, 1, 5, 64, 100] {
+        let mut tree = MerkleTree::with_capacity(capacity);
+        assert!(tree.root().is_none());
+        let mut leaves = Vec::new();
+        // Growing past the expected number of leaves keeps working.
+        for i in 0..150 {
+            leaves.push(i.to_string());
+            tree.append(i.to_string());
+            assert_eq!(
+                tree.root().unwrap().borrow().value,
+                MerkleTree::merkle_root(&leaves).borrow().value
+            );
+        }
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    let leaves: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let mut tree = MerkleTree::from_leaves(&leaves);
+    let snapshot = tree.snapshot();
+    let root = snapshot.root().unwrap();
+
+    for i in 100..300 {
+        tree.append(i.to_string());
+    }
+    assert_eq!(snapshot.num_of_leaves(), 100);
+    assert_eq!(
+        snapshot.root().unwrap().borrow().value,
+        MerkleTree::merkle_root(&leaves).borrow().value
+    );
+    assert_ne!(tree.root().unwrap().borrow().value, root.borrow().value);
+    for leaf_index in 0..100 {
+        let proof = snapshot.proof(leaf_index).unwrap();
+        assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+        assert!(MerkleTree::verify_proof(
+            tree.root().unwrap(),
+            &tree.proof(leaf_index).unwrap()
+        ));
+    }
+    assert!(snapshot.proof(100).is_none());
+}
@@ -0,0 +1,61 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{solidity, Concatenation, MerkleTree};
+
+#[test]
+fn test_verifier_source() {
+    let hex = solidity::verifier_source(&MerkleTree::from_leaves(&leaves(3))).unwrap();
+    assert!(hex.contains("library MerkleTreeVerifier"));
+    assert!(hex.contains("sha256(abi.encodePacked(toHex(left), toHex(right)))"));
+
+    let bytes = solidity::verifier_source(
+        &MerkleTree::builder()
+            .concatenation(Concatenation::Bytes)
+            .build(&leaves(3)),
+    )
+    .unwrap();
+    assert!(bytes.contains("sha256(abi.encodePacked(left, right))"));
+    assert!(!bytes.contains("toHex"));
+
+    for source in [hex, bytes] {
+        assert_eq!(source.matches('{').count(), source.matches('}').count());
+    }
+}
+
+#[test]
+fn test_proof_arguments() {
+    let tree = MerkleTree::from_leaves(&leaves(5));
+    let (hashes, is_left) = solidity::proof_arguments(&tree.proof(4).unwrap());
+    assert_eq!(hashes.len(), 1); // Leaf 4 is promoted twice.
+    assert_eq!(is_left, vec![true]);
+    assert_eq!(
+        hashes[0],
+        format!("0x{}", MerkleTree::merkle_root(&leaves(4)).borrow().value)
+    );
+}
+
+#[test]
+fn test_truncated_verifier_source() {
+    let tree = MerkleTree::builder()
+        .concatenation(Concatenation::Bytes)
+        .truncation(20)
+        .build(&leaves(3));
+    let source = solidity::verifier_source(&tree).unwrap();
+    assert!(source.contains("abi.encodePacked(bytes20(left), bytes20(right))"));
+    assert!(source.contains(&format!("0x{}{}", "ff".repeat(20), "00".repeat(12))));
+
+    let (hashes, _) = solidity::proof_arguments(&tree.proof(0).unwrap());
+    assert_eq!(hashes[0].len(), 66);
+    assert!(hashes[0].ends_with(&"0".repeat(24)));
+    assert_eq!(
+        solidity::to_bytes32(&tree.root().unwrap().borrow().value),
+        format!(
+            "0x{}{}",
+            tree.root().unwrap().borrow().value,
+            "0".repeat(24)
+        )
+    );
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::static_site::{export, StaticSite};
+use merkle_tree::{CommittedRoot, MerkleTree};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+fn site_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_export_and_read() {
+    let dir = site_dir("static-site");
+    let leaves = leaves(13);
+    let tree = MerkleTree::from_leaves(&leaves);
+    export(&dir, &tree, 1700000000, 2).unwrap();
+
+    let site = StaticSite::open(&dir).unwrap();
+    assert_eq!(site.root(), &CommittedRoot::of(&tree, 1700000000).unwrap());
+    let json = fs::read_to_string(dir.join("root.json")).unwrap();
+    assert_eq!(
+        json,
+        format!(
+            "{{\"root\":\"{}\",\"leafCount\":13,\"builtAt\":1700000000,\"tileHeight\":2}}",
+            site.root().root
+        )
+    );
+    assert!(dir.join("tile/2/0/003.p/1").is_file());
+    assert!(dir.join("proof/012").is_file());
+
+    let tiled = site.tiled(2);
+    for (index, leaf) in leaves.iter().enumerate() {
+        let proof = site.proof(index).unwrap();
+        assert_eq!(proof.leaf_content, *leaf);
+        assert_eq!(proof.to_bytes(), tree.proof(index).unwrap().to_bytes());
+        assert_eq!(
+            tiled.proof(index, leaf).unwrap().to_bytes(),
+            proof.to_bytes()
+        );
+    }
+    assert_eq!(
+        site.proof(13).err().unwrap().kind(),
+        io::ErrorKind::NotFound
+    );
+
+    // Files swapped or tampered with on the server are rejected.
+    fs::copy(dir.join("proof/001"), dir.join("proof/000")).unwrap();
+    assert_eq!(
+        site.proof(0).err().unwrap().kind(),
+        io::ErrorKind::InvalidData
+    );
+    let other = MerkleTree::from_leaves(&self::leaves(14));
+    fs::write(dir.join("proof/002"), other.proof(2).unwrap().to_bytes()).unwrap();
+    assert_eq!(
+        site.proof(2).err().unwrap().kind(),
+        io::ErrorKind::InvalidData
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_export_empty_tree() {
+    let dir = site_dir("static-site-empty");
+    assert_eq!(
+        export(&dir, &MerkleTree::from_leaves(&[]), 0, 8)
+            .unwrap_err()
+            .kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        StaticSite::open(&dir).unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{Concatenation, MerkleError, MerkleTree, Padding, StreamingProver};
+
+fn assert_same_proofs(builder: merkle_tree::MerkleTreeBuilder, n: usize) {
+    let targets: Vec<usize> = (0..n + 2).collect();
+    let mut prover = builder.streaming_prover(&targets);
+    for leaf in leaves(n) {
+        prover.push(&leaf);
+    }
+    assert_eq!(prover.num_of_leaves(), n);
+    let (root, proofs) = prover.finish();
+
+    let tree = builder.build(&leaves(n));
+    assert_eq!(root, tree.root().map(|root| root.borrow().value.to_owned()));
+    assert_eq!(proofs.len(), n);
+    for (leaf_index, proof) in proofs {
+        let expected = tree.proof(leaf_index).unwrap();
+        assert_eq!(proof.num_of_leaves, n);
+        assert_eq!(proof.leaf_content, expected.leaf_content);
+        assert_eq!(proof.blinder, expected.blinder);
+        let hashes: Vec<(String, bool)> = proof
+            .hashes
+            .iter()
+            .map(|hash| (hash.borrow().value.to_owned(), hash.borrow().is_left))
+            .collect();
+        let expected: Vec<(String, bool)> = expected
+            .hashes
+            .iter()
+            .map(|hash| (hash.borrow().value.to_owned(), hash.borrow().is_left))
+            .collect();
+        assert_eq!(hashes, expected);
+    }
+}
+
+#[test]
+fn test_streaming_prover() {
+    for n in 0..18 {
+        assert_same_proofs(MerkleTree::builder(), n);
+        assert_same_proofs(
+            MerkleTree::builder()
+                .padding(Padding::DuplicateLast)
+                .concatenation(Concatenation::Bytes),
+            n,
+        );
+        assert_same_proofs(
+            MerkleTree::builder()
+                .padding(Padding::Constant("ab".repeat(20)))
+                .truncation(20)
+                .blinding([7; 32]),
+            n,
+        );
+    }
+}
+
+#[test]
+fn test_targets() {
+    let mut prover = StreamingProver::new(&[5, 2, 2, 40]);
+    for leaf in leaves(11) {
+        prover.push(&leaf);
+    }
+    let (root, proofs) = prover.finish();
+    let indices: Vec<usize> = proofs.iter().map(|(leaf_index, _)| *leaf_index).collect();
+    assert_eq!(indices, vec![2, 5]);
+    let tree = MerkleTree::from_leaves(&leaves(11));
+    assert_eq!(root.unwrap(), tree.root().unwrap().borrow().value);
+    for (_, proof) in &proofs {
+        assert!(MerkleTree::verify_proof(tree.root().unwrap(), proof));
+    }
+}
+
+#[test]
+fn test_streaming_prover_limits() {
+    let mut prover = MerkleTree::builder()
+        .max_leaves(2)
+        .max_leaf_size(3)
+        .streaming_prover(&[0]);
+    assert_eq!(prover.try_push("abc"), Ok(()));
+    assert_eq!(prover.try_push("abcd"), Err(MerkleError::LeafTooLarge(1)));
+    assert_eq!(prover.try_push("bcd"), Ok(()));
+    assert_eq!(prover.try_push("cde"), Err(MerkleError::TooManyLeaves(3)));
+    assert_eq!(prover.num_of_leaves(), 2);
+    let (root, _) = prover.finish();
+    let tree = MerkleTree::from_leaves(&["abc".to_string(), "bcd".to_string()]);
+    assert_eq!(root.unwrap(), tree.root().unwrap().borrow().value);
+}
+
+#[test]
+#[should_panic(expected = "3 leaves exceed the maximum")]
+fn test_streaming_prover_limits_panics() {
+    let mut prover = MerkleTree::builder().max_leaves(2).streaming_prover(&[]);
+    for leaf in leaves(3) {
+        prover.push(&leaf);
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{MerkleTree, Padding};
+
+#[test]
+fn test_verify_subtree() {
+    for padding in [Padding::Promote, Padding::DuplicateLast] {
+        for n in 1..10 {
+            let tree = MerkleTree::builder()
+                .padding(padding.clone())
+                .build(&leaves(n));
+            let root = tree.root().unwrap();
+            for (level, nodes) in tree.levels().iter().enumerate() {
+                for (index, node) in nodes.iter().enumerate() {
+                    let proof = tree.subtree_proof(level, index).unwrap();
+                    assert!(MerkleTree::verify_subtree(
+                        root.clone(),
+                        node,
+                        (level, index),
+                        &proof
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_shard_root() {
+    let tree = MerkleTree::from_leaves(&leaves(8));
+    let shard = MerkleTree::from_leaves(&leaves(8)[4..]);
+    let shard_root = shard.root().unwrap().borrow().value.to_owned();
+    let proof = tree.subtree_proof(2, 1).unwrap();
+    assert!(MerkleTree::verify_subtree(
+        tree.root().unwrap(),
+        &shard_root,
+        (2, 1),
+        &proof
+    ));
+}
+
+#[test]
+fn test_verify_subtree_wrong_position() {
+    let tree = MerkleTree::from_leaves(&leaves(6));
+    let root = tree.root().unwrap();
+    let levels = tree.levels();
+    let proof = tree.subtree_proof(1, 1).unwrap();
+    assert!(!MerkleTree::verify_subtree(
+        root.clone(),
+        &levels[1][1],
+        (1, 0),
+        &proof
+    ));
+    assert!(!MerkleTree::verify_subtree(
+        root.clone(),
+        &levels[1][1],
+        (1, 3),
+        &proof
+    ));
+    assert!(!MerkleTree::verify_subtree(
+        root.clone(),
+        &levels[1][0],
+        (1, 1),
+        &proof
+    ));
+    let proof = tree.subtree_proof(3, 0).unwrap();
+    assert!(!MerkleTree::verify_subtree(
+        root.clone(),
+        &levels[3][0],
+        (4, 0),
+        &proof
+    ));
+    assert!(tree.subtree_proof(1, 3).is_none());
+    assert!(tree.subtree_proof(4, 0).is_none());
+}
@@ -0,0 +1,49 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::sum_tree::{SumNode, SumTree};
+
+fn accounts(n: u64) -> Vec<(String, u64)> {
+    (0..n).map(|i| (format!("user{}", i), i * 100)).collect()
+}
+
+#[test]
+fn test_sum_tree() {
+    assert!(SumTree::new(&[]).unwrap().root().is_none());
+    for n in 1..=10 {
+        let accounts = accounts(n);
+        let tree = SumTree::new(&accounts).unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(
+            root.sum,
+            accounts.iter().map(|(_, amount)| amount).sum::<u64>()
+        );
+
+        for leaf_index in 0..accounts.len() {
+            let mut proof = tree.proof(leaf_index).unwrap();
+            assert!(SumTree::verify_proof(root, &proof));
+
+            proof.amount += 1;
+            assert!(!SumTree::verify_proof(root, &proof));
+            proof.amount -= 1;
+
+            // Understating the total at the root is detected.
+            let understated = SumNode {
+                value: root.value.to_owned(),
+                sum: root.sum.wrapping_sub(1),
+            };
+            assert!(!SumTree::verify_proof(&understated, &proof));
+
+            if let Some((node, _)) = proof.nodes.first_mut() {
+                node.sum = node.sum.wrapping_sub(1);
+                assert!(!SumTree::verify_proof(root, &proof));
+            }
+        }
+        assert!(tree.proof(accounts.len()).is_none());
+    }
+}
+
+#[test]
+fn test_sum_tree_overflow() {
+    let accounts = vec![("a".to_string(), u64::MAX), ("b".to_string(), 1)];
+    assert!(SumTree::new(&accounts).is_none());
+}
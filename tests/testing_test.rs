@@ -0,0 +1,36 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "test-util")]
+use merkle_tree::testing::{random_leaves, random_proof, random_tree};
+use merkle_tree::MerkleTree;
+
+#[test]
+fn test_random_tree() {
+    let tree = random_tree(42, 100);
+    assert_eq!(tree.num_of_leaves(), 100);
+    assert_eq!(
+        tree.root().unwrap().borrow().value,
+        random_tree(42, 100).root().unwrap().borrow().value
+    );
+    assert_ne!(
+        tree.root().unwrap().borrow().value,
+        random_tree(43, 100).root().unwrap().borrow().value
+    );
+    let leaves = random_leaves(42, 100);
+    assert_eq!(random_leaves(42, 10), leaves[..10]);
+    assert!(leaves.iter().all(|leaf| leaf.len() < 32));
+    assert!(leaves
+        .iter()
+        .all(|leaf| leaf.bytes().all(|byte| byte.is_ascii_alphanumeric())));
+    assert_eq!(random_tree(0, 0).num_of_leaves(), 0);
+}
+
+#[test]
+fn test_random_proof() {
+    for seed in 0..50 {
+        let (tree, proof) = random_proof(seed);
+        assert!((1..=64).contains(&tree.num_of_leaves()));
+        assert!(MerkleTree::verify_proof(tree.root().unwrap(), &proof));
+        let (_, again) = random_proof(seed);
+        assert_eq!(again.to_bytes(), proof.to_bytes());
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::tiles::{tiles_of, Tile, TileFetcher, TiledTree};
+use merkle_tree::{CommittedRoot, LeafEncoding, MerkleError, MerkleTree, Padding};
+use std::cell::Cell;
+use std::collections::HashMap;
+
+fn serve(tree: &MerkleTree, height: u8) -> HashMap<String, Vec<u8>> {
+    tiles_of(tree, height)
+        .into_iter()
+        .map(|(tile, bytes)| (tile.path(), bytes))
+        .collect()
+}
+
+#[test]
+fn test_tile_paths() {
+    let tile = |height, level, index, width| Tile {
+        height,
+        level,
+        index,
+        width,
+    };
+    for (tile, path) in [
+        (tile(8, 0, 0, 256), "tile/8/0/000"),
+        (tile(8, 1, 67, 256), "tile/8/1/067"),
+        (tile(8, 2, 1234067, 256), "tile/8/2/x001/x234/067"),
+        (tile(8, 0, 1000, 5), "tile/8/0/x001/000.p/5"),
+        (tile(2, 3, 7, 3), "tile/2/3/007.p/3"),
+    ] {
+        assert_eq!(tile.path(), path);
+        assert_eq!(Tile::from_path(path), Ok(tile));
+    }
+    for invalid in [
+        "tiles/8/0/000",
+        "tile/0/0/000",
+        "tile/31/0/000",
+        "tile/8/00/000",
+        "tile/8/0/00",
+        "tile/8/0/001/002",
+        "tile/8/0/x001",
+        "tile/8/0/000.p",
+        "tile/8/0/000.p/0",
+        "tile/8/0/000.p/256",
+        "tile/8/0/000.p/05",
+        "tile/8/0",
+    ] {
+        assert_eq!(Tile::from_path(invalid), Err(MerkleError::InvalidEncoding));
+    }
+}
+
+#[test]
+fn test_tiles_of() {
+    let tree = MerkleTree::from_leaves(&leaves(11));
+    let tiles = tiles_of(&tree, 2);
+    let paths: Vec<String> = tiles.iter().map(|(tile, _)| tile.path()).collect();
+    assert_eq!(
+        paths,
+        [
+            "tile/2/0/000",
+            "tile/2/0/001",
+            "tile/2/0/002.p/3",
+            "tile/2/1/000.p/2"
+        ]
+    );
+    // Tile level 1 holds the complete nodes of tree level 2.
+    let level: Vec<u8> = tree.levels()[2][..2]
+        .iter()
+        .flat_map(|node| LeafEncoding::Hex.decode(node).unwrap())
+        .collect();
+    assert_eq!(tiles[3].1, level);
+    assert!(tiles_of(&MerkleTree::from_leaves(&[]), 8).is_empty());
+}
+
+#[test]
+fn test_proofs_from_tiles() {
+    for padding in [
+        Padding::Promote,
+        Padding::DuplicateLast,
+        Padding::Constant("00".repeat(32)),
+    ] {
+        for n in [1, 2, 5, 8, 11, 17, 64, 100] {
+            let leaves = leaves(n);
+            let tree = MerkleTree::builder()
+                .padding(padding.clone())
+                .build(&leaves);
+            let root = CommittedRoot::of(&tree, 0).unwrap();
+            for height in [1, 2, 3, 8] {
+                let tiled = TiledTree::new(root.clone(), height, serve(&tree, height));
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let proof = tiled.proof(index, leaf).unwrap();
+                    let expected = tree.proof(index).unwrap();
+                    assert_eq!(proof.to_bytes(), expected.to_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Fetcher counting fetches, to check tiles are kept.
+struct CountingFetcher {
+    tiles: HashMap<String, Vec<u8>>,
+    fetches: Cell<usize>,
+}
+
+impl TileFetcher for CountingFetcher {
+    fn fetch(&self, tile: &Tile) -> Option<Vec<u8>> {
+        self.fetches.set(self.fetches.get() + 1);
+        self.tiles.fetch(tile)
+    }
+}
+
+#[test]
+fn test_untrusted_tiles() {
+    let leaves = leaves(20);
+    let tree = MerkleTree::from_leaves(&leaves);
+    let root = CommittedRoot::of(&tree, 0).unwrap();
+
+    let fetcher = CountingFetcher {
+        tiles: serve(&tree, 2),
+        fetches: Cell::new(0),
+    };
+    let tiled = TiledTree::new(root.clone(), 2, fetcher);
+    tiled.proof(0, &leaves[0]).unwrap();
+    let fetches = tiled.fetcher().fetches.get();
+    assert_eq!(fetches, tiled.num_of_cached_tiles());
+    tiled.proof(1, &leaves[1]).unwrap();
+    assert_eq!(tiled.fetcher().fetches.get(), fetches);
+    assert_eq!(
+        tiled.proof(2, "forged").err(),
+        Some(MerkleError::ProofHashMismatch)
+    );
+
+    let mut tiles = serve(&tree, 2);
+    tiles.get_mut("tile/2/1/000").unwrap()[32] ^= 1;
+    let tiled = TiledTree::new(root.clone(), 2, tiles.clone());
+    assert_eq!(
+        tiled.proof(0, &leaves[0]).err(),
+        Some(MerkleError::ProofHashMismatch)
+    );
+
+    tiles.get_mut("tile/2/1/000").unwrap().pop();
+    let tiled = TiledTree::new(root.clone(), 2, tiles.clone());
+    assert_eq!(
+        tiled.proof(0, &leaves[0]).err(),
+        Some(MerkleError::InvalidEncoding)
+    );
+
+    tiles.remove("tile/2/1/000");
+    let tiled = TiledTree::new(root, 2, tiles);
+    assert_eq!(
+        tiled.proof(0, &leaves[0]).err(),
+        Some(MerkleError::MissingTile("tile/2/1/000".to_owned()))
+    );
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{MerkleTree, VerifiedVec};
+
+fn expected_root(values: &[u32]) -> String {
+    let leaves: Vec<String> = values.iter().map(u32::to_string).collect();
+    MerkleTree::merkle_root(&leaves).borrow().value.to_owned()
+}
+
+#[test]
+fn test_verified_vec() {
+    let mut vec = VerifiedVec::new();
+    assert!(vec.is_empty());
+    assert_eq!(vec.root(), None);
+    assert!(vec.get(0).is_none());
+
+    let mut values = Vec::new();
+    for value in 0..9 {
+        vec.push(value);
+        values.push(value);
+        assert_eq!(vec.root(), Some(expected_root(&values)));
+    }
+    assert_eq!(vec.len(), 9);
+    assert_eq!(vec.as_slice(), values);
+
+    vec.set(4, 40);
+    values[4] = 40;
+    assert_eq!(vec.pop(), Some(8));
+    values.pop();
+    assert_eq!(vec.root(), Some(expected_root(&values)));
+    assert_eq!(vec.set(8, 0), None);
+
+    let root = vec.tree().root().unwrap();
+    for (index, value) in values.iter().enumerate() {
+        let (element, proof) = vec.get(index).unwrap();
+        assert_eq!(element, value);
+        assert_eq!(proof.leaf_content, value.to_string());
+        assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+    }
+
+    let rebuilt = VerifiedVec::from(values.to_owned());
+    assert_eq!(rebuilt.root(), vec.root());
+}
@@ -0,0 +1,40 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+//! Runs without the `tree` feature, so it must not use tree construction.
+use merkle_tree::{audit_path_positions, verify_proof_const, verify_proof_in_place};
+use sha2::{Digest, Sha256};
+
+/// Hash the concatenation of two raw digests.
+///
+/// # Arguments
+///
+/// * `left` - Left child.
+/// * `right` - Right child.
+fn parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+#[test]
+fn test_verify_without_tree() {
+    let leaves: Vec<[u8; 32]> = [b"a", b"b", b"c"]
+        .iter()
+        .map(|leaf| Sha256::digest(leaf).into())
+        .collect();
+    let root = parent(&parent(&leaves[0], &leaves[1]), &leaves[2]);
+
+    // The rightmost leaf of 3 is promoted, so only the top level has a sibling.
+    assert_eq!(audit_path_positions(2, 3), [(1, 0, true)]);
+    let siblings = [parent(&leaves[0], &leaves[1])];
+    assert!(verify_proof_in_place(&root, b"c", &siblings, 1));
+    assert!(verify_proof_const(&root, b"c", &siblings, 1));
+    assert!(!verify_proof_in_place(&root, b"c", &siblings, 0));
+
+    let siblings = [leaves[0], leaves[2]];
+    assert_eq!(audit_path_positions(1, 3), [(0, 0, true), (1, 1, false)]);
+    assert!(verify_proof_in_place(&root, b"b", &siblings, 0b01));
+    assert!(verify_proof_const(&root, b"b", &siblings, 0b01));
+    assert!(!verify_proof_const(&root, b"a", &siblings, 0b01));
+}
@@ -0,0 +1,186 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::{
+    audit_path_positions, verify, verify_proof_const, verify_proof_in_place, Concatenation,
+    HashAlgorithm, LeafEncoding, MerkleTree,
+};
+
+#[test]
+fn test_verify() {
+    for num_of_leaves in 1..=10 {
+        let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+        let root = MerkleTree::merkle_root(&leaves).borrow().value.to_owned();
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = MerkleTree::merkle_proof(&leaves, index);
+            let siblings: Vec<String> = proof
+                .hashes
+                .iter()
+                .map(|hash| hash.borrow().value.to_owned())
+                .collect();
+            let siblings: Vec<&str> = siblings.iter().map(String::as_str).collect();
+
+            assert!(verify(
+                &root,
+                leaf.as_bytes(),
+                index,
+                num_of_leaves,
+                &siblings
+            ));
+            assert!(!verify(&root, b"tainted", index, num_of_leaves, &siblings));
+            assert!(!verify(
+                &root,
+                leaf.as_bytes(),
+                num_of_leaves,
+                num_of_leaves,
+                &siblings
+            ));
+            if !siblings.is_empty() {
+                assert!(!verify(
+                    &root,
+                    leaf.as_bytes(),
+                    index,
+                    num_of_leaves,
+                    &siblings[1..]
+                ));
+            }
+        }
+    }
+    let root = MerkleTree::merkle_root(&vec!["0".to_string()])
+        .borrow()
+        .value
+        .to_owned();
+    assert!(!verify(&root, &[0xff], 0, 1, &[]));
+}
+
+#[test]
+fn test_verify_num_of_leaves() {
+    let leaves: Vec<String> = (0..3).map(|i| i.to_string()).collect();
+    let root = MerkleTree::merkle_root(&leaves).borrow().value.to_owned();
+    let proof = MerkleTree::merkle_proof(&leaves, 2);
+    let sibling = proof.hashes[0].borrow().value.to_owned();
+
+    // Leaf 2 of 3 is hashed with a single left sibling, like leaf 1 of 2.
+    assert!(verify(&root, b"2", 2, 3, &[&sibling]));
+    assert!(!verify(&root, b"2", 1, 3, &[&sibling]));
+    assert!(verify(&root, b"2", 1, 2, &[&sibling]));
+}
+
+#[test]
+fn test_verify_proof_in_place() {
+    let digest =
+        |value: &str| -> [u8; 32] { LeafEncoding::Hex.decode(value).unwrap().try_into().unwrap() };
+    for num_of_leaves in 1..=10 {
+        let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+        let tree = MerkleTree::builder()
+            .concatenation(Concatenation::Bytes)
+            .build(&leaves);
+        let root = digest(&tree.root().unwrap().borrow().value);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            let siblings: Vec<[u8; 32]> = proof
+                .hashes
+                .iter()
+                .map(|hash| digest(&hash.borrow().value))
+                .collect();
+            let directions = proof
+                .hashes
+                .iter()
+                .enumerate()
+                .fold(0, |directions, (level, hash)| {
+                    directions | (hash.borrow().is_left as u64) << level
+                });
+
+            assert!(verify_proof_in_place(
+                &root,
+                leaf.as_bytes(),
+                &siblings,
+                directions
+            ));
+            assert!(!verify_proof_in_place(
+                &root, b"tainted", &siblings, directions
+            ));
+            let extra_bit = 1 << siblings.len();
+            assert!(!verify_proof_in_place(
+                &root,
+                leaf.as_bytes(),
+                &siblings,
+                directions | extra_bit
+            ));
+            if !siblings.is_empty() {
+                assert!(!verify_proof_in_place(
+                    &root,
+                    leaf.as_bytes(),
+                    &siblings,
+                    directions ^ 1
+                ));
+            }
+        }
+    }
+    assert!(!verify_proof_in_place(&[0; 32], b"", &[[0; 32]; 65], 0));
+}
+
+#[test]
+fn test_verify_proof_const_hashes_every_length() {
+    // Cover messages ending on both sides of the length field of the last block.
+    for len in 0..200 {
+        let leaf: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let root: [u8; 32] = HashAlgorithm::Sha256.digest(&leaf).try_into().unwrap();
+        assert!(verify_proof_const(&root, &leaf, &[], 0));
+        assert!(!verify_proof_const(&root, &leaf, &[], 1));
+    }
+    assert!(!verify_proof_const(&[0; 32], b"", &[[0; 32]; 65], 0));
+}
+
+#[test]
+fn test_audit_path_positions() {
+    assert_eq!(
+        audit_path_positions(4, 7),
+        [(0, 5, false), (1, 3, false), (2, 0, true)]
+    );
+    // The rightmost leaf of 5 has no sibling until the top level.
+    assert_eq!(audit_path_positions(4, 5), [(2, 0, true)]);
+    assert!(audit_path_positions(0, 1).is_empty());
+    assert!(audit_path_positions(5, 5).is_empty());
+
+    // Positions match the stored nodes that proofs are made of.
+    for num_of_leaves in 1..=20 {
+        let leaves: Vec<String> = (0..num_of_leaves).map(|i| i.to_string()).collect();
+        let tree = MerkleTree::from_leaves(&leaves);
+        let levels = tree.levels();
+        for leaf_index in 0..num_of_leaves {
+            let proof = tree.proof(leaf_index).unwrap();
+            let positions = audit_path_positions(leaf_index, num_of_leaves);
+            assert_eq!(positions.len(), proof.hashes.len());
+            for ((level, index, is_left), hash) in positions.into_iter().zip(&proof.hashes) {
+                assert_eq!(levels[level][index], hash.borrow().value);
+                assert_eq!(is_left, hash.borrow().is_left);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_verify_any() {
+    let leaves: Vec<String> = (0..7).map(|i| i.to_string()).collect();
+    let roots: Vec<_> = (5..=7)
+        .map(|num_of_leaves| MerkleTree::merkle_root(&leaves[..num_of_leaves].to_vec()))
+        .collect();
+    for (expected, num_of_leaves) in (5..=7).enumerate() {
+        let proof = MerkleTree::merkle_proof(&leaves[..num_of_leaves].to_vec(), 4);
+        assert_eq!(MerkleTree::verify_any(&roots, &proof), Some(expected));
+    }
+
+    // A proof matching no candidate, e.g. of a root that rotated out of the window.
+    let proof = MerkleTree::merkle_proof(&leaves[..4].to_vec(), 0);
+    assert_eq!(MerkleTree::verify_any(&roots, &proof), None);
+    assert_eq!(MerkleTree::verify_any(&[], &proof), None);
+
+    // The first of equal candidates is returned.
+    let proof = MerkleTree::merkle_proof(&leaves[..6].to_vec(), 1);
+    let repeated = [
+        roots[0].to_owned(),
+        roots[1].to_owned(),
+        roots[1].to_owned(),
+    ];
+    assert_eq!(MerkleTree::verify_any(&repeated, &proof), Some(1));
+}
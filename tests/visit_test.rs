@@ -0,0 +1,57 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+mod common;
+
+use common::leaves;
+use merkle_tree::{Hash, MerkleTree, Traversal, TOMBSTONE};
+
+fn walk(tree: &MerkleTree, order: Traversal) -> Vec<(usize, usize, String)> {
+    let mut nodes = Vec::new();
+    tree.visit(order, |level, index, value| {
+        nodes.push((level, index, value.to_string()))
+    });
+    nodes
+}
+
+#[test]
+fn test_visit() {
+    let tree = MerkleTree::from_leaves(&leaves(3));
+    let positions: Vec<(usize, usize)> = walk(&tree, Traversal::PreOrder)
+        .iter()
+        .map(|(level, index, _)| (*level, *index))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![(2, 0), (1, 0), (0, 0), (0, 1), (1, 1), (0, 2)]
+    );
+    let positions: Vec<(usize, usize)> = walk(&tree, Traversal::PostOrder)
+        .iter()
+        .map(|(level, index, _)| (*level, *index))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![(0, 0), (0, 1), (1, 0), (0, 2), (1, 1), (2, 0)]
+    );
+
+    let nodes = walk(&tree, Traversal::PreOrder);
+    assert_eq!(nodes[0].2, tree.root().unwrap().borrow().value);
+    assert_eq!(nodes[2].2, Hash::hash("leaf0"));
+    // The promoted leaf is stored on both levels.
+    assert_eq!(nodes[4].2, nodes[5].2);
+
+    for n in 0..20 {
+        let tree = MerkleTree::from_leaves(&leaves(n));
+        let nodes = walk(&tree, Traversal::PostOrder);
+        let visited_leaves: Vec<&String> = nodes
+            .iter()
+            .filter(|(level, _, _)| *level == 0)
+            .map(|(_, _, value)| value)
+            .collect();
+        assert_eq!(visited_leaves.len(), n);
+        assert_eq!(walk(&tree, Traversal::PreOrder).len(), nodes.len());
+    }
+
+    let mut tree = MerkleTree::from_leaves(&leaves(4));
+    tree.remove(1);
+    assert!(walk(&tree, Traversal::PreOrder).contains(&(0, 1, TOMBSTONE.to_string())));
+}
@@ -0,0 +1,42 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "tree")]
+use merkle_tree::cas::{Cas, MemoryStorage};
+use merkle_tree::{MerkleTree, MerkleWriter};
+use std::io::{self, Write};
+
+#[test]
+fn test_merkle_writer() {
+    let bytes: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 251) as u8).collect();
+    for chunk_size in [1, 3, 64, 100, 999, 1000, 4096] {
+        for len in [0, 1, 99, 100, 101, 1000] {
+            let mut writer = MerkleWriter::new(chunk_size);
+            io::copy(&mut &bytes[..len], &mut writer).unwrap();
+            let expected = Cas::new(MemoryStorage::default(), chunk_size).put(&bytes[..len]);
+            assert_eq!(writer.num_of_chunks(), len.div_ceil(chunk_size));
+            assert_eq!(writer.finish(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_uneven_writes() {
+    let mut writer = MerkleWriter::new(5);
+    for piece in [&b"ab"[..], b"", b"cdefgh", b"i", b"jklmnopqrstuv"] {
+        writer.write_all(piece).unwrap();
+    }
+    writer.flush().unwrap();
+    let chunks: Vec<String> = b"abcdefghijklmnopqrstuv"
+        .chunks(5)
+        .map(|chunk| merkle_tree::Hash::hash_reader(chunk).unwrap())
+        .collect();
+    assert_eq!(
+        writer.finish(),
+        MerkleTree::merkle_root(&chunks).borrow().value
+    );
+}
+
+#[test]
+#[should_panic(expected = "chunk size must be at least 1 byte")]
+fn test_zero_chunk_size() {
+    MerkleWriter::new(0);
+}
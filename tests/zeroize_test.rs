@@ -0,0 +1,22 @@
+// Copyright (c) 2024 Wu Tingfeng <wutingfeng@outlook.com>
+#![cfg(feature = "zeroize")]
+use merkle_tree::MerkleTree;
+
+#[test]
+fn test_shared_leaves_outlive_tree() {
+    let leaves: Vec<String> = (0..100).map(|i| format!("secret{}", i)).collect();
+    let mut tree = MerkleTree::from_leaves(&leaves);
+    let snapshot = tree.snapshot();
+    tree.append("secret100".to_string());
+    let clone = tree.clone();
+    drop(tree);
+
+    // Leaves still held by a snapshot or clone are not wiped.
+    let root = snapshot.root().unwrap();
+    for leaf_index in [0, 50, 99] {
+        let proof = snapshot.proof(leaf_index).unwrap();
+        assert_eq!(proof.leaf_content, leaves[leaf_index]);
+        assert!(MerkleTree::verify_proof(root.to_owned(), &proof));
+    }
+    assert_eq!(clone.proof(100).unwrap().leaf_content, "secret100");
+}